@@ -0,0 +1,271 @@
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![warn(missing_docs)]
+#![warn(clippy::allow_attributes)]
+#![doc = include_str!("../README.md")]
+
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use bril_rs::extension::{ExtensionError, ExtensionSet};
+use bril_rs::{Program, try_output_program};
+use thiserror::Error;
+
+/// The reasons a [`Backend`] can not be made or run
+#[derive(Error, Debug)]
+pub enum BackendError {
+    /// A backend was not written as `name=command` or `name:extensions=command`
+    #[error("invalid backend `{0}`, expected `name=command` or `name:extensions=command`")]
+    InvalidBackend(String),
+    /// The extensions of a backend could not be parsed
+    #[error("invalid backend `{0}`: {1}")]
+    InvalidExtensions(String, Box<ExtensionError>),
+    /// A backend could not be started or talked to
+    #[error("could not run backend `{backend}`: {source}")]
+    Run {
+        /// The name of the backend
+        backend: String,
+        /// What went wrong
+        source: io::Error,
+    },
+}
+
+/// A program which runs Bril programs, like an interpreter or a compiler and its output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backend {
+    /// The name of the backend in reports
+    pub name: String,
+    /// A shell command which reads a Bril program as JSON from stdin and takes the arguments of `main` as its own arguments
+    pub command: String,
+    /// The extensions the backend supports. Programs which use others are not given to it.
+    pub extensions: ExtensionSet,
+}
+
+impl Backend {
+    /// `brilirs`, `brilift`, and `brillvm`, which are expected to be installed
+    /// # Errors
+    /// Will return an error if `brilift` or `brillvm` could not be asked which extensions they support
+    pub fn defaults() -> Result<Vec<Self>, BackendError> {
+        Ok(vec![
+            Self {
+                name: "brilirs".to_string(),
+                command: "brilirs -p".to_string(),
+                extensions: ExtensionSet::ALL,
+            },
+            Self::asking_for_extensions("brilift", "brilift -j")?,
+            Self::asking_for_extensions("brillvm", "brillvm -i")?,
+        ])
+    }
+
+    /// The backend ```name``` run with ```command```, which supports the extensions it prints when `--extensions` is added to ```command```
+    /// # Errors
+    /// Will return an error if ```command``` could not be run, fails, or does not print a set of extensions
+    pub fn asking_for_extensions(name: &str, command: &str) -> Result<Self, BackendError> {
+        let backend_error = |source| BackendError::Run {
+            backend: name.to_string(),
+            source,
+        };
+        let output = shell_command(name, command, &["--extensions".to_string()])
+            .stdin(Stdio::null())
+            .output()
+            .map_err(backend_error)?;
+        if !output.status.success() {
+            return Err(backend_error(io::Error::other(format!(
+                "`{command} --extensions` failed with {}",
+                output.status
+            ))));
+        }
+        let extensions = String::from_utf8_lossy(&output.stdout)
+            .trim()
+            .parse()
+            .map_err(|e| BackendError::InvalidExtensions(name.to_string(), Box::new(e)))?;
+        Ok(Self {
+            name: name.to_string(),
+            command: command.to_string(),
+            extensions,
+        })
+    }
+
+    /// Runs ```program``` with ```args```, killing the backend if it takes longer than ```timeout```
+    /// # Errors
+    /// Will return an error if the backend could not be started or its output could not be read
+    pub fn run(
+        &self,
+        program: &Program,
+        args: &[String],
+        timeout: Duration,
+    ) -> Result<Outcome, BackendError> {
+        self.try_run(program, args, timeout)
+            .map_err(|source| BackendError::Run {
+                backend: self.name.clone(),
+                source,
+            })
+    }
+
+    fn try_run(
+        &self,
+        program: &Program,
+        args: &[String],
+        timeout: Duration,
+    ) -> io::Result<Outcome> {
+        let mut json = Vec::new();
+        try_output_program(program, &mut json).map_err(io::Error::other)?;
+
+        let mut child = shell_command(&self.name, &self.command, args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let writer = thread::spawn(move || {
+            // The backend may exit without reading all of its input, which is its own problem to report
+            let _ = stdin.write_all(&json);
+        });
+        let stdout = read_in_background(child.stdout.take().expect("stdout is piped"));
+        let stderr = read_in_background(child.stderr.take().expect("stderr is piped"));
+
+        let exit = wait(&mut child, timeout)?;
+        writer.join().expect("the writer does not panic");
+        let stdout = stdout.join().expect("the reader does not panic")?;
+        let stderr = stderr.join().expect("the reader does not panic")?;
+
+        let dyn_inst = stderr
+            .lines()
+            .find_map(|line| line.strip_prefix("total_dyn_inst: "))
+            .and_then(|count| count.trim().parse().ok());
+        Ok(Outcome {
+            stdout,
+            stderr,
+            exit,
+            dyn_inst,
+        })
+    }
+}
+
+/// Parses `name=command`, or `name:extensions=command` for a backend which only supports some extensions, like `brilift:float,memory=brilift -j`
+impl FromStr for Backend {
+    type Err = BackendError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, command) = s
+            .split_once('=')
+            .filter(|(name, command)| !name.is_empty() && !command.trim().is_empty())
+            .ok_or_else(|| BackendError::InvalidBackend(s.to_string()))?;
+        let (name, extensions) = match name.split_once(':') {
+            Some((name, extensions)) => (
+                name,
+                extensions
+                    .parse()
+                    .map_err(|e| BackendError::InvalidExtensions(s.to_string(), Box::new(e)))?,
+            ),
+            None => (name, ExtensionSet::ALL),
+        };
+        Ok(Self {
+            name: name.to_string(),
+            command: command.to_string(),
+            extensions,
+        })
+    }
+}
+
+/// Runs ```command``` with ```args``` in `sh`, which passes the arguments on with "$@".
+/// It gets its own process group so that everything it starts can be killed after the timeout.
+#[cfg(unix)]
+fn shell_command(name: &str, command: &str, args: &[String]) -> Command {
+    use std::os::unix::process::CommandExt;
+
+    let mut shell = Command::new("sh");
+    shell
+        .arg("-c")
+        .arg(format!("{command} \"$@\""))
+        .arg(name)
+        .args(args)
+        .process_group(0);
+    shell
+}
+
+/// Runs ```command``` with ```args``` in `cmd`
+#[cfg(not(unix))]
+fn shell_command(_name: &str, command: &str, args: &[String]) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command).args(args);
+    shell
+}
+
+/// Kills the process group of ```child```
+#[cfg(unix)]
+fn kill(child: &Child) -> io::Result<()> {
+    Command::new("kill")
+        .args(["-KILL", "--", &format!("-{}", child.id())])
+        .status()?;
+    Ok(())
+}
+
+/// Kills ```child```, but not the processes it started
+#[cfg(not(unix))]
+fn kill(child: &mut Child) -> io::Result<()> {
+    child.kill()
+}
+
+fn read_in_background(
+    mut pipe: impl Read + Send + 'static,
+) -> thread::JoinHandle<io::Result<String>> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        pipe.read_to_end(&mut bytes)?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    })
+}
+
+fn wait(child: &mut Child, timeout: Duration) -> io::Result<Exit> {
+    let start = Instant::now();
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(status.code().map_or(Exit::Signal, Exit::Code));
+        }
+        if start.elapsed() > timeout {
+            kill(child)?;
+            child.wait()?;
+            return Ok(Exit::Timeout);
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+}
+
+/// How a backend stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    /// It exited with this status
+    Code(i32),
+    /// It was killed by a signal, like a segmentation fault
+    Signal,
+    /// It ran for too long and was killed by the harness
+    Timeout,
+}
+
+impl Display for Exit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Code(code) => write!(f, "{code}"),
+            Self::Signal => write!(f, "killed by a signal"),
+            Self::Timeout => write!(f, "timed out"),
+        }
+    }
+}
+
+/// What a backend did with a program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// Everything printed to stdout
+    pub stdout: String,
+    /// Everything printed to stderr, which is not compared
+    pub stderr: String,
+    /// How the backend stopped
+    pub exit: Exit,
+    /// The number of instructions executed if the backend reported it with a `total_dyn_inst: ` line on stderr
+    pub dyn_inst: Option<u64>,
+}
@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use bril_backend::{Backend, Exit};
+use bril_rs::Program;
+use bril_rs::extension::ExtensionSet;
+
+fn program() -> Program {
+    let json = r#"{"functions": [{"name": "main", "instrs": []}]}"#;
+    bril_rs::load_program_from_read(json.as_bytes())
+}
+
+#[test]
+fn backends_report_what_they_did() {
+    let backend: Backend = "echo=cat >/dev/null; echo \"$@\"; echo 'total_dyn_inst: 3' >&2; exit 4"
+        .parse()
+        .unwrap();
+    let args = ["1".to_string(), "true".to_string()];
+    let outcome = backend
+        .run(&program(), &args, Duration::from_secs(10))
+        .unwrap();
+    assert_eq!(outcome.stdout, "1 true\n");
+    assert_eq!(outcome.exit, Exit::Code(4));
+    assert_eq!(outcome.dyn_inst, Some(3));
+}
+
+#[test]
+fn backends_are_parsed() {
+    let backend: Backend = "lift:float,memory=brilift -j".parse().unwrap();
+    assert_eq!(backend.name, "lift");
+    assert_eq!(backend.command, "brilift -j");
+    assert_eq!(
+        backend.extensions,
+        "float,memory".parse::<ExtensionSet>().unwrap()
+    );
+    assert!("brilirs".parse::<Backend>().is_err());
+    assert!("x:floats=cat".parse::<Backend>().is_err());
+}
+
+#[test]
+fn backends_are_asked_for_their_extensions() {
+    let backend = Backend::asking_for_extensions("lift", "echo float,memory #").unwrap();
+    assert_eq!(backend.command, "echo float,memory #");
+    assert_eq!(
+        backend.extensions,
+        "float,memory".parse::<ExtensionSet>().unwrap()
+    );
+    assert!(Backend::asking_for_extensions("x", "echo floats #").is_err());
+    assert!(Backend::asking_for_extensions("x", "false").is_err());
+}
+
+#[test]
+fn backends_are_killed_after_the_timeout() {
+    let backend: Backend = "slow=sleep 5; true".parse().unwrap();
+    let outcome = backend
+        .run(&program(), &[], Duration::from_millis(100))
+        .unwrap();
+    assert_eq!(outcome.exit, Exit::Timeout);
+}
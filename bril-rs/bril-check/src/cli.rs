@@ -0,0 +1,16 @@
+use bril_rs::extension::ExtensionSet;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(about, version, author)] // keeps the cli synced with Cargo.toml
+pub struct Cli {
+    /// The Bril file to check. stdin is assumed if file is not provided.
+    #[arg(short, long, action)]
+    pub file: Option<String>,
+    /// Flag for when the Bril program is in text form
+    #[arg(short, long, action)]
+    pub text: bool,
+    /// A comma separated list of the extensions the program may use, like `float,memory`. All extensions are allowed by default.
+    #[arg(short, long, action, default_value = "all")]
+    pub extensions: ExtensionSet,
+}
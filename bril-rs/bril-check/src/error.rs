@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "I allow for the Error suffix on enums"
+)]
+#[derive(Error, Debug)]
+pub enum CheckError {
+    #[error(transparent)]
+    ParseError(#[from] bril2json::ParseError),
+    #[error(transparent)]
+    BrilError(#[from] bril_rs::BrilError),
+}
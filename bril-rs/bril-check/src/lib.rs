@@ -0,0 +1,42 @@
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![warn(missing_docs)]
+#![warn(clippy::allow_attributes)]
+#![doc = include_str!("../README.md")]
+
+#[doc(hidden)]
+pub mod cli;
+
+#[doc(hidden)]
+pub mod error;
+
+use std::fs::File;
+use std::io::Read;
+
+use bril_rs::{BrilError, Program, try_load_program_from_abstract_read};
+use bril2json::try_parse_abstract_program_from_read;
+
+use crate::error::CheckError;
+
+/// Reads all of ```file```, or of [`std::io::Stdin`] if it is [None]
+/// # Errors
+/// Will return an error if the input can not be read
+pub fn read_source(file: Option<&str>) -> std::io::Result<String> {
+    let mut source = String::new();
+    match file {
+        Some(f) => File::open(f)?.read_to_string(&mut source)?,
+        None => std::io::stdin().lock().read_to_string(&mut source)?,
+    };
+    Ok(source)
+}
+
+/// Parses ```source``` as Bril text with source positions if ```text``` is set and as JSON otherwise
+/// # Errors
+/// Will return an error if ```source``` is not well-formed Bril text or is not a valid Bril program. Every syntax error is reported in [`bril2json::ParseError::Syntax`] and every invalid op and type in [`BrilError::Conversion`].
+pub fn load_program(source: &str, text: bool) -> Result<Program, CheckError> {
+    if text {
+        // The file name is left out of the positions so that problems are reported as just a line and column
+        let program = try_parse_abstract_program_from_read(source.as_bytes(), true, true, None)?;
+        return Ok(Program::try_from(program).map_err(BrilError::from)?);
+    }
+    Ok(try_load_program_from_abstract_read(source.as_bytes())?)
+}
@@ -0,0 +1,36 @@
+use std::fmt::Display;
+
+use clap::Parser;
+
+use bril_check::error::CheckError;
+use bril_check::{cli::Cli, load_program, read_source};
+use bril_rs::BrilError;
+use bril_rs::check::check_program;
+
+fn fail(e: &impl Display) -> ! {
+    eprintln!("{e}");
+    std::process::exit(1)
+}
+
+fn main() {
+    let args = Cli::parse();
+
+    let source = read_source(args.file.as_deref()).unwrap_or_else(|e| fail(&e));
+    let program = match load_program(&source, args.text) {
+        Ok(program) => program,
+        // Positions only point into the source when it is the Bril text itself
+        Err(CheckError::BrilError(BrilError::Conversion(errors))) if args.text => {
+            eprint!("{}", errors.render(&source));
+            std::process::exit(1);
+        }
+        Err(e) => fail(&e),
+    };
+    let diagnostics = check_program(&program, args.extensions);
+    for d in &diagnostics {
+        eprintln!("{d}");
+    }
+
+    if !diagnostics.is_empty() {
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,29 @@
+use clap::{Parser, ValueEnum};
+
+#[derive(Parser)]
+#[command(about, version, author)] // keeps the cli synced with Cargo.toml
+pub struct Cli {
+    /// The original program
+    pub old: String,
+    /// The changed program
+    pub new: String,
+    /// Flag for when the Bril programs are in text form
+    #[arg(short, long, action)]
+    pub text: bool,
+    /// Print the differences as JSON instead of text
+    #[arg(short, long, action)]
+    pub json: bool,
+    /// When to color the text output
+    #[arg(long, value_enum, default_value_t = Color::Auto)]
+    pub color: Color,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Color {
+    /// Only when stdout is a terminal
+    Auto,
+    /// Always
+    Always,
+    /// Never
+    Never,
+}
@@ -0,0 +1,574 @@
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![warn(missing_docs)]
+#![warn(clippy::allow_attributes)]
+#![doc = include_str!("../README.md")]
+
+#[doc(hidden)]
+pub mod cli;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Display, Formatter, Write};
+
+use bril_rs::cfg::{Cfg, CfgError};
+use bril_rs::{Function, Instruction, Program};
+use serde::Serialize;
+
+/// The differences between an old and a new version of a [Program]
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct ProgramDiff {
+    /// The functions which are only in the new program
+    pub added_functions: Vec<String>,
+    /// The functions which are only in the old program
+    pub removed_functions: Vec<String>,
+    /// The functions in both programs which differ, in the order of the old program
+    pub changed_functions: Vec<FunctionDiff>,
+}
+
+/// The differences between two versions of a [Function] with the same name
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FunctionDiff {
+    /// The name of the function
+    pub name: String,
+    /// The old and the new signature, like `@main(n: int): int`, if the types of the arguments or the return type differ
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<(String, String)>,
+    /// The variables of the old function which are consistently named differently in the new function, along with their new names
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub renamed: BTreeMap<String, String>,
+    /// The blocks which differ. Blocks in the old function come first, in order, followed by the blocks which are only in the new function.
+    pub blocks: Vec<BlockDiff>,
+}
+
+impl FunctionDiff {
+    /// Whether the two versions are the same up to renaming variables
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.signature.is_none() && self.blocks.is_empty()
+    }
+}
+
+/// How a basic block is matched with the block it corresponds to in the other version of a function
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockId {
+    /// The first block of the function when it has no label
+    Entry,
+    /// A block starting with this label
+    Label(String),
+    /// The n-th block without a label after the entry block, counting from 1. These only follow a terminator, so they are unreachable.
+    Unlabeled(usize),
+}
+
+impl Display for BlockId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Entry => write!(f, "<entry>"),
+            Self::Label(label) => write!(f, ".{label}"),
+            Self::Unlabeled(n) => write!(f, "<unlabeled {n}>"),
+        }
+    }
+}
+
+/// How a basic block differs between the two versions of a function
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockDiff {
+    /// Which block this is
+    pub block: BlockId,
+    /// What changed
+    pub change: BlockChange,
+}
+
+/// The ways a basic block can differ
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum BlockChange {
+    /// The block is only in the new function
+    Added {
+        /// The instructions of the block
+        instrs: Vec<Instruction>,
+    },
+    /// The block is only in the old function
+    Removed {
+        /// The instructions of the block
+        instrs: Vec<Instruction>,
+    },
+    /// The block is in both functions, but some of its instructions differ
+    Changed {
+        /// The differing instructions, in the order of the block
+        edits: Vec<InstrEdit>,
+    },
+}
+
+/// A difference between the instructions of two versions of a basic block
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum InstrEdit {
+    /// An instruction which is only in the new block
+    Insert {
+        /// The inserted instruction
+        new: Instruction,
+    },
+    /// An instruction which is only in the old block
+    Delete {
+        /// The deleted instruction
+        old: Instruction,
+    },
+    /// An instruction which was changed in place, like a `const` with a new value or an instruction whose variables do not follow the renaming of the function
+    Edit {
+        /// The instruction in the old block
+        old: Box<Instruction>,
+        /// The instruction in the new block
+        new: Box<Instruction>,
+    },
+}
+
+/// Compares ```old``` and ```new```, matching their functions by name
+/// # Errors
+/// Will return an error if a function of either program can not be split into basic blocks
+pub fn diff_programs(old: &Program, new: &Program) -> Result<ProgramDiff, CfgError> {
+    let new_functions: HashMap<&str, &Function> =
+        new.functions.iter().map(|f| (f.name.as_str(), f)).collect();
+    let old_names: HashSet<&str> = old.functions.iter().map(|f| f.name.as_str()).collect();
+
+    let mut diff = ProgramDiff::default();
+    for func in &old.functions {
+        match new_functions.get(func.name.as_str()) {
+            Some(new_func) => {
+                let func_diff = diff_functions(func, new_func)?;
+                if !func_diff.is_empty() {
+                    diff.changed_functions.push(func_diff);
+                }
+            }
+            None => diff.removed_functions.push(func.name.clone()),
+        }
+    }
+    diff.added_functions = new
+        .functions
+        .iter()
+        .filter(|f| !old_names.contains(f.name.as_str()))
+        .map(|f| f.name.clone())
+        .collect();
+    Ok(diff)
+}
+
+/// Compares two versions of a function, matching their basic blocks by label
+/// # Errors
+/// Will return an error if either function can not be split into basic blocks
+pub fn diff_functions(old: &Function, new: &Function) -> Result<FunctionDiff, CfgError> {
+    let mut renaming = Renaming::default();
+    let old_types = old.args.iter().map(|a| &a.arg_type);
+    let new_types = new.args.iter().map(|a| &a.arg_type);
+    let signature = (!old_types.eq(new_types) || old.return_type != new.return_type)
+        .then(|| (signature(old), signature(new)));
+    renaming.unify(
+        old.args
+            .iter()
+            .map(|a| &a.name)
+            .zip(new.args.iter().map(|a| &a.name)),
+    );
+
+    let old_cfg = Cfg::new(old.clone())?;
+    let new_cfg = Cfg::new(new.clone())?;
+    let old_ids = block_ids(&old_cfg);
+    let new_ids = block_ids(&new_cfg);
+    let new_index: HashMap<&BlockId, usize> =
+        new_ids.iter().enumerate().map(|(i, id)| (id, i)).collect();
+    let old_index: HashSet<&BlockId> = old_ids.iter().collect();
+
+    let mut blocks = Vec::new();
+    for (id, block) in old_ids.iter().zip(&old_cfg.blocks) {
+        let change = match new_index.get(id) {
+            Some(&j) => {
+                let edits = diff_instrs(&block.instrs, &new_cfg.blocks[j].instrs, &mut renaming);
+                if edits.is_empty() {
+                    continue;
+                }
+                BlockChange::Changed { edits }
+            }
+            None => BlockChange::Removed {
+                instrs: block.instrs.iter().map(without_pos).collect(),
+            },
+        };
+        blocks.push(BlockDiff {
+            block: id.clone(),
+            change,
+        });
+    }
+    for (id, block) in new_ids.iter().zip(&new_cfg.blocks) {
+        if !old_index.contains(id) {
+            blocks.push(BlockDiff {
+                block: id.clone(),
+                change: BlockChange::Added {
+                    instrs: block.instrs.iter().map(without_pos).collect(),
+                },
+            });
+        }
+    }
+
+    Ok(FunctionDiff {
+        name: old.name.clone(),
+        signature,
+        renamed: renaming
+            .forward
+            .into_iter()
+            .filter(|(old, new)| old != new)
+            .collect(),
+        blocks,
+    })
+}
+
+fn signature(func: &Function) -> String {
+    let mut s = format!("@{}", func.name);
+    if !func.args.is_empty() {
+        let args: Vec<String> = func.args.iter().map(ToString::to_string).collect();
+        write!(s, "({})", args.join(", ")).unwrap();
+    }
+    if let Some(t) = &func.return_type {
+        write!(s, ": {t}").unwrap();
+    }
+    s
+}
+
+fn block_ids(cfg: &Cfg) -> Vec<BlockId> {
+    let mut unlabeled = 0;
+    cfg.blocks
+        .iter()
+        .enumerate()
+        .map(|(i, block)| match &block.label {
+            Some(label) => BlockId::Label(label.clone()),
+            None if i == cfg.entry() => BlockId::Entry,
+            None => {
+                unlabeled += 1;
+                BlockId::Unlabeled(unlabeled)
+            }
+        })
+        .collect()
+}
+
+/// Source positions always differ after a program is transformed, and comments are not part of what an instruction does, so both are left out of the reported instructions
+fn without_pos(i: &Instruction) -> Instruction {
+    let mut i = i.clone();
+    match &mut i {
+        Instruction::Constant { pos, comments, .. }
+        | Instruction::Value { pos, comments, .. }
+        | Instruction::Effect { pos, comments, .. } => {
+            *pos = None;
+            *comments = None;
+        }
+    }
+    i
+}
+
+/// A one-to-one mapping from the variables of the old function to those of the new function
+#[derive(Debug, Default)]
+struct Renaming {
+    forward: HashMap<String, String>,
+    backward: HashMap<String, String>,
+}
+
+impl Renaming {
+    /// The new name which ```old``` is bound to, if any
+    fn get(&self, old: &str) -> Option<&str> {
+        self.forward.get(old).map(String::as_str)
+    }
+
+    /// Whether ```old``` and ```new``` are bound to each other, or could be because neither is bound yet
+    fn agrees(&self, old: &str, new: &str) -> bool {
+        match (self.get(old), self.backward.get(new)) {
+            (None, None) => true,
+            (Some(n), _) => n == new,
+            (None, Some(_)) => false,
+        }
+    }
+
+    /// Binds each pair of variables to each other, unless one of them is already bound to a different variable.
+    /// Returns whether every pair is consistent with the renaming; nothing is bound if one is not.
+    fn unify<'a>(&mut self, pairs: impl IntoIterator<Item = (&'a String, &'a String)>) -> bool {
+        let mut added: Vec<(&String, &String)> = Vec::new();
+        for (old, new) in pairs {
+            let forward = self.get(old).or_else(|| {
+                added
+                    .iter()
+                    .find(|(o, _)| *o == old)
+                    .map(|(_, n)| n.as_str())
+            });
+            let backward = self.backward.get(new).map(String::as_str).or_else(|| {
+                added
+                    .iter()
+                    .find(|(_, n)| *n == new)
+                    .map(|(o, _)| o.as_str())
+            });
+            match (forward, backward) {
+                (None, None) => added.push((old, new)),
+                (Some(f), Some(b)) if f == new && b == old => {}
+                _ => return false,
+            }
+        }
+        for (old, new) in added {
+            self.forward.insert(old.clone(), new.clone());
+            self.backward.insert(new.clone(), old.clone());
+        }
+        true
+    }
+}
+
+/// The variables of ```i``` in order, starting with its destination
+fn vars(i: &Instruction) -> Vec<&String> {
+    match i {
+        Instruction::Constant { dest, .. } => vec![dest],
+        Instruction::Value { dest, args, .. } => std::iter::once(dest).chain(args).collect(),
+        Instruction::Effect { args, .. } => args.iter().collect(),
+    }
+}
+
+/// Whether ```a``` and ```b``` are the same instruction apart from the names of their variables
+fn same_shape(a: &Instruction, b: &Instruction) -> bool {
+    match (a, b) {
+        (
+            Instruction::Constant {
+                const_type: t1,
+                value: v1,
+                ..
+            },
+            Instruction::Constant {
+                const_type: t2,
+                value: v2,
+                ..
+            },
+        ) => t1 == t2 && v1 == v2,
+        (
+            Instruction::Value {
+                op: o1,
+                op_type: t1,
+                args: a1,
+                funcs: f1,
+                labels: l1,
+                ..
+            },
+            Instruction::Value {
+                op: o2,
+                op_type: t2,
+                args: a2,
+                funcs: f2,
+                labels: l2,
+                ..
+            },
+        ) => o1 == o2 && t1 == t2 && a1.len() == a2.len() && f1 == f2 && l1 == l2,
+        (
+            Instruction::Effect {
+                op: o1,
+                args: a1,
+                funcs: f1,
+                labels: l1,
+                ..
+            },
+            Instruction::Effect {
+                op: o2,
+                args: a2,
+                funcs: f2,
+                labels: l2,
+                ..
+            },
+        ) => o1 == o2 && a1.len() == a2.len() && f1 == f2 && l1 == l2,
+        _ => false,
+    }
+}
+
+/// Whether ```new``` looks like ```old``` changed in place: both assign to corresponding variables, or both are effects with the same op
+fn counterparts(old: &Instruction, new: &Instruction, renaming: &Renaming) -> bool {
+    match (old, new) {
+        (Instruction::Effect { op: o1, .. }, Instruction::Effect { op: o2, .. }) => o1 == o2,
+        _ => match (old.dest(), new.dest()) {
+            (Some(d1), Some(d2)) => renaming.agrees(d1, d2),
+            _ => false,
+        },
+    }
+}
+
+enum Step {
+    Match(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Aligns ```old``` and ```new``` with a longest common subsequence of instructions which have the same shape
+fn align(old: &[Instruction], new: &[Instruction]) -> Vec<Step> {
+    // lcs[i][j] is the length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if same_shape(&old[i], &new[j]) {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut steps = Vec::with_capacity(old.len().max(new.len()));
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && same_shape(&old[i], &new[j]) {
+            steps.push(Step::Match(i, j));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            steps.push(Step::Delete(i));
+            i += 1;
+        } else {
+            steps.push(Step::Insert(j));
+            j += 1;
+        }
+    }
+    steps
+}
+
+fn diff_instrs(
+    old: &[Instruction],
+    new: &[Instruction],
+    renaming: &mut Renaming,
+) -> Vec<InstrEdit> {
+    let mut edits = Vec::new();
+    let mut deleted = Vec::new();
+    let mut inserted = Vec::new();
+    for step in align(old, new) {
+        match step {
+            Step::Match(i, j) => {
+                flush_gap(&deleted, &inserted, renaming, &mut edits);
+                deleted.clear();
+                inserted.clear();
+                if !renaming.unify(vars(&old[i]).into_iter().zip(vars(&new[j]))) {
+                    edits.push(InstrEdit::Edit {
+                        old: Box::new(without_pos(&old[i])),
+                        new: Box::new(without_pos(&new[j])),
+                    });
+                }
+            }
+            Step::Delete(i) => deleted.push(&old[i]),
+            Step::Insert(j) => inserted.push(&new[j]),
+        }
+    }
+    flush_gap(&deleted, &inserted, renaming, &mut edits);
+    edits
+}
+
+/// Reports the instructions between two aligned instructions, pairing up deleted and inserted instructions which are [`counterparts`] as edits
+fn flush_gap(
+    deleted: &[&Instruction],
+    inserted: &[&Instruction],
+    renaming: &mut Renaming,
+    edits: &mut Vec<InstrEdit>,
+) {
+    let mut used = vec![false; inserted.len()];
+    for old in deleted {
+        let paired =
+            (0..inserted.len()).find(|&j| !used[j] && counterparts(old, inserted[j], renaming));
+        match paired {
+            Some(j) => {
+                used[j] = true;
+                if let (Some(d1), Some(d2)) = (old.dest(), inserted[j].dest()) {
+                    renaming.unify([(d1, d2)]);
+                }
+                edits.push(InstrEdit::Edit {
+                    old: Box::new(without_pos(old)),
+                    new: Box::new(without_pos(inserted[j])),
+                });
+            }
+            None => edits.push(InstrEdit::Delete {
+                old: without_pos(old),
+            }),
+        }
+    }
+    for (new, _) in inserted.iter().zip(used).filter(|(_, used)| !used) {
+        edits.push(InstrEdit::Insert {
+            new: without_pos(new),
+        });
+    }
+}
+
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+impl ProgramDiff {
+    /// Whether the two programs are the same up to renaming variables
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.added_functions.is_empty()
+            && self.removed_functions.is_empty()
+            && self.changed_functions.is_empty()
+    }
+
+    /// Renders the differences as text, with lines starting with `+`, `-`, and `~` for what was added, removed, and edited.
+    /// When ```color``` is set, these lines are colored green, red, and yellow with ANSI escape codes.
+    #[must_use]
+    pub fn render(&self, color: bool) -> String {
+        let paint = |style: &str, text: &str| {
+            if color {
+                format!("{style}{text}{RESET}")
+            } else {
+                text.to_string()
+            }
+        };
+        let mut out = String::new();
+        for name in &self.removed_functions {
+            writeln!(out, "{}", paint(RED, &format!("- @{name}"))).unwrap();
+        }
+        for name in &self.added_functions {
+            writeln!(out, "{}", paint(GREEN, &format!("+ @{name}"))).unwrap();
+        }
+        for func in &self.changed_functions {
+            writeln!(out, "{}", paint(BOLD, &format!("@{}", func.name))).unwrap();
+            if let Some((old, new)) = &func.signature {
+                writeln!(out, "  signature: {old} => {new}").unwrap();
+            }
+            if !func.renamed.is_empty() {
+                let renamed: Vec<String> = func
+                    .renamed
+                    .iter()
+                    .map(|(old, new)| format!("{old} => {new}"))
+                    .collect();
+                writeln!(out, "  renamed: {}", renamed.join(", ")).unwrap();
+            }
+            for block in &func.blocks {
+                match &block.change {
+                    BlockChange::Added { instrs } => {
+                        writeln!(out, "  {}", paint(GREEN, &format!("+ {}:", block.block)))
+                            .unwrap();
+                        for i in instrs {
+                            writeln!(out, "    {}", paint(GREEN, &format!("+ {i}"))).unwrap();
+                        }
+                    }
+                    BlockChange::Removed { instrs } => {
+                        writeln!(out, "  {}", paint(RED, &format!("- {}:", block.block))).unwrap();
+                        for i in instrs {
+                            writeln!(out, "    {}", paint(RED, &format!("- {i}"))).unwrap();
+                        }
+                    }
+                    BlockChange::Changed { edits } => {
+                        writeln!(out, "  {}:", block.block).unwrap();
+                        for edit in edits {
+                            let line = match edit {
+                                InstrEdit::Insert { new } => paint(GREEN, &format!("+ {new}")),
+                                InstrEdit::Delete { old } => paint(RED, &format!("- {old}")),
+                                InstrEdit::Edit { old, new } => {
+                                    paint(YELLOW, &format!("~ {old} => {new}"))
+                                }
+                            };
+                            writeln!(out, "    {line}").unwrap();
+                        }
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+impl Display for ProgramDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(false))
+    }
+}
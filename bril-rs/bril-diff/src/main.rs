@@ -0,0 +1,57 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::IsTerminal;
+
+use clap::Parser;
+
+use bril_diff::{
+    cli::{Cli, Color},
+    diff_programs,
+};
+use bril_rs::{BrilError, Program, try_load_program_from_abstract_read};
+use bril2json::try_parse_abstract_program_from_read;
+
+fn load(file: &str, text: bool) -> Result<Program, Box<dyn Error>> {
+    if text {
+        let program = try_parse_abstract_program_from_read(
+            File::open(file)?,
+            false,
+            false,
+            Some(file.to_string()),
+        )?;
+        Ok(Program::try_from(program).map_err(BrilError::from)?)
+    } else {
+        Ok(try_load_program_from_abstract_read(File::open(file)?)?)
+    }
+}
+
+/// Prints the differences and returns whether there were any
+fn run(args: Cli) -> Result<bool, Box<dyn Error>> {
+    let old = load(&args.old, args.text)?;
+    let new = load(&args.new, args.text)?;
+    let diff = diff_programs(&old, &new)?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else {
+        let color = match args.color {
+            Color::Auto => std::io::stdout().is_terminal(),
+            Color::Always => true,
+            Color::Never => false,
+        };
+        print!("{}", diff.render(color));
+    }
+
+    Ok(!diff.is_empty())
+}
+
+fn main() {
+    match run(Cli::parse()) {
+        Ok(false) => {}
+        Ok(true) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(2);
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use bril_diff::{BlockChange, BlockId, InstrEdit, ProgramDiff, diff_programs};
+use bril_rs::{Instruction, Program};
+
+fn parse(source: &str) -> Program {
+    bril2json::parse_abstract_program_from_read(source.as_bytes(), false, false, None)
+        .try_into()
+        .unwrap()
+}
+
+fn diff(old: &str, new: &str) -> ProgramDiff {
+    diff_programs(&parse(old), &parse(new)).unwrap()
+}
+
+const LOOP: &str = "@main(n: int) {
+  i: int = const 0;
+  one: int = const 1;
+.loop:
+  cond: bool = lt i n;
+  br cond .body .done;
+.body:
+  i: int = add i one;
+  jmp .loop;
+.done:
+  print i;
+}
+";
+
+#[test]
+fn identical_programs_have_no_diff() {
+    let diff = diff(LOOP, LOOP);
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "");
+}
+
+#[test]
+fn consistent_renaming_is_not_a_change() {
+    let renamed = "@main(m: int) {
+  x: int = const 0;
+  k: int = const 1;
+.loop:
+  c: bool = lt x m;
+  br c .body .done;
+.body:
+  x: int = add x k;
+  jmp .loop;
+.done:
+  print x;
+}
+";
+    let diff = diff(LOOP, renamed);
+    assert!(diff.is_empty());
+
+    // Only changed functions are reported, so compare the function directly
+    let old = parse(LOOP);
+    let new = parse(renamed);
+    let func = bril_diff::diff_functions(&old.functions[0], &new.functions[0]).unwrap();
+    assert!(func.is_empty());
+    let renamed: Vec<_> = func
+        .renamed
+        .iter()
+        .map(|(o, n)| (o.as_str(), n.as_str()))
+        .collect();
+    assert_eq!(
+        renamed,
+        vec![("cond", "c"), ("i", "x"), ("n", "m"), ("one", "k")]
+    );
+}
+
+#[test]
+fn inserts_deletes_and_edits_are_reported() {
+    let new = LOOP
+        .replace("one: int = const 1;", "one: int = const 2;")
+        .replace("  jmp .loop;", "  print i;\n  jmp .loop;")
+        .replace("  print i;\n}", "}");
+    let diff = diff(LOOP, &new);
+    assert_eq!(diff.changed_functions.len(), 1);
+    let blocks: Vec<_> = diff.changed_functions[0]
+        .blocks
+        .iter()
+        .map(|b| match &b.change {
+            BlockChange::Changed { edits } => (
+                b.block.clone(),
+                edits
+                    .iter()
+                    .map(|e| match e {
+                        InstrEdit::Insert { new } => format!("+ {new}"),
+                        InstrEdit::Delete { old } => format!("- {old}"),
+                        InstrEdit::Edit { old, new } => format!("~ {old} => {new}"),
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            other => panic!("unexpected block change {other:?}"),
+        })
+        .collect();
+    assert_eq!(
+        blocks,
+        vec![
+            (
+                BlockId::Entry,
+                vec!["~ one: int = const 1; => one: int = const 2;".to_string()]
+            ),
+            (
+                BlockId::Label("body".to_string()),
+                vec!["+ print i;".to_string()]
+            ),
+            (
+                BlockId::Label("done".to_string()),
+                vec!["- print i;".to_string()]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn functions_and_blocks_are_matched_by_name() {
+    let old = "@f {
+  ret;
+}
+@main {
+  jmp .a;
+.a:
+  x: int = const 1;
+  jmp .b;
+.b:
+  print x;
+}
+";
+    let new = "@main {
+  jmp .a;
+.c:
+  nop;
+.a:
+  x: int = const 1;
+  print x;
+}
+@g {
+  ret;
+}
+";
+    let diff = diff(old, new);
+    assert_eq!(diff.removed_functions, vec!["f".to_string()]);
+    assert_eq!(diff.added_functions, vec!["g".to_string()]);
+    assert_eq!(
+        diff.to_string(),
+        "- @f
++ @g
+@main
+  .a:
+    - jmp .b;
+    + print x;
+  - .b:
+    - print x;
+  + .c:
+    + nop;
+"
+    );
+}
+
+#[test]
+fn inconsistent_renaming_is_an_edit() {
+    let old = "@main {
+  a: int = const 1;
+  b: int = const 2;
+  c: int = add a b;
+  d: int = add a b;
+  print c d;
+}
+";
+    let new = "@main {
+  a: int = const 1;
+  b: int = const 2;
+  c: int = add a b;
+  d: int = add b a;
+  print c d;
+}
+";
+    let diff = diff(old, new);
+    let func = &diff.changed_functions[0];
+    assert!(func.renamed.is_empty());
+    let [block] = func.blocks.as_slice() else {
+        panic!("expected one changed block")
+    };
+    match &block.change {
+        BlockChange::Changed { edits } => match edits.as_slice() {
+            [InstrEdit::Edit { old, new }] => {
+                assert!(matches!(&**old, Instruction::Value { args, .. } if args == &["a", "b"]));
+                assert!(matches!(&**new, Instruction::Value { args, .. } if args == &["b", "a"]));
+            }
+            other => panic!("expected one edit, found {other:?}"),
+        },
+        other => panic!("unexpected block change {other:?}"),
+    }
+}
+
+#[test]
+fn json_output_tags_each_change() {
+    let new = LOOP.replace("  print i;\n}", "}");
+    let json = serde_json::to_value(diff(LOOP, &new)).unwrap();
+    assert_eq!(
+        json["changed_functions"][0]["blocks"][0],
+        serde_json::json!({
+            "block": {"label": "done"},
+            "change": {"kind": "changed", "edits": [{"kind": "delete", "old": {"op": "print", "args": ["i"]}}]}
+        })
+    );
+}
+
+#[test]
+fn reported_instructions_leave_out_comments() {
+    let parse = |source: &str| -> Program {
+        let options = bril2json::PositionOptions {
+            comments: true,
+            ..bril2json::PositionOptions::new(true, false)
+        };
+        bril2json::try_parse_abstract_program_with_options(source.as_bytes(), options, None)
+            .unwrap()
+            .try_into()
+            .unwrap()
+    };
+    let new = LOOP.replace(
+        "  jmp .loop;",
+        "  # checking\n  print i; # the counter\n  jmp .loop;",
+    );
+    let diff = diff_programs(&parse(LOOP), &parse(&new)).unwrap();
+    let BlockChange::Changed { edits } = &diff.changed_functions[0].blocks[0].change else {
+        panic!(
+            "unexpected block change {:?}",
+            diff.changed_functions[0].blocks[0]
+        );
+    };
+    let [InstrEdit::Insert { new }] = edits.as_slice() else {
+        panic!("unexpected edits {edits:?}");
+    };
+    assert_eq!(new.to_string(), "print i;");
+    assert!(matches!(
+        new,
+        Instruction::Effect {
+            comments: None,
+            pos: None,
+            ..
+        }
+    ));
+}
@@ -0,0 +1,26 @@
+use std::path::PathBuf;
+
+use bril_rs::extension::ExtensionSet;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(about, version, author)] // keeps the cli synced with Cargo.toml
+pub struct Cli {
+    /// Bril files to run, in text form if they end in `.bril` and as JSON otherwise. Directories are searched for `.bril` and `.json` files.
+    pub paths: Vec<PathBuf>,
+    /// A backend as `name=command` or `name:extensions=command`. The command is run by the shell with the program as JSON on stdin and the arguments of `main` after it. `brilirs`, `brilift`, and `brillvm` are used if none are provided, where `brilift` and `brillvm` are asked for their extensions with `--extensions`.
+    #[arg(short, long, action)]
+    pub backend: Vec<String>,
+    /// The number of programs to generate with `bril-gen`
+    #[arg(short, long, action, default_value_t = 0)]
+    pub generate: u64,
+    /// The seed of the first generated program. Each program after it uses the next seed.
+    #[arg(short, long, action, default_value_t = 0)]
+    pub seed: u64,
+    /// The extensions generated programs may use. By default, these are the extensions every backend supports, so that each program is compared across all of them.
+    #[arg(short, long, action)]
+    pub extensions: Option<ExtensionSet>,
+    /// How many seconds a backend may run a program for before it is killed
+    #[arg(short, long, action, default_value_t = 10)]
+    pub timeout: u64,
+}
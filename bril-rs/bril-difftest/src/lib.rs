@@ -0,0 +1,297 @@
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![warn(missing_docs)]
+#![warn(clippy::allow_attributes)]
+#![doc = include_str!("../README.md")]
+
+#[doc(hidden)]
+pub mod cli;
+
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use bril_backend::{Backend, BackendError, Outcome};
+use bril_gen::GenOptions;
+use bril_rs::extension::ExtensionSet;
+use bril_rs::{BrilError, Program, try_load_program_from_read};
+use bril2json::ParseError;
+use thiserror::Error;
+
+/// The errors which stop the harness, as opposed to the disagreements it reports
+#[derive(Error, Debug)]
+pub enum HarnessError {
+    /// A backend could not be made or run
+    #[error(transparent)]
+    Backend(#[from] BackendError),
+    /// A test program could not be read or parsed
+    #[error("{}: {source}", .path.display())]
+    Program {
+        /// The file of the program
+        path: PathBuf,
+        /// What went wrong
+        source: Box<BrilError>,
+    },
+    /// A test program in text form has syntax errors
+    #[error("{}: {source}", .path.display())]
+    Parse {
+        /// The file of the program
+        path: PathBuf,
+        /// Every syntax error in the program
+        source: Box<ParseError>,
+    },
+    /// Fewer than two backends support the extensions a test program uses, so there is nothing to compare it against
+    #[error("{case}: nothing to compare since the extensions it uses, `{used}`, are supported by {}", names(.backends))]
+    TooFewBackends {
+        /// The name of the test case
+        case: String,
+        /// The extensions the program uses
+        used: ExtensionSet,
+        /// The backends which support them
+        backends: Vec<String>,
+    },
+    /// A directory of test programs could not be listed
+    #[error("{}: {source}", .path.display())]
+    Directory {
+        /// The directory
+        path: PathBuf,
+        /// What went wrong
+        source: io::Error,
+    },
+}
+
+fn names(backends: &[String]) -> String {
+    if backends.is_empty() {
+        "none of the backends".to_string()
+    } else {
+        format!("only `{}`", backends.join("`, `"))
+    }
+}
+
+/// A program to run, along with the arguments of `main`
+#[derive(Debug, Clone)]
+pub struct TestCase {
+    /// Where the program came from, like its file or the seed it was generated from
+    pub name: String,
+    /// The program
+    pub program: Program,
+    /// The arguments of `main`
+    pub args: Vec<String>,
+}
+
+/// The arguments from the first `# ARGS:` line of ```source```, as used by `turnt` and `brench`
+#[must_use]
+pub fn parse_args(source: &str) -> Vec<String> {
+    source
+        .lines()
+        .find_map(|line| {
+            line.split_once("ARGS:")
+                .filter(|(before, _)| before.trim() == "#")
+        })
+        .map(|(_, args)| args.split_whitespace().map(ToString::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Reads a test case from a `.bril` file in text form or any other file as JSON
+/// # Errors
+/// Will return an error if the file could not be read or does not contain a valid program
+pub fn load_case(path: &Path) -> Result<TestCase, HarnessError> {
+    let error = |source: BrilError| HarnessError::Program {
+        path: path.to_path_buf(),
+        source: Box::new(source),
+    };
+    let mut source = String::new();
+    File::open(path)
+        .and_then(|mut f| f.read_to_string(&mut source))
+        .map_err(|e| error(e.into()))?;
+    let program = if path.extension().is_some_and(|e| e == "bril") {
+        bril2json::try_parse_abstract_program_from_read(
+            source.as_bytes(),
+            true,
+            true,
+            Some(path.display().to_string()),
+        )
+        .map_err(|source| HarnessError::Parse {
+            path: path.to_path_buf(),
+            source: Box::new(source),
+        })?
+        .try_into()
+        .map_err(|e: bril_rs::conversion::ConversionErrors| error(e.into()))?
+    } else {
+        try_load_program_from_read(source.as_bytes()).map_err(error)?
+    };
+    Ok(TestCase {
+        name: path.display().to_string(),
+        program,
+        args: parse_args(&source),
+    })
+}
+
+/// Reads the test cases in ```path```: the file itself, or every `.bril` and `.json` file under a directory, in order of their paths
+/// # Errors
+/// Will return an error if a directory could not be listed or a program could not be read
+pub fn load_cases(path: &Path) -> Result<Vec<TestCase>, HarnessError> {
+    if !path.is_dir() {
+        return Ok(vec![load_case(path)?]);
+    }
+    let mut files = Vec::new();
+    collect_files(path, &mut files).map_err(|source| HarnessError::Directory {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    files.sort();
+    files.iter().map(|file| load_case(file)).collect()
+}
+
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else if path.extension().is_some_and(|e| e == "bril" || e == "json") {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Generates ```count``` test cases with `bril-gen`, using the seeds which follow the one in ```options```
+#[must_use]
+pub fn generated_cases(options: &GenOptions, count: u64) -> Vec<TestCase> {
+    (0..count)
+        .map(|i| {
+            let seed = options.seed.wrapping_add(i);
+            TestCase {
+                name: format!("bril-gen --seed {seed}"),
+                program: bril_gen::generate(&GenOptions { seed, ..*options }),
+                args: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// The outcomes of a test case on which the backends disagree
+#[derive(Debug, Clone)]
+pub struct Disagreement {
+    /// The name of the test case
+    pub case: String,
+    /// The arguments of `main`
+    pub args: Vec<String>,
+    /// The name of each backend which ran the program, with its outcome
+    pub outcomes: Vec<(String, Outcome)>,
+}
+
+/// The extensions which every one of ```backends``` supports, which are the ones generated programs can use and still be compared
+#[must_use]
+pub fn common_extensions(backends: &[Backend]) -> ExtensionSet {
+    backends
+        .iter()
+        .fold(ExtensionSet::ALL, |set, b| set.intersection(b.extensions))
+}
+
+/// Runs ```case``` on every backend which supports the extensions it uses and compares their stdout, exit status, and `total_dyn_inst` when more than one backend reports it
+/// # Errors
+/// Will return an error if a backend could not be run or fewer than two backends support the extensions ```case``` uses
+pub fn compare(
+    case: &TestCase,
+    backends: &[Backend],
+    timeout: Duration,
+) -> Result<Option<Disagreement>, HarnessError> {
+    let used = ExtensionSet::used_by(&case.program);
+    let supporting: Vec<&Backend> = backends
+        .iter()
+        .filter(|b| used.is_subset(b.extensions))
+        .collect();
+    if supporting.len() < 2 {
+        return Err(HarnessError::TooFewBackends {
+            case: case.name.clone(),
+            used,
+            backends: supporting.iter().map(|b| b.name.clone()).collect(),
+        });
+    }
+    let outcomes = supporting
+        .iter()
+        .map(|b| Ok((b.name.clone(), b.run(&case.program, &case.args, timeout)?)))
+        .collect::<Result<Vec<_>, HarnessError>>()?;
+
+    let (_, first) = &outcomes[0];
+    let counts: Vec<u64> = outcomes.iter().filter_map(|(_, o)| o.dyn_inst).collect();
+    let agree = outcomes
+        .iter()
+        .all(|(_, o)| o.stdout.lines().eq(first.stdout.lines()) && o.exit == first.exit)
+        && counts.windows(2).all(|w| w[0] == w[1]);
+    Ok((!agree).then(|| Disagreement {
+        case: case.name.clone(),
+        args: case.args.clone(),
+        outcomes,
+    }))
+}
+
+/// Groups the names of the backends by the value ```key``` gives their outcomes, in order of first appearance
+fn group<T: PartialEq>(
+    outcomes: &[(String, Outcome)],
+    key: impl Fn(&Outcome) -> Option<T>,
+) -> Vec<(T, Vec<&str>)> {
+    let mut groups: Vec<(T, Vec<&str>)> = Vec::new();
+    for (name, outcome) in outcomes {
+        let Some(value) = key(outcome) else { continue };
+        match groups.iter_mut().find(|(v, _)| *v == value) {
+            Some((_, names)) => names.push(name),
+            None => groups.push((value, vec![name])),
+        }
+    }
+    groups
+}
+
+fn write_groups<T: Display>(f: &mut Formatter<'_>, groups: &[(T, Vec<&str>)]) -> fmt::Result {
+    for (value, names) in groups {
+        writeln!(f, "    {}: {value}", names.join(", "))?;
+    }
+    Ok(())
+}
+
+/// Reports only what differs: the first line of stdout on which the backends disagree, their exit statuses, and their instruction counts
+impl Display for Disagreement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.args.is_empty() {
+            writeln!(f, "{}", self.case)?;
+        } else {
+            writeln!(f, "{} (args: {})", self.case, self.args.join(" "))?;
+        }
+
+        let lines: Vec<Vec<&str>> = self
+            .outcomes
+            .iter()
+            .map(|(_, o)| o.stdout.lines().collect())
+            .collect();
+        let longest = lines.iter().map(Vec::len).max().unwrap_or(0);
+        if let Some(line) =
+            (0..longest).find(|&i| lines.iter().any(|l| l.get(i) != lines[0].get(i)))
+        {
+            writeln!(f, "  stdout differs on line {}:", line + 1)?;
+            let groups = group(&self.outcomes, |o| {
+                Some(
+                    o.stdout
+                        .lines()
+                        .nth(line)
+                        .map_or_else(|| "<end of output>".to_string(), |l| format!("`{l}`")),
+                )
+            });
+            write_groups(f, &groups)?;
+        }
+
+        let exits = group(&self.outcomes, |o| Some(o.exit));
+        if exits.len() > 1 {
+            writeln!(f, "  exit status differs:")?;
+            write_groups(f, &exits)?;
+        }
+
+        let counts = group(&self.outcomes, |o| o.dyn_inst);
+        if counts.len() > 1 {
+            writeln!(f, "  total_dyn_inst differs:")?;
+            write_groups(f, &counts)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use clap::Parser;
+
+use bril_backend::Backend;
+use bril_difftest::{
+    HarnessError, cli::Cli, common_extensions, compare, generated_cases, load_cases,
+};
+use bril_gen::GenOptions;
+
+fn main() -> Result<(), HarnessError> {
+    let args = Cli::parse();
+
+    let backends = if args.backend.is_empty() {
+        Backend::defaults()?
+    } else {
+        args.backend
+            .iter()
+            .map(|b| b.parse())
+            .collect::<Result<_, _>>()?
+    };
+
+    let mut cases = Vec::new();
+    for path in &args.paths {
+        cases.extend(load_cases(path)?);
+    }
+    cases.extend(generated_cases(
+        &GenOptions {
+            seed: args.seed,
+            extensions: args
+                .extensions
+                .unwrap_or_else(|| common_extensions(&backends)),
+            ..GenOptions::default()
+        },
+        args.generate,
+    ));
+
+    let timeout = Duration::from_secs(args.timeout);
+    let mut disagreements = 0;
+    for case in &cases {
+        if let Some(disagreement) = compare(case, &backends, timeout)? {
+            disagreements += 1;
+            print!("{disagreement}");
+        }
+    }
+    eprintln!(
+        "{} programs, {disagreements} with disagreements",
+        cases.len()
+    );
+
+    if disagreements > 0 {
+        std::process::exit(1);
+    }
+    Ok(())
+}
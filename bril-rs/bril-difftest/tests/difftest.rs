@@ -0,0 +1,126 @@
+use std::path::Path;
+use std::time::Duration;
+
+use bril_backend::Backend;
+use bril_difftest::{
+    HarnessError, TestCase, common_extensions, compare, load_case, load_cases, parse_args,
+};
+use bril_rs::extension::ExtensionSet;
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn case(source: &str) -> TestCase {
+    TestCase {
+        name: "case".to_string(),
+        program: bril2json::parse_abstract_program_from_read(source.as_bytes(), false, false, None)
+            .try_into()
+            .unwrap(),
+        args: parse_args(source),
+    }
+}
+
+fn backends(specs: &[&str]) -> Vec<Backend> {
+    specs.iter().map(|s| s.parse().unwrap()).collect()
+}
+
+const PRINT: &str = "# ARGS: 4 true
+@main(n: int, b: bool) {
+  print n b;
+}
+";
+
+#[test]
+fn args_come_from_the_header() {
+    assert_eq!(parse_args(PRINT), vec!["4", "true"]);
+    assert_eq!(parse_args("@main {}\n# ARGS: -1\n"), vec!["-1"]);
+    assert_eq!(
+        parse_args("# not ARGS: 1\n@main {}\n"),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn agreeing_backends_are_not_reported() {
+    let case = case(PRINT);
+    let backends = backends(&[
+        "a=cat >/dev/null; echo \"$@\"",
+        "b=cat >/dev/null; echo \"$1\" \"$2\"",
+    ]);
+    assert!(compare(&case, &backends, TIMEOUT).unwrap().is_none());
+}
+
+#[test]
+fn only_the_first_difference_is_reported() {
+    let case = case(PRINT);
+    let backends = backends(&[
+        "a=cat >/dev/null; printf '1\\n2\\n3\\n'; echo 'total_dyn_inst: 5' >&2; true",
+        "b=cat >/dev/null; printf '1\\n2\\n4\\n'; echo 'total_dyn_inst: 5' >&2; true",
+        "c=cat >/dev/null; printf '1\\n2\\n3\\n'; echo 'total_dyn_inst: 6' >&2; true",
+        "d=cat >/dev/null; printf '1\\n'; exit 2",
+    ]);
+    let disagreement = compare(&case, &backends, TIMEOUT).unwrap().unwrap();
+    assert_eq!(
+        disagreement.to_string(),
+        "case (args: 4 true)
+  stdout differs on line 2:
+    a, b, c: `2`
+    d: <end of output>
+  exit status differs:
+    a, b, c: 0
+    d: 2
+  total_dyn_inst differs:
+    a, b: 5
+    c: 6
+"
+    );
+}
+
+#[test]
+fn programs_are_only_given_to_backends_which_support_them() {
+    let case = case("@main {\n  x: float = const 0.5;\n  print x;\n}\n");
+    let backends = backends(&[
+        "a=cat >/dev/null; echo 0.5",
+        "b:float=cat >/dev/null; echo 0.5",
+        "c:core=echo wrong",
+    ]);
+    assert!(compare(&case, &backends, TIMEOUT).unwrap().is_none());
+}
+
+#[test]
+fn programs_only_one_backend_supports_are_errors() {
+    let case = case("@main {\n  x: float = const 0.5;\n  print x;\n}\n");
+    let backends = backends(&["a=cat >/dev/null; echo 0.5", "b:core=echo wrong"]);
+    assert!(matches!(
+        compare(&case, &backends, TIMEOUT),
+        Err(HarnessError::TooFewBackends { backends, .. }) if backends == ["a"]
+    ));
+}
+
+#[test]
+fn generated_programs_use_the_extensions_every_backend_supports() {
+    let backends = backends(&["a=true", "b:float,memory=true", "c:memory,char=true"]);
+    assert_eq!(
+        common_extensions(&backends),
+        "memory".parse::<ExtensionSet>().unwrap()
+    );
+}
+
+#[test]
+fn malformed_programs_are_errors() {
+    let path = std::env::temp_dir().join("bril-difftest-malformed.bril");
+    std::fs::write(&path, "@main {\n  print x\n").unwrap();
+    assert!(matches!(load_case(&path), Err(HarnessError::Parse { .. })));
+}
+
+#[test]
+fn directories_are_searched_for_programs() {
+    let cases = load_cases(Path::new("../../benchmarks/core")).unwrap();
+    assert!(cases.len() > 10);
+    assert!(cases.windows(2).all(|w| w[0].name < w[1].name));
+    let ackermann = cases
+        .iter()
+        .find(|c| c.name.ends_with("ackermann.bril"))
+        .unwrap();
+    assert_eq!(ackermann.args, vec!["3", "6"]);
+    assert!(ackermann.program.functions.iter().any(|f| f.name == "ack"));
+}
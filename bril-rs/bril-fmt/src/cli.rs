@@ -0,0 +1,21 @@
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(about, version, author)] // keeps the cli synced with Cargo.toml
+pub struct Cli {
+    /// The Bril file to format. stdin is assumed if file is not provided.
+    #[arg(short, long, action)]
+    pub file: Option<String>,
+    /// Only check whether the program is already formatted, exiting with status 1 if it is not
+    #[arg(long, action)]
+    pub check: bool,
+    /// The number of spaces instructions are indented by
+    #[arg(short, long, action, default_value_t = 2)]
+    pub indent: usize,
+    /// The number of spaces labels are indented by
+    #[arg(short, long, action, default_value_t = 0)]
+    pub label_indent: usize,
+    /// Remove the blank lines inside of functions instead of keeping them
+    #[arg(long, action)]
+    pub no_blank_lines: bool,
+}
@@ -0,0 +1,343 @@
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![warn(missing_docs)]
+#![warn(clippy::allow_attributes)]
+#![doc = include_str!("../README.md")]
+
+#[doc(hidden)]
+pub mod cli;
+
+use std::fmt::Write;
+
+use bril_rs::{
+    AbstractCode, AbstractFunction, AbstractInstruction, AbstractProgram, AbstractType, Comments,
+    Import, Literal, Position,
+};
+use bril2json::{ParseError, PositionOptions, try_parse_abstract_program_with_options};
+use thiserror::Error;
+
+/// How [`format_source`] lays out a program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// The number of spaces instructions are indented by
+    pub indent: usize,
+    /// The number of spaces labels are indented by
+    pub label_indent: usize,
+    /// Whether a blank line is kept wherever the original program had one or more blank lines. Functions are always separated by exactly one blank line.
+    pub keep_blank_lines: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent: 2,
+            label_indent: 0,
+            keep_blank_lines: true,
+        }
+    }
+}
+
+/// The reasons [`check_formatted`] can reject a program
+#[derive(Error, Debug)]
+pub enum FormatError {
+    /// The program is not well-formed Bril text
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    /// Formatting would change the program, starting at this line
+    #[error("not formatted, the first difference is on line {0}")]
+    Unformatted(usize),
+    /// Formatting the formatted program changes it again at this line, which is a bug in the formatter
+    #[error(
+        "formatting is not idempotent, the formatted program changes on line {0} when it is formatted again"
+    )]
+    NotIdempotent(usize),
+}
+
+/// Formats the Bril text in ```source``` according to ```options```, keeping its comments
+/// # Errors
+/// Will return every syntax error if ```source``` is not well-formed Bril text
+pub fn format_source(source: &str, options: &FormatOptions) -> Result<String, ParseError> {
+    let position_options = PositionOptions {
+        comments: true,
+        ..PositionOptions::new(true, true)
+    };
+    let program =
+        try_parse_abstract_program_with_options(source.as_bytes(), position_options, None)?;
+    Ok(Printer::new(source, options).program(&program))
+}
+
+/// Checks that ```source``` is already formatted according to ```options```, and that formatting it again does not change it
+/// # Errors
+/// Will return an error naming the first line that formatting changes, or every syntax error if ```source``` is not well-formed Bril text
+pub fn check_formatted(source: &str, options: &FormatOptions) -> Result<(), FormatError> {
+    let formatted = format_source(source, options)?;
+    if let Some(line) = first_difference(&formatted, &format_source(&formatted, options)?) {
+        return Err(FormatError::NotIdempotent(line));
+    }
+    first_difference(source, &formatted).map_or(Ok(()), |line| Err(FormatError::Unformatted(line)))
+}
+
+/// The line number, starting from 1, of the first line which differs between ```a``` and ```b```
+fn first_difference(a: &str, b: &str) -> Option<usize> {
+    if a == b {
+        return None;
+    }
+    let common = a
+        .lines()
+        .zip(b.lines())
+        .position(|(x, y)| x != y)
+        .unwrap_or_else(|| a.lines().count().min(b.lines().count()));
+    Some(common + 1)
+}
+
+/// The first and last rows of the code at ```pos```, which is always recorded by [`format_source`]
+fn rows(pos: Option<&Position>) -> (usize, usize) {
+    let row = |r: u64| usize::try_from(r).unwrap_or_default();
+    pos.map_or((0, 0), |p| {
+        let start = row(p.pos.row);
+        (start, p.pos_end.as_ref().map_or(start, |end| row(end.row)))
+    })
+}
+
+fn code_parts(code: &AbstractCode) -> (Option<&Position>, Option<&Comments>) {
+    match code {
+        AbstractCode::Label { pos, comments, .. }
+        | AbstractCode::Instruction(
+            AbstractInstruction::Constant { pos, comments, .. }
+            | AbstractInstruction::Value { pos, comments, .. }
+            | AbstractInstruction::Effect { pos, comments, .. },
+        ) => (pos.as_ref(), comments.as_deref()),
+    }
+}
+
+struct Printer<'a> {
+    options: &'a FormatOptions,
+    /// Whether each line of the source is blank
+    blank: Vec<bool>,
+    /// Whether nothing has been printed yet since the start of the file, a function, or a function body
+    block_start: bool,
+    out: String,
+}
+
+impl<'a> Printer<'a> {
+    fn new(source: &str, options: &'a FormatOptions) -> Self {
+        Self {
+            options,
+            blank: source.lines().map(|l| l.trim().is_empty()).collect(),
+            block_start: true,
+            out: String::new(),
+        }
+    }
+
+    fn program(mut self, program: &AbstractProgram) -> String {
+        for import in &program.imports {
+            // Imports have no positions, so the blank lines between them are not kept
+            let comments = import.comments.as_deref();
+            self.leading(0, comments, &[]);
+            self.line(0, &import_line(import), comments, false);
+        }
+        for func in &program.functions {
+            if !self.block_start {
+                self.out.push('\n');
+                self.block_start = true;
+            }
+            self.function(func);
+        }
+        if let Some(after) = program
+            .functions
+            .last()
+            .and_then(|f| f.comments.as_deref())
+            .map(|c| &c.after)
+        {
+            let gaps = self.gaps_before(self.blank.len() + 1, after.len());
+            for (comment, gap) in after.iter().zip(gaps) {
+                self.comment(0, comment, gap);
+            }
+        }
+        self.out
+    }
+
+    fn function(&mut self, func: &AbstractFunction) {
+        let mut header = format!("@{}", func.name);
+        if !func.args.is_empty() {
+            let args: Vec<String> = func
+                .args
+                .iter()
+                .map(|a| format!("{}: {}", a.name, a.arg_type))
+                .collect();
+            write!(header, "({})", args.join(", ")).unwrap();
+        }
+        header.push_str(&type_annotation(func.return_type.as_ref()));
+        header.push_str(" {");
+        let comments = func.comments.as_deref();
+        let (start, mut end) = rows(func.pos.as_ref());
+        let gaps = self.gaps_before(start, comments.map_or(0, |c| c.leading.len()));
+        self.leading(0, comments, &gaps);
+        self.line(0, &header, comments, false);
+        self.block_start = true;
+
+        for code in &func.instrs {
+            let (text, indent) = match code {
+                AbstractCode::Label { label, .. } => {
+                    (format!(".{label}:"), self.options.label_indent)
+                }
+                AbstractCode::Instruction(i) => (instruction(i), self.options.indent),
+            };
+            let (pos, comments) = code_parts(code);
+            let (start, code_end) = rows(pos);
+            let gaps = self.gaps_before(start, comments.map_or(0, |c| c.leading.len()));
+            self.leading(indent, comments, &gaps);
+            self.line(
+                indent,
+                &text,
+                comments,
+                gaps.last().copied().unwrap_or_default(),
+            );
+            end = code_end;
+        }
+        // Comments at the end of the body are indented like the instructions before them, but the `}` never has a blank line before it
+        let closing = comments.map_or(&[][..], |c| &c.closing);
+        let gaps = self.gaps_after(end, closing.len());
+        for (comment, gap) in closing.iter().zip(gaps) {
+            self.comment(self.options.indent, comment, gap);
+        }
+        self.line(0, "}", None, false);
+    }
+
+    /// Whether the source has a blank line before each of the ```count``` lines of comments which come just before ```row```, and before ```row``` itself
+    fn gaps_before(&self, row: usize, count: usize) -> Vec<bool> {
+        let mut gaps = vec![false; count + 1];
+        // The number of lines before the one being looked at, which is also the index of the line before it
+        let mut above = row.saturating_sub(1);
+        for gap in gaps.iter_mut().rev() {
+            while above > 0 && self.blank[above - 1] {
+                *gap = true;
+                above -= 1;
+            }
+            above = above.saturating_sub(1);
+        }
+        gaps
+    }
+
+    /// Whether the source has a blank line before each of the ```count``` lines of comments which come just after ```row```
+    fn gaps_after(&self, row: usize, count: usize) -> Vec<bool> {
+        let mut gaps = vec![false; count];
+        // Rows count from 1, so this is the index of the line after ```row```
+        let mut below = row;
+        for gap in &mut gaps {
+            while self.blank.get(below).copied().unwrap_or_default() {
+                *gap = true;
+                below += 1;
+            }
+            below += 1;
+        }
+        gaps
+    }
+
+    /// Prints the leading ```comments``` of some code, where ```gaps``` says which of them had a blank line before them
+    fn leading(&mut self, indent: usize, comments: Option<&Comments>, gaps: &[bool]) {
+        for (i, comment) in comments.iter().flat_map(|c| &c.leading).enumerate() {
+            self.comment(indent, comment, gaps.get(i).copied().unwrap_or_default());
+        }
+    }
+
+    fn comment(&mut self, indent: usize, comment: &str, gap: bool) {
+        self.line(indent, &format!("#{comment}"), None, gap);
+    }
+
+    /// Prints ```text``` on its own line followed by the trailing comment in ```comments```, after a blank line if ```gap``` is set and blank lines are kept
+    fn line(&mut self, indent: usize, text: &str, comments: Option<&Comments>, gap: bool) {
+        if gap && self.options.keep_blank_lines && !self.block_start {
+            self.out.push('\n');
+        }
+        self.block_start = false;
+        self.out.push_str(&" ".repeat(indent));
+        self.out.push_str(text);
+        if let Some(comment) = comments.and_then(|c| c.trailing.as_ref()) {
+            write!(self.out, " #{}", comment.trim_end()).unwrap();
+        }
+        self.out.push('\n');
+    }
+}
+
+fn import_line(import: &Import) -> String {
+    let names: Vec<String> = import
+        .functions
+        .iter()
+        .map(|f| {
+            f.alias.as_ref().map_or_else(
+                || format!("@{}", f.name),
+                |alias| format!("@{} as @{alias}", f.name),
+            )
+        })
+        .collect();
+    if names.is_empty() {
+        format!("from \"{}\" import;", import.path.display())
+    } else {
+        format!(
+            "from \"{}\" import {};",
+            import.path.display(),
+            names.join(", ")
+        )
+    }
+}
+
+fn type_annotation(t: Option<&AbstractType>) -> String {
+    t.map_or_else(String::new, |t| format!(": {t}"))
+}
+
+fn literal(value: &Literal) -> String {
+    match value {
+        // `Display` prints `1.0` as `1`, which would be parsed back as an integer
+        Literal::Float(f) => format!("{f:?}"),
+        // Chars are escaped with `bril_rs`'s `escape_char`, which covers every escape the grammar accepts
+        Literal::Int(_) | Literal::Bool(_) | Literal::Char(_) => value.to_string(),
+    }
+}
+
+/// Prints ```i``` with the function names, then the arguments, then the labels it refers to
+fn instruction(i: &AbstractInstruction) -> String {
+    let operands = |funcs: &[String], args: &[String], labels: &[String]| -> String {
+        funcs
+            .iter()
+            .map(|f| format!(" @{f}"))
+            .chain(args.iter().map(|a| format!(" {a}")))
+            .chain(labels.iter().map(|l| format!(" .{l}")))
+            .collect()
+    };
+    match i {
+        AbstractInstruction::Constant {
+            dest,
+            op,
+            const_type,
+            value,
+            pos: _,
+            comments: _,
+        } => format!(
+            "{dest}{} = {op} {};",
+            type_annotation(const_type.as_ref()),
+            literal(value)
+        ),
+        AbstractInstruction::Value {
+            args,
+            dest,
+            funcs,
+            labels,
+            op,
+            op_type,
+            pos: _,
+            comments: _,
+        } => format!(
+            "{dest}{} = {op}{};",
+            type_annotation(op_type.as_ref()),
+            operands(funcs, args, labels)
+        ),
+        AbstractInstruction::Effect {
+            args,
+            funcs,
+            labels,
+            op,
+            pos: _,
+            comments: _,
+        } => format!("{op}{};", operands(funcs, args, labels)),
+    }
+}
@@ -0,0 +1,38 @@
+use clap::Parser;
+
+use bril_fmt::{FormatOptions, check_formatted, cli::Cli, format_source};
+
+fn main() -> std::io::Result<()> {
+    let args = Cli::parse();
+
+    let mut source = String::new();
+    match &args.file {
+        Some(f) => source = std::fs::read_to_string(f)?,
+        None => {
+            std::io::Read::read_to_string(&mut std::io::stdin().lock(), &mut source)?;
+        }
+    }
+
+    let options = FormatOptions {
+        indent: args.indent,
+        label_indent: args.label_indent,
+        keep_blank_lines: !args.no_blank_lines,
+    };
+
+    let name = args.file.as_deref().unwrap_or("<stdin>");
+    if args.check {
+        if let Err(e) = check_formatted(&source, &options) {
+            eprintln!("{name}: {e}");
+            std::process::exit(1);
+        }
+    } else {
+        match format_source(&source, &options) {
+            Ok(formatted) => print!("{formatted}"),
+            Err(e) => {
+                eprintln!("{name}: {e}");
+                std::process::exit(1);
+            }
+        }
+    }
+    Ok(())
+}
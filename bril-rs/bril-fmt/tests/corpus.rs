@@ -0,0 +1,55 @@
+use std::path::{Path, PathBuf};
+
+use bril_fmt::{FormatOptions, check_formatted, format_source};
+use bril_rs::AbstractProgram;
+use bril2json::{ParseError, PositionOptions, try_parse_abstract_program_with_options};
+
+fn collect_programs(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect_programs(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "bril") {
+            files.push(path);
+        }
+    }
+}
+
+/// Parses ```source``` with its comments but without positions, which formatting changes
+fn parse(source: &str) -> Result<AbstractProgram, ParseError> {
+    let options = PositionOptions {
+        comments: true,
+        ..PositionOptions::new(false, false)
+    };
+    try_parse_abstract_program_with_options(source.as_bytes(), options, None)
+}
+
+#[test]
+fn formatting_is_idempotent_and_keeps_the_program() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let mut files = Vec::new();
+    collect_programs(&root.join("test"), &mut files);
+    collect_programs(&root.join("benchmarks"), &mut files);
+    files.sort();
+
+    let options = FormatOptions::default();
+    for path in files {
+        let source = std::fs::read_to_string(&path).unwrap();
+        // Some tests check that malformed programs are rejected by the parser
+        let Ok(original) = parse(&source) else {
+            assert!(format_source(&source, &options).is_err());
+            continue;
+        };
+        let formatted = format_source(&source, &options).unwrap();
+        // `AbstractProgram` does not implement `PartialEq`, but its `Debug` output covers every field
+        assert_eq!(
+            format!("{:?}", parse(&formatted).unwrap()),
+            format!("{original:?}"),
+            "{} changed meaning or lost comments when formatted",
+            path.display()
+        );
+        if let Err(e) = check_formatted(&formatted, &options) {
+            panic!("{}: {e}", path.display());
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use bril_rs::extension::ExtensionSet;
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(about, version, author)] // keeps the cli synced with Cargo.toml
+pub struct Cli {
+    /// The seed of the generator. A random seed is chosen and printed to stderr if it is not provided.
+    #[arg(short, long, action)]
+    pub seed: Option<u64>,
+    /// A comma separated list of the extensions the program may use, like `float,memory`. Only `float`, `memory`, and `char` are generated.
+    #[arg(short, long, action, default_value = "float,memory,char")]
+    pub extensions: ExtensionSet,
+    /// The number of functions besides `main`
+    #[arg(long, action, default_value_t = 3)]
+    pub functions: usize,
+    /// How deeply loops may be nested
+    #[arg(long, action, default_value_t = 2)]
+    pub loop_depth: usize,
+    /// How deeply loops and conditionals together may be nested
+    #[arg(long, action, default_value_t = 3)]
+    pub nesting: usize,
+    /// The most statements in a block
+    #[arg(long, action, default_value_t = 8)]
+    pub block_size: usize,
+    /// The probability that a function allocates memory and that a statement accesses it, from 0 to 1
+    #[arg(long, action, default_value_t = 0.3)]
+    pub memory: f64,
+    /// The probability that a new value is a float, from 0 to 1
+    #[arg(long, action, default_value_t = 0.2)]
+    pub floats: f64,
+    /// Flag to output the program in text form instead of JSON
+    #[arg(long, action)]
+    pub text_output: bool,
+}
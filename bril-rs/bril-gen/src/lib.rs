@@ -0,0 +1,575 @@
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![warn(missing_docs)]
+#![warn(clippy::allow_attributes)]
+#![doc = include_str!("../README.md")]
+
+#[doc(hidden)]
+pub mod cli;
+
+use bril_rs::builder::{FunctionBuilder, ProgramBuilder};
+use bril_rs::extension::{Extension, ExtensionSet};
+use bril_rs::{Literal, Program, Type, ValueOps};
+
+/// The knobs of [generate]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenOptions {
+    /// The seed of the random number generator. The same options always generate the same program.
+    pub seed: u64,
+    /// The extensions the program may use. Of these, only [`Extension::Float`], [`Extension::Memory`], and [`Extension::Char`] are generated.
+    pub extensions: ExtensionSet,
+    /// The number of functions besides `main`
+    pub functions: usize,
+    /// How deeply loops may be nested
+    pub max_loop_depth: usize,
+    /// How deeply loops and conditionals together may be nested
+    pub max_nesting: usize,
+    /// The most statements in a block, where a loop or a conditional counts as one statement
+    pub block_size: usize,
+    /// The most times a loop runs
+    pub max_trip_count: i64,
+    /// The probability that a function allocates memory and that a statement accesses it, from 0 to 1
+    pub memory: f64,
+    /// The probability that a new value is a float, from 0 to 1
+    pub floats: f64,
+}
+
+impl Default for GenOptions {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            extensions: ExtensionSet::CORE
+                .with(Extension::Float)
+                .with(Extension::Memory)
+                .with(Extension::Char),
+            functions: 3,
+            max_loop_depth: 2,
+            max_nesting: 3,
+            block_size: 8,
+            max_trip_count: 5,
+            memory: 0.3,
+            floats: 0.2,
+        }
+    }
+}
+
+const MAX_ARGS: usize = 3;
+const MAX_ARRAY_LEN: i64 = 8;
+const CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+const EXTREME_INTS: [i64; 4] = [i64::MAX, i64::MIN, i64::MAX - 1, i64::MIN + 1];
+
+/// Generates a random program from ```options```
+///
+/// The program is well-formed, only uses the extensions in [`GenOptions::extensions`], and runs to completion without errors:
+/// - Every loop counts up to a small constant bound and no statement in its body assigns to the counter.
+/// - Functions only call the functions after them in the program, and never from inside a loop, so there is no recursion.
+/// - Integer division is always by a constant other than `0` and `-1`, and float division by a constant other than `0`.
+/// - Memory is allocated when a function starts, every element is stored before the first access, every access is in bounds, and everything is freed before the function returns.
+/// - Every variable is assigned before its first use on every path to that use.
+///
+/// `main` takes no arguments and prints some of its variables before it returns. Integer arithmetic may overflow, which wraps around.
+#[must_use]
+pub fn generate(options: &GenOptions) -> Program {
+    let mut rng = Rng(options.seed);
+
+    let mut signatures = vec![Signature {
+        name: "main".to_string(),
+        args: Vec::new(),
+        return_type: None,
+    }];
+    for i in 1..=options.functions {
+        let args = (0..rng.below(MAX_ARGS + 1))
+            .map(|_| value_type(&mut rng, options))
+            .collect();
+        let return_type = rng.chance(0.7).then(|| value_type(&mut rng, options));
+        signatures.push(Signature {
+            name: format!("f{i}"),
+            args,
+            return_type,
+        });
+    }
+
+    let mut program = ProgramBuilder::new();
+    for index in 0..signatures.len() {
+        let func = FunctionGen::new(options, &mut rng, &signatures, index).generate();
+        program.add_function(func);
+    }
+    program.build()
+}
+
+/// `SplitMix64`, which is small enough to keep here so that a seed keeps generating the same program regardless of the versions of our dependencies
+struct Rng(u64);
+
+impl Rng {
+    const fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number from `0` up to but not including ```n```
+    fn below(&mut self, n: usize) -> usize {
+        usize::try_from(self.next() % n as u64).expect("the remainder is less than a usize")
+    }
+
+    /// A number from ```lo``` to ```hi```, inclusive
+    const fn int(&mut self, lo: i64, hi: i64) -> i64 {
+        let span = hi.abs_diff(lo) + 1;
+        lo.wrapping_add_unsigned(self.next() % span)
+    }
+
+    /// A number from ```lo``` to ```hi```, inclusive, small enough to convert into a float without losing precision
+    fn small(&mut self, lo: i32, hi: i32) -> i32 {
+        i32::try_from(self.int(lo.into(), hi.into())).expect("the number is between two i32s")
+    }
+
+    fn chance(&mut self, p: f64) -> bool {
+        let x = u32::try_from(self.next() >> 32).expect("the top 32 bits fit in a u32");
+        f64::from(x) < p * f64::from(u32::MAX)
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+}
+
+/// The type of a value which is not a pointer
+fn value_type(rng: &mut Rng, options: &GenOptions) -> Type {
+    if options.extensions.contains(Extension::Float) && rng.chance(options.floats) {
+        return Type::Float;
+    }
+    let types: &[Type] = if options.extensions.contains(Extension::Char) {
+        &[Type::Int, Type::Int, Type::Bool, Type::Char]
+    } else {
+        &[Type::Int, Type::Int, Type::Bool]
+    };
+    rng.pick(types).clone()
+}
+
+struct Signature {
+    name: String,
+    args: Vec<Type>,
+    return_type: Option<Type>,
+}
+
+struct Var {
+    name: String,
+    value_type: Type,
+    /// Loop counters can not be assigned to, or their loop might not terminate
+    mutable: bool,
+}
+
+struct Array {
+    ptr: String,
+    len: i64,
+    elem_type: Type,
+}
+
+struct Counter {
+    name: String,
+    trip_count: i64,
+}
+
+/// The state of generating one function. Variables go out of scope at the end of the block they were first assigned in, so that they are always assigned before they are used.
+struct FunctionGen<'a> {
+    options: &'a GenOptions,
+    rng: &'a mut Rng,
+    signatures: &'a [Signature],
+    index: usize,
+    b: FunctionBuilder,
+    vars: Vec<Var>,
+    arrays: Vec<Array>,
+    counters: Vec<Counter>,
+    loop_depth: usize,
+    nesting: usize,
+}
+
+impl<'a> FunctionGen<'a> {
+    fn new(
+        options: &'a GenOptions,
+        rng: &'a mut Rng,
+        signatures: &'a [Signature],
+        index: usize,
+    ) -> Self {
+        let signature = &signatures[index];
+        Self {
+            options,
+            rng,
+            signatures,
+            index,
+            b: FunctionBuilder::new(&signature.name, signature.return_type.clone()),
+            vars: Vec::new(),
+            arrays: Vec::new(),
+            counters: Vec::new(),
+            loop_depth: 0,
+            nesting: 0,
+        }
+    }
+
+    fn generate(mut self) -> bril_rs::Function {
+        let signatures = self.signatures;
+        let signature = &signatures[self.index];
+        for (i, arg_type) in signature.args.iter().enumerate() {
+            let name = self.b.add_arg(&format!("arg{i}"), arg_type.clone());
+            self.vars.push(Var {
+                name,
+                value_type: arg_type.clone(),
+                mutable: true,
+            });
+        }
+
+        if self.options.extensions.contains(Extension::Memory)
+            && self.rng.chance(self.options.memory)
+        {
+            for _ in 0..=self.rng.below(2) {
+                self.alloc();
+            }
+        }
+
+        self.block();
+
+        if self.index == 0 {
+            self.print();
+        }
+        for array in std::mem::take(&mut self.arrays) {
+            self.b.free(&array.ptr);
+        }
+        if let Some(return_type) = &signature.return_type {
+            let value = self.operand(return_type);
+            self.b.ret(Some(&value));
+        }
+        self.b.build()
+    }
+
+    fn value_type(&mut self) -> Type {
+        value_type(self.rng, self.options)
+    }
+
+    fn literal(&mut self, t: &Type) -> Literal {
+        match t {
+            Type::Int if self.rng.chance(0.05) => Literal::Int(*self.rng.pick(&EXTREME_INTS)),
+            Type::Int => Literal::Int(self.rng.int(-100, 100)),
+            Type::Bool => Literal::Bool(self.rng.chance(0.5)),
+            Type::Float => Literal::Float(f64::from(self.rng.small(-40, 40)) / 4.0),
+            Type::Char => Literal::Char(char::from(*self.rng.pick(CHARS))),
+            t => unreachable!("values of type {t} are not generated"),
+        }
+    }
+
+    /// A fresh constant of type ```t```, which stays in scope until the end of the current block
+    fn constant(&mut self, t: &Type) -> String {
+        let value = self.literal(t);
+        let name = self.b.constant(value);
+        self.vars.push(Var {
+            name: name.clone(),
+            value_type: t.clone(),
+            mutable: true,
+        });
+        name
+    }
+
+    /// A variable of type ```t``` to use as an argument, which is usually one already in scope
+    fn operand(&mut self, t: &Type) -> String {
+        let candidates: Vec<&str> = self
+            .vars
+            .iter()
+            .filter(|v| v.value_type == *t)
+            .map(|v| v.name.as_str())
+            .collect();
+        if candidates.is_empty() || self.rng.chance(0.2) {
+            self.constant(t)
+        } else {
+            (*self.rng.pick(&candidates)).to_string()
+        }
+    }
+
+    /// A constant integer other than `0` and `-1` to divide by
+    fn divisor(&mut self) -> String {
+        let value = self.rng.int(-9, 8);
+        let value = if value >= -1 { value + 2 } else { value };
+        self.b.constant(Literal::Int(value))
+    }
+
+    /// Emits ```op``` into either a variable in scope or a fresh one and returns it
+    fn emit(&mut self, op: ValueOps, t: &Type, args: &[&str], funcs: &[&str]) -> String {
+        let mutable: Vec<&str> = self
+            .vars
+            .iter()
+            .filter(|v| v.mutable && v.value_type == *t)
+            .map(|v| v.name.as_str())
+            .collect();
+        if !mutable.is_empty() && self.rng.chance(0.25) {
+            let dest = (*self.rng.pick(&mutable)).to_string();
+            self.b.value_into(&dest, op, t.clone(), args, funcs);
+            dest
+        } else {
+            let dest = self.b.fresh_var();
+            self.b.value_into(&dest, op, t.clone(), args, funcs);
+            self.vars.push(Var {
+                name: dest.clone(),
+                value_type: t.clone(),
+                mutable: true,
+            });
+            dest
+        }
+    }
+
+    fn binary(&mut self, op: ValueOps, arg_type: &Type, t: &Type) -> String {
+        let a = self.operand(arg_type);
+        let b = self.operand(arg_type);
+        self.emit(op, t, &[&a, &b], &[])
+    }
+
+    /// Computes a new value of type ```t``` out of the variables in scope
+    fn value(&mut self, t: &Type) {
+        let chars = self.options.extensions.contains(Extension::Char);
+        match t {
+            Type::Int => match self.rng.below(6) {
+                0 => {
+                    self.constant(t);
+                }
+                1 => {
+                    let a = self.operand(t);
+                    let d = self.divisor();
+                    self.emit(ValueOps::Div, t, &[&a, &d], &[]);
+                }
+                2 if chars => {
+                    let c = self.operand(&Type::Char);
+                    self.emit(ValueOps::Char2int, t, &[&c], &[]);
+                }
+                n => {
+                    let op = [ValueOps::Add, ValueOps::Sub, ValueOps::Mul][n % 3];
+                    self.binary(op, t, t);
+                }
+            },
+            Type::Bool => match self.rng.below(5) {
+                0 => {
+                    let a = self.operand(t);
+                    self.emit(ValueOps::Not, t, &[&a], &[]);
+                }
+                1 => {
+                    let op = *self.rng.pick(&[ValueOps::And, ValueOps::Or]);
+                    self.binary(op, t, t);
+                }
+                2 if self.options.extensions.contains(Extension::Float)
+                    && self.rng.chance(self.options.floats) =>
+                {
+                    let op = *self.rng.pick(&[
+                        ValueOps::Feq,
+                        ValueOps::Flt,
+                        ValueOps::Fgt,
+                        ValueOps::Fle,
+                        ValueOps::Fge,
+                    ]);
+                    self.binary(op, &Type::Float, t);
+                }
+                3 if chars => {
+                    let op = *self.rng.pick(&[
+                        ValueOps::Ceq,
+                        ValueOps::Clt,
+                        ValueOps::Cgt,
+                        ValueOps::Cle,
+                        ValueOps::Cge,
+                    ]);
+                    self.binary(op, &Type::Char, t);
+                }
+                _ => {
+                    let op = *self.rng.pick(&[
+                        ValueOps::Eq,
+                        ValueOps::Lt,
+                        ValueOps::Gt,
+                        ValueOps::Le,
+                        ValueOps::Ge,
+                    ]);
+                    self.binary(op, &Type::Int, t);
+                }
+            },
+            Type::Float => match self.rng.below(4) {
+                0 => {
+                    let a = self.operand(t);
+                    let value = match self.rng.small(-8, 7) {
+                        n if n >= 0 => n + 1,
+                        n => n,
+                    };
+                    let d = self.b.constant(Literal::Float(f64::from(value) / 2.0));
+                    self.emit(ValueOps::Fdiv, t, &[&a, &d], &[]);
+                }
+                n => {
+                    let op = [ValueOps::Fadd, ValueOps::Fsub, ValueOps::Fmul][n - 1];
+                    self.binary(op, t, t);
+                }
+            },
+            _ => {
+                let a = self.operand(t);
+                self.emit(ValueOps::Id, t, &[&a], &[]);
+            }
+        }
+    }
+
+    fn statement(&mut self) {
+        if !self.arrays.is_empty() && self.rng.chance(self.options.memory) {
+            self.memory_access();
+            return;
+        }
+        let can_nest = self.nesting < self.options.max_nesting;
+        match self.rng.below(10) {
+            0 if can_nest => self.conditional(),
+            1 if can_nest && self.loop_depth < self.options.max_loop_depth => {
+                self.counted_loop();
+            }
+            2 if self.loop_depth == 0 && self.index + 1 < self.signatures.len() => self.call(),
+            3 => self.print(),
+            _ => {
+                let t = self.value_type();
+                self.value(&t);
+            }
+        }
+    }
+
+    fn block(&mut self) {
+        for _ in 0..=self.rng.below(self.options.block_size.max(1)) {
+            self.statement();
+        }
+    }
+
+    /// Runs ```f``` one level deeper, dropping the variables it assigns first from the scope afterwards
+    fn nested(&mut self, f: impl FnOnce(&mut Self)) {
+        let scope = self.vars.len();
+        self.nesting += 1;
+        f(self);
+        self.nesting -= 1;
+        self.vars.truncate(scope);
+    }
+
+    fn conditional(&mut self) {
+        let cond = self.operand(&Type::Bool);
+        let then_label = self.b.fresh_label();
+        let else_label = self.b.fresh_label();
+        let end_label = self.b.fresh_label();
+        self.b.br(&cond, &then_label, &else_label);
+        self.nested(|g| {
+            g.b.label(&then_label);
+            g.block();
+            g.b.jmp(&end_label);
+        });
+        self.nested(|g| {
+            g.b.label(&else_label);
+            if g.rng.chance(0.7) {
+                g.block();
+            }
+        });
+        self.b.label(&end_label);
+    }
+
+    fn counted_loop(&mut self) {
+        let trip_count = self.rng.int(1, self.options.max_trip_count.max(1));
+        let counter = self.b.constant(Literal::Int(0));
+        let bound = self.b.constant(Literal::Int(trip_count));
+        let one = self.b.constant(Literal::Int(1));
+        let head = self.b.new_block();
+        let body = self.b.fresh_label();
+        let exit = self.b.fresh_label();
+        let cond = self.b.lt(&counter, &bound);
+        self.b.br(&cond, &body, &exit);
+        self.nested(|g| {
+            g.loop_depth += 1;
+            g.vars.push(Var {
+                name: counter.clone(),
+                value_type: Type::Int,
+                mutable: false,
+            });
+            g.counters.push(Counter {
+                name: counter.clone(),
+                trip_count,
+            });
+            g.b.label(&body);
+            g.block();
+            g.b.value_into(&counter, ValueOps::Add, Type::Int, &[&counter, &one], &[]);
+            g.b.jmp(&head);
+            g.counters.pop();
+            g.loop_depth -= 1;
+        });
+        self.b.label(&exit);
+    }
+
+    fn call(&mut self) {
+        let signatures = self.signatures;
+        let callee =
+            &signatures[self.index + 1 + self.rng.below(signatures.len() - self.index - 1)];
+        let args: Vec<String> = callee.args.iter().map(|t| self.operand(t)).collect();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        match &callee.return_type {
+            Some(t) => {
+                self.emit(ValueOps::Call, t, &args, &[&callee.name]);
+            }
+            None => self.b.call_void(&callee.name, &args),
+        }
+    }
+
+    fn print(&mut self) {
+        let args: Vec<String> = (0..=self.rng.below(3))
+            .map(|_| {
+                let t = self.value_type();
+                self.operand(&t)
+            })
+            .collect();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.b.print(&args);
+    }
+
+    /// Allocates an array and stores a value into each of its elements
+    fn alloc(&mut self) {
+        let elem_type = if self.rng.chance(0.5) {
+            Type::Int
+        } else {
+            self.value_type()
+        };
+        let len = self.rng.int(1, MAX_ARRAY_LEN);
+        let size = self.b.constant(Literal::Int(len));
+        let ptr = self.b.alloc(&size, elem_type.clone());
+        for i in 0..len {
+            let value = self.operand(&elem_type);
+            let elem = self.element(&ptr, i);
+            self.b.store(&elem, &value);
+        }
+        self.arrays.push(Array {
+            ptr,
+            len,
+            elem_type,
+        });
+    }
+
+    fn element(&mut self, ptr: &str, i: i64) -> String {
+        if i == 0 {
+            return ptr.to_string();
+        }
+        let offset = self.b.constant(Literal::Int(i));
+        self.b.ptradd(ptr, &offset)
+    }
+
+    /// Loads from or stores to an element of an array, indexed by a loop counter when the loop runs few enough times
+    fn memory_access(&mut self) {
+        let array = &self.arrays[self.rng.below(self.arrays.len())];
+        let (ptr, len, elem_type) = (array.ptr.clone(), array.len, array.elem_type.clone());
+        let counters: Vec<&str> = self
+            .counters
+            .iter()
+            .filter(|c| c.trip_count <= len)
+            .map(|c| c.name.as_str())
+            .collect();
+        let elem = if !counters.is_empty() && self.rng.chance(0.5) {
+            let counter = (*self.rng.pick(&counters)).to_string();
+            self.b.ptradd(&ptr, &counter)
+        } else {
+            let i = self.rng.int(0, len - 1);
+            self.element(&ptr, i)
+        };
+        if self.rng.chance(0.5) {
+            let value = self.operand(&elem_type);
+            self.b.store(&elem, &value);
+        } else {
+            self.emit(ValueOps::Load, &elem_type, &[&elem], &[]);
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::Parser;
+
+use bril_gen::{GenOptions, cli::Cli, generate};
+use bril_rs::{BrilError, try_output_program};
+
+fn main() -> Result<(), BrilError> {
+    let args = Cli::parse();
+
+    let seed = args.seed.unwrap_or_else(|| {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() ^ u64::from(d.subsec_nanos()));
+        eprintln!("seed: {seed}");
+        seed
+    });
+    let program = generate(&GenOptions {
+        seed,
+        extensions: args.extensions,
+        functions: args.functions,
+        max_loop_depth: args.loop_depth,
+        max_nesting: args.nesting,
+        block_size: args.block_size,
+        memory: args.memory,
+        floats: args.floats,
+        ..GenOptions::default()
+    });
+
+    if args.text_output {
+        print!("{program}");
+    } else {
+        try_output_program(&program, std::io::stdout().lock())?;
+    }
+
+    Ok(())
+}
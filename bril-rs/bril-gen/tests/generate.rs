@@ -0,0 +1,103 @@
+use bril_gen::{GenOptions, generate};
+use bril_rs::check::check_program;
+use bril_rs::extension::{Extension, ExtensionSet};
+use bril_rs::{Code, Instruction, Program};
+use brilirs::basic_block::BBProgram;
+
+fn run(prog: &Program) -> String {
+    brilirs::check::type_check(prog).unwrap();
+    let bbprog = BBProgram::try_from(prog.clone()).unwrap();
+    let mut out = Vec::new();
+    brilirs::interp::execute_main(&bbprog, &mut out, &[], false, std::io::sink()).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn generated_programs_are_well_formed_and_terminate() {
+    for seed in 0..200 {
+        let options = GenOptions {
+            seed,
+            ..GenOptions::default()
+        };
+        let prog = generate(&options);
+        assert_eq!(
+            check_program(&prog, options.extensions),
+            Vec::new(),
+            "seed {seed}"
+        );
+        run(&prog);
+    }
+}
+
+#[test]
+fn the_same_seed_generates_the_same_program() {
+    let options = GenOptions {
+        seed: 7,
+        ..GenOptions::default()
+    };
+    assert_eq!(
+        generate(&options).to_string(),
+        generate(&options).to_string()
+    );
+    let other = GenOptions {
+        seed: 8,
+        ..GenOptions::default()
+    };
+    assert_ne!(generate(&options).to_string(), generate(&other).to_string());
+}
+
+#[test]
+fn only_the_chosen_extensions_are_used() {
+    for seed in 0..50 {
+        let prog = generate(&GenOptions {
+            seed,
+            extensions: ExtensionSet::CORE,
+            ..GenOptions::default()
+        });
+        ExtensionSet::CORE.accepts(&prog).unwrap();
+        run(&prog);
+    }
+}
+
+#[test]
+fn knobs_shape_the_program() {
+    let prog = generate(&GenOptions {
+        functions: 5,
+        memory: 1.0,
+        floats: 1.0,
+        ..GenOptions::default()
+    });
+    assert_eq!(prog.functions.len(), 6);
+    let used = ExtensionSet::used_by(&prog);
+    assert!(used.contains(Extension::Memory));
+    assert!(used.contains(Extension::Float));
+    run(&prog);
+
+    let prog = generate(&GenOptions {
+        memory: 0.0,
+        floats: 0.0,
+        max_loop_depth: 0,
+        ..GenOptions::default()
+    });
+    let used = ExtensionSet::used_by(&prog);
+    assert!(!used.contains(Extension::Memory));
+    assert!(!used.contains(Extension::Float));
+    assert!(has_no_back_edges(&prog));
+}
+
+/// Loops are the only code which jumps backwards
+fn has_no_back_edges(prog: &Program) -> bool {
+    prog.functions.iter().all(|f| {
+        let mut seen = Vec::new();
+        f.instrs.iter().all(|code| match code {
+            Code::Label { label, .. } => {
+                seen.push(label.clone());
+                true
+            }
+            Code::Instruction(Instruction::Effect { labels, .. }) => {
+                labels.iter().all(|l| !seen.contains(l))
+            }
+            Code::Instruction(_) => true,
+        })
+    })
+}
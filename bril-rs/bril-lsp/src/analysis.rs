@@ -0,0 +1,759 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use bril_rs::{
+    AbstractArgument, AbstractCode, AbstractFunction, AbstractInstruction, AbstractProgram,
+    Function, Position, Program, try_load_abstract_program_from_read,
+};
+use bril2json::{
+    PositionOptions, Span, SpanKind, parse_abstract_program_recovering,
+    parse_abstract_program_with_spans,
+};
+use brilirs::basic_block::BBProgram;
+use lsp_types::{DocumentSymbol, SymbolKind};
+
+/// A name in a Bril program, which every [`Occurrence`] of it refers to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Symbol {
+    /// A variable of the function at this index of the program
+    Variable {
+        /// The index of the function
+        function: usize,
+        /// The name of the variable
+        name: String,
+    },
+    /// A label of the function at this index of the program
+    Label {
+        /// The index of the function
+        function: usize,
+        /// The name of the label, without its `.`
+        name: String,
+    },
+    /// A function defined in the document or imported into it, by the name it has in the document
+    Function(String),
+    /// A function which is imported under an alias, by the name it has in the file it is imported from
+    Imported {
+        /// The path of the file as it is written in the import
+        path: String,
+        /// The name of the function, without its `@`
+        name: String,
+    },
+}
+
+/// A place where a [`Symbol`] is named in the document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Occurrence {
+    /// The bytes of the name, without its `@` or `.`
+    pub range: Range<usize>,
+    /// What is named
+    pub symbol: Symbol,
+    /// Whether the symbol is defined here, like by the destination of an instruction or the signature of a function
+    pub definition: bool,
+}
+
+/// A problem with the document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// The bytes of the document the problem is about
+    pub range: Range<usize>,
+    /// What is wrong
+    pub message: String,
+    /// The tool which found the problem
+    pub source: &'static str,
+}
+
+/// Where the definition of a symbol is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Target {
+    /// The file the definition is in, or [None] if it is in the document itself
+    pub file: Option<PathBuf>,
+    /// Where the definition is in that file
+    pub range: lsp_types::Range,
+}
+
+/// A function imported into the document
+#[derive(Debug, Clone)]
+struct Import {
+    /// The path of the file as it is written in the import
+    path: String,
+    /// The name of the function in that file
+    name: String,
+    /// The file the import was found in, if it was found
+    file: Option<PathBuf>,
+    /// The definition of the function in that file, without its instructions, and where its name is
+    definition: Option<(AbstractFunction, lsp_types::Range)>,
+}
+
+/// The analysis of one Bril text document, which answers the questions of an editor about it
+#[derive(Debug, Clone)]
+pub struct Document {
+    text: String,
+    lines: LineIndex,
+    program: Option<AbstractProgram>,
+    /// Every occurrence of a symbol, in the order they appear in
+    occurrences: Vec<Occurrence>,
+    /// The functions imported into the document, by the name they have in it
+    imports: HashMap<String, Import>,
+    /// The bytes of each function of the program, from its name to its closing brace
+    function_ranges: Vec<Range<usize>>,
+    /// The type of each definition of a variable, by the byte its name starts at
+    types: HashMap<usize, String>,
+    problems: Vec<Problem>,
+}
+
+impl Document {
+    /// Analyzes the Bril program in ```text```, which is stored at ```path``` if it has been saved.
+    /// Imports are looked up in the directory of ```path``` and then in each of ```libs```, taking the first file which exists.
+    #[must_use]
+    pub fn new(text: String, path: Option<&Path>, libs: &[PathBuf]) -> Self {
+        let options = PositionOptions {
+            byte_spans: true,
+            ..PositionOptions::new(true, true)
+        };
+        let (program, syntax_errors, spans) =
+            parse_abstract_program_with_spans(&text, options, None);
+        let mut document = Self {
+            lines: LineIndex::new(&text),
+            program,
+            occurrences: Vec::new(),
+            imports: HashMap::new(),
+            function_ranges: Vec::new(),
+            types: HashMap::new(),
+            problems: syntax_errors
+                .iter()
+                .map(|e| Problem {
+                    range: bytes(&e.pos).unwrap_or(0..0),
+                    message: e.message(),
+                    source: "bril2json",
+                })
+                .collect(),
+            text,
+        };
+        let dirs: Vec<PathBuf> = path
+            .and_then(Path::parent)
+            .map(Path::to_path_buf)
+            .into_iter()
+            .chain(libs.iter().cloned())
+            .collect();
+        document.index_imports(&spans, &dirs);
+        document.index_functions(&spans);
+        document.occurrences.sort_by_key(|o| o.range.start);
+        if syntax_errors.is_empty() {
+            document.check();
+        }
+        document
+    }
+
+    /// The text of the document
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Every problem found in the document, starting with syntax errors
+    #[must_use]
+    pub fn problems(&self) -> &[Problem] {
+        &self.problems
+    }
+
+    /// The occurrence of a symbol whose name contains the byte at ```offset``` or ends just before it
+    #[must_use]
+    pub fn occurrence_at(&self, offset: usize) -> Option<&Occurrence> {
+        let after = self
+            .occurrences
+            .partition_point(|o| o.range.start <= offset);
+        self.occurrences[..after]
+            .last()
+            .filter(|o| offset <= o.range.end)
+    }
+
+    /// Every occurrence of ```symbol```, in the order they appear in
+    pub fn occurrences_of<'a>(
+        &'a self,
+        symbol: &'a Symbol,
+    ) -> impl Iterator<Item = &'a Occurrence> {
+        self.occurrences.iter().filter(move |o| &o.symbol == symbol)
+    }
+
+    /// Where ```symbol``` is defined. Functions imported from a file which could be found are defined in that file.
+    #[must_use]
+    pub fn definitions(&self, symbol: &Symbol) -> Vec<Target> {
+        let external = match symbol {
+            Symbol::Function(name) => self.imports.get(name),
+            Symbol::Imported { path, name } => self
+                .imports
+                .values()
+                .find(|i| &i.path == path && &i.name == name),
+            Symbol::Variable { .. } | Symbol::Label { .. } => None,
+        };
+        if let Some(Import {
+            file: Some(file),
+            definition: Some((_, range)),
+            ..
+        }) = external
+        {
+            return vec![Target {
+                file: Some(file.clone()),
+                range: *range,
+            }];
+        }
+        self.occurrences_of(symbol)
+            .filter(|o| o.definition)
+            .map(|o| Target {
+                file: None,
+                range: self.range(&o.range),
+            })
+            .collect()
+    }
+
+    /// A description of ```symbol``` in Markdown, which is the type of a variable or the signature of a function
+    #[must_use]
+    pub fn hover(&self, symbol: &Symbol) -> Option<String> {
+        let signature = match symbol {
+            Symbol::Variable { name, .. } => {
+                let mut types: Vec<&String> = self
+                    .occurrences_of(symbol)
+                    .filter(|o| o.definition)
+                    .filter_map(|o| self.types.get(&o.range.start))
+                    .collect();
+                types.sort();
+                types.dedup();
+                if types.is_empty() {
+                    return None;
+                }
+                types
+                    .iter()
+                    .map(|t| format!("{name}: {t}"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+            Symbol::Label { .. } => return None,
+            Symbol::Function(name) => match self.imports.get(name) {
+                Some(import) => imported_signature(name, import),
+                None => signature(self.function(name)?),
+            },
+            Symbol::Imported { path, name } => {
+                let import = self
+                    .imports
+                    .values()
+                    .find(|i| &i.path == path && &i.name == name)?;
+                imported_signature(name, import)
+            }
+        };
+        Some(format!("```bril\n{signature}\n```"))
+    }
+
+    /// The byte ranges to replace with ```new_name``` to rename ```symbol```, which must be the name of a variable, label, or function without its `@` or `.`
+    /// # Errors
+    /// Will return why ```symbol``` can not be renamed to ```new_name```, like when ```new_name``` is already taken
+    pub fn rename(&self, symbol: &Symbol, new_name: &str) -> Result<Vec<Range<usize>>, String> {
+        let valid = new_name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '%')
+            && new_name.chars().all(is_ident_char);
+        if !valid {
+            return Err(format!("`{new_name}` is not a valid name"));
+        }
+        let renamed = match symbol {
+            Symbol::Variable { function, .. } => Symbol::Variable {
+                function: *function,
+                name: new_name.to_string(),
+            },
+            Symbol::Label { function, .. } => Symbol::Label {
+                function: *function,
+                name: new_name.to_string(),
+            },
+            Symbol::Function(name) => {
+                if self.imports.get(name).is_some_and(|i| &i.name == name) {
+                    return Err(format!(
+                        "`@{name}` is defined in another file, import it with `as` to give it another name here"
+                    ));
+                }
+                Symbol::Function(new_name.to_string())
+            }
+            Symbol::Imported { name, .. } => {
+                return Err(format!("`@{name}` is defined in another file"));
+            }
+        };
+        if self.occurrences_of(&renamed).next().is_some() {
+            return Err(format!("`{new_name}` is already used in this scope"));
+        }
+        Ok(self
+            .occurrences_of(symbol)
+            .map(|o| o.range.clone())
+            .collect())
+    }
+
+    /// The functions of the document, with their arguments, variables, and labels as children
+    #[must_use]
+    pub fn symbols(&self) -> Vec<DocumentSymbol> {
+        let Some(program) = &self.program else {
+            return Vec::new();
+        };
+        program
+            .functions
+            .iter()
+            .zip(&self.function_ranges)
+            .enumerate()
+            .filter_map(|(index, (func, range))| {
+                let name = self
+                    .occurrences
+                    .iter()
+                    .find(|o| o.definition && o.symbol == Symbol::Function(func.name.clone()))?;
+                let mut children: Vec<DocumentSymbol> = Vec::new();
+                let mut seen = Vec::new();
+                for o in &self.occurrences {
+                    let (kind, detail) = match &o.symbol {
+                        Symbol::Variable { function, name }
+                            if *function == index && o.definition && !seen.contains(&name) =>
+                        {
+                            seen.push(name);
+                            (
+                                SymbolKind::VARIABLE,
+                                self.types.get(&o.range.start).cloned(),
+                            )
+                        }
+                        Symbol::Label { function, .. } if *function == index && o.definition => {
+                            (SymbolKind::KEY, None)
+                        }
+                        _ => continue,
+                    };
+                    children.push(self.symbol(
+                        &self.text[o.range.clone()],
+                        detail,
+                        kind,
+                        &o.range,
+                        &o.range,
+                        None,
+                    ));
+                }
+                Some(self.symbol(
+                    &format!("@{}", func.name),
+                    Some(signature(func)),
+                    SymbolKind::FUNCTION,
+                    range,
+                    &name.range,
+                    Some(children),
+                ))
+            })
+            .collect()
+    }
+
+    /// The range of the editor which covers ```bytes``` of the document
+    #[must_use]
+    pub fn range(&self, bytes: &Range<usize>) -> lsp_types::Range {
+        lsp_types::Range {
+            start: self.lines.position(&self.text, bytes.start),
+            end: self.lines.position(&self.text, bytes.end),
+        }
+    }
+
+    /// The byte of the document at ```position``` in the editor
+    #[must_use]
+    pub fn offset(&self, position: lsp_types::Position) -> usize {
+        self.lines.offset(&self.text, position)
+    }
+
+    #[expect(deprecated, reason = "the field is deprecated but has to be set")]
+    fn symbol(
+        &self,
+        name: &str,
+        detail: Option<String>,
+        kind: SymbolKind,
+        range: &Range<usize>,
+        selection: &Range<usize>,
+        children: Option<Vec<DocumentSymbol>>,
+    ) -> DocumentSymbol {
+        DocumentSymbol {
+            name: name.to_string(),
+            detail,
+            kind,
+            tags: None,
+            deprecated: None,
+            range: self.range(range),
+            selection_range: self.range(selection),
+            children,
+        }
+    }
+
+    fn function(&self, name: &str) -> Option<&AbstractFunction> {
+        self.program
+            .as_ref()?
+            .functions
+            .iter()
+            .find(|f| f.name == name)
+    }
+
+    fn occurrence(&mut self, range: &Range<usize>, symbol: Symbol, definition: bool) {
+        self.occurrences.push(Occurrence {
+            range: range.clone(),
+            symbol,
+            definition,
+        });
+    }
+
+    /// Looks up the functions imported by the `from "path" import @f as @g;` statements
+    fn index_imports(&mut self, spans: &[Span], dirs: &[PathBuf]) {
+        let mut spans = spans.iter().peekable();
+        let mut from: Option<(String, Option<ImportedProgram>)> = None;
+        while let Some(span) = spans.next() {
+            match span.kind {
+                SpanKind::ImportPath => {
+                    let path = self.text[span.range.start + 1..span.range.end - 1].to_string();
+                    let imported = self.load_import(&path, &span.range, dirs);
+                    from = Some((path, imported));
+                }
+                SpanKind::ImportedFunction => {
+                    let Some((path, imported)) = &from else {
+                        continue;
+                    };
+                    let alias = spans.next_if(|s| s.kind == SpanKind::ImportAlias);
+                    let name = self.text[span.range.clone()].to_string();
+                    let definition = imported.as_ref().and_then(|program| {
+                        let func = program.functions.iter().find(|f| f.name == name)?;
+                        let range = program_name_range(program, func);
+                        Some((
+                            AbstractFunction {
+                                instrs: Vec::new(),
+                                ..func.clone()
+                            },
+                            range,
+                        ))
+                    });
+                    if imported.is_some() && definition.is_none() {
+                        self.problems.push(Problem {
+                            range: span.range.clone(),
+                            message: format!("`@{name}` is not defined in `{path}`"),
+                            source: "brild",
+                        });
+                    }
+                    let path = path.clone();
+                    let file = imported.as_ref().map(|p| p.file.clone());
+                    let local = if let Some(alias) = alias {
+                        self.occurrence(
+                            &span.range,
+                            Symbol::Imported {
+                                path: path.clone(),
+                                name: name.clone(),
+                            },
+                            false,
+                        );
+                        let local = self.text[alias.range.clone()].to_string();
+                        self.occurrence(&alias.range, Symbol::Function(local.clone()), true);
+                        local
+                    } else {
+                        self.occurrence(&span.range, Symbol::Function(name.clone()), true);
+                        name.clone()
+                    };
+                    self.imports.insert(
+                        local,
+                        Import {
+                            path,
+                            name,
+                            file,
+                            definition,
+                        },
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Reads the program imported from ```path```, reporting a problem at ```range``` if it can not be found or read
+    fn load_import(
+        &mut self,
+        path: &str,
+        range: &Range<usize>,
+        dirs: &[PathBuf],
+    ) -> Option<ImportedProgram> {
+        let Some(file) = brild::import_candidates(Path::new(path), dirs)
+            .into_iter()
+            .next()
+        else {
+            self.problems.push(Problem {
+                range: range.clone(),
+                message: format!(
+                    "could not find `{path}` next to this file or in any library path"
+                ),
+                source: "brild",
+            });
+            return None;
+        };
+        let file = file.canonicalize().unwrap_or(file);
+        let loaded = match file.extension().and_then(std::ffi::OsStr::to_str) {
+            Some("bril") => std::fs::read_to_string(&file)
+                .map_err(|e| e.to_string())
+                .map(|text| {
+                    let options = PositionOptions {
+                        byte_spans: true,
+                        ..PositionOptions::new(true, false)
+                    };
+                    let program = parse_abstract_program_recovering(&text, options, None).0;
+                    (program, Some(text))
+                }),
+            Some("json") => File::open(&file)
+                .map_err(|e| e.to_string())
+                .and_then(|f| try_load_abstract_program_from_read(f).map_err(|e| e.to_string()))
+                .map(|program| (Some(program), None)),
+            _ => Err("imports must be `.bril` or `.json` files".to_string()),
+        };
+        match loaded {
+            Ok((Some(program), text)) => Some(ImportedProgram {
+                file,
+                lines: text.as_deref().map(LineIndex::new),
+                text,
+                functions: program.functions,
+            }),
+            Ok((None, _)) => {
+                self.problems.push(Problem {
+                    range: range.clone(),
+                    message: format!("`{path}` has syntax errors"),
+                    source: "brild",
+                });
+                None
+            }
+            Err(e) => {
+                self.problems.push(Problem {
+                    range: range.clone(),
+                    message: format!("could not read `{path}`: {e}"),
+                    source: "brild",
+                });
+                None
+            }
+        }
+    }
+
+    /// Finds the functions, variables, and labels named in each function of the program
+    fn index_functions(&mut self, spans: &[Span]) {
+        let Some(program) = self.program.take() else {
+            return;
+        };
+        // Functions with syntax errors are left out of both the program and the spans, so every function has one
+        let mut functions = program.functions.iter().enumerate();
+        let mut current = None;
+        let mut dest_types = Vec::new().into_iter();
+        for span in spans {
+            if span.kind == SpanKind::Function {
+                current = functions.next();
+                self.function_ranges.push(span.range.clone());
+                dest_types = current
+                    .map_or_else(Vec::new, |(_, func)| destination_types(func))
+                    .into_iter();
+                continue;
+            }
+            let Some((function, func)) = current else {
+                continue;
+            };
+            let name = self.text[span.range.clone()].to_string();
+            let (symbol, definition) = match span.kind {
+                SpanKind::FunctionName => (Symbol::Function(name), true),
+                SpanKind::FunctionUse => (Symbol::Function(name), false),
+                SpanKind::Argument => {
+                    if let Some(AbstractArgument { arg_type, .. }) =
+                        func.args.iter().find(|a| a.name == name)
+                    {
+                        self.types.insert(span.range.start, arg_type.to_string());
+                    }
+                    (Symbol::Variable { function, name }, true)
+                }
+                SpanKind::Destination => {
+                    if let Some(t) = dest_types.next().flatten() {
+                        self.types.insert(span.range.start, t);
+                    }
+                    (Symbol::Variable { function, name }, true)
+                }
+                SpanKind::Variable => (Symbol::Variable { function, name }, false),
+                SpanKind::LabelDefinition => (Symbol::Label { function, name }, true),
+                SpanKind::LabelUse => (Symbol::Label { function, name }, false),
+                SpanKind::Function
+                | SpanKind::ImportPath
+                | SpanKind::ImportedFunction
+                | SpanKind::ImportAlias => continue,
+            };
+            self.occurrence(&span.range, symbol, definition);
+        }
+        self.program = Some(program);
+    }
+
+    /// Checks the program with the type checker of `brilirs`, once it has no syntax errors
+    fn check(&mut self) {
+        let Some(program) = &self.program else {
+            return;
+        };
+        let mut program: Program = match Program::try_from(program.clone()) {
+            Ok(program) => program,
+            Err(errors) => {
+                for e in errors.0 {
+                    self.problems.push(Problem {
+                        range: e.pos.as_ref().and_then(bytes).unwrap_or(0..0),
+                        message: e.e.to_string(),
+                        source: "bril-rs",
+                    });
+                }
+                return;
+            }
+        };
+        // Imported functions are checked in their own file, so only their signatures are needed.
+        // Calls to an import which could not be found would all be errors, so the check waits until it is.
+        for (name, import) in &self.imports {
+            let Some((func, _)) = &import.definition else {
+                return;
+            };
+            let Ok(func) = Function::try_from(AbstractFunction {
+                name: name.clone(),
+                pos: None,
+                ..func.clone()
+            }) else {
+                return;
+            };
+            program.functions.push(func);
+        }
+        let problem = if let Err(e) = brilirs::check::type_check(&program) {
+            Some((e.pos.as_ref().and_then(bytes), e.e.to_string()))
+        } else {
+            BBProgram::try_from(program)
+                .err()
+                .map(|e| (None, e.to_string()))
+        };
+        if let Some((range, message)) = problem {
+            self.problems.push(Problem {
+                range: range.unwrap_or(0..0),
+                message,
+                source: "brilirs",
+            });
+        }
+    }
+}
+
+/// A program which is imported into the document
+struct ImportedProgram {
+    file: PathBuf,
+    /// The text of the program, if it is in the text format
+    text: Option<String>,
+    lines: Option<LineIndex>,
+    functions: Vec<AbstractFunction>,
+}
+
+/// Where the name of ```func``` is in the file of ```program```
+fn program_name_range(program: &ImportedProgram, func: &AbstractFunction) -> lsp_types::Range {
+    let pos = func.pos.as_ref();
+    if let (Some(span), Some(text), Some(lines)) =
+        (pos.and_then(bytes), &program.text, &program.lines)
+    {
+        let end = (span.start + 1 + func.name.len()).min(text.len());
+        return lsp_types::Range {
+            start: lines.position(text, span.start + 1),
+            end: lines.position(text, end),
+        };
+    }
+    // Programs in JSON only have rows and columns
+    let start = pos.map_or_else(lsp_types::Position::default, |p| {
+        lsp_types::Position::new(
+            u32::try_from(p.pos.row.saturating_sub(1)).unwrap_or(u32::MAX),
+            u32::try_from(p.pos.col.saturating_sub(1)).unwrap_or(u32::MAX),
+        )
+    });
+    lsp_types::Range { start, end: start }
+}
+
+fn signature(func: &AbstractFunction) -> String {
+    let args: Vec<String> = func.args.iter().map(ToString::to_string).collect();
+    let args = if args.is_empty() {
+        String::new()
+    } else {
+        format!("({})", args.join(", "))
+    };
+    let return_type = func
+        .return_type
+        .as_ref()
+        .map_or_else(String::new, |t| format!(": {t}"));
+    format!("@{}{args}{return_type}", func.name)
+}
+
+fn imported_signature(name: &str, import: &Import) -> String {
+    let from = format!("from \"{}\" import @{}", import.path, import.name);
+    match &import.definition {
+        Some((func, _)) => format!(
+            "{}\n# {from}",
+            signature(&AbstractFunction {
+                name: name.to_string(),
+                ..func.clone()
+            })
+        ),
+        None => from,
+    }
+}
+
+/// The type of the destination of each instruction of ```func``` which has one, in order
+fn destination_types(func: &AbstractFunction) -> Vec<Option<String>> {
+    func.instrs
+        .iter()
+        .filter_map(|code| match code {
+            AbstractCode::Instruction(AbstractInstruction::Constant { const_type, .. }) => {
+                Some(const_type.as_ref().map(ToString::to_string))
+            }
+            AbstractCode::Instruction(AbstractInstruction::Value { op_type, .. }) => {
+                Some(op_type.as_ref().map(ToString::to_string))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// The bytes of the source covered by ```pos```
+fn bytes(pos: &Position) -> Option<Range<usize>> {
+    let span = pos.byte_span?;
+    Some(usize::try_from(span.start).ok()?..usize::try_from(span.end).ok()?)
+}
+
+/// Converts between byte offsets into a text and the lines and UTF-16 columns which editors use
+#[derive(Debug, Clone)]
+struct LineIndex {
+    /// The byte each line starts at
+    starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(text: &str) -> Self {
+        Self {
+            starts: std::iter::once(0)
+                .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+                .collect(),
+        }
+    }
+
+    fn position(&self, text: &str, offset: usize) -> lsp_types::Position {
+        let offset = offset.min(text.len());
+        let line = self.starts.partition_point(|s| *s <= offset) - 1;
+        let start = self.starts[line];
+        let character = text
+            .get(start..offset)
+            .map_or(offset - start, |s| s.encode_utf16().count());
+        lsp_types::Position::new(
+            u32::try_from(line).unwrap_or(u32::MAX),
+            u32::try_from(character).unwrap_or(u32::MAX),
+        )
+    }
+
+    fn offset(&self, text: &str, position: lsp_types::Position) -> usize {
+        let Some(&start) = self.starts.get(position.line as usize) else {
+            return text.len();
+        };
+        let mut units = 0;
+        for (i, c) in text[start..].char_indices() {
+            if units >= position.character as usize || c == '\n' {
+                return start + i;
+            }
+            units += c.len_utf16();
+        }
+        text.len()
+    }
+}
+
+const fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '%' | '.')
+}
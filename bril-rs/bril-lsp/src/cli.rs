@@ -0,0 +1,10 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+#[command(about, version, author)] // keeps the cli synced with Cargo.toml
+pub struct Cli {
+    /// A list of library paths to look for imported Bril files, after the directory of the importing file.
+    #[arg(short, long, action, num_args=1..)]
+    pub libs: Vec<PathBuf>,
+}
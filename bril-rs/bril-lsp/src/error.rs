@@ -0,0 +1,15 @@
+use thiserror::Error;
+
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "I allow for the Error suffix on enums"
+)]
+#[derive(Error, Debug)]
+pub enum LspError {
+    #[error(transparent)]
+    ProtocolError(#[from] lsp_server::ProtocolError),
+    #[error("The editor disconnected before the server was shut down")]
+    Disconnected,
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+}
@@ -0,0 +1,343 @@
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![warn(missing_docs)]
+#![warn(clippy::allow_attributes)]
+#![doc = include_str!("../README.md")]
+
+#[doc(hidden)]
+pub mod cli;
+
+#[doc(hidden)]
+pub mod error;
+
+/// Provides [`analysis::Document`], which finds the symbols and problems of a Bril text document
+pub mod analysis;
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use lsp_server::{Connection, ErrorCode, ExtractError, Message, Notification, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument,
+    Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{
+    DocumentSymbolRequest, GotoDefinition, HoverRequest, Rename, Request as _,
+};
+use lsp_types::{
+    Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, DocumentSymbolResponse, GotoDefinitionResponse, Hover,
+    HoverContents, HoverProviderCapability, Location, MarkupContent, MarkupKind, OneOf,
+    PublishDiagnosticsParams, SaveOptions, ServerCapabilities, TextDocumentPositionParams,
+    TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions,
+    TextDocumentSyncSaveOptions, TextEdit, Uri, WorkspaceEdit,
+};
+
+use crate::analysis::{Document, Symbol};
+use crate::error::LspError;
+
+/// What the server can do, which is sent to the editor when it connects
+#[must_use]
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Options(
+            TextDocumentSyncOptions {
+                open_close: Some(true),
+                change: Some(TextDocumentSyncKind::FULL),
+                save: Some(TextDocumentSyncSaveOptions::SaveOptions(SaveOptions {
+                    include_text: Some(false),
+                })),
+                ..TextDocumentSyncOptions::default()
+            },
+        )),
+        definition_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        rename_provider: Some(OneOf::Left(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        ..ServerCapabilities::default()
+    }
+}
+
+/// Initializes ```connection``` and then answers the editor on the other end until it shuts the server down
+///
+/// Imports are looked up next to the importing file, then in ```libs```, then in the paths the editor lists under `libs` in its `initializationOptions`.
+/// # Errors
+/// Will return an error if the editor does not follow the protocol or disconnects before shutting the server down
+/// # Panics
+/// Panics if the capabilities of the server can not be serialized, which does not happen
+pub fn serve(connection: &Connection, mut libs: Vec<PathBuf>) -> Result<(), LspError> {
+    let params = connection.initialize(serde_json::to_value(capabilities()).unwrap())?;
+    if let Some(extra) = params
+        .pointer("/initializationOptions/libs")
+        .and_then(|l| serde_json::from_value::<Vec<PathBuf>>(l.clone()).ok())
+    {
+        libs.extend(extra);
+    }
+    let mut server = Server {
+        connection,
+        libs,
+        documents: HashMap::new(),
+    };
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                server.request(request)?;
+            }
+            Message::Notification(notification) => server.notification(notification)?,
+            Message::Response(_) => {}
+        }
+    }
+    Err(LspError::Disconnected)
+}
+
+struct Server<'a> {
+    connection: &'a Connection,
+    libs: Vec<PathBuf>,
+    /// The documents which are open in the editor
+    documents: HashMap<Uri, Document>,
+}
+
+impl Server<'_> {
+    fn send(&self, message: impl Into<Message>) -> Result<(), LspError> {
+        self.connection
+            .sender
+            .send(message.into())
+            .map_err(|_| LspError::Disconnected)
+    }
+
+    fn notification(&mut self, notification: Notification) -> Result<(), LspError> {
+        let uris = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let Ok(params) =
+                    notification.extract::<DidOpenTextDocumentParams>(DidOpenTextDocument::METHOD)
+                else {
+                    return Ok(());
+                };
+                self.open(params.text_document.uri.clone(), params.text_document.text);
+                vec![params.text_document.uri]
+            }
+            DidChangeTextDocument::METHOD => {
+                let Ok(mut params) = notification
+                    .extract::<DidChangeTextDocumentParams>(DidChangeTextDocument::METHOD)
+                else {
+                    return Ok(());
+                };
+                // Only full changes are asked for, so the last one is the whole document
+                let Some(change) = params.content_changes.pop() else {
+                    return Ok(());
+                };
+                self.open(params.text_document.uri.clone(), change.text);
+                vec![params.text_document.uri]
+            }
+            DidSaveTextDocument::METHOD => {
+                // Other documents may import the one which was saved, so they are analyzed again with what is now on disk
+                let uris: Vec<Uri> = self.documents.keys().cloned().collect();
+                for uri in &uris {
+                    let text = self.documents[uri].text().to_string();
+                    self.open(uri.clone(), text);
+                }
+                uris
+            }
+            DidCloseTextDocument::METHOD => {
+                let Ok(params) = notification
+                    .extract::<DidCloseTextDocumentParams>(DidCloseTextDocument::METHOD)
+                else {
+                    return Ok(());
+                };
+                self.documents.remove(&params.text_document.uri);
+                vec![params.text_document.uri]
+            }
+            _ => Vec::new(),
+        };
+        for uri in uris {
+            self.publish_diagnostics(uri)?;
+        }
+        Ok(())
+    }
+
+    fn open(&mut self, uri: Uri, text: String) {
+        let document = Document::new(text, uri_to_path(&uri).as_deref(), &self.libs);
+        self.documents.insert(uri, document);
+    }
+
+    fn publish_diagnostics(&self, uri: Uri) -> Result<(), LspError> {
+        let diagnostics = self.documents.get(&uri).map_or_else(Vec::new, |document| {
+            document
+                .problems()
+                .iter()
+                .map(|problem| Diagnostic {
+                    range: document.range(&problem.range),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    source: Some(problem.source.to_string()),
+                    message: problem.message.clone(),
+                    ..Diagnostic::default()
+                })
+                .collect()
+        });
+        self.send(Notification::new(
+            PublishDiagnostics::METHOD.to_string(),
+            PublishDiagnosticsParams {
+                uri,
+                diagnostics,
+                version: None,
+            },
+        ))
+    }
+
+    fn request(&self, request: lsp_server::Request) -> Result<(), LspError> {
+        match request.method.as_str() {
+            GotoDefinition::METHOD => self.respond::<GotoDefinition>(request, |params| {
+                let Some((uri, document, symbol)) =
+                    self.symbol_at(&params.text_document_position_params)
+                else {
+                    return Ok(None);
+                };
+                let locations = document
+                    .definitions(symbol)
+                    .into_iter()
+                    .filter_map(|target| {
+                        Some(Location {
+                            uri: match target.file {
+                                Some(file) => path_to_uri(&file)?,
+                                None => uri.clone(),
+                            },
+                            range: target.range,
+                        })
+                    })
+                    .collect();
+                Ok(Some(GotoDefinitionResponse::Array(locations)))
+            }),
+            HoverRequest::METHOD => self.respond::<HoverRequest>(request, |params| {
+                Ok(self
+                    .symbol_at(&params.text_document_position_params)
+                    .and_then(|(_, document, symbol)| document.hover(symbol))
+                    .map(|value| Hover {
+                        contents: HoverContents::Markup(MarkupContent {
+                            kind: MarkupKind::Markdown,
+                            value,
+                        }),
+                        range: None,
+                    }))
+            }),
+            Rename::METHOD => self.respond::<Rename>(request, |params| {
+                let Some((uri, document, symbol)) = self.symbol_at(&params.text_document_position)
+                else {
+                    return Err(
+                        "there is no variable, label, or function here to rename".to_string()
+                    );
+                };
+                let new_name = match symbol {
+                    Symbol::Function(_) => params.new_name.strip_prefix('@'),
+                    Symbol::Label { .. } => params.new_name.strip_prefix('.'),
+                    Symbol::Variable { .. } | Symbol::Imported { .. } => None,
+                }
+                .unwrap_or(&params.new_name);
+                let edits = document
+                    .rename(symbol, new_name)?
+                    .into_iter()
+                    .map(|range| TextEdit {
+                        range: document.range(&range),
+                        new_text: new_name.to_string(),
+                    })
+                    .collect();
+                Ok(Some(WorkspaceEdit {
+                    changes: Some(HashMap::from([(uri.clone(), edits)])),
+                    ..WorkspaceEdit::default()
+                }))
+            }),
+            DocumentSymbolRequest::METHOD => {
+                self.respond::<DocumentSymbolRequest>(request, |params| {
+                    Ok(self
+                        .documents
+                        .get(&params.text_document.uri)
+                        .map(|document| DocumentSymbolResponse::Nested(document.symbols())))
+                })
+            }
+            _ => self.send(Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("`{}` is not supported", request.method),
+            )),
+        }
+    }
+
+    /// Answers ```request``` with what ```answer``` returns for its parameters
+    fn respond<R: lsp_types::request::Request>(
+        &self,
+        request: lsp_server::Request,
+        answer: impl FnOnce(R::Params) -> Result<R::Result, String>,
+    ) -> Result<(), LspError> {
+        let id = request.id.clone();
+        let response = match request.extract::<R::Params>(R::METHOD) {
+            Ok((id, params)) => match answer(params) {
+                Ok(result) => Response::new_ok(id, result),
+                Err(message) => Response::new_err(id, ErrorCode::RequestFailed as i32, message),
+            },
+            Err(ExtractError::JsonError { error, .. }) => {
+                Response::new_err(id, ErrorCode::InvalidParams as i32, error.to_string())
+            }
+            Err(ExtractError::MethodMismatch(request)) => Response::new_err(
+                id,
+                ErrorCode::MethodNotFound as i32,
+                format!("`{}` is not supported", request.method),
+            ),
+        };
+        self.send(response)
+    }
+
+    /// The symbol at a position of an open document
+    fn symbol_at<'a>(
+        &'a self,
+        params: &'a TextDocumentPositionParams,
+    ) -> Option<(&'a Uri, &'a Document, &'a Symbol)> {
+        let document = self.documents.get(&params.text_document.uri)?;
+        let occurrence = document.occurrence_at(document.offset(params.position))?;
+        Some((&params.text_document.uri, document, &occurrence.symbol))
+    }
+}
+
+/// The path of a `file:` ```uri```
+#[must_use]
+pub fn uri_to_path(uri: &Uri) -> Option<PathBuf> {
+    if !uri.scheme()?.as_str().eq_ignore_ascii_case("file") {
+        return None;
+    }
+    let path = uri.path().as_str().as_bytes();
+    let mut decoded = Vec::with_capacity(path.len());
+    let mut i = 0;
+    while i < path.len() {
+        let hex = path
+            .get(i + 1..i + 3)
+            .and_then(|h| std::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (path[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok().map(PathBuf::from)
+}
+
+/// The `file:` uri of an absolute ```path```
+#[must_use]
+pub fn path_to_uri(path: &Path) -> Option<Uri> {
+    let mut uri = String::from("file://");
+    for byte in path.to_str()?.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~".contains(&byte) {
+            uri.push(char::from(byte));
+        } else {
+            write!(uri, "%{byte:02X}").unwrap();
+        }
+    }
+    Uri::from_str(&uri).ok()
+}
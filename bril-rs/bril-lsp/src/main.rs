@@ -0,0 +1,20 @@
+use clap::Parser;
+use lsp_server::Connection;
+
+use bril_lsp::{cli::Cli, error::LspError, serve};
+
+fn main() {
+    if let Err(e) = run() {
+        eprintln!("error: {e}");
+        std::process::exit(1)
+    }
+}
+
+fn run() -> Result<(), LspError> {
+    let args = Cli::parse();
+    let (connection, io_threads) = Connection::stdio();
+    serve(&connection, args.libs)?;
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
@@ -0,0 +1,279 @@
+use std::path::{Path, PathBuf};
+
+use bril_lsp::analysis::{Document, Symbol};
+use lsp_types::{Position, Range, SymbolKind};
+
+const PROGRAM: &str = "\
+@add(a: int, b: int): int {
+  sum: int = add a b;
+  ret sum;
+}
+
+@main {
+  x: int = const 1;
+  y: int = call @add x x;
+.loop:
+  cond: bool = lt y x;
+  br cond .loop .done;
+.done:
+  print y;
+}
+";
+
+fn document(text: &str) -> Document {
+    Document::new(text.to_string(), None, &[])
+}
+
+/// The symbol named at the ```nth``` occurrence of ```needle``` in the document
+fn symbol_at(document: &Document, needle: &str, nth: usize) -> Symbol {
+    let offset = document
+        .text()
+        .match_indices(needle)
+        .nth(nth)
+        .unwrap_or_else(|| panic!("`{needle}` does not occur {} times", nth + 1))
+        .0;
+    document
+        .occurrence_at(offset + needle.len() - 1)
+        .unwrap_or_else(|| panic!("nothing is named at `{needle}`"))
+        .symbol
+        .clone()
+}
+
+fn range(line: u32, start: u32, end: u32) -> Range {
+    Range::new(Position::new(line, start), Position::new(line, end))
+}
+
+fn local_definitions(document: &Document, symbol: &Symbol) -> Vec<Range> {
+    document
+        .definitions(symbol)
+        .into_iter()
+        .map(|target| {
+            assert_eq!(target.file, None);
+            target.range
+        })
+        .collect()
+}
+
+fn link_ops() -> (Document, PathBuf) {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+    let path = root.join("test/linking/link_ops.bril");
+    let text = std::fs::read_to_string(&path).unwrap();
+    let libs = [root.join("benchmarks/core")];
+    (Document::new(text, Some(&path), &libs), root)
+}
+
+#[test]
+fn definitions() {
+    let document = document(PROGRAM);
+    assert!(document.problems().is_empty(), "{:?}", document.problems());
+
+    let sum = symbol_at(&document, "ret sum", 0);
+    assert_eq!(local_definitions(&document, &sum), vec![range(1, 2, 5)]);
+
+    let add = symbol_at(&document, "@add", 1);
+    assert_eq!(add, Symbol::Function("add".to_string()));
+    assert_eq!(local_definitions(&document, &add), vec![range(0, 1, 4)]);
+
+    let argument = symbol_at(&document, "add a", 0);
+    assert_eq!(
+        local_definitions(&document, &argument),
+        vec![range(0, 5, 6)]
+    );
+
+    let label = symbol_at(&document, ".loop", 1);
+    assert_eq!(local_definitions(&document, &label), vec![range(8, 1, 5)]);
+
+    // The operation and the keywords of an instruction are not symbols
+    let offset = document.text().find("lt y").unwrap();
+    assert_eq!(document.occurrence_at(offset), None);
+}
+
+#[test]
+fn variables_are_scoped_to_their_function() {
+    let text = "@f(x: int) {\n  print x;\n}\n@g {\n  x: bool = const true;\n  print x;\n}\n";
+    let document = document(text);
+    let first = symbol_at(&document, "print x", 0);
+    let second = symbol_at(&document, "print x", 1);
+    assert_ne!(first, second);
+    assert_eq!(local_definitions(&document, &first), vec![range(0, 3, 4)]);
+    assert_eq!(local_definitions(&document, &second), vec![range(4, 2, 3)]);
+}
+
+#[test]
+fn hover() {
+    let document = document(PROGRAM);
+    let y = symbol_at(&document, "lt y", 0);
+    assert_eq!(document.hover(&y).unwrap(), "```bril\ny: int\n```");
+    let add = symbol_at(&document, "@add", 1);
+    assert_eq!(
+        document.hover(&add).unwrap(),
+        "```bril\n@add(a: int, b: int): int\n```"
+    );
+    let label = symbol_at(&document, ".done", 0);
+    assert_eq!(document.hover(&label), None);
+}
+
+#[test]
+fn rename() {
+    let document = document(PROGRAM);
+    let x = symbol_at(&document, "x:", 0);
+    let mut ranges = document.rename(&x, "input").unwrap();
+    assert_eq!(ranges.len(), 4);
+
+    let mut text = document.text().to_string();
+    ranges.sort_by_key(|r| std::cmp::Reverse(r.start));
+    for r in ranges {
+        text.replace_range(r, "input");
+    }
+    assert!(text.contains("y: int = call @add input input;"));
+    assert!(text.contains("cond: bool = lt y input;"));
+
+    let add = symbol_at(&document, "@add", 0);
+    assert_eq!(document.rename(&add, "plus").unwrap().len(), 2);
+    let label = symbol_at(&document, ".loop", 0);
+    assert_eq!(document.rename(&label, "top").unwrap().len(), 2);
+
+    assert!(document.rename(&x, "y").is_err(), "`y` is already taken");
+    assert!(document.rename(&x, "1x").is_err(), "`1x` is not a name");
+    assert!(
+        document.rename(&label, "done").is_err(),
+        "`.done` is already taken"
+    );
+}
+
+#[test]
+fn symbols() {
+    let document = document(PROGRAM);
+    let symbols = document.symbols();
+    let names: Vec<&str> = symbols.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["@add", "@main"]);
+
+    let main = &symbols[1];
+    assert_eq!(main.kind, SymbolKind::FUNCTION);
+    assert_eq!(
+        main.range,
+        Range::new(Position::new(5, 0), Position::new(13, 1))
+    );
+    assert_eq!(main.selection_range, range(5, 1, 5));
+    let children: Vec<(&str, SymbolKind)> = main
+        .children
+        .iter()
+        .flatten()
+        .map(|c| (c.name.as_str(), c.kind))
+        .collect();
+    assert_eq!(
+        children,
+        vec![
+            ("x", SymbolKind::VARIABLE),
+            ("y", SymbolKind::VARIABLE),
+            ("loop", SymbolKind::KEY),
+            ("cond", SymbolKind::VARIABLE),
+            ("done", SymbolKind::KEY),
+        ]
+    );
+}
+
+#[test]
+fn all_syntax_errors_are_problems() {
+    let text =
+        "@main {\n  x: int = const 1;\n  y int = add x x;\n  print x;\n  z = ;\n  ret x;\n}\n";
+    let document = document(text);
+    let problems = document.problems();
+    assert_eq!(problems.len(), 2, "{problems:?}");
+    assert!(problems.iter().all(|p| p.source == "bril2json"));
+    assert_eq!(document.range(&problems[1].range).start.line, 4);
+    // What still parses can be navigated
+    let x = symbol_at(&document, "ret x", 0);
+    assert_eq!(local_definitions(&document, &x), vec![range(1, 2, 3)]);
+}
+
+#[test]
+fn type_errors_are_problems() {
+    let text = "@main {\n  b: bool = const true;\n  x: int = add b b;\n}\n";
+    let document = document(text);
+    let problems = document.problems();
+    assert_eq!(problems.len(), 1, "{problems:?}");
+    assert_eq!(problems[0].source, "brilirs");
+    assert_eq!(
+        document.range(&problems[0].range).start,
+        Position::new(2, 2)
+    );
+}
+
+#[test]
+fn programs_brilirs_can_not_check_are_problems() {
+    for text in [
+        "@main {\n  speculate;\n  commit;\n}\n",
+        "@main {\n  x: int = call @missing;\n}\n",
+    ] {
+        let document = document(text);
+        let problems = document.problems();
+        assert_eq!(problems.len(), 1, "{problems:?}");
+        assert_eq!(problems[0].source, "brilirs");
+    }
+}
+
+#[test]
+fn every_type_of_a_variable_is_shown_once() {
+    let text = "@main {\n  x: int = const 1;\n  x: bool = const true;\n  x: int = const 2;\n  print x;\n}\n";
+    let document = document(text);
+    let x = symbol_at(&document, "print x", 0);
+    assert_eq!(document.hover(&x).unwrap(), "```bril\nx: bool\nx: int\n```");
+}
+
+#[test]
+fn imported_functions() {
+    let (document, root) = link_ops();
+    assert!(document.problems().is_empty(), "{:?}", document.problems());
+    let library = root
+        .join("benchmarks/core/bitwise-ops.bril")
+        .canonicalize()
+        .unwrap();
+
+    // A call of an aliased import goes to the function in the imported file
+    let alias = symbol_at(&document, "call @LIB_OR", 0);
+    assert_eq!(alias, Symbol::Function("LIB_OR".to_string()));
+    let targets = document.definitions(&alias);
+    assert_eq!(targets.len(), 1);
+    assert_eq!(targets[0].file.as_ref(), Some(&library));
+    let library_text = std::fs::read_to_string(&library).unwrap();
+    let line = library_text
+        .lines()
+        .nth(targets[0].range.start.line as usize)
+        .unwrap();
+    assert!(line.starts_with("@OR("), "{line}");
+
+    // The local function of the same name is another symbol
+    let local = symbol_at(&document, "call @OR", 0);
+    assert_eq!(local_definitions(&document, &local), vec![range(4, 1, 3)]);
+
+    let and = symbol_at(&document, "call @AND", 0);
+    assert_eq!(
+        document.hover(&and).unwrap(),
+        "```bril\n@AND(a: int, b: int): int\n# from \"bitwise-ops.bril\" import @AND\n```"
+    );
+
+    assert!(document.rename(&alias, "either").is_ok());
+    assert!(document.rename(&and, "both").is_err());
+}
+
+#[test]
+fn unresolved_imports_are_problems() {
+    let text = "from \"missing.bril\" import @f;\n@main {\n  call @f;\n}\n";
+    let document = document(text);
+    let problems = document.problems();
+    assert_eq!(problems.len(), 1, "{problems:?}");
+    assert_eq!(problems[0].source, "brild");
+    assert_eq!(&text[problems[0].range.clone()], "\"missing.bril\"");
+
+    let (document, _) = link_ops();
+    let text = document.text().replace("@loop_subroutine", "@nope");
+    let document = Document::new(
+        text,
+        None,
+        &[Path::new(env!("CARGO_MANIFEST_DIR")).join("../../benchmarks/core")],
+    );
+    let problems = document.problems();
+    assert_eq!(problems.len(), 1, "{problems:?}");
+    assert_eq!(&document.text()[problems[0].range.clone()], "nope");
+}
@@ -0,0 +1,158 @@
+use std::path::Path;
+use std::str::FromStr;
+use std::thread;
+
+use bril_lsp::{path_to_uri, serve, uri_to_path};
+use lsp_server::{Connection, Message, Notification, Request, RequestId, Response};
+use lsp_types::{
+    GotoDefinitionResponse, PublishDiagnosticsParams, Uri, WorkspaceEdit,
+    notification::{Notification as _, PublishDiagnostics},
+};
+use serde_json::{Value, json};
+
+/// An editor talking to a server which runs on another thread
+struct Client {
+    connection: Connection,
+    server: thread::JoinHandle<()>,
+    next_id: i32,
+}
+
+impl Client {
+    fn start() -> Self {
+        let (server, connection) = Connection::memory();
+        let server = thread::spawn(move || serve(&server, Vec::new()).unwrap());
+        let mut client = Self {
+            connection,
+            server,
+            next_id: 0,
+        };
+        let result = client.request("initialize", json!({ "capabilities": {} }));
+        assert_eq!(result["capabilities"]["definitionProvider"], json!(true));
+        client.notify("initialized", json!({}));
+        client
+    }
+
+    fn notify(&self, method: &str, params: Value) {
+        let notification = Notification::new(method.to_string(), params);
+        self.connection.sender.send(notification.into()).unwrap();
+    }
+
+    /// The response to a request, skipping the notifications sent before it
+    fn request_response(&mut self, method: &str, params: Value) -> Response {
+        self.next_id += 1;
+        let id = RequestId::from(self.next_id);
+        let request = Request::new(id.clone(), method.to_string(), params);
+        self.connection.sender.send(request.into()).unwrap();
+        loop {
+            match self.connection.receiver.recv().unwrap() {
+                Message::Response(response) if response.id == id => return response,
+                _ => {}
+            }
+        }
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Value {
+        let response = self.request_response(method, params);
+        assert!(response.error.is_none(), "{:?}", response.error);
+        response.result.unwrap()
+    }
+
+    fn diagnostics(&self) -> PublishDiagnosticsParams {
+        loop {
+            if let Message::Notification(n) = self.connection.receiver.recv().unwrap()
+                && n.method == PublishDiagnostics::METHOD
+            {
+                return serde_json::from_value(n.params).unwrap();
+            }
+        }
+    }
+
+    fn shutdown(mut self) {
+        self.request("shutdown", Value::Null);
+        self.notify("exit", Value::Null);
+        self.server.join().unwrap();
+    }
+}
+
+fn position(uri: &str, line: u32, character: u32) -> Value {
+    json!({
+        "textDocument": { "uri": uri },
+        "position": { "line": line, "character": character },
+    })
+}
+
+#[test]
+fn editing_session() {
+    let uri = "file:///tmp/session.bril";
+    let mut client = Client::start();
+    client.notify(
+        "textDocument/didOpen",
+        json!({ "textDocument": {
+            "uri": uri, "languageId": "bril", "version": 1,
+            "text": "@main {\n  x: int = const 1;\n  y: bool = add x x;\n}\n",
+        }}),
+    );
+    let diagnostics = client.diagnostics();
+    assert_eq!(diagnostics.uri.as_str(), uri);
+    assert_eq!(diagnostics.diagnostics.len(), 1);
+    assert_eq!(diagnostics.diagnostics[0].range.start.line, 2);
+
+    client.notify(
+        "textDocument/didChange",
+        json!({
+            "textDocument": { "uri": uri, "version": 2 },
+            "contentChanges": [{ "text": "@main {\n  x: int = const 1;\n  y: int = add x x;\n  print y;\n}\n" }],
+        }),
+    );
+    assert!(client.diagnostics().diagnostics.is_empty());
+
+    let definition: GotoDefinitionResponse =
+        serde_json::from_value(client.request("textDocument/definition", position(uri, 3, 8)))
+            .unwrap();
+    let GotoDefinitionResponse::Array(locations) = definition else {
+        panic!("expected a list of locations");
+    };
+    assert_eq!(locations.len(), 1);
+    assert_eq!(locations[0].range.start, lsp_types::Position::new(2, 2));
+
+    let hover = client.request("textDocument/hover", position(uri, 2, 16));
+    assert_eq!(hover["contents"]["value"], json!("```bril\nx: int\n```"));
+
+    let mut params = position(uri, 1, 2);
+    params["newName"] = json!("one");
+    let edit: WorkspaceEdit =
+        serde_json::from_value(client.request("textDocument/rename", params)).unwrap();
+    let edits = &edit.changes.unwrap()[&Uri::from_str(uri).unwrap()];
+    assert_eq!(edits.len(), 3);
+    assert!(edits.iter().all(|e| e.new_text == "one"));
+
+    let mut params = position(uri, 1, 2);
+    params["newName"] = json!("y");
+    let response = client.request_response("textDocument/rename", params);
+    assert!(response.error.is_some());
+
+    let symbols = client.request(
+        "textDocument/documentSymbol",
+        json!({ "textDocument": { "uri": uri } }),
+    );
+    assert_eq!(symbols[0]["name"], json!("@main"));
+
+    client.notify(
+        "textDocument/didClose",
+        json!({ "textDocument": { "uri": uri } }),
+    );
+    assert!(client.diagnostics().diagnostics.is_empty());
+    client.shutdown();
+}
+
+#[test]
+fn file_uris() {
+    let path = Path::new("/tmp/a dir/ünïcode.bril");
+    let uri = path_to_uri(path).unwrap();
+    assert_eq!(uri.as_str(), "file:///tmp/a%20dir/%C3%BCn%C3%AFcode.bril");
+    assert_eq!(uri_to_path(&uri).unwrap(), path);
+    assert_eq!(
+        uri_to_path(&Uri::from_str("untitled:Untitled-1").unwrap()),
+        None
+    );
+}
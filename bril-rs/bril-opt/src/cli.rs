@@ -0,0 +1,31 @@
+use bril_rs::opt::PASS_NAMES;
+use clap::Parser;
+use clap::builder::PossibleValuesParser;
+
+#[derive(Parser)]
+#[command(about, version, author)] // keeps the cli synced with Cargo.toml
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "each flag is an independent command line switch"
+)]
+pub struct Cli {
+    /// The Bril file to optimize. stdin is assumed if file is not provided.
+    #[arg(short, long, action)]
+    pub file: Option<String>,
+    /// Flag for when the Bril program is in text form
+    #[arg(short, long, action)]
+    pub text: bool,
+    /// Flag to output the optimized program in text form instead of JSON
+    #[arg(long, action)]
+    pub text_output: bool,
+    /// The passes to run, in order. Every pass is run if none are provided.
+    #[arg(short, long, value_delimiter = ',', num_args = 1.., value_parser = PossibleValuesParser::new(PASS_NAMES))]
+    pub passes: Vec<String>,
+    /// Flag to run the passes only once instead of until the program stops changing
+    #[arg(long, action)]
+    pub once: bool,
+    /// Flag to report on stderr each function where the passes left instructions or labels without a source position.
+    /// Bril text is parsed with source positions when this is set.
+    #[arg(long, action)]
+    pub position_coverage: bool,
+}
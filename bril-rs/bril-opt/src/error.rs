@@ -0,0 +1,15 @@
+use thiserror::Error;
+
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "I allow for the Error suffix on enums"
+)]
+#[derive(Error, Debug)]
+pub enum OptError {
+    #[error(transparent)]
+    ParseError(#[from] bril2json::ParseError),
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    BrilError(#[from] bril_rs::BrilError),
+}
@@ -0,0 +1,60 @@
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![warn(missing_docs)]
+#![warn(clippy::allow_attributes)]
+#![doc = include_str!("../README.md")]
+
+#[doc(hidden)]
+pub mod cli;
+
+#[doc(hidden)]
+pub mod error;
+
+use std::fs::File;
+
+use bril_rs::opt::{PassManager, pass_by_name};
+use bril_rs::{BrilError, Program, try_load_program_from_abstract_read};
+use bril2json::{PositionOptions, try_parse_abstract_program};
+
+use crate::error::OptError;
+
+/// Loads the program in ```file```, or from [`std::io::Stdin`] if it is [None]. The program is parsed as Bril text if ```text``` is set and as JSON otherwise.
+///
+/// Bril text is given source positions if ```positions``` is set.
+/// # Errors
+/// Will return an error if the input can not be read, is not well-formed Bril text, or is not a valid Bril program
+pub fn load_program(
+    file: Option<String>,
+    text: bool,
+    positions: bool,
+) -> Result<Program, OptError> {
+    if text {
+        let program = try_parse_abstract_program(PositionOptions::new(positions, positions), file)?;
+        return Ok(Program::try_from(program).map_err(BrilError::from)?);
+    }
+    let program = match file {
+        Some(f) => try_load_program_from_abstract_read(File::open(f)?),
+        None => try_load_program_from_abstract_read(std::io::stdin().lock()),
+    }?;
+    Ok(program)
+}
+
+/// Builds a [`PassManager`] running the passes named in ```passes```, or every built-in pass if there are none.
+/// With ```once``` set, each pass is run a single time.
+/// # Panics
+/// Will panic if one of ```passes``` is not the name of a built-in pass
+#[must_use]
+pub fn pass_manager(passes: &[String], once: bool) -> PassManager {
+    let mut manager = if passes.is_empty() {
+        PassManager::standard()
+    } else {
+        let mut manager = PassManager::new();
+        for name in passes {
+            manager.add_pass(pass_by_name(name).unwrap_or_else(|| panic!("Unknown pass: {name}")));
+        }
+        manager
+    };
+    if once {
+        manager.set_max_iterations(1);
+    }
+    manager
+}
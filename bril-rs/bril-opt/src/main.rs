@@ -0,0 +1,35 @@
+use clap::Parser;
+
+use bril_opt::error::OptError;
+use bril_opt::{cli::Cli, load_program, pass_manager};
+use bril_rs::provenance::lost_positions;
+use bril_rs::{BrilError, try_output_program};
+
+fn run(args: Cli) -> Result<(), OptError> {
+    let mut program = load_program(args.file, args.text, args.position_coverage)?;
+    let original = args.position_coverage.then(|| program.clone());
+    pass_manager(&args.passes, args.once)
+        .run(&mut program)
+        .map_err(BrilError::from)?;
+
+    if let Some(original) = original {
+        for coverage in lost_positions(&original, &program) {
+            eprint!("{coverage}");
+        }
+    }
+
+    if args.text_output {
+        print!("{program}");
+    } else {
+        try_output_program(&program, std::io::stdout().lock())?;
+    }
+
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run(Cli::parse()) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
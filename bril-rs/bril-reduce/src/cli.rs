@@ -0,0 +1,21 @@
+use clap::Parser;
+
+#[derive(Parser)]
+#[command(about, version, author)] // keeps the cli synced with Cargo.toml
+pub struct Cli {
+    /// The Bril file to reduce. stdin is assumed if file is not provided.
+    #[arg(short, long, action)]
+    pub file: Option<String>,
+    /// Flag for when the Bril program is in text form
+    #[arg(short, long, action)]
+    pub text: bool,
+    /// The interestingness test: a shell command which is given each candidate program as JSON on stdin, and which exits with status 0 if the candidate is interesting
+    #[arg(long, action)]
+    pub test: String,
+    /// How many seconds the test may run before it is killed and the candidate is considered uninteresting
+    #[arg(long, action, default_value_t = 10)]
+    pub timeout: u64,
+    /// Flag to output the reduced program in text form instead of JSON
+    #[arg(long, action)]
+    pub text_output: bool,
+}
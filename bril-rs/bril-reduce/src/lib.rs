@@ -0,0 +1,444 @@
+#![warn(clippy::all, clippy::pedantic, clippy::nursery, clippy::cargo)]
+#![warn(missing_docs)]
+#![warn(clippy::allow_attributes)]
+#![doc = include_str!("../README.md")]
+
+#[doc(hidden)]
+pub mod cli;
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use bril_rs::check::{CheckError, check_program};
+use bril_rs::extension::ExtensionSet;
+use bril_rs::{Code, ConstOps, EffectOps, Function, Instruction, Literal, Program, Type, ValueOps};
+use thiserror::Error;
+
+/// The reasons [reduce] can not start
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ReduceError {
+    /// The program given to [reduce] is not interesting to begin with, so there is nothing to preserve
+    #[error("the original program is not interesting")]
+    NotInteresting,
+}
+
+/// Shrinks ```program``` for as long as ```interesting``` holds, and returns the smallest program it found
+///
+/// Each step deletes functions, blocks, labels, or instructions, replaces a `br` with a `jmp` to one of its targets, replaces an operation with a constant, or replaces a constant with a simpler one.
+/// Steps are repeated until none of them keeps the program interesting, with deletions tried in chunks first, after the delta debugging algorithm.
+///
+/// Programs which [`check_program`] reports a problem for that the original does not have are never given to ```interesting```, so the result of reducing a well-formed program is well-formed.
+/// # Errors
+/// Will return [`ReduceError::NotInteresting`] if ```program``` itself is not interesting
+pub fn reduce(
+    program: &Program,
+    interesting: impl FnMut(&Program) -> bool,
+) -> Result<Program, ReduceError> {
+    let mut reducer = Reducer {
+        baseline: problems(program),
+        current: program.clone(),
+        interesting,
+    };
+    if !(reducer.interesting)(program) {
+        return Err(ReduceError::NotInteresting);
+    }
+    loop {
+        let mut changed = reducer.delete_chunks(count_functions, delete_functions);
+        changed |= reducer.delete_chunks(count_blocks, delete_blocks);
+        changed |= reducer.delete_chunks(count_blocks, delete_labels);
+        changed |= reducer.delete_chunks(count_instructions, delete_instructions);
+        changed |= reducer.simplify(count_branches, collapse_branch);
+        changed |= reducer.simplify(count_values, value_to_constant);
+        changed |= reducer.simplify(count_constants, simplify_constant);
+        if !changed {
+            return Ok(reducer.current);
+        }
+    }
+}
+
+/// The number of instructions in ```program```, not counting labels
+#[must_use]
+pub fn size(program: &Program) -> usize {
+    count_instructions(program)
+}
+
+/// The problems [`check_program`] reports for ```program```, without their positions since those of new code differ from the code it replaces
+fn problems(program: &Program) -> Vec<(Option<String>, CheckError)> {
+    check_program(program, ExtensionSet::ALL)
+        .into_iter()
+        .map(|d| (d.func, d.error))
+        .collect()
+}
+
+struct Reducer<F> {
+    current: Program,
+    /// The problems of the original program, which candidates may keep but not add to
+    baseline: Vec<(Option<String>, CheckError)>,
+    interesting: F,
+}
+
+impl<F: FnMut(&Program) -> bool> Reducer<F> {
+    fn try_candidate(&mut self, candidate: Program) -> bool {
+        if candidate == self.current
+            || !problems(&candidate)
+                .iter()
+                .all(|p| self.baseline.contains(p))
+            || !(self.interesting)(&candidate)
+        {
+            return false;
+        }
+        self.current = candidate;
+        true
+    }
+
+    /// Deletes chunks of the items which ```count``` counts, starting with chunks of half of them and halving down to single items
+    fn delete_chunks(
+        &mut self,
+        count: fn(&Program) -> usize,
+        delete: fn(&Program, Range<usize>) -> Program,
+    ) -> bool {
+        let mut changed = false;
+        let mut chunk = (count(&self.current) / 2).max(1);
+        loop {
+            let mut start = 0;
+            while start < count(&self.current) {
+                let end = (start + chunk).min(count(&self.current));
+                if self.try_candidate(delete(&self.current, start..end)) {
+                    changed = true;
+                } else {
+                    start += chunk;
+                }
+            }
+            if chunk == 1 {
+                return changed;
+            }
+            chunk /= 2;
+        }
+    }
+
+    /// Tries each of the simpler versions ```alternatives``` gives of each item which ```count``` counts, keeping the first interesting one
+    fn simplify(
+        &mut self,
+        count: fn(&Program) -> usize,
+        alternatives: fn(&Program, usize) -> Vec<Program>,
+    ) -> bool {
+        let mut changed = false;
+        let mut i = 0;
+        while i < count(&self.current) {
+            for candidate in alternatives(&self.current, i) {
+                if self.try_candidate(candidate) {
+                    changed = true;
+                    break;
+                }
+            }
+            i += 1;
+        }
+        changed
+    }
+}
+
+/// The simplest constant of type ```t```, if it has constants
+const fn simplest(t: &Type) -> Option<Literal> {
+    match t {
+        Type::Int => Some(Literal::Int(0)),
+        Type::Bool => Some(Literal::Bool(false)),
+        Type::Float => Some(Literal::Float(0.0)),
+        Type::Char => Some(Literal::Char('a')),
+        _ => None,
+    }
+}
+
+const fn constant(dest: String, const_type: Type, value: Literal) -> Instruction {
+    Instruction::Constant {
+        dest,
+        op: ConstOps::Const,
+        pos: None,
+        comments: None,
+        const_type,
+        value,
+    }
+}
+
+/// The functions other than `main`, which is never deleted
+fn deletable_functions(program: &Program) -> impl Iterator<Item = usize> + '_ {
+    program
+        .functions
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| f.name != "main")
+        .map(|(i, _)| i)
+}
+
+fn count_functions(program: &Program) -> usize {
+    deletable_functions(program).count()
+}
+
+/// Deletes functions along with the calls of them, replacing calls which produce a value with a constant
+fn delete_functions(program: &Program, range: Range<usize>) -> Program {
+    let deleted: HashSet<usize> = deletable_functions(program)
+        .skip(range.start)
+        .take(range.len())
+        .collect();
+    let names: HashSet<&str> = deleted
+        .iter()
+        .map(|&i| program.functions[i].name.as_str())
+        .collect();
+    let is_deleted_call =
+        |funcs: &[String]| funcs.first().is_some_and(|f| names.contains(f.as_str()));
+
+    let mut reduced = program.clone();
+    reduced.functions = program
+        .functions
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !deleted.contains(i))
+        .map(|(_, f)| {
+            let mut f = f.clone();
+            f.instrs = f
+                .instrs
+                .into_iter()
+                .filter_map(|code| match code {
+                    Code::Instruction(Instruction::Effect {
+                        op: EffectOps::Call,
+                        ref funcs,
+                        ..
+                    }) if is_deleted_call(funcs) => None,
+                    Code::Instruction(Instruction::Value {
+                        op: ValueOps::Call,
+                        ref funcs,
+                        ref dest,
+                        ref op_type,
+                        ..
+                    }) if is_deleted_call(funcs) => Some(simplest(op_type).map_or_else(
+                        || code.clone(),
+                        |value| Code::Instruction(constant(dest.clone(), op_type.clone(), value)),
+                    )),
+                    code => Some(code),
+                })
+                .collect();
+            f
+        })
+        .collect();
+    reduced
+}
+
+/// Every label in the program as the index of its function and its index in that function
+fn labels(program: &Program) -> Vec<(usize, usize)> {
+    locations(program, |code| matches!(code, Code::Label { .. }))
+}
+
+fn locations(program: &Program, filter: impl Fn(&Code) -> bool) -> Vec<(usize, usize)> {
+    program
+        .functions
+        .iter()
+        .enumerate()
+        .flat_map(|(f, func)| {
+            func.instrs
+                .iter()
+                .enumerate()
+                .filter(|(_, code)| filter(code))
+                .map(move |(i, _)| (f, i))
+        })
+        .collect()
+}
+
+fn count_blocks(program: &Program) -> usize {
+    labels(program).len()
+}
+
+/// Deletes labeled blocks, sending jumps to a deleted block to the next block which is kept instead
+fn delete_blocks(program: &Program, range: Range<usize>) -> Program {
+    let deleted: HashSet<(usize, usize)> = labels(program)[range].iter().copied().collect();
+    let mut reduced = program.clone();
+    for (f, func) in reduced.functions.iter_mut().enumerate() {
+        let mut retarget: HashMap<String, Option<String>> = HashMap::new();
+        let mut pending = Vec::new();
+        let mut instrs = Vec::new();
+        let mut deleting = false;
+        for (i, code) in program.functions[f].instrs.iter().enumerate() {
+            if let Code::Label { label, .. } = code {
+                deleting = deleted.contains(&(f, i));
+                if deleting {
+                    pending.push(label.clone());
+                } else {
+                    for old in std::mem::take(&mut pending) {
+                        retarget.insert(old, Some(label.clone()));
+                    }
+                }
+            }
+            if !deleting {
+                instrs.push(code.clone());
+            }
+        }
+        // A deleted block at the end of the function has no next block, so jumps to it are left alone and the result is rejected
+        for old in pending {
+            retarget.insert(old, None);
+        }
+        for code in &mut instrs {
+            if let Code::Instruction(
+                Instruction::Value { labels, .. } | Instruction::Effect { labels, .. },
+            ) = code
+            {
+                for label in labels {
+                    if let Some(Some(new)) = retarget.get(label) {
+                        label.clone_from(new);
+                    }
+                }
+            }
+        }
+        func.instrs = instrs;
+    }
+    reduced
+}
+
+/// Deletes only the labels, which merges their blocks into the blocks before them
+fn delete_labels(program: &Program, range: Range<usize>) -> Program {
+    delete_locations(program, &labels(program)[range])
+}
+
+fn instructions(program: &Program) -> Vec<(usize, usize)> {
+    locations(program, |code| matches!(code, Code::Instruction(_)))
+}
+
+fn count_instructions(program: &Program) -> usize {
+    instructions(program).len()
+}
+
+fn delete_instructions(program: &Program, range: Range<usize>) -> Program {
+    delete_locations(program, &instructions(program)[range])
+}
+
+fn delete_locations(program: &Program, locations: &[(usize, usize)]) -> Program {
+    let deleted: HashSet<&(usize, usize)> = locations.iter().collect();
+    let mut reduced = program.clone();
+    for (f, func) in reduced.functions.iter_mut().enumerate() {
+        func.instrs = program.functions[f]
+            .instrs
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !deleted.contains(&(f, *i)))
+            .map(|(_, code)| code.clone())
+            .collect();
+    }
+    reduced
+}
+
+fn find(program: &Program, filter: impl Fn(&Instruction) -> bool) -> Vec<(usize, usize)> {
+    locations(
+        program,
+        |code| matches!(code, Code::Instruction(i) if filter(i)),
+    )
+}
+
+/// Replaces the instruction at ```location``` with each of ```replacements```
+fn replace(
+    program: &Program,
+    (f, i): (usize, usize),
+    replacements: impl IntoIterator<Item = Instruction>,
+) -> Vec<Program> {
+    replacements
+        .into_iter()
+        .map(|instr| {
+            let mut reduced = program.clone();
+            let func: &mut Function = &mut reduced.functions[f];
+            func.instrs[i] = Code::Instruction(instr);
+            reduced
+        })
+        .collect()
+}
+
+fn instruction(program: &Program, (f, i): (usize, usize)) -> &Instruction {
+    match &program.functions[f].instrs[i] {
+        Code::Instruction(instr) => instr,
+        Code::Label { .. } => unreachable!("locations of instructions do not point at labels"),
+    }
+}
+
+const fn is_branch(i: &Instruction) -> bool {
+    matches!(
+        i,
+        Instruction::Effect {
+            op: EffectOps::Branch,
+            ..
+        }
+    )
+}
+
+fn count_branches(program: &Program) -> usize {
+    find(program, is_branch).len()
+}
+
+/// Replaces a `br` with a `jmp` to either of its targets
+fn collapse_branch(program: &Program, index: usize) -> Vec<Program> {
+    let location = find(program, is_branch)[index];
+    let Instruction::Effect { labels, .. } = instruction(program, location) else {
+        unreachable!("branches are effect operations")
+    };
+    let jumps = labels.iter().map(|label| Instruction::Effect {
+        args: Vec::new(),
+        funcs: Vec::new(),
+        labels: vec![label.clone()],
+        op: EffectOps::Jump,
+        pos: None,
+        comments: None,
+    });
+    replace(program, location, jumps)
+}
+
+/// Operations which produce a value of a type that has constants
+const fn is_simplifiable_value(i: &Instruction) -> bool {
+    matches!(i, Instruction::Value { op_type, .. } if simplest(op_type).is_some())
+}
+
+fn count_values(program: &Program) -> usize {
+    find(program, is_simplifiable_value).len()
+}
+
+/// Replaces an operation with a constant of its type, which cuts the dependencies on its arguments
+fn value_to_constant(program: &Program, index: usize) -> Vec<Program> {
+    let location = find(program, is_simplifiable_value)[index];
+    let Instruction::Value { dest, op_type, .. } = instruction(program, location) else {
+        unreachable!("only value operations are counted")
+    };
+    let value = simplest(op_type).expect("only types with constants are counted");
+    replace(
+        program,
+        location,
+        [constant(dest.clone(), op_type.clone(), value)],
+    )
+}
+
+const fn is_constant(i: &Instruction) -> bool {
+    matches!(i, Instruction::Constant { .. })
+}
+
+fn count_constants(program: &Program) -> usize {
+    find(program, is_constant).len()
+}
+
+/// Replaces the literal of a constant with the simplest one of its type, or with `1` for integers.
+/// Only literals which come before the current one are tried, so that constants never change back and forth.
+fn simplify_constant(program: &Program, index: usize) -> Vec<Program> {
+    let location = find(program, is_constant)[index];
+    let Instruction::Constant {
+        dest,
+        const_type,
+        value,
+        ..
+    } = instruction(program, location)
+    else {
+        unreachable!("only constants are counted")
+    };
+    let simpler = match const_type {
+        Type::Int => vec![Literal::Int(0), Literal::Int(1)],
+        t => simplest(t).into_iter().collect(),
+    };
+    replace(
+        program,
+        location,
+        simpler
+            .into_iter()
+            .take_while(|literal| literal != value)
+            .map(|literal| constant(dest.clone(), const_type.clone(), literal)),
+    )
+}
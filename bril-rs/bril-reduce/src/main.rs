@@ -0,0 +1,66 @@
+use std::error::Error;
+use std::fs::File;
+use std::io::Read;
+use std::time::Duration;
+
+use clap::Parser;
+
+use bril_backend::{Backend, Exit};
+use bril_reduce::{cli::Cli, reduce, size};
+use bril_rs::extension::ExtensionSet;
+use bril_rs::{BrilError, Program, try_load_program_from_abstract_read, try_output_program};
+use bril2json::try_parse_abstract_program_from_read;
+
+fn load(file: Option<&str>, text: bool) -> Result<Program, Box<dyn Error>> {
+    let input: Box<dyn Read> = match file {
+        Some(f) => Box::new(File::open(f)?),
+        None => Box::new(std::io::stdin()),
+    };
+    if text {
+        let program =
+            try_parse_abstract_program_from_read(input, true, true, file.map(ToString::to_string))?;
+        Ok(Program::try_from(program).map_err(BrilError::from)?)
+    } else {
+        Ok(try_load_program_from_abstract_read(input)?)
+    }
+}
+
+fn run(args: Cli) -> Result<(), Box<dyn Error>> {
+    let program = load(args.file.as_deref(), args.text)?;
+    let test = Backend {
+        name: "test".to_string(),
+        command: args.test,
+        extensions: ExtensionSet::ALL,
+    };
+    let timeout = Duration::from_secs(args.timeout);
+    let mut tests = 0;
+    let reduced = reduce(&program, |candidate| {
+        tests += 1;
+        // A test which can not even be started is not going to find anything interesting
+        let outcome = test.run(candidate, &[], timeout).unwrap_or_else(|e| {
+            eprintln!("{e}");
+            std::process::exit(2)
+        });
+        outcome.exit == Exit::Code(0)
+    })?;
+    eprintln!(
+        "reduced from {} to {} instructions in {tests} tests",
+        size(&program),
+        size(&reduced)
+    );
+
+    if args.text_output {
+        print!("{reduced}");
+    } else {
+        try_output_program(&reduced, std::io::stdout().lock())?;
+    }
+
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run(Cli::parse()) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
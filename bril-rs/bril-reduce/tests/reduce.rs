@@ -0,0 +1,117 @@
+use bril_reduce::{ReduceError, reduce};
+use bril_rs::check::check_program;
+use bril_rs::extension::ExtensionSet;
+use bril_rs::{Code, Instruction, Program, ValueOps};
+use brilirs::basic_block::BBProgram;
+
+fn parse(source: &str) -> Program {
+    bril2json::parse_abstract_program_from_read(source.as_bytes(), false, false, None)
+        .try_into()
+        .unwrap()
+}
+
+/// What `brilirs` prints for ```prog```, or [None] if it is rejected or fails.
+/// Candidates are not always valid programs at run time, like a function which ends without returning its value, and `brilirs` may panic on those.
+fn run(prog: &Program) -> Option<String> {
+    std::panic::catch_unwind(|| {
+        brilirs::check::type_check(prog).ok()?;
+        let bbprog = BBProgram::try_from(prog.clone()).ok()?;
+        let mut out = Vec::new();
+        brilirs::interp::execute_main(&bbprog, &mut out, &[], false, std::io::sink()).ok()?;
+        String::from_utf8(out).ok()
+    })
+    .ok()
+    .flatten()
+}
+
+/// Without loops, every candidate terminates when it is run
+const PROGRAM: &str = "@double(x: int): int {
+  two: int = const 2;
+  y: int = mul x two;
+  ret y;
+}
+@main {
+  n: int = const 4;
+  one: int = const 1;
+  big: bool = gt n one;
+  br big .then .else;
+.then:
+  d: int = call @double n;
+  print d;
+  five: int = const 5;
+  print five;
+  jmp .end;
+.else:
+  print one;
+.end:
+  print n;
+}
+";
+
+#[test]
+fn uninteresting_programs_are_rejected() {
+    assert_eq!(
+        reduce(&parse(PROGRAM), |_| false),
+        Err(ReduceError::NotInteresting)
+    );
+}
+
+#[test]
+fn programs_shrink_to_what_makes_them_interesting() {
+    let reduced = reduce(&parse(PROGRAM), |p| {
+        run(p).is_some_and(|out| out.lines().any(|l| l == "5"))
+    })
+    .unwrap();
+    assert_eq!(
+        reduced.to_string(),
+        "@main {\n  five: int = const 5;\n  print five;\n}\n"
+    );
+}
+
+#[test]
+fn reduced_programs_stay_well_formed() {
+    let has_mul = |p: &Program| {
+        p.functions.iter().flat_map(|f| &f.instrs).any(|code| {
+            matches!(
+                code,
+                Code::Instruction(Instruction::Value {
+                    op: ValueOps::Mul,
+                    ..
+                })
+            )
+        })
+    };
+    let reduced = reduce(&parse(PROGRAM), |p| {
+        assert!(check_program(p, ExtensionSet::ALL).is_empty());
+        has_mul(p)
+    })
+    .unwrap();
+    // The call of @double is replaced with a constant, so everything but `mul` and its arguments goes away
+    assert_eq!(
+        reduced.to_string(),
+        "@double(x: int): int {\n  two: int = const 0;\n  y: int = mul x two;\n}\n@main {\n}\n"
+    );
+}
+
+#[test]
+fn problems_of_the_original_are_not_traded_for_new_ones() {
+    // `z` is never defined, and deleting the `add` would leave `y` undefined instead
+    let program = parse("@main {\n  x: int = const 1;\n  y: int = add x z;\n  print y;\n}\n");
+    let problems = |p: &Program| -> Vec<String> {
+        check_program(p, ExtensionSet::ALL)
+            .iter()
+            .map(|d| d.error.to_string())
+            .collect()
+    };
+    let original = problems(&program);
+    let reduced = reduce(&program, |p| {
+        assert!(problems(p).iter().all(|e| original.contains(e)), "{p}");
+        p.to_string().contains("print")
+    })
+    .unwrap();
+    // Replacing the `add` with a constant fixes the problem instead
+    assert_eq!(
+        reduced.to_string(),
+        "@main {\n  y: int = const 0;\n  print y;\n}\n"
+    );
+}
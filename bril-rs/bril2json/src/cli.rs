@@ -1,5 +1,7 @@
 use clap::{ArgAction::Count, Parser};
 
+use crate::ColumnMode;
+
 #[derive(Parser)]
 #[command(about, version, author)] // keeps the cli synced with Cargo.toml
 pub struct Cli {
@@ -9,4 +11,16 @@ pub struct Cli {
     /// Flag for whether position information should be included
     #[arg(short, action = Count)]
     pub position: u8,
+    /// How the columns of positions are counted
+    #[arg(long, value_enum, default_value_t = ColumnMode::Bytes)]
+    pub columns: ColumnMode,
+    /// Flag for whether positions should also include their byte offsets in the file
+    #[arg(long, action)]
+    pub byte_spans: bool,
+    /// Flag for whether functions, labels, and instructions should also include the comments around them, so that they are printed back by bril2txt
+    #[arg(long, action)]
+    pub comments: bool,
+    /// Flag for whether to parse and output the program one function at a time, for files too large to hold in memory. Output stops at the first syntax error.
+    #[arg(long, action)]
+    pub stream: bool,
 }
@@ -8,9 +8,17 @@
 pub mod bril_grammar;
 #[doc(hidden)]
 pub mod cli;
+/// Provides [`stream::StreamingParser`], which parses Bril text one function at a time, and [`stream::try_stream_abstract_program`] to convert it to JSON as it goes
+pub mod stream;
+use std::cell::RefCell;
+use std::fmt::Display;
 use std::fs::File;
 
-use bril_rs::{AbstractProgram, ColRow, Position};
+use std::ops::Range;
+
+use bril_rs::{AbstractFunction, AbstractProgram, ByteSpan, ColRow, Comments, Position};
+use lalrpop_util::lexer::Token;
+use thiserror::Error;
 
 /// A helper function for processing the accepted Bril characters from their text representation
 #[must_use]
@@ -29,28 +37,116 @@ pub fn escape_control_chars(s: &str) -> Option<char> {
     }
 }
 
+/// How the column of a source position is counted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ColumnMode {
+    /// Columns count bytes from the start of the line, which is what `bril2json` has always done
+    #[default]
+    Bytes,
+    /// Columns count UTF-8 characters from the start of the line, which is what editors show
+    Chars,
+}
+
+/// Which source positions the parser attaches to the program
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "each field is an independent setting"
+)]
+pub struct PositionOptions {
+    /// Whether to attach positions at all
+    pub use_pos: bool,
+    /// Whether positions also have an end
+    pub with_end: bool,
+    /// How columns are counted
+    pub columns: ColumnMode,
+    /// Whether positions also have a [`bril_rs::ByteSpan`]
+    pub byte_spans: bool,
+    /// Whether functions, labels, and instructions also get the [`Comments`] written around them.
+    /// Unlike the other options, this does not need ```use_pos```, since comments are not part of a [`Position`].
+    pub comments: bool,
+}
+
+impl PositionOptions {
+    /// The options used by the ```use_pos``` and ```with_end``` flags of [`parse_abstract_program_from_read`]
+    #[must_use]
+    pub fn new(use_pos: bool, with_end: bool) -> Self {
+        Self {
+            use_pos,
+            with_end,
+            ..Self::default()
+        }
+    }
+}
+
+/// What a [`Span`] of Bril text is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpanKind {
+    /// A whole function, from the `@` of its name to its closing `}`
+    Function,
+    /// The name of a function in its signature
+    FunctionName,
+    /// The name of an argument in the signature of a function
+    Argument,
+    /// The name of a label where it is placed, like `.loop` in `.loop:`
+    LabelDefinition,
+    /// The destination of an instruction
+    Destination,
+    /// A variable used as an argument of an instruction
+    Variable,
+    /// A function used by an instruction, like the function of a `call`
+    FunctionUse,
+    /// A label used by an instruction, like the targets of a `br`
+    LabelUse,
+    /// The path of an import, along with its quotes
+    ImportPath,
+    /// The name of an imported function in the file it is imported from
+    ImportedFunction,
+    /// The name an imported function is given with `as`
+    ImportAlias,
+}
+
+/// The bytes of a function or of a name in Bril text, as found by [`parse_abstract_program_with_spans`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    /// What the bytes are
+    pub kind: SpanKind,
+    /// The bytes, where names leave out the `@` of a function and the `.` of a label
+    pub range: Range<usize>,
+}
+
 #[doc(hidden)]
 #[derive(Clone)]
-pub struct Lines {
-    use_pos: bool,
-    with_end: bool,
+pub struct Lines<'input> {
+    input: &'input str,
+    options: PositionOptions,
+    /// The byte index of each newline, in order, so that the line of an index can be found with a binary search
     #[expect(clippy::struct_field_names, reason = "Names are hard")]
     new_lines: Vec<usize>,
+    /// The byte range of each comment, from its `#` to the end of its line, if comments are being kept
+    comments: Vec<Range<usize>>,
     src_name: Option<String>,
+    /// The number of bytes and lines of the file before ```input```, when it is only a piece of the file
+    offset: usize,
+    rows_before: usize,
+    /// The number of bytes at the start of ```input``` which are only there so that comments after them are seen in context, and are not parsed
+    skip: usize,
+    /// The spans found by the parser, if they are being recorded
+    spans: Option<RefCell<Vec<Span>>>,
 }
 
-// For use in the parser
+// For use in the parser, where each name comes with where it starts and ends
 enum ParsingArgs {
-    Func(String),
-    Ident(String),
-    Label(String),
+    Func((usize, String, usize)),
+    Ident((usize, String, usize)),
+    Label((usize, String, usize)),
 }
 
-impl Lines {
-    fn new(input: &str, use_pos: bool, with_end: bool, src_name: Option<String>) -> Self {
+impl<'input> Lines<'input> {
+    fn new(input: &'input str, options: PositionOptions, src_name: Option<String>) -> Self {
         Self {
-            use_pos,
-            with_end,
+            input,
+            options,
             src_name,
             new_lines: input
                 .as_bytes()
@@ -58,78 +154,541 @@ impl Lines {
                 .enumerate()
                 .filter_map(|(idx, b)| if *b == b'\n' { Some(idx) } else { None })
                 .collect(),
+            comments: if options.comments {
+                find_comments(input)
+            } else {
+                Vec::new()
+            },
+            offset: 0,
+            rows_before: 0,
+            skip: 0,
+            spans: None,
+        }
+    }
+
+    /// Records that the bytes from ```start``` to ```end``` are a ```kind```, if spans are being recorded
+    fn span(&self, kind: SpanKind, start: usize, end: usize) {
+        if let Some(spans) = &self.spans {
+            spans.borrow_mut().push(Span {
+                kind,
+                range: self.offset + self.local(start)..self.offset + self.local(end),
+            });
+        }
+    }
+
+    /// Records the span of a name which the grammar found along with where it starts and ends, and returns the name
+    fn named(&self, kind: SpanKind, (start, name, end): (usize, String, usize)) -> String {
+        self.span(kind, start, end);
+        name
+    }
+
+    /// Splits the arguments of an instruction into its variables, functions, and labels
+    fn split_args(&self, args: Vec<ParsingArgs>) -> (Vec<String>, Vec<String>, Vec<String>) {
+        let mut vars = Vec::new();
+        let mut funcs = Vec::new();
+        let mut labels = Vec::new();
+        for arg in args {
+            match arg {
+                ParsingArgs::Ident(name) => vars.push(self.named(SpanKind::Variable, name)),
+                ParsingArgs::Func(name) => funcs.push(self.named(SpanKind::FunctionUse, name)),
+                ParsingArgs::Label(name) => labels.push(self.named(SpanKind::LabelUse, name)),
+            }
         }
+        (vars, funcs, labels)
+    }
+
+    /// The text which is parsed
+    fn source(&self) -> &'input str {
+        &self.input[self.skip..]
+    }
+
+    /// The index into ```input``` of ```index```, an index into the text which is parsed
+    const fn local(&self, index: usize) -> usize {
+        self.skip + index
     }
 
     fn get_position(&self, starting_index: usize, ending_index: usize) -> Option<Position> {
-        if self.use_pos {
-            Some(Position {
-                pos: self.get_row_col(starting_index).unwrap(),
-                pos_end: if self.with_end {
-                    self.get_row_col(ending_index)
-                } else {
-                    None
-                },
-                src: self.src_name.clone(),
-            })
+        let (starting_index, ending_index) = (self.local(starting_index), self.local(ending_index));
+        if self.options.use_pos {
+            Some(self.position(starting_index, ending_index, self.options.with_end))
         } else {
             None
         }
     }
 
-    fn get_row_col(&self, index: usize) -> Option<ColRow> {
-        if self.use_pos {
-            Some(
-                self.new_lines
-                    .iter()
-                    .enumerate()
-                    //(i+1) because line numbers start at 1
-                    .map(|(i, j)| (i + 1, j))
-                    .fold(
-                        ColRow {
-                            // (index + 1) because column numbers start at 1
-                            col: (index + 1) as u64,
-                            // Hard code the first row to be 1
-                            row: 1,
-                        },
-                        |current, (line_num, idx)| {
-                            if *idx < index {
-                                ColRow {
-                                    // (line_num + 1) because line numbers start at 1
-                                    row: (line_num + 1) as u64,
-                                    // column values are kept relative to the previous index
-                                    col: ((index) - idx) as u64,
-                                }
-                            } else {
-                                current
-                            }
-                        },
-                    ),
-            )
+    /// The comments of the label or instruction from ```starting_index``` to ```ending_index```
+    fn get_comments(&self, starting_index: usize, ending_index: usize) -> Option<Box<Comments>> {
+        if !self.options.comments {
+            return None;
+        }
+        let (starting_index, ending_index) = (self.local(starting_index), self.local(ending_index));
+        non_empty(Comments {
+            leading: self.leading_comments(starting_index),
+            trailing: self.trailing_comment(ending_index),
+            ..Comments::default()
+        })
+    }
+
+    /// The comments of a function which starts at ```start```, and whose body is between the index just after its `{` and the index of its `}`
+    fn get_function_comments(
+        &self,
+        start: usize,
+        open: usize,
+        close: usize,
+    ) -> Option<Box<Comments>> {
+        if !self.options.comments {
+            return None;
+        }
+        let (start, open, close) = (self.local(start), self.local(open), self.local(close));
+        non_empty(Comments {
+            leading: self.leading_comments(start),
+            trailing: self.trailing_comment(open),
+            closing: self.leading_comments(close),
+            ..Comments::default()
+        })
+    }
+
+    /// Gives ```last```, the last function of the program which ends at ```end```, the comments after its closing brace
+    fn attach_end_comments(&self, last: Option<&mut AbstractFunction>, end: usize) {
+        let Some(last) = last else {
+            return;
+        };
+        let end = self.local(end);
+        let after: Vec<String> = self.comments[self.comments.partition_point(|c| c.start < end)..]
+            .iter()
+            .map(|c| self.comment_text(c))
+            .collect();
+        if !after.is_empty() {
+            last.comments.get_or_insert_default().after = after;
+        }
+    }
+
+    /// The comments on their own lines just before ```index```, with only whitespace between them, and the comment after a `}` before them if there is one
+    fn leading_comments(&self, index: usize) -> Vec<String> {
+        let mut leading = Vec::new();
+        let mut boundary = index;
+        for comment in self.comments[..self.comments.partition_point(|c| c.end <= index)]
+            .iter()
+            .rev()
+        {
+            if !self.input[comment.end..boundary].trim().is_empty() {
+                break;
+            }
+            let line_start = self.input[..comment.start].rfind('\n').map_or(0, |i| i + 1);
+            let before = self.input[line_start..comment.start].trim();
+            // A comment after other code on its line is the trailing comment of that code, except after a `}` which can not have one
+            if !before.is_empty() && !before.ends_with('}') {
+                break;
+            }
+            leading.push(self.comment_text(comment));
+            boundary = comment.start;
+            if !before.is_empty() {
+                break;
+            }
+        }
+        leading.reverse();
+        leading
+    }
+
+    /// The comment which starts on the same line as ```index```, with only spaces between them
+    fn trailing_comment(&self, index: usize) -> Option<String> {
+        let comment = self
+            .comments
+            .get(self.comments.partition_point(|c| c.start < index))?;
+        self.input[index..comment.start]
+            .chars()
+            .all(|c| c == ' ' || c == '\t')
+            .then(|| self.comment_text(comment))
+    }
+
+    fn comment_text(&self, comment: &Range<usize>) -> String {
+        self.input[comment.start + 1..comment.end].to_string()
+    }
+
+    /// The position of the bytes from ```start``` to ```end```, whether or not positions are being recorded
+    fn position(&self, start: usize, end: usize, with_end: bool) -> Position {
+        Position {
+            pos: self.row_col(start),
+            pos_end: with_end.then(|| self.row_col(end)),
+            src: self.src_name.clone(),
+            byte_span: self.options.byte_spans.then_some(ByteSpan {
+                start: (self.offset + start) as u64,
+                end: (self.offset + end) as u64,
+            }),
+            origin: None,
+        }
+    }
+
+    /// The row and column of the byte at ```index```
+    fn row_col(&self, index: usize) -> ColRow {
+        // The number of lines which end before the index
+        let line = self.new_lines.partition_point(|idx| *idx < index);
+        let line_start = if line == 0 {
+            0
         } else {
-            None
+            self.new_lines[line - 1] + 1
+        };
+        let col = match self.options.columns {
+            ColumnMode::Bytes => index - line_start,
+            ColumnMode::Chars => self
+                .input
+                .get(line_start..index)
+                .map_or(index - line_start, |s| s.chars().count()),
+        };
+        ColRow {
+            // Both rows and columns start at 1
+            row: (self.rows_before + line + 1) as u64,
+            col: (col + 1) as u64,
+        }
+    }
+}
+
+fn non_empty(comments: Comments) -> Option<Box<Comments>> {
+    (!comments.is_empty()).then(|| Box::new(comments))
+}
+
+/// The byte range of every comment in ```input```, from its `#` up to the end of its line
+///
+/// This follows the tokens of the grammar just enough to skip a `#` inside a character or string literal.
+fn find_comments(input: &str) -> Vec<Range<usize>> {
+    let bytes = input.as_bytes();
+    let mut comments = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'#' => {
+                let end = bytes[i..]
+                    .iter()
+                    .position(|b| *b == b'\n' || *b == b'\r')
+                    .map_or(bytes.len(), |len| i + len);
+                comments.push(i..end);
+                i = end;
+            }
+            b'\'' => i += char_literal_len(&input[i..]).unwrap_or(1),
+            b'"' => {
+                i += bytes[i + 1..]
+                    .iter()
+                    .position(|b| *b == b'"')
+                    .map_or(1, |len| len + 2);
+            }
+            _ => i += 1,
         }
     }
+    comments
+}
+
+/// The length in bytes of the character literal at the start of ```s```, like `'#'` or `'\n'`
+fn char_literal_len(s: &str) -> Option<usize> {
+    let mut chars = s.char_indices().skip(1);
+    let (_, c) = chars.next()?;
+    let (end, next) = chars.next()?;
+    if c != '\n' && c != '\r' && next == '\'' {
+        return Some(end + 1);
+    }
+    let (end, quote) = chars.next()?;
+    (c == '\\' && "0abtnvfr".contains(next) && quote == '\'').then_some(end + 1)
+}
+
+/// An integer literal which does not fit in an `i64`, reported by the grammar as a user error
+#[doc(hidden)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidLiteral {
+    start: usize,
+    end: usize,
+    literal: String,
+}
+
+/// What went wrong at a [`SyntaxError`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxErrorKind {
+    /// Text which is not any Bril token, like a stray `$`
+    InvalidToken,
+    /// The input ended in the middle of the program
+    UnexpectedEof,
+    /// A token appeared where it is not allowed
+    UnexpectedToken(String),
+    /// An integer literal which does not fit in 64 bits
+    IntegerOverflow(String),
+}
+
+impl Display for SyntaxErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidToken => write!(f, "invalid token"),
+            Self::UnexpectedEof => write!(f, "unexpected end of file"),
+            Self::UnexpectedToken(token) => write!(f, "unexpected token `{token}`"),
+            Self::IntegerOverflow(literal) => {
+                write!(f, "integer literal `{literal}` does not fit in 64 bits")
+            }
+        }
+    }
+}
+
+/// A syntax error found while parsing Bril text
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxError {
+    /// What went wrong
+    pub kind: SyntaxErrorKind,
+    /// Where the error is, which always has an end and has a `src` if the text was read from a file
+    pub pos: Position,
+    /// Descriptions of the tokens which would have been accepted instead, like `` `;` `` or `identifier`
+    pub expected: Vec<String>,
+}
+
+impl SyntaxError {
+    /// Describes the error without its position, for tools which show the position themselves
+    #[must_use]
+    pub fn message(&self) -> String {
+        match self.expected.as_slice() {
+            [] => self.kind.to_string(),
+            [token] => format!("{}, expected {token}", self.kind),
+            tokens => format!("{}, expected one of {}", self.kind, tokens.join(", ")),
+        }
+    }
+}
+
+impl Display for SyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(src) = &self.pos.src {
+            write!(f, "{src}:")?;
+        }
+        write!(
+            f,
+            "{}:{}: {}",
+            self.pos.pos.row,
+            self.pos.pos.col,
+            self.message()
+        )
+    }
+}
+
+/// The errors that can occur while parsing Bril text
+#[derive(Error, Debug)]
+pub enum ParseError {
+    /// Reading the input failed
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    /// The file the text is said to come from could not be found
+    #[error("could not find `{path}`: {source}")]
+    File {
+        /// The name of the file
+        path: String,
+        /// Why it could not be found
+        source: std::io::Error,
+    },
+    /// The text is not a Bril program. Every syntax error found is listed in the order they appear in.
+    #[error("{}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n"))]
+    Syntax(Vec<SyntaxError>),
+    /// Writing the program as it was parsed failed
+    #[error(transparent)]
+    Output(#[from] bril_rs::BrilError),
+}
+
+type GrammarError<'input> = lalrpop_util::ParseError<usize, Token<'input>, InvalidLiteral>;
+
+impl Lines<'_> {
+    fn syntax_error(&self, error: GrammarError<'_>) -> SyntaxError {
+        let (start, end, kind, expected) = match error {
+            GrammarError::InvalidToken { location } => (
+                location,
+                location + 1,
+                SyntaxErrorKind::InvalidToken,
+                Vec::new(),
+            ),
+            GrammarError::UnrecognizedEof { location, expected } => {
+                (location, location, SyntaxErrorKind::UnexpectedEof, expected)
+            }
+            GrammarError::UnrecognizedToken {
+                token: (start, token, end),
+                expected,
+            } => (
+                start,
+                end,
+                SyntaxErrorKind::UnexpectedToken(token.1.to_string()),
+                expected,
+            ),
+            GrammarError::ExtraToken {
+                token: (start, token, end),
+            } => (
+                start,
+                end,
+                SyntaxErrorKind::UnexpectedToken(token.1.to_string()),
+                Vec::new(),
+            ),
+            GrammarError::User {
+                error:
+                    InvalidLiteral {
+                        start,
+                        end,
+                        literal,
+                    },
+            } => (
+                start,
+                end,
+                SyntaxErrorKind::IntegerOverflow(literal),
+                Vec::new(),
+            ),
+        };
+        SyntaxError {
+            kind,
+            pos: self.position(self.local(start), self.local(end), true),
+            expected: expected.iter().map(|t| describe_token(t)).collect(),
+        }
+    }
+}
+
+/// Describes a terminal of the grammar as it is listed in ```expected``` by LALRPOP: either a quoted literal token or the name of a regular expression
+fn describe_token(token: &str) -> String {
+    if let Some(literal) = token.strip_prefix('"').and_then(|t| t.strip_suffix('"')) {
+        return format!("`{literal}`");
+    }
+    match token {
+        "IDENT_TOKEN" => "identifier",
+        "INT_TOKEN" => "integer",
+        "FLOAT_TOKEN" => "float",
+        "CHAR_TOKEN" => "character",
+        "STRING_TOKEN" => "string",
+        t => t,
+    }
+    .to_string()
 }
 
 /// The entrance point to the bril2json parser. It takes an ```input```:[`std::io::Read`] which should be the Bril text file. You can control whether it includes source code positions with ```use_pos```.
+///
+/// ```file_name``` is the file the text was read from, which is recorded in source positions and syntax errors.
+/// Parsing continues after a syntax error at the end of the instruction it is in, so every error in the text can be reported at once.
+/// # Errors
+/// Will return an error if ```input``` can not be read, ```file_name``` does not exist, or the input is not well-formed Bril text
+pub fn try_parse_abstract_program_from_read<R: std::io::Read>(
+    input: R,
+    use_pos: bool,
+    with_end: bool,
+    file_name: Option<String>,
+) -> Result<AbstractProgram, ParseError> {
+    try_parse_abstract_program_with_options(
+        input,
+        PositionOptions::new(use_pos, with_end),
+        file_name,
+    )
+}
+
+/// A version of [`try_parse_abstract_program_from_read`] which also controls how columns are counted and whether byte spans are included, see [`PositionOptions`]
+/// # Errors
+/// Will return an error if ```input``` can not be read, ```file_name``` does not exist, or the input is not well-formed Bril text
+pub fn try_parse_abstract_program_with_options<R: std::io::Read>(
+    mut input: R,
+    options: PositionOptions,
+    file_name: Option<String>,
+) -> Result<AbstractProgram, ParseError> {
+    let mut buffer = String::new();
+    input.read_to_string(&mut buffer)?;
+
+    match parse_abstract_program_recovering(&buffer, options, src_name(file_name)?) {
+        (Some(program), errors) if errors.is_empty() => Ok(program),
+        (_, errors) => Err(ParseError::Syntax(errors)),
+    }
+}
+
+/// The name recorded in positions for the file at ```file_name```, which is its canonical path
+fn src_name(file_name: Option<String>) -> Result<Option<String>, ParseError> {
+    file_name
+        .map(|path| match std::fs::canonicalize(&path) {
+            Ok(f) => Ok(f.display().to_string()),
+            Err(source) => Err(ParseError::File { path, source }),
+        })
+        .transpose()
+}
+
+/// Parses ```source``` and returns every syntax error along with as much of the program as could be parsed, for tools like editors which work with programs that are being written
+///
+/// The program leaves out each instruction or function which had a syntax error in it. It is [None] when the parser could not recover, like when ```source``` ends in the middle of a function.
+/// Unlike [`try_parse_abstract_program_with_options`], ```src_name``` is used in positions as it is given.
+#[must_use]
+pub fn parse_abstract_program_recovering(
+    source: &str,
+    options: PositionOptions,
+    src_name: Option<String>,
+) -> (Option<AbstractProgram>, Vec<SyntaxError>) {
+    parse_lines(&Lines::new(source, options, src_name), false)
+}
+
+/// A version of [`parse_abstract_program_recovering`] which also returns where each function and each name is in ```source```, in the order they start in, for tools like editors which need to find them
+///
+/// Only the functions and instructions which are part of the program have spans, so those with syntax errors have none.
+#[must_use]
+pub fn parse_abstract_program_with_spans(
+    source: &str,
+    options: PositionOptions,
+    src_name: Option<String>,
+) -> (Option<AbstractProgram>, Vec<SyntaxError>, Vec<Span>) {
+    let lines = Lines {
+        spans: Some(RefCell::default()),
+        ..Lines::new(source, options, src_name)
+    };
+    let (program, errors) = parse_lines(&lines, false);
+    let mut spans = lines.spans.map(RefCell::into_inner).unwrap_or_default();
+    spans.sort_by_key(|s| s.range.start);
+    (program, errors, spans)
+}
+
+/// Parses the source of ```lines```, which only has functions and no imports if ```functions_only``` is set
+fn parse_lines(
+    lines: &Lines<'_>,
+    functions_only: bool,
+) -> (Option<AbstractProgram>, Vec<SyntaxError>) {
+    let mut recovered = Vec::new();
+    let result = if functions_only {
+        bril_grammar::FunctionsParser::new()
+            .parse(lines, &mut recovered, lines.source())
+            .map(|functions| AbstractProgram {
+                functions,
+                imports: Vec::new(),
+            })
+    } else {
+        bril_grammar::AbstractProgramParser::new().parse(lines, &mut recovered, lines.source())
+    };
+    let mut errors: Vec<_> = recovered
+        .into_iter()
+        .map(|e| lines.syntax_error(e.error))
+        .collect();
+    match result {
+        Ok(program) => (Some(program), errors),
+        Err(e) => {
+            errors.push(lines.syntax_error(e));
+            (None, errors)
+        }
+    }
+}
+
+/// A wrapper around [`try_parse_abstract_program_with_options`] which assumes [`std::io::Stdin`] if `file_name` is [`None`]
+/// # Errors
+/// Will return an error if `file_name` can not be read or the input is not well-formed Bril text
+pub fn try_parse_abstract_program(
+    options: PositionOptions,
+    file_name: Option<String>,
+) -> Result<AbstractProgram, ParseError> {
+    let input: Box<dyn std::io::Read> = match &file_name {
+        None => Box::new(std::io::stdin()),
+        Some(f) => Box::new(File::open(f).map_err(|source| ParseError::File {
+            path: f.clone(),
+            source,
+        })?),
+    };
+
+    try_parse_abstract_program_with_options(input, options, file_name)
+}
+
+/// A version of [`try_parse_abstract_program_from_read`] for input which is known to be well-formed, like in tests
 /// # Panics
 /// Will panic if the input is not well-formed Bril text
 pub fn parse_abstract_program_from_read<R: std::io::Read>(
-    mut input: R,
+    input: R,
     use_pos: bool,
     with_end: bool,
     file_name: Option<String>,
 ) -> AbstractProgram {
-    let mut buffer = String::new();
-    input.read_to_string(&mut buffer).unwrap();
-    let parser = bril_grammar::AbstractProgramParser::new();
-
-    let src_name = file_name.map(|f| std::fs::canonicalize(f).unwrap().display().to_string());
-
-    parser
-        .parse(&Lines::new(&buffer, use_pos, with_end, src_name), &buffer)
-        .unwrap()
+    try_parse_abstract_program_from_read(input, use_pos, with_end, file_name)
+        .unwrap_or_else(|e| panic!("{e}"))
 }
 
 #[must_use]
@@ -141,10 +700,6 @@ pub fn parse_abstract_program(
     with_end: bool,
     file_name: Option<String>,
 ) -> AbstractProgram {
-    let input = file_name.clone().map_or_else(
-        || -> Box<dyn std::io::Read> { Box::new(std::io::stdin()) },
-        |f| Box::new(File::open(f).unwrap()),
-    );
-
-    parse_abstract_program_from_read(input, use_pos, with_end, file_name)
+    try_parse_abstract_program(PositionOptions::new(use_pos, with_end), file_name)
+        .unwrap_or_else(|e| panic!("{e}"))
 }
@@ -1,13 +1,35 @@
+use std::io::{BufWriter, Write};
+
 use bril_rs::output_abstract_program;
 use bril2json::cli::Cli;
-use bril2json::parse_abstract_program;
+use bril2json::stream::try_stream_abstract_program;
+use bril2json::{PositionOptions, try_parse_abstract_program};
 use clap::Parser;
 
 fn main() {
     let args = Cli::parse();
-    output_abstract_program(&parse_abstract_program(
-        args.position >= 1,
-        args.position >= 2,
-        args.file,
-    ))
+    let options = PositionOptions {
+        use_pos: args.position >= 1 || args.byte_spans,
+        with_end: args.position >= 2,
+        columns: args.columns,
+        byte_spans: args.byte_spans,
+        comments: args.comments,
+    };
+    if args.stream {
+        let output = BufWriter::new(std::io::stdout().lock());
+        if let Err(e) = try_stream_abstract_program(output, options, args.file)
+            .and_then(|mut output| Ok(output.flush()?))
+        {
+            eprintln!("{e}");
+            std::process::exit(1)
+        }
+        return;
+    }
+    match try_parse_abstract_program(options, args.file) {
+        Ok(program) => output_abstract_program(&program),
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1)
+        }
+    }
 }
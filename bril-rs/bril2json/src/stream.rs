@@ -0,0 +1,281 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+
+use bril_rs::{AbstractFunction, AbstractProgramWriter, Import};
+
+use crate::{Lines, ParseError, PositionOptions, char_literal_len, parse_lines, src_name};
+
+/// A part of a program at the top level of the file
+#[derive(Debug, Clone)]
+pub enum ProgramItem {
+    /// An import of functions from another file
+    Import(Import),
+    /// A function defined in the file
+    Function(AbstractFunction),
+}
+
+/// Text from the file which is parsed on its own
+#[derive(Debug, Default)]
+struct Piece {
+    /// Whole lines of the file, except that the last may be cut short
+    text: String,
+    /// The number of bytes at the start of ```text``` which belong to the piece before this one
+    skip: usize,
+    /// The number of bytes and lines of the file before ```text```
+    offset: usize,
+    rows_before: usize,
+}
+
+/// Follows the braces and literals of each line which is read, to find where pieces end
+#[derive(Debug, Default)]
+struct Scanner {
+    /// The depth of braces at the end of the last line, and whether it ends inside of a string
+    depth: usize,
+    in_string: bool,
+    /// Whether the current piece has anything besides whitespace and comments
+    has_code: bool,
+}
+
+impl Scanner {
+    /// Scans ```line```, and returns the index just after the `}` or `;` it ends with if that ends a piece
+    fn scan(&mut self, line: &str) -> Option<usize> {
+        let bytes = line.as_bytes();
+        let mut last = None;
+        let mut i = 0;
+        while i < bytes.len() {
+            if self.in_string {
+                let Some(len) = bytes[i..].iter().position(|b| *b == b'"') else {
+                    break;
+                };
+                self.in_string = false;
+                i += len + 1;
+                last = Some((i, b'"'));
+                continue;
+            }
+            let start = i;
+            match bytes[i] {
+                b'#' => break,
+                b if b.is_ascii_whitespace() => {
+                    i += 1;
+                    continue;
+                }
+                b'\'' => i += char_literal_len(&line[i..]).unwrap_or(1),
+                b'"' => {
+                    self.in_string = true;
+                    i += 1;
+                }
+                b'{' => {
+                    self.depth += 1;
+                    i += 1;
+                }
+                b'}' => {
+                    self.depth = self.depth.saturating_sub(1);
+                    i += 1;
+                }
+                _ => i += 1,
+            }
+            self.has_code = true;
+            last = Some((i, bytes[start]));
+        }
+        match last {
+            Some((end, b'}' | b';')) if self.depth == 0 && !self.in_string => Some(end),
+            _ => None,
+        }
+    }
+}
+
+/// Parses Bril text one function at a time, so that files which are too large to hold in memory can be converted
+///
+/// The input is read a line at a time and cut after each `}` or `;` which ends a line at the top level. Each piece is parsed on its own
+/// with the same grammar and positions as [`crate::try_parse_abstract_program_with_options`], so the items are the same as the
+/// imports and functions of the program it would return. Comments at the end of the file are attached to the last function,
+/// which is held back until the input ends.
+///
+/// Syntax errors are returned as a [`ParseError::Syntax`] for each piece they are in, before the items of that piece,
+/// and parsing goes on with the next piece. Reading stops at the first [`ParseError::Io`].
+#[derive(Debug)]
+pub struct StreamingParser<R> {
+    input: R,
+    options: PositionOptions,
+    src_name: Option<String>,
+    /// The lines read since the end of the last piece
+    pending: Piece,
+    scanner: Scanner,
+    /// The last piece, which waits for the next one in case only comments follow it
+    held: Option<Piece>,
+    /// Whether a function has been parsed, after which imports are not allowed
+    seen_function: bool,
+    items: VecDeque<Result<ProgramItem, ParseError>>,
+    done: bool,
+}
+
+impl<R: BufRead> StreamingParser<R> {
+    /// Parses ```input``` with ```options```, recording ```src_name``` as the file of each position
+    pub fn new(input: R, options: PositionOptions, src_name: Option<String>) -> Self {
+        Self {
+            input,
+            options,
+            src_name,
+            pending: Piece::default(),
+            scanner: Scanner::default(),
+            held: None,
+            seen_function: false,
+            items: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Reads lines until a piece is complete or the input ends, and parses the piece before it
+    fn read_piece(&mut self) {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match self.input.read_line(&mut line) {
+                Err(e) => {
+                    self.items.push_back(Err(e.into()));
+                    self.done = true;
+                    return;
+                }
+                Ok(0) => {
+                    let pending = std::mem::take(&mut self.pending);
+                    match self.held.take() {
+                        Some(mut held) if !self.scanner.has_code => {
+                            held.text.push_str(&pending.text[pending.skip..]);
+                            self.parse(&held);
+                        }
+                        held => {
+                            if let Some(held) = held {
+                                self.parse(&held);
+                            }
+                            if self.scanner.has_code {
+                                self.parse(&pending);
+                            }
+                        }
+                    }
+                    self.done = true;
+                    return;
+                }
+                Ok(_) => {}
+            }
+            let line_start = self.pending.text.len();
+            self.pending.text.push_str(&line);
+            let Some(mut end) = self.scanner.scan(&line) else {
+                continue;
+            };
+            // The comment after the `;` of an import is its trailing comment, while one after a `}` belongs to what comes next
+            if line[..end].ends_with(';') {
+                end = line.len();
+            }
+            // The rest of the line starts the next piece, along with what is before it so that its comments are seen in context
+            let next = Piece {
+                text: line.clone(),
+                skip: end,
+                offset: self.pending.offset + line_start,
+                rows_before: self.pending.rows_before
+                    + self.pending.text[..line_start].matches('\n').count(),
+            };
+            let mut piece = std::mem::replace(&mut self.pending, next);
+            piece.text.truncate(line_start + end);
+            self.scanner.has_code = false;
+            if let Some(held) = self.held.replace(piece) {
+                self.parse(&held);
+                return;
+            }
+        }
+    }
+
+    fn parse(&mut self, piece: &Piece) {
+        let mut lines = Lines::new(&piece.text, self.options, self.src_name.clone());
+        lines.offset = piece.offset;
+        lines.rows_before = piece.rows_before;
+        lines.skip = piece.skip;
+        let (program, errors) = parse_lines(&lines, self.seen_function);
+        if !errors.is_empty() {
+            self.items.push_back(Err(ParseError::Syntax(errors)));
+        }
+        let Some(program) = program else {
+            return;
+        };
+        self.seen_function |= !program.functions.is_empty();
+        self.items.extend(
+            program
+                .imports
+                .into_iter()
+                .map(|i| Ok(ProgramItem::Import(i)))
+                .chain(
+                    program
+                        .functions
+                        .into_iter()
+                        .map(|f| Ok(ProgramItem::Function(f))),
+                ),
+        );
+    }
+}
+
+impl<R: BufRead> Iterator for StreamingParser<R> {
+    type Item = Result<ProgramItem, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.items.pop_front() {
+                return Some(item);
+            }
+            if self.done {
+                return None;
+            }
+            self.read_piece();
+        }
+    }
+}
+
+/// Parses Bril text from ```input``` one function at a time with a [`StreamingParser`], writing each function to ```output``` as JSON once it is parsed
+///
+/// The output is the same as [`bril_rs::try_output_abstract_program`] writes for the program which [`crate::try_parse_abstract_program_with_options`] returns,
+/// but memory use only grows with the largest function instead of the whole program.
+/// Writing stops at the first syntax error, but the rest of the input is still parsed so that every syntax error is returned.
+/// # Errors
+/// Will return an error if ```input``` can not be read, ```output``` can not be written, ```file_name``` does not exist, or the input is not well-formed Bril text
+pub fn try_stream_abstract_program_from_read<R: BufRead, W: Write>(
+    input: R,
+    output: W,
+    options: PositionOptions,
+    file_name: Option<String>,
+) -> Result<W, ParseError> {
+    let mut writer = AbstractProgramWriter::new(output);
+    let mut errors = Vec::new();
+    for item in StreamingParser::new(input, options, src_name(file_name)?) {
+        match item {
+            Ok(_) if !errors.is_empty() => {}
+            Ok(ProgramItem::Import(import)) => writer.add_import(import),
+            Ok(ProgramItem::Function(function)) => writer.write_function(&function)?,
+            Err(ParseError::Syntax(mut e)) => errors.append(&mut e),
+            Err(e) => return Err(e),
+        }
+    }
+    if !errors.is_empty() {
+        return Err(ParseError::Syntax(errors));
+    }
+    Ok(writer.finish()?)
+}
+
+/// A wrapper around [`try_stream_abstract_program_from_read`] which assumes [`std::io::Stdin`] if `file_name` is [`None`]
+/// # Errors
+/// Will return an error if `file_name` can not be read, ```output``` can not be written, or the input is not well-formed Bril text
+pub fn try_stream_abstract_program<W: Write>(
+    output: W,
+    options: PositionOptions,
+    file_name: Option<String>,
+) -> Result<W, ParseError> {
+    let input: Box<dyn BufRead> = match &file_name {
+        None => Box::new(std::io::stdin().lock()),
+        Some(f) => Box::new(BufReader::new(File::open(f).map_err(|source| {
+            ParseError::File {
+                path: f.clone(),
+                source,
+            }
+        })?)),
+    };
+
+    try_stream_abstract_program_from_read(input, output, options, file_name)
+}
@@ -0,0 +1,147 @@
+use bril_rs::{AbstractCode, AbstractInstruction, AbstractProgram, Code, Comments, Program};
+use bril2json::{PositionOptions, try_parse_abstract_program_with_options};
+
+const SOURCE: &str = "# Counts down
+@main {  # the entry point
+  # the counter
+  i: int = const 3;
+.loop:  # back edge target
+  c: char = const '#';  # not a comment
+  jmp .loop;
+  # never reached
+}
+# after every function
+";
+
+fn parse(source: &str, use_pos: bool, comments: bool) -> AbstractProgram {
+    let options = PositionOptions {
+        comments,
+        ..PositionOptions::new(use_pos, false)
+    };
+    try_parse_abstract_program_with_options(source.as_bytes(), options, None).unwrap()
+}
+
+fn comments(code: &AbstractCode) -> Option<Box<Comments>> {
+    match code {
+        AbstractCode::Instruction(
+            AbstractInstruction::Constant { comments, .. }
+            | AbstractInstruction::Value { comments, .. }
+            | AbstractInstruction::Effect { comments, .. },
+        )
+        | AbstractCode::Label { comments, .. } => comments.clone(),
+    }
+}
+
+fn strings(comments: &[&str]) -> Vec<String> {
+    comments.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn comments_are_attached_to_nearby_code() {
+    let program = parse(SOURCE, false, true);
+    let main = &program.functions[0];
+    assert_eq!(
+        main.comments,
+        Some(Box::new(Comments {
+            leading: strings(&[" Counts down"]),
+            trailing: Some(" the entry point".to_string()),
+            closing: strings(&[" never reached"]),
+            after: strings(&[" after every function"]),
+        }))
+    );
+    let code: Vec<_> = main.instrs.iter().map(comments).collect();
+    assert_eq!(
+        code[0].as_ref().unwrap().leading,
+        strings(&[" the counter"])
+    );
+    assert_eq!(
+        code[1].as_ref().unwrap().trailing.as_deref(),
+        Some(" back edge target")
+    );
+    assert_eq!(
+        code[2].as_ref().unwrap().trailing.as_deref(),
+        Some(" not a comment")
+    );
+    assert_eq!(code[3], None);
+
+    assert!(
+        parse(SOURCE, true, false).functions[0]
+            .instrs
+            .iter()
+            .all(|c| comments(c).is_none())
+    );
+}
+
+#[test]
+fn comments_are_printed_back() {
+    assert_eq!(parse(SOURCE, false, true).to_string(), SOURCE);
+
+    for use_pos in [false, true] {
+        let program: Program = parse(SOURCE, use_pos, true).try_into().unwrap();
+        let mut bytes = Vec::new();
+        bril_rs::binary::write_program(&program, &mut bytes, use_pos).unwrap();
+        assert_eq!(
+            bril_rs::binary::read_program(bytes.as_slice()).unwrap(),
+            program
+        );
+    }
+}
+
+#[test]
+fn comments_are_kept_apart_from_positions() {
+    let with_comments: Program = parse(SOURCE, true, true).try_into().unwrap();
+    let without_comments: Program = parse(SOURCE, true, false).try_into().unwrap();
+    let positions = |program: &Program| -> Vec<_> {
+        program.functions[0]
+            .instrs
+            .iter()
+            .map(|code| match code {
+                Code::Label { pos, .. } => pos.clone(),
+                Code::Instruction(i) => i.get_pos(),
+            })
+            .collect()
+    };
+    assert_eq!(positions(&with_comments), positions(&without_comments));
+
+    // Dropping positions, as passes which make new code do, keeps the comments
+    let mut program = with_comments;
+    program.functions[0].pos = None;
+    for code in &mut program.functions[0].instrs {
+        match code {
+            Code::Label { pos, .. } => *pos = None,
+            Code::Instruction(i) => i.set_pos(None),
+        }
+    }
+    assert_eq!(program.to_string(), SOURCE);
+}
+
+#[test]
+fn comments_are_attached_to_imports() {
+    let source = "# Math helpers
+from \"math.bril\" import @sqrt;  # for the distance
+# before the function
+@main {
+}
+";
+    let program = parse(source, false, true);
+    assert_eq!(
+        program.imports[0].comments,
+        Some(Box::new(Comments {
+            leading: strings(&[" Math helpers"]),
+            trailing: Some(" for the distance".to_string()),
+            ..Comments::default()
+        }))
+    );
+    assert_eq!(
+        program.functions[0].comments.as_ref().unwrap().leading,
+        strings(&[" before the function"])
+    );
+
+    let program: Program = program.try_into().unwrap();
+    let mut bytes = Vec::new();
+    bril_rs::binary::write_program(&program, &mut bytes, false).unwrap();
+    assert_eq!(
+        bril_rs::binary::read_program(bytes.as_slice()).unwrap(),
+        program
+    );
+}
@@ -0,0 +1,64 @@
+use bril2json::{ParseError, SyntaxErrorKind, try_parse_abstract_program_from_read};
+
+fn errors(source: &str) -> Vec<String> {
+    match try_parse_abstract_program_from_read(source.as_bytes(), false, false, None) {
+        Err(ParseError::Syntax(errors)) => errors.iter().map(ToString::to_string).collect(),
+        other => panic!("expected syntax errors, found {other:?}"),
+    }
+}
+
+#[test]
+fn every_error_is_reported() {
+    assert_eq!(
+        errors(
+            "@main {
+  x: int = const 1
+  print x;
+  y: int = const 99999999999999999999;
+  z: int = $ x;
+}
+@f(a: int {
+}
+@g {
+  ret
+}
+"
+        ),
+        vec![
+            "3:3: unexpected token `print`, expected `;`",
+            "4:18: integer literal `99999999999999999999` does not fit in 64 bits",
+            "5:12: unexpected token `$`, expected one of identifier, `const`, `false`, `from`, `true`",
+            "7:11: unexpected token `{`, expected one of `)`, `,`",
+            "11:1: unexpected token `}`, expected one of identifier, `.`, `:`, `;`, `=`, `@`, `false`, `from`, `true`",
+        ]
+    );
+}
+
+#[test]
+fn errors_have_spans() {
+    let Err(ParseError::Syntax(errors)) =
+        try_parse_abstract_program_from_read("@main {\n  print x".as_bytes(), true, true, None)
+    else {
+        panic!("expected a syntax error");
+    };
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind, SyntaxErrorKind::UnexpectedEof);
+    assert_eq!((errors[0].pos.pos.row, errors[0].pos.pos.col), (2, 10));
+    assert!(errors[0].pos.pos_end.is_some());
+}
+
+#[test]
+fn missing_files_are_errors() {
+    assert!(matches!(
+        try_parse_abstract_program_from_read(
+            "@main {}".as_bytes(),
+            true,
+            true,
+            Some("does/not/exist.bril".to_string())
+        ),
+        Err(ParseError::File { .. })
+    ));
+    assert!(
+        try_parse_abstract_program_from_read("@main {}".as_bytes(), false, false, None).is_ok()
+    );
+}
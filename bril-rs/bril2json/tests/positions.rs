@@ -0,0 +1,102 @@
+use bril_rs::{AbstractCode, AbstractInstruction, AbstractProgram, ByteSpan, ColRow, Position};
+use bril2json::{
+    ColumnMode, PositionOptions, SpanKind, parse_abstract_program_with_spans,
+    try_parse_abstract_program_with_options,
+};
+
+const SOURCE: &str = "@main {
+  # héllo
+  s: char = const 'é'; x: int = const 1;
+}
+";
+
+fn parse(options: PositionOptions) -> AbstractProgram {
+    try_parse_abstract_program_with_options(SOURCE.as_bytes(), options, None).unwrap()
+}
+
+fn positions(program: &AbstractProgram) -> Vec<Position> {
+    program.functions[0]
+        .instrs
+        .iter()
+        .map(|code| match code {
+            AbstractCode::Instruction(
+                AbstractInstruction::Constant { pos, .. }
+                | AbstractInstruction::Value { pos, .. }
+                | AbstractInstruction::Effect { pos, .. },
+            )
+            | AbstractCode::Label { pos, .. } => pos.clone().unwrap(),
+        })
+        .collect()
+}
+
+#[test]
+fn columns_count_bytes_or_characters() {
+    let bytes = positions(&parse(PositionOptions::new(true, true)));
+    assert_eq!(bytes[1].pos, ColRow { row: 3, col: 25 });
+    assert_eq!(bytes[1].pos_end, Some(ColRow { row: 3, col: 42 }));
+
+    let chars = positions(&parse(PositionOptions {
+        columns: ColumnMode::Chars,
+        ..PositionOptions::new(true, true)
+    }));
+    assert_eq!(chars[0].pos, ColRow { row: 3, col: 3 });
+    assert_eq!(chars[1].pos, ColRow { row: 3, col: 24 });
+    assert_eq!(chars[1].pos_end, Some(ColRow { row: 3, col: 41 }));
+}
+
+#[test]
+fn byte_spans_slice_the_source() {
+    let spans = positions(&parse(PositionOptions {
+        byte_spans: true,
+        ..PositionOptions::new(true, false)
+    }));
+    let ByteSpan { start, end } = spans[1].byte_span.unwrap();
+    assert_eq!(
+        &SOURCE[usize::try_from(start).unwrap()..usize::try_from(end).unwrap()],
+        "x: int = const 1;"
+    );
+    assert_eq!(spans[1].pos_end, None);
+
+    assert!(
+        positions(&parse(PositionOptions::new(true, true)))
+            .iter()
+            .all(|p| p.byte_span.is_none())
+    );
+}
+
+#[test]
+fn spans_cover_every_name() {
+    let source = "from \"lib.bril\" import @f as @g;
+@main(n: int) {
+.top:
+  x: int = call @g n;
+  y: int = ;
+  br x .top .top;
+}
+";
+    let (_, errors, spans) =
+        parse_abstract_program_with_spans(source, PositionOptions::default(), None);
+    assert_eq!(errors.len(), 1);
+    let named: Vec<(SpanKind, &str)> = spans
+        .iter()
+        .map(|s| (s.kind, &source[s.range.clone()]))
+        .collect();
+    assert_eq!(
+        named,
+        vec![
+            (SpanKind::ImportPath, "\"lib.bril\""),
+            (SpanKind::ImportedFunction, "f"),
+            (SpanKind::ImportAlias, "g"),
+            (SpanKind::Function, source[33..].trim_end()),
+            (SpanKind::FunctionName, "main"),
+            (SpanKind::Argument, "n"),
+            (SpanKind::LabelDefinition, "top"),
+            (SpanKind::Destination, "x"),
+            (SpanKind::FunctionUse, "g"),
+            (SpanKind::Variable, "n"),
+            (SpanKind::Variable, "x"),
+            (SpanKind::LabelUse, "top"),
+            (SpanKind::LabelUse, "top"),
+        ]
+    );
+}
@@ -0,0 +1,149 @@
+use std::io::{BufReader, Read};
+
+use bril_rs::try_output_abstract_program;
+use bril2json::stream::{ProgramItem, StreamingParser, try_stream_abstract_program_from_read};
+use bril2json::{ColumnMode, ParseError, PositionOptions, try_parse_abstract_program_with_options};
+
+/// Pieces which do not end at the end of a line, comments around them, and literals which look like they end one
+const SOURCE: &str = "# before the imports
+from \"lib}.bril\" import @f as @g; # after the import
+@main { ret; } # before two
+# also before two
+@two(a: int,
+  b: int): int
+{
+  c: int = add a b; # after c
+  ret c;
+} @three { x: char = const '}'; print x; }
+# after every function
+   # still after";
+
+const OPTIONS: PositionOptions = PositionOptions {
+    use_pos: true,
+    with_end: true,
+    columns: ColumnMode::Chars,
+    byte_spans: true,
+    comments: true,
+};
+
+/// Comments are kept without positions
+const COMMENTS_ONLY: PositionOptions = PositionOptions {
+    use_pos: false,
+    with_end: false,
+    columns: ColumnMode::Bytes,
+    byte_spans: false,
+    comments: true,
+};
+
+fn whole_with(source: &str, options: PositionOptions) -> Result<String, ParseError> {
+    let program = try_parse_abstract_program_with_options(source.as_bytes(), options, None)?;
+    let mut output = Vec::new();
+    try_output_abstract_program(&program, &mut output)?;
+    Ok(String::from_utf8(output).unwrap())
+}
+
+fn streamed_with(source: &str, options: PositionOptions) -> Result<String, ParseError> {
+    let output =
+        try_stream_abstract_program_from_read(source.as_bytes(), Vec::new(), options, None)?;
+    Ok(String::from_utf8(output).unwrap())
+}
+
+fn whole(source: &str) -> Result<String, ParseError> {
+    whole_with(source, OPTIONS)
+}
+
+fn streamed(source: &str) -> Result<String, ParseError> {
+    streamed_with(source, OPTIONS)
+}
+
+#[test]
+fn output_is_the_same_as_for_the_whole_file() {
+    for source in [
+        SOURCE,
+        "",
+        "# only a comment\n",
+        "from \"a.bril\" import @a;\n",
+    ] {
+        for options in [OPTIONS, COMMENTS_ONLY] {
+            assert_eq!(
+                streamed_with(source, options).unwrap(),
+                whole_with(source, options).unwrap(),
+                "{source}"
+            );
+        }
+    }
+    let parse = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../../test/parse");
+    for entry in std::fs::read_dir(parse).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().is_some_and(|e| e == "bril") {
+            let source = std::fs::read_to_string(&path).unwrap();
+            assert_eq!(
+                streamed(&source).unwrap(),
+                whole(&source).unwrap(),
+                "{}",
+                path.display()
+            );
+        }
+    }
+}
+
+#[test]
+fn every_syntax_error_is_reported() {
+    let source = "@a {\n  x: int = ;\n}\n@b {\n  y int = const 1;\n  ret y;\n}\n@c { z = ; }\n";
+    let (Err(ParseError::Syntax(streamed)), Err(ParseError::Syntax(whole))) =
+        (streamed(source), whole(source))
+    else {
+        panic!("expected syntax errors");
+    };
+    assert_eq!(streamed.len(), 3);
+    assert_eq!(streamed, whole);
+}
+
+#[test]
+fn imports_must_come_first() {
+    let source = "@a {\n}\nfrom \"b.bril\" import @b;\n";
+    let Err(ParseError::Syntax(errors)) = streamed(source) else {
+        panic!("expected a syntax error");
+    };
+    assert_eq!(
+        errors[0].to_string(),
+        "3:1: unexpected token `from`, expected `@`"
+    );
+}
+
+/// An input which never ends, made of the same function over and over
+struct Endless {
+    function: &'static [u8],
+    read: usize,
+}
+
+impl Read for Endless {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        for byte in buf.iter_mut() {
+            *byte = self.function[self.read % self.function.len()];
+            self.read += 1;
+        }
+        Ok(buf.len())
+    }
+}
+
+#[test]
+fn functions_are_parsed_as_they_are_read() {
+    let function = b"@f {\n  ret;\n}\n";
+    let input = BufReader::new(Endless { function, read: 0 });
+    let functions: Vec<_> = StreamingParser::new(input, OPTIONS, None)
+        .take(3)
+        .map(|item| match item.unwrap() {
+            ProgramItem::Function(f) => f,
+            ProgramItem::Import(_) => panic!("there are no imports"),
+        })
+        .collect();
+    let positions: Vec<_> = functions
+        .iter()
+        .map(|f| {
+            let pos = f.pos.as_ref().unwrap();
+            (pos.pos.row, pos.byte_span.unwrap().start)
+        })
+        .collect();
+    assert_eq!(positions, vec![(1, 0), (4, 14), (7, 28)]);
+}
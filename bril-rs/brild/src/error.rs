@@ -12,6 +12,10 @@ pub enum BrildError {
     MissingOrUnknownFileExtension(std::path::PathBuf),
     #[error("Function `{0}` declared more than once")]
     DuplicateFunction(String),
+    #[error("Could not load imported file `{0}`: {1}")]
+    LoadError(std::path::PathBuf, bril_rs::BrilError),
     #[error(transparent)]
     IoError(#[from] std::io::Error),
+    #[error(transparent)]
+    ParseError(#[from] bril2json::ParseError),
 }
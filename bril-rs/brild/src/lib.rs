@@ -15,10 +15,10 @@ use std::hash::BuildHasher;
 use std::path::{Path, PathBuf};
 
 use bril_rs::{
-    AbstractCode, AbstractFunction, AbstractInstruction, AbstractProgram, ImportedFunction,
-    load_abstract_program_from_read,
+    AbstractCode, AbstractFunction, AbstractProgram, ImportedFunction,
+    try_load_abstract_program_from_read,
 };
-use bril2json::parse_abstract_program_from_read;
+use bril2json::try_parse_abstract_program_from_read;
 
 use crate::error::BrildError;
 
@@ -38,56 +38,20 @@ fn mangle_name(path: &Path, func_name: &str) -> String {
     p.join("___")
 }
 
-fn mangle_instr(code: AbstractCode, name_resolution_map: &HashMap<String, String>) -> AbstractCode {
-    match code {
-        AbstractCode::Instruction(AbstractInstruction::Value {
-            op,
-            funcs,
-            args,
-            dest,
-            labels,
-            pos,
-            op_type,
-        }) => AbstractCode::Instruction(AbstractInstruction::Value {
-            op,
-            funcs: funcs
-                .into_iter()
-                .map(|f| {
-                    name_resolution_map
-                        .get(&f)
-                        .unwrap_or_else(|| panic!("Could not find name for {f}"))
-                        .clone()
-                })
-                .collect(),
-            args,
-            dest,
-            labels,
-            pos,
-            op_type,
-        }),
-        AbstractCode::Instruction(AbstractInstruction::Effect {
-            op,
-            funcs,
-            args,
-            labels,
-            pos,
-        }) => AbstractCode::Instruction(AbstractInstruction::Effect {
-            op,
-            funcs: funcs
-                .into_iter()
-                .map(|f| {
-                    name_resolution_map
-                        .get(&f)
-                        .unwrap_or_else(|| panic!("Could not find name for {f}"))
-                        .clone()
-                })
-                .collect(),
-            args,
-            labels,
-            pos,
-        }),
-        _ => code,
+fn mangle_instr(
+    mut code: AbstractCode,
+    name_resolution_map: &HashMap<String, String>,
+) -> AbstractCode {
+    if let AbstractCode::Instruction(instr) = &mut code {
+        for f in instr.funcs_mut() {
+            f.clone_from(
+                name_resolution_map
+                    .get(f)
+                    .unwrap_or_else(|| panic!("Could not find name for {f}")),
+            );
+        }
     }
+    code
 }
 
 fn mangle_function(
@@ -96,6 +60,7 @@ fn mangle_function(
         args,
         instrs,
         pos,
+        comments,
         return_type,
     }: AbstractFunction,
     name_resolution_map: &HashMap<String, String>,
@@ -105,7 +70,7 @@ fn mangle_function(
         name: if is_toplevel && name == "main" {
             name
         } else {
-            name_resolution_map.get(&name).unwrap().to_string()
+            name_resolution_map.get(&name).unwrap().clone()
         },
         args,
         instrs: instrs
@@ -113,6 +78,7 @@ fn mangle_function(
             .map(|i| mangle_instr(i, name_resolution_map))
             .collect(),
         pos,
+        comments,
         return_type,
     }
 }
@@ -203,17 +169,20 @@ pub fn do_import<S: BuildHasher>(
     path_map.insert(canonical_path.clone(), None);
 
     // Find the correct parser for this path based on the extension
-    let f: Box<dyn Fn(_) -> AbstractProgram> =
+    let f: Box<dyn Fn(_) -> Result<AbstractProgram, BrildError>> =
         match canonical_path.extension().and_then(std::ffi::OsStr::to_str) {
             Some("bril") => Box::new(|s| {
-                parse_abstract_program_from_read(
+                Ok(try_parse_abstract_program_from_read(
                     s,
                     true,
                     true,
                     Some(canonical_path.display().to_string()),
-                )
+                )?)
+            }),
+            Some("json") => Box::new(|s| {
+                try_load_abstract_program_from_read(s)
+                    .map_err(|e| BrildError::LoadError(canonical_path.clone(), e))
             }),
-            Some("json") => Box::new(load_abstract_program_from_read),
             Some(_) | None => {
                 return Err(BrildError::MissingOrUnknownFileExtension(
                     canonical_path.clone(),
@@ -222,34 +191,44 @@ pub fn do_import<S: BuildHasher>(
         };
 
     // Get the AbstractProgram representation of the file
-    let program = f(File::open(canonical_path)?);
+    let program = f(File::open(canonical_path)?)?;
 
     handle_program(path_map, program, canonical_path, libs, is_toplevel)?;
     Ok(())
 }
 
+/// The files which the import of ```path``` could refer to, found by joining it to each of ```libs``` in order.
+/// Linking uses the first one.
+#[must_use]
+pub fn import_candidates(path: &Path, libs: &[PathBuf]) -> Vec<PathBuf> {
+    libs.iter()
+        .map(|lib| lib.join(path))
+        .filter(|candidate| candidate.exists())
+        .collect()
+}
+
 // Finds the correct full path for `path` by adding it to each of the lib paths till it gets a hit
 #[doc(hidden)]
 fn locate_import<S: BuildHasher>(
     path_map: &mut HashMap<PathBuf, Option<AbstractProgram>, S>,
-    path: &PathBuf,
+    path: &Path,
     libs: &[PathBuf],
     is_toplevel: bool,
 ) -> Result<PathBuf, BrildError> {
-    let located_libs: Vec<_> = libs.iter().filter(|lib| lib.join(path).exists()).collect();
+    let located = import_candidates(path, libs);
 
-    if located_libs.is_empty() {
-        return Err(BrildError::NoPathExists(path.clone()));
+    if located.is_empty() {
+        return Err(BrildError::NoPathExists(path.to_path_buf()));
     }
 
-    if located_libs.len() > 1 {
+    if located.len() > 1 {
         eprintln!(
             "Warning, more than one valid path for {} was found, using the first one.",
             path.display()
         );
     }
 
-    let next_path = canonicalize(located_libs.first().unwrap().join(path))?;
+    let next_path = canonicalize(&located[0])?;
 
     do_import(path_map, &next_path, libs, is_toplevel)?;
 
@@ -7,7 +7,14 @@ use std::path::PathBuf;
 use bril_rs::{AbstractProgram, load_abstract_program, output_abstract_program};
 use brild::{cli::Cli, do_import, error::BrildError, handle_program};
 
-fn main() -> Result<(), BrildError> {
+fn main() {
+    if let Err(e) = link() {
+        eprintln!("error: {e}");
+        std::process::exit(1)
+    }
+}
+
+fn link() -> Result<(), BrildError> {
     let mut map = HashMap::new();
     let args = Cli::parse();
 
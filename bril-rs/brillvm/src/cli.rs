@@ -16,6 +16,10 @@ pub struct Cli {
     #[arg(short, long, action)]
     pub interpreter: bool,
 
+    /// Print the extensions which brillvm can compile and exit
+    #[arg(long, action)]
+    pub extensions: bool,
+
     /// Arguments for the main function
     #[arg(action)]
     pub args: Vec<String>,
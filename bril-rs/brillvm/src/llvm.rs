@@ -12,8 +12,14 @@ use inkwell::{
 
 use bril_rs::{
     Argument, Code, ConstOps, EffectOps, Function, Instruction, Literal, Program, Type, ValueOps,
+    extension::{Extension, ExtensionSet},
 };
 
+/// The extensions which brillvm can compile. Use [`ExtensionSet::accepts`] to reject other programs before calling [`create_module_from_program`].
+pub const EXTENSIONS: ExtensionSet = ExtensionSet::CORE
+    .with(Extension::Float)
+    .with(Extension::Memory);
+
 /// A helper function for performing operations over LLVM types
 fn llvm_type_map<'ctx, A, F>(context: &'ctx Context, ty: &Type, mut fn_map: F) -> A
 where
@@ -24,6 +30,7 @@ where
         Type::Bool => fn_map(context.bool_type().into()),
         Type::Float => fn_map(context.f64_type().into()),
         Type::Pointer(_) => fn_map(context.ptr_type(AddressSpace::default()).into()),
+        Type::Char | Type::Any => unreachable!("`{ty}` is not in {EXTENSIONS}"),
     }
 }
 
@@ -208,6 +215,8 @@ fn build_instruction<'a, 'b>(
             op: ConstOps::Const,
             const_type: Type::Float,
             value: Literal::Int(i),
+            pos: _,
+            comments: _,
         } => {
             #[expect(clippy::cast_precision_loss)]
             builder
@@ -222,6 +231,8 @@ fn build_instruction<'a, 'b>(
             op: ConstOps::Const,
             const_type: _,
             value: Literal::Int(i),
+            pos: _,
+            comments: _,
         } => {
             #[expect(clippy::cast_sign_loss, reason = "u64 because of the C++/C API")]
             builder
@@ -236,6 +247,8 @@ fn build_instruction<'a, 'b>(
             op: ConstOps::Const,
             const_type: _,
             value: Literal::Bool(b),
+            pos: _,
+            comments: _,
         } => {
             builder
                 .build_store(
@@ -249,6 +262,8 @@ fn build_instruction<'a, 'b>(
             op: ConstOps::Const,
             const_type: _,
             value: Literal::Float(f),
+            pos: _,
+            comments: _,
         } => {
             builder
                 .build_store(heap.get(dest).ptr, context.f64_type().const_float(*f))
@@ -261,6 +276,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Add,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -289,6 +306,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Sub,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -317,6 +336,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Mul,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -345,6 +366,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Div,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -373,6 +396,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Eq,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -402,6 +427,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Lt,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -431,6 +458,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Gt,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -460,6 +489,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Le,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -489,6 +520,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Ge,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -518,6 +551,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Not,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -542,6 +577,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::And,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -570,6 +607,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Or,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -598,6 +637,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Call,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let func_name = if funcs[0] == "main" {
                 "_main"
@@ -637,6 +678,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Id,
             op_type: _,
+            pos: _,
+            comments: _,
         } => build_op(context, builder, heap, fresh, |v| v[0], args, dest),
         Instruction::Value {
             args,
@@ -645,6 +688,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Fadd,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -673,6 +718,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Fsub,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -701,6 +748,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Fmul,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -729,6 +778,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Fdiv,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -757,6 +808,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Feq,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -786,6 +839,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Flt,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -815,6 +870,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Fgt,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -844,6 +901,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Fle,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -873,6 +932,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Fge,
             op_type: _,
+            pos: _,
+            comments: _,
         } => {
             let ret_name = fresh.fresh_var();
             build_op(
@@ -900,6 +961,8 @@ fn build_instruction<'a, 'b>(
             funcs: _,
             labels: _,
             op: EffectOps::Return,
+            pos: _,
+            comments: _,
         } => {
             if args.is_empty() {
                 builder.build_return(None).unwrap();
@@ -919,6 +982,8 @@ fn build_instruction<'a, 'b>(
             funcs,
             labels: _,
             op: EffectOps::Call,
+            pos: _,
+            comments: _,
         } => {
             let func_name = if funcs[0] == "main" {
                 "_main"
@@ -952,12 +1017,16 @@ fn build_instruction<'a, 'b>(
             funcs: _,
             labels: _,
             op: EffectOps::Nop,
+            pos: _,
+            comments: _,
         } => {}
         Instruction::Effect {
             args,
             funcs: _,
             labels: _,
             op: EffectOps::Print,
+            pos: _,
+            comments: _,
         } => {
             let print_int = module.get_function("_bril_print_int").unwrap();
             let print_bool = module.get_function("_bril_print_bool").unwrap();
@@ -997,7 +1066,7 @@ fn build_instruction<'a, 'b>(
                             .build_call(print_float, &[v.into()], "print_float")
                             .unwrap();
                     }
-                    Type::Pointer(_) => {
+                    Type::Pointer(_) | Type::Char | Type::Any => {
                         unreachable!()
                     }
                 }
@@ -1012,6 +1081,8 @@ fn build_instruction<'a, 'b>(
             funcs: _,
             labels,
             op: EffectOps::Jump,
+            pos: _,
+            comments: _,
         } => {
             builder
                 .build_unconditional_branch(block_map_get(
@@ -1024,6 +1095,8 @@ fn build_instruction<'a, 'b>(
             funcs: _,
             labels,
             op: EffectOps::Branch,
+            pos: _,
+            comments: _,
         } => {
             let then_block = block_map_get(context, llvm_func, block_map, &labels[0]);
             let else_block = block_map_get(context, llvm_func, block_map, &labels[1]);
@@ -1047,6 +1120,8 @@ fn build_instruction<'a, 'b>(
             labels,
             op: ValueOps::Phi,
             op_type,
+            pos: _,
+            comments: _,
         } => {
             let name = fresh.fresh_var();
             let blocks = labels
@@ -1105,6 +1180,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Alloc,
             op_type,
+            pos: _,
+            comments: _,
         } => {
             let alloc_name = fresh.fresh_var();
             let ty = unwrap_bril_ptrtype(op_type);
@@ -1132,6 +1209,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::Load,
             op_type,
+            pos: _,
+            comments: _,
         } => {
             let name = fresh.fresh_var();
             llvm_type_map(context, op_type, |pointee_ty| {
@@ -1157,6 +1236,8 @@ fn build_instruction<'a, 'b>(
             labels: _,
             op: ValueOps::PtrAdd,
             op_type,
+            pos: _,
+            comments: _,
         } => {
             let name = fresh.fresh_var();
             let op_type = unwrap_bril_ptrtype(op_type);
@@ -1187,6 +1268,8 @@ fn build_instruction<'a, 'b>(
             funcs: _,
             labels: _,
             op: EffectOps::Store,
+            pos: _,
+            comments: _,
         } => {
             build_effect_op(
                 context,
@@ -1204,6 +1287,8 @@ fn build_instruction<'a, 'b>(
             funcs: _,
             labels: _,
             op: EffectOps::Free,
+            pos: _,
+            comments: _,
         } => {
             build_effect_op(
                 context,
@@ -1216,6 +1301,7 @@ fn build_instruction<'a, 'b>(
                 args,
             );
         }
+        _ => unreachable!("`{i}` is not in {EXTENSIONS}"),
     }
 }
 
@@ -1228,6 +1314,8 @@ const fn is_terminating_instr(i: Option<&Instruction>) -> bool {
             funcs: _,
             labels: _,
             op: EffectOps::Branch | EffectOps::Jump | EffectOps::Return,
+            pos: _,
+            comments: _,
         })
     )
 }
@@ -1235,7 +1323,7 @@ const fn is_terminating_instr(i: Option<&Instruction>) -> bool {
 /// Given a Bril program, create an LLVM module from it
 /// The `runtime_module` is the module containing the runtime library
 /// # Panics
-/// Panics if the program is invalid
+/// Panics if the program is invalid or uses an extension which is not in [`EXTENSIONS`]
 #[must_use]
 pub fn create_module_from_program<'a>(
     context: &'a Context,
@@ -1256,6 +1344,8 @@ pub fn create_module_from_program<'a>(
                  instrs,
                  name,
                  return_type,
+                 pos: _,
+                 comments: _,
              }| {
                 // Setup function in module
                 let ty = build_functiontype(
@@ -1446,7 +1536,7 @@ pub fn create_module_from_program<'a>(
                     .unwrap()
                     .try_as_basic_value()
                     .unwrap_left(),
-                Type::Pointer(_) => unreachable!(),
+                Type::Pointer(_) | Type::Char | Type::Any => unreachable!(),
             };
             builder.build_store(ptr, arg).unwrap();
         });
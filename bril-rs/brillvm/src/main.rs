@@ -1,5 +1,8 @@
-use bril_rs::load_program_from_read;
-use brillvm::{cli::Cli, llvm::create_module_from_program};
+use bril_rs::try_load_program_from_read;
+use brillvm::{
+    cli::Cli,
+    llvm::{EXTENSIONS, create_module_from_program},
+};
 use clap::Parser;
 use inkwell::{
     context::Context,
@@ -11,15 +14,32 @@ use std::io::Read;
 fn main() {
     let args = Cli::parse();
 
+    if args.extensions {
+        println!("{EXTENSIONS}");
+        return;
+    }
+
     let mut src = String::new();
-    if let Some(f) = &args.file {
-        let path = std::fs::canonicalize(f).unwrap();
-        let mut file = std::fs::File::open(path).unwrap();
-        file.read_to_string(&mut src).unwrap()
+    let read = if let Some(f) = &args.file {
+        std::fs::File::open(f).and_then(|mut file| file.read_to_string(&mut src))
     } else {
-        std::io::stdin().read_to_string(&mut src).unwrap()
+        std::io::stdin().read_to_string(&mut src)
     };
-    let prog = load_program_from_read(src.as_bytes());
+    if let Err(e) = read {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+    let prog = match try_load_program_from_read(src.as_bytes()) {
+        Ok(prog) => prog,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = EXTENSIONS.accepts(&prog) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
 
     let context = Context::create();
     let runtime_path = args.runtime.as_ref().map_or("rt.bc", |f| f);
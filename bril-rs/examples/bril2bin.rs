@@ -0,0 +1,6 @@
+use bril_rs::{binary::write_abstract_program, load_abstract_program};
+
+fn main() {
+    write_abstract_program(&load_abstract_program(), std::io::stdout().lock(), true)
+        .unwrap_or_else(|e| panic!("{e}"));
+}
@@ -8,6 +8,7 @@
 #[doc(hidden)]
 pub mod cli;
 
+use bril_rs::builder::FreshNames;
 use bril_rs::{
     Argument, Code, ColRow, ConstOps, EffectOps, Function, Instruction, Literal, Position, Program,
     Type, ValueOps,
@@ -31,7 +32,7 @@ use std::collections::HashMap;
 struct State {
     is_pos: bool,
     src: Option<String>,
-    temp_var_count: u64,
+    names: FreshNames,
     ident_type_map: HashMap<String, Type>,
     func_context_map: HashMap<String, (HashMap<String, Type>, Option<Type>)>,
 }
@@ -41,25 +42,20 @@ impl State {
         Self {
             is_pos,
             src,
-            temp_var_count: 0,
+            names: FreshNames::default(),
             ident_type_map: HashMap::new(),
             func_context_map: HashMap::new(),
         }
     }
 
     fn fresh_var(&mut self, ty: Type) -> String {
-        let num = self.temp_var_count;
-        self.temp_var_count += 1;
-        let name = format!("tmp{num}");
+        let name = self.names.var();
         self.add_type_for_ident(name.clone(), ty);
         name
     }
 
     fn fresh_label(&mut self) -> String {
-        let num = self.temp_var_count;
-        self.temp_var_count += 1;
-        let name = format!("label{num}");
-        name
+        self.names.label()
     }
 
     fn starting_new_function(&mut self, name: &String) {
@@ -245,7 +241,7 @@ fn from_span_to_position(
     src: Option<String>,
 ) -> Position {
     let start = starting_span.start();
-    let end = ending_span.map_or(starting_span.end(), |s| s.end());
+    let end = ending_span.map_or_else(|| starting_span.end(), |s| s.end());
     Position {
         pos: ColRow {
             col: start.column as u64,
@@ -256,6 +252,8 @@ fn from_span_to_position(
             row: end.line as u64,
         }),
         src,
+        byte_span: None,
+        origin: None,
     }
 }
 
@@ -388,6 +386,7 @@ fn from_signature_to_function(
         } else {
             None
         },
+        comments: None,
         instrs: Vec::new(),
         args,
         return_type,
@@ -407,6 +406,7 @@ fn array_repetition_helper(
         dest: one.clone(),
         op: ConstOps::Const,
         pos: None,
+        comments: None,
         const_type: Type::Int,
         value: Literal::Int(1),
     }));
@@ -418,6 +418,7 @@ fn array_repetition_helper(
         dest: size.clone(),
         op: ConstOps::Const,
         pos: None,
+        comments: None,
         const_type: Type::Int,
         value: Literal::Int(num_reps),
     }));
@@ -428,6 +429,7 @@ fn array_repetition_helper(
         labels: Vec::new(),
         op: ValueOps::Alloc,
         pos: None,
+        comments: None,
         op_type: arr_type.clone(),
     }));
 
@@ -440,6 +442,7 @@ fn array_repetition_helper(
         labels: Vec::new(),
         op: ValueOps::Id,
         pos: None,
+        comments: None,
         op_type: arr_type.clone(),
     }));
 
@@ -452,6 +455,7 @@ fn array_repetition_helper(
         dest: iter.clone(),
         op: ConstOps::Const,
         pos: None,
+        comments: None,
         const_type: Type::Int,
         value: Literal::Int(0),
     }));
@@ -459,6 +463,7 @@ fn array_repetition_helper(
     code.push(Code::Label {
         label: start_label.clone(),
         pos: None,
+        comments: None,
     });
 
     // check if iter < size
@@ -470,6 +475,7 @@ fn array_repetition_helper(
         labels: Vec::new(),
         op: ValueOps::Lt,
         pos: None,
+        comments: None,
         op_type: Type::Bool,
     }));
 
@@ -479,11 +485,13 @@ fn array_repetition_helper(
         labels: vec![then_label.clone(), end_label.clone()],
         op: EffectOps::Branch,
         pos: None,
+        comments: None,
     }));
 
     code.push(Code::Label {
         label: then_label,
         pos: None,
+        comments: None,
     });
 
     // write var to the current ptr
@@ -493,6 +501,7 @@ fn array_repetition_helper(
         labels: Vec::new(),
         op: EffectOps::Store,
         pos: None,
+        comments: None,
     }));
 
     // increment current ptr
@@ -503,6 +512,7 @@ fn array_repetition_helper(
         labels: Vec::new(),
         op: ValueOps::PtrAdd,
         pos: None,
+        comments: None,
         op_type: arr_type,
     }));
 
@@ -514,6 +524,7 @@ fn array_repetition_helper(
         labels: Vec::new(),
         op: ValueOps::Add,
         pos: None,
+        comments: None,
         op_type: Type::Int,
     }));
 
@@ -524,11 +535,13 @@ fn array_repetition_helper(
         labels: vec![start_label],
         op: EffectOps::Jump,
         pos: None,
+        comments: None,
     }));
 
     code.push(Code::Label {
         label: end_label,
         pos: None,
+        comments: None,
     });
 
     (Some(pointer), code)
@@ -546,6 +559,7 @@ fn array_init_helper(
         dest: size.clone(),
         op: ConstOps::Const,
         pos: None,
+        comments: None,
         const_type: Type::Int,
         value: Literal::Int(i64::try_from(vars.len()).unwrap()),
     }));
@@ -556,6 +570,7 @@ fn array_init_helper(
         labels: Vec::new(),
         op: ValueOps::Alloc,
         pos: None,
+        comments: None,
         op_type: op_type.clone(),
     }));
     vars.into_iter().enumerate().for_each(|(i, v)| {
@@ -564,6 +579,7 @@ fn array_init_helper(
             dest: idx.clone(),
             op: ConstOps::Const,
             pos: None,
+            comments: None,
             const_type: Type::Int,
             value: Literal::Int(i64::try_from(i).unwrap()),
         }));
@@ -575,6 +591,7 @@ fn array_init_helper(
             labels: Vec::new(),
             op: ValueOps::PtrAdd,
             pos: None,
+            comments: None,
             op_type: op_type.clone(),
         }));
         code.push(Code::Instruction(Instruction::Effect {
@@ -583,6 +600,7 @@ fn array_init_helper(
             labels: Vec::new(),
             op: EffectOps::Store,
             pos: None,
+            comments: None,
         }));
     });
     (Some(pointer), code)
@@ -636,6 +654,7 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                         labels: Vec::new(),
                         op: ValueOps::Id,
                         pos,
+                        comments: None,
                         op_type,
                     }));
                     (None, code)
@@ -660,6 +679,7 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                         labels: Vec::new(),
                         op: ValueOps::PtrAdd,
                         pos: pos.clone(),
+                        comments: None,
                         op_type,
                     }));
                     code1.push(Code::Instruction(Instruction::Effect {
@@ -668,6 +688,7 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                         labels: Vec::new(),
                         op: EffectOps::Store,
                         pos,
+                        comments: None,
                     }));
                     (None, code1)
                 }
@@ -755,6 +776,7 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                 labels: Vec::new(),
                 op: value_op,
                 pos,
+                comments: None,
                 op_type,
             }));
             (Some(dest), code1)
@@ -795,6 +817,7 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                     labels: Vec::new(),
                     op: EffectOps::Free,
                     pos,
+                    comments: None,
                 }));
                 (None, code)
             } else {
@@ -806,6 +829,7 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                             labels: Vec::new(),
                             op: EffectOps::Call,
                             pos,
+                            comments: None,
                         }));
                         (None, code)
                     }
@@ -818,6 +842,7 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                             labels: Vec::new(),
                             op: ValueOps::Call,
                             pos,
+                            comments: None,
                             op_type: ret,
                         }));
                         (Some(dest), code)
@@ -860,10 +885,12 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                 labels: vec![then_label.clone(), else_label.clone()],
                 op: EffectOps::Branch,
                 pos: pos.clone(),
+                comments: None,
             }));
             code.push(Code::Label {
                 label: then_label,
                 pos: pos.clone(),
+                comments: None,
             });
 
             code.append(&mut from_block_to_vec_code(then_branch, state));
@@ -874,10 +901,12 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                 labels: vec![end_label.clone()],
                 op: EffectOps::Jump,
                 pos: pos.clone(),
+                comments: None,
             }));
             code.push(Code::Label {
                 label: else_label,
                 pos: pos.clone(),
+                comments: None,
             });
 
             if let Some((_, else_branch)) = else_branch {
@@ -894,10 +923,12 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                 labels: vec![end_label.clone()],
                 op: EffectOps::Jump,
                 pos: pos.clone(),
+                comments: None,
             }));
             code.push(Code::Label {
                 label: end_label,
                 pos,
+                comments: None,
             });
             (None, code)
         }
@@ -923,6 +954,7 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                 labels: Vec::new(),
                 op: ValueOps::PtrAdd,
                 pos: pos.clone(),
+                comments: None,
                 op_type: pointer_type,
             }));
             let load_dest = state.fresh_var(load_type.clone());
@@ -933,6 +965,7 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                 labels: Vec::new(),
                 op: ValueOps::Load,
                 pos,
+                comments: None,
                 op_type: load_type,
             }));
             (Some(load_dest), code1)
@@ -946,6 +979,7 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                         dest,
                         op: ConstOps::Const,
                         pos,
+                        comments: None,
                         const_type: Type::Int,
                         value: Literal::Int(x.base10_parse::<i64>().unwrap()),
                     })],
@@ -959,6 +993,7 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                         dest,
                         op: ConstOps::Const,
                         pos,
+                        comments: None,
                         const_type: Type::Float,
                         value: Literal::Float(x.base10_parse::<f64>().unwrap()),
                     })],
@@ -972,6 +1007,7 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                         dest,
                         op: ConstOps::Const,
                         pos,
+                        comments: None,
                         const_type: Type::Bool,
                         value: Literal::Bool(x.value()),
                     })],
@@ -1012,6 +1048,7 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                     labels: Vec::new(),
                     op: EffectOps::Print,
                     pos,
+                    comments: None,
                 })],
             )
         }
@@ -1075,6 +1112,7 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                 labels: Vec::new(),
                 op: EffectOps::Return,
                 pos,
+                comments: None,
             }));
             (None, code)
         }
@@ -1098,6 +1136,7 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                                     const_type: ty.clone(),
                                     value: Literal::Int(-1),
                                     pos: None,
+                                    comments: None,
                                 }));
                                 args.push(tmp);
                                 ValueOps::Mul
@@ -1110,6 +1149,7 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                                     const_type: ty.clone(),
                                     value: Literal::Float(-1.0),
                                     pos: None,
+                                    comments: None,
                                 }));
                                 args.push(tmp);
                                 ValueOps::Fmul
@@ -1131,6 +1171,7 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                 labels: Vec::new(),
                 op,
                 pos,
+                comments: None,
                 op_type,
             }));
             (Some(dest), code)
@@ -1149,6 +1190,7 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
             let mut code = vec![Code::Label {
                 label: start_label.clone(),
                 pos: pos.clone(),
+                comments: None,
             }];
             code.append(&mut cond_code);
             code.push(Code::Instruction(Instruction::Effect {
@@ -1157,10 +1199,12 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                 labels: vec![then_label.clone(), end_label.clone()],
                 op: EffectOps::Branch,
                 pos: pos.clone(),
+                comments: None,
             }));
             code.push(Code::Label {
                 label: then_label,
                 pos: pos.clone(),
+                comments: None,
             });
 
             code.append(&mut from_block_to_vec_code(body, state));
@@ -1171,11 +1215,13 @@ fn from_expr_to_bril(expr: Expr, state: &mut State) -> (Option<String>, Vec<Code
                 labels: vec![start_label],
                 op: EffectOps::Jump,
                 pos: pos.clone(),
+                comments: None,
             }));
 
             code.push(Code::Label {
                 label: end_label,
                 pos,
+                comments: None,
             });
             (None, code)
         }
@@ -1222,6 +1268,7 @@ fn from_stmt_to_vec_code(s: Stmt, state: &mut State) -> Vec<Code> {
                         } else {
                             None
                         },
+                        comments: None,
                         op_type,
                     }));
                     code
@@ -1318,7 +1365,6 @@ pub fn from_file_to_program(
                 from_empty_function_to_function(f, &mut state)
             })
             .collect(),
-        #[cfg(feature = "import")]
         imports: vec![],
     }
 }
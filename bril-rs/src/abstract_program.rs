@@ -3,10 +3,10 @@ use std::marker::PhantomData;
 
 use crate::{ConstOps, program::Literal};
 
-#[cfg(feature = "position")]
-use crate::program::Position;
+use crate::program::{
+    Comments, Position, write_closing_comments, write_leading_comments, write_trailing_comment,
+};
 
-#[cfg(feature = "import")]
 use crate::program::Import;
 
 use serde::{Deserialize, Serialize};
@@ -21,7 +21,6 @@ pub struct AbstractProgram {
     /// A list of functions declared in the program
     pub functions: Vec<AbstractFunction>,
     /// A list of imports for this program
-    #[cfg(feature = "import")]
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub imports: Vec<Import>,
 }
@@ -41,13 +40,15 @@ pub struct AbstractFunction {
     /// Any arguments the function accepts
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub args: Vec<AbstractArgument>,
+    /// The `#` comments written around this function in the original source code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comments: Option<Box<Comments>>,
     /// The instructions of this function
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub instrs: Vec<AbstractCode>,
     /// The name of the function
     pub name: String,
     /// The position of this function in the original source code
-    #[cfg(feature = "position")]
     #[serde(flatten, skip_serializing_if = "Option::is_none")]
     pub pos: Option<Position>,
     /// The possible return type of this function
@@ -58,6 +59,8 @@ pub struct AbstractFunction {
 
 impl Display for AbstractFunction {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let comments = self.comments.as_deref();
+        write_leading_comments(f, comments, "")?;
         write!(f, "@{}", self.name)?;
         if !self.args.is_empty() {
             write!(f, "(")?;
@@ -72,11 +75,17 @@ impl Display for AbstractFunction {
         if let Some(tpe) = self.return_type.as_ref() {
             write!(f, ": {tpe}")?;
         }
-        writeln!(f, " {{")?;
+        write!(f, " {{")?;
+        write_trailing_comment(f, comments)?;
+        writeln!(f)?;
         for instr in &self.instrs {
             writeln!(f, "{instr}")?;
         }
+        write_closing_comments(f, comments)?;
         write!(f, "}}")?;
+        for comment in comments.iter().flat_map(|c| &c.after) {
+            write!(f, "\n#{comment}")?;
+        }
         Ok(())
     }
 }
@@ -101,16 +110,17 @@ impl Display for AbstractArgument {
 
 /// <https://capra.cs.cornell.edu/bril/lang/syntax.html#function>
 /// Code is a Label or an Instruction
-#[cfg_attr(not(feature = "float"), derive(Eq))]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum AbstractCode {
     /// <https://capra.cs.cornell.edu/bril/lang/syntax.html#label>
     Label {
+        /// The `#` comments written around the label in source code
+        #[serde(skip_serializing_if = "Option::is_none")]
+        comments: Option<Box<Comments>>,
         /// The name of the label
         label: String,
         /// Where the label is located in source code
-        #[cfg(feature = "position")]
         #[serde(flatten, skip_serializing_if = "Option::is_none")]
         pos: Option<Position>,
     },
@@ -122,28 +132,37 @@ impl Display for AbstractCode {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Self::Label {
-                label,
-                #[cfg(feature = "position")]
-                    pos: _,
-            } => write!(f, ".{label}:"),
-            Self::Instruction(instr) => write!(f, "  {instr}"),
+                label, comments, ..
+            } => {
+                let comments = comments.as_deref();
+                write_leading_comments(f, comments, "")?;
+                write!(f, ".{label}:")?;
+                write_trailing_comment(f, comments)
+            }
+            Self::Instruction(instr) => {
+                let comments = instr.comments();
+                write_leading_comments(f, comments, "  ")?;
+                write!(f, "  {instr}")?;
+                write_trailing_comment(f, comments)
+            }
         }
     }
 }
 
 /// <https://capra.cs.cornell.edu/bril/lang/syntax.html#instruction>
-#[cfg_attr(not(feature = "float"), derive(Eq))]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum AbstractInstruction {
     /// <https://capra.cs.cornell.edu/bril/lang/syntax.html#constant>
     Constant {
+        /// The `#` comments written around the instruction if provided
+        #[serde(skip_serializing_if = "Option::is_none")]
+        comments: Option<Box<Comments>>,
         /// destination variable
         dest: String,
         /// "const"
         op: ConstOps,
         /// The source position of the instruction if provided
-        #[cfg(feature = "position")]
         #[serde(flatten, skip_serializing_if = "Option::is_none")]
         pos: Option<Position>,
         /// Type of variable
@@ -157,6 +176,9 @@ pub enum AbstractInstruction {
         /// List of variables as arguments
         #[serde(default, skip_serializing_if = "Vec::is_empty")]
         args: Vec<String>,
+        /// The `#` comments written around the instruction if provided
+        #[serde(skip_serializing_if = "Option::is_none")]
+        comments: Option<Box<Comments>>,
         /// destination variable
         dest: String,
         /// List of strings as function names
@@ -168,7 +190,6 @@ pub enum AbstractInstruction {
         /// Operation being executed
         op: String,
         /// The source position of the instruction if provided
-        #[cfg(feature = "position")]
         #[serde(flatten, skip_serializing_if = "Option::is_none")]
         pos: Option<Position>,
         /// Type of variable
@@ -180,6 +201,9 @@ pub enum AbstractInstruction {
         /// List of variables as arguments
         #[serde(default, skip_serializing_if = "Vec::is_empty")]
         args: Vec<String>,
+        /// The `#` comments written around the instruction if provided
+        #[serde(skip_serializing_if = "Option::is_none")]
+        comments: Option<Box<Comments>>,
         /// List of strings as function names
         #[serde(default, skip_serializing_if = "Vec::is_empty")]
         funcs: Vec<String>,
@@ -189,12 +213,40 @@ pub enum AbstractInstruction {
         /// Operation being executed
         op: String,
         /// The source position of the instruction if provided
-        #[cfg(feature = "position")]
         #[serde(flatten, skip_serializing_if = "Option::is_none")]
         pos: Option<Position>,
     },
 }
 
+impl AbstractInstruction {
+    /// The functions called by this instruction
+    pub fn funcs(&self) -> impl Iterator<Item = &String> {
+        let funcs: &[String] = match self {
+            Self::Constant { .. } => &[],
+            Self::Value { funcs, .. } | Self::Effect { funcs, .. } => funcs,
+        };
+        funcs.iter()
+    }
+
+    /// The comments attached to the instruction
+    fn comments(&self) -> Option<&Comments> {
+        match self {
+            Self::Constant { comments, .. }
+            | Self::Value { comments, .. }
+            | Self::Effect { comments, .. } => comments.as_deref(),
+        }
+    }
+
+    /// The functions called by this instruction, for tools which rename them
+    pub fn funcs_mut(&mut self) -> impl Iterator<Item = &mut String> {
+        let funcs: &mut [String] = match self {
+            Self::Constant { .. } => &mut [],
+            Self::Value { funcs, .. } | Self::Effect { funcs, .. } => funcs,
+        };
+        funcs.iter_mut()
+    }
+}
+
 impl Display for AbstractInstruction {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
@@ -203,8 +255,8 @@ impl Display for AbstractInstruction {
                 dest,
                 const_type,
                 value,
-                #[cfg(feature = "position")]
-                    pos: _,
+                pos: _,
+                comments: _,
             } => match const_type {
                 Some(const_type) => write!(f, "{dest}: {const_type} = {op} {value};"),
                 None => write!(f, "{dest} = {op} {value};"),
@@ -216,8 +268,8 @@ impl Display for AbstractInstruction {
                 args,
                 funcs,
                 labels,
-                #[cfg(feature = "position")]
-                    pos: _,
+                pos: _,
+                comments: _,
             } => {
                 match op_type {
                     Some(op_type) => write!(f, "{dest}: {op_type} = {op}")?,
@@ -239,8 +291,8 @@ impl Display for AbstractInstruction {
                 args,
                 funcs,
                 labels,
-                #[cfg(feature = "position")]
-                    pos: _,
+                pos: _,
+                comments: _,
             } => {
                 write!(f, "{op}")?;
                 for func in funcs {
@@ -0,0 +1,839 @@
+use std::collections::HashMap;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+
+use thiserror::Error;
+
+use crate::{
+    AbstractArgument, AbstractCode, AbstractFunction, AbstractInstruction, AbstractProgram,
+    AbstractType, BrilError, ByteSpan, ColRow, Comments, ConstOps, Literal, Position, Program,
+};
+use crate::{Import, ImportedFunction};
+
+/// The first bytes of every binary Bril program.
+///
+/// `0xB7` can never start a UTF-8 encoded character, so binary programs can not be confused with JSON or the text format.
+pub const MAGIC: [u8; 4] = [0xB7, b'B', b'R', b'L'];
+
+/// The version of the encoding written by [`ProgramWriter`]. [`ProgramReader`] rejects every other version.
+pub const VERSION: u64 = 1;
+
+/// How deeply types and the origins of positions may be nested, the same limit `serde_json` puts on JSON
+const MAX_DEPTH: usize = 128;
+
+const FLAG_POSITIONS: u8 = 1;
+
+const RECORD_END: u8 = 0;
+const RECORD_FUNCTION: u8 = 1;
+const RECORD_IMPORT: u8 = 2;
+
+const CODE_LABEL: u8 = 0;
+const CODE_CONSTANT: u8 = 1;
+const CODE_VALUE: u8 = 2;
+const CODE_EFFECT: u8 = 3;
+
+const TYPE_NONE: u8 = 0;
+const TYPE_PRIMITIVE: u8 = 1;
+const TYPE_PARAMETERIZED: u8 = 2;
+
+const LITERAL_INT: u8 = 0;
+const LITERAL_FALSE: u8 = 1;
+const LITERAL_TRUE: u8 = 2;
+const LITERAL_FLOAT: u8 = 3;
+const LITERAL_CHAR: u8 = 4;
+
+/// The errors that can occur while decoding a binary Bril program
+#[derive(Error, Debug)]
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "I allow the `Error` suffix for enums"
+)]
+pub enum BinaryError {
+    /// The input does not start with [`MAGIC`]
+    #[error("input is not a binary Bril program")]
+    BadMagic,
+    /// The input was written by another version of the encoding
+    #[error("unsupported binary Bril version {0}, expected {VERSION}")]
+    UnsupportedVersion(u64),
+    /// A tag byte does not name any known kind of `{kind}`
+    #[error("invalid {kind} tag {tag}")]
+    InvalidTag {
+        /// What was being decoded
+        kind: &'static str,
+        /// The unknown tag
+        tag: u8,
+    },
+    /// A string refers past the end of the string table
+    #[error("string reference {0} is not in the string table")]
+    InvalidStringRef(u64),
+    /// A string in the string table is not valid UTF-8
+    #[error(transparent)]
+    InvalidUtf8(#[from] std::string::FromUtf8Error),
+    /// A varint does not fit in 64 bits
+    #[error("varint does not fit in 64 bits")]
+    VarintOverflow,
+    /// A character literal is not a Unicode scalar value
+    #[error("{0:#x} is not a valid character")]
+    InvalidChar(u64),
+    /// A type or the origins of a position are nested more deeply than the reader allows
+    #[error("types and positions may not be nested more than {MAX_DEPTH} deep")]
+    TooDeep,
+}
+
+/// Whether ```bytes``` starts with [`MAGIC`], for tools which accept both binary and JSON input
+#[must_use]
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.starts_with(&MAGIC)
+}
+
+/// Encodes a program into ```output``` one item at a time.
+///
+/// Strings are added to the string table the first time they are written and referred to by index afterwards, so functions can be written as they are produced without first collecting the whole program.
+/// The writer does many small writes, so ```output``` should be buffered.
+pub struct ProgramWriter<W: Write> {
+    output: W,
+    strings: HashMap<String, u64>,
+    positions: bool,
+}
+
+impl<W: Write> ProgramWriter<W> {
+    /// Writes the header to ```output```. Source positions are only written if ```positions``` is set.
+    /// # Errors
+    /// Will return an error if writing to ```output``` fails
+    pub fn new(output: W, positions: bool) -> Result<Self, BrilError> {
+        let mut writer = Self {
+            output,
+            strings: HashMap::new(),
+            positions,
+        };
+        writer.output.write_all(&MAGIC)?;
+        writer.write_varint(VERSION)?;
+        writer.write_u8(if writer.positions { FLAG_POSITIONS } else { 0 })?;
+        Ok(writer)
+    }
+
+    /// Appends ```func``` to the program
+    /// # Errors
+    /// Will return an error if writing to the output fails
+    pub fn write_function(&mut self, func: &AbstractFunction) -> Result<(), BrilError> {
+        let AbstractFunction {
+            args,
+            comments,
+            instrs,
+            name,
+            pos,
+            return_type,
+        } = func;
+        self.write_u8(RECORD_FUNCTION)?;
+        self.write_str(name)?;
+        self.write_len(args.len())?;
+        for AbstractArgument { name, arg_type } in args {
+            self.write_str(name)?;
+            self.write_type(Some(arg_type))?;
+        }
+        self.write_type(return_type.as_ref())?;
+        self.write_pos(pos.as_ref())?;
+        self.write_comments(comments.as_deref())?;
+        self.write_len(instrs.len())?;
+        for code in instrs {
+            self.write_code(code)?;
+        }
+        Ok(())
+    }
+
+    /// Appends ```import``` to the program
+    /// # Errors
+    /// Will return an error if writing to the output fails
+    pub fn write_import(&mut self, import: &Import) -> Result<(), BrilError> {
+        self.write_u8(RECORD_IMPORT)?;
+        self.write_str(&import.path.to_string_lossy())?;
+        self.write_len(import.functions.len())?;
+        for ImportedFunction { alias, name } in &import.functions {
+            self.write_str(name)?;
+            self.write_opt_str(alias.as_deref())?;
+        }
+        self.write_comments(import.comments.as_deref())?;
+        Ok(())
+    }
+
+    /// Marks the end of the program and flushes the output, which is then handed back
+    /// # Errors
+    /// Will return an error if writing to the output fails
+    pub fn finish(mut self) -> Result<W, BrilError> {
+        self.write_u8(RECORD_END)?;
+        self.output.flush()?;
+        Ok(self.output)
+    }
+
+    fn write_code(&mut self, code: &AbstractCode) -> io::Result<()> {
+        match code {
+            AbstractCode::Label {
+                comments,
+                label,
+                pos,
+            } => {
+                self.write_u8(CODE_LABEL)?;
+                self.write_str(label)?;
+                self.write_pos(pos.as_ref())?;
+                self.write_comments(comments.as_deref())
+            }
+            AbstractCode::Instruction(AbstractInstruction::Constant {
+                comments,
+                dest,
+                op: ConstOps::Const,
+                pos,
+                const_type,
+                value,
+            }) => {
+                self.write_u8(CODE_CONSTANT)?;
+                self.write_str(dest)?;
+                self.write_type(const_type.as_ref())?;
+                self.write_literal(value)?;
+                self.write_pos(pos.as_ref())?;
+                self.write_comments(comments.as_deref())
+            }
+            AbstractCode::Instruction(AbstractInstruction::Value {
+                args,
+                comments,
+                dest,
+                funcs,
+                labels,
+                op,
+                pos,
+                op_type,
+            }) => {
+                self.write_u8(CODE_VALUE)?;
+                self.write_str(op)?;
+                self.write_str(dest)?;
+                self.write_type(op_type.as_ref())?;
+                self.write_strs(args)?;
+                self.write_strs(funcs)?;
+                self.write_strs(labels)?;
+                self.write_pos(pos.as_ref())?;
+                self.write_comments(comments.as_deref())
+            }
+            AbstractCode::Instruction(AbstractInstruction::Effect {
+                args,
+                comments,
+                funcs,
+                labels,
+                op,
+                pos,
+            }) => {
+                self.write_u8(CODE_EFFECT)?;
+                self.write_str(op)?;
+                self.write_strs(args)?;
+                self.write_strs(funcs)?;
+                self.write_strs(labels)?;
+                self.write_pos(pos.as_ref())?;
+                self.write_comments(comments.as_deref())
+            }
+        }
+    }
+
+    fn write_type(&mut self, ty: Option<&AbstractType>) -> io::Result<()> {
+        match ty {
+            None => self.write_u8(TYPE_NONE),
+            Some(AbstractType::Primitive(name)) => {
+                self.write_u8(TYPE_PRIMITIVE)?;
+                self.write_str(name)
+            }
+            Some(AbstractType::Parameterized(name, inner)) => {
+                self.write_u8(TYPE_PARAMETERIZED)?;
+                self.write_str(name)?;
+                self.write_type(Some(inner))
+            }
+        }
+    }
+
+    fn write_literal(&mut self, value: &Literal) -> io::Result<()> {
+        match value {
+            Literal::Int(i) => {
+                self.write_u8(LITERAL_INT)?;
+                // Zigzag encoding keeps small negative numbers short
+                self.write_varint(((i << 1) ^ (i >> 63)).cast_unsigned())
+            }
+            Literal::Bool(false) => self.write_u8(LITERAL_FALSE),
+            Literal::Bool(true) => self.write_u8(LITERAL_TRUE),
+            Literal::Float(f) => {
+                self.write_u8(LITERAL_FLOAT)?;
+                self.output.write_all(&f.to_bits().to_le_bytes())
+            }
+            Literal::Char(c) => {
+                self.write_u8(LITERAL_CHAR)?;
+                self.write_varint(u64::from(u32::from(*c)))
+            }
+        }
+    }
+
+    fn write_pos(&mut self, position: Option<&Position>) -> io::Result<()> {
+        if !self.positions {
+            return Ok(());
+        }
+        let Some(Position {
+            pos: start,
+            pos_end,
+            src,
+            byte_span,
+            origin,
+        }) = position
+        else {
+            return self.write_u8(0);
+        };
+        self.write_u8(1)?;
+        self.write_col_row(*start)?;
+        match pos_end {
+            None => self.write_u8(0)?,
+            Some(end) => {
+                self.write_u8(1)?;
+                self.write_col_row(*end)?;
+            }
+        }
+        self.write_opt_str(src.as_deref())?;
+        match byte_span {
+            None => self.write_u8(0)?,
+            Some(ByteSpan { start, end }) => {
+                self.write_u8(1)?;
+                self.write_varint(*start)?;
+                self.write_varint(*end)?;
+            }
+        }
+        self.write_pos(origin.as_deref())
+    }
+
+    /// Comments are written whether or not positions are, since they do not depend on them
+    fn write_comments(&mut self, comments: Option<&Comments>) -> io::Result<()> {
+        let Some(Comments {
+            leading,
+            trailing,
+            closing,
+            after,
+        }) = comments
+        else {
+            return self.write_u8(0);
+        };
+        self.write_u8(1)?;
+        self.write_strs(leading)?;
+        self.write_opt_str(trailing.as_deref())?;
+        self.write_strs(closing)?;
+        self.write_strs(after)
+    }
+
+    fn write_col_row(&mut self, ColRow { col, row }: ColRow) -> io::Result<()> {
+        self.write_varint(row)?;
+        self.write_varint(col)
+    }
+
+    fn write_strs(&mut self, strs: &[String]) -> io::Result<()> {
+        self.write_len(strs.len())?;
+        for s in strs {
+            self.write_str(s)?;
+        }
+        Ok(())
+    }
+
+    fn write_opt_str(&mut self, s: Option<&str>) -> io::Result<()> {
+        match s {
+            None => self.write_u8(0),
+            Some(s) => {
+                self.write_u8(1)?;
+                self.write_str(s)
+            }
+        }
+    }
+
+    /// A reference of 0 introduces a new string, and `n` refers to the `n-1`th string of the table
+    fn write_str(&mut self, s: &str) -> io::Result<()> {
+        if let Some(&idx) = self.strings.get(s) {
+            return self.write_varint(idx + 1);
+        }
+        self.strings
+            .insert(s.to_string(), self.strings.len() as u64);
+        self.write_varint(0)?;
+        self.write_len(s.len())?;
+        self.output.write_all(s.as_bytes())
+    }
+
+    fn write_len(&mut self, len: usize) -> io::Result<()> {
+        self.write_varint(len as u64)
+    }
+
+    fn write_varint(&mut self, mut value: u64) -> io::Result<()> {
+        let mut buf = [0; 10];
+        let mut len = 0;
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                buf[len] = byte;
+                len += 1;
+                break;
+            }
+            buf[len] = byte | 0x80;
+            len += 1;
+        }
+        self.output.write_all(&buf[..len])
+    }
+
+    fn write_u8(&mut self, byte: u8) -> io::Result<()> {
+        self.output.write_all(&[byte])
+    }
+}
+
+/// An item of a binary Bril program
+#[derive(Debug, Clone)]
+pub enum Record {
+    /// A function of the program
+    Function(AbstractFunction),
+    /// An import of the program
+    Import(Import),
+}
+
+/// Decodes a program from ```input``` one [`Record`] at a time.
+///
+/// The reader does many small reads, so ```input``` should be buffered.
+pub struct ProgramReader<R: Read> {
+    input: R,
+    strings: Vec<String>,
+    positions: bool,
+    finished: bool,
+}
+
+impl<R: Read> ProgramReader<R> {
+    /// Reads and checks the header of ```input```
+    /// # Errors
+    /// Will return an error if ```input``` can not be read, does not start with [`MAGIC`], or was written by another version of the encoding
+    pub fn new(input: R) -> Result<Self, BrilError> {
+        let mut reader = Self {
+            input,
+            strings: Vec::new(),
+            positions: false,
+            finished: false,
+        };
+        let mut magic = [0; MAGIC.len()];
+        reader.input.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(BinaryError::BadMagic.into());
+        }
+        let version = reader.read_varint()?;
+        if version != VERSION {
+            return Err(BinaryError::UnsupportedVersion(version).into());
+        }
+        let flags = reader.read_u8()?;
+        if flags & !FLAG_POSITIONS != 0 {
+            return Err(BinaryError::InvalidTag {
+                kind: "header flag",
+                tag: flags,
+            }
+            .into());
+        }
+        reader.positions = flags & FLAG_POSITIONS != 0;
+        Ok(reader)
+    }
+
+    /// Whether the program was written with source positions
+    #[must_use]
+    pub const fn has_positions(&self) -> bool {
+        self.positions
+    }
+
+    /// Reads the next item of the program, or [None] once the end of the program has been reached
+    /// # Errors
+    /// Will return an error if the input can not be read or is not a well-formed binary Bril program
+    pub fn next_record(&mut self) -> Result<Option<Record>, BrilError> {
+        if self.finished {
+            return Ok(None);
+        }
+        // Stop after the first error so that iterating does not keep decoding garbage
+        self.finished = true;
+        let record = match self.read_u8()? {
+            RECORD_END => return Ok(None),
+            RECORD_FUNCTION => Record::Function(self.read_function()?),
+            RECORD_IMPORT => Record::Import(self.read_import()?),
+            tag => {
+                return Err(BinaryError::InvalidTag {
+                    kind: "record",
+                    tag,
+                }
+                .into());
+            }
+        };
+        self.finished = false;
+        Ok(Some(record))
+    }
+
+    fn read_function(&mut self) -> Result<AbstractFunction, BrilError> {
+        let name = self.read_str()?;
+        let num_args = self.read_len()?;
+        let mut args = Vec::new();
+        for _ in 0..num_args {
+            args.push(AbstractArgument {
+                name: self.read_str()?,
+                arg_type: self.read_type()?,
+            });
+        }
+        let return_type = self.read_opt_type()?;
+        let position = self.read_pos()?;
+        let comments = self.read_comments()?;
+        let num_instrs = self.read_len()?;
+        let mut instrs = Vec::new();
+        for _ in 0..num_instrs {
+            instrs.push(self.read_code()?);
+        }
+        Ok(AbstractFunction {
+            args,
+            comments,
+            instrs,
+            name,
+            pos: position,
+            return_type,
+        })
+    }
+
+    fn read_import(&mut self) -> Result<Import, BrilError> {
+        let path = self.read_str()?.into();
+        let num_functions = self.read_len()?;
+        let mut functions = Vec::new();
+        for _ in 0..num_functions {
+            functions.push(ImportedFunction {
+                name: self.read_str()?,
+                alias: self.read_opt_str()?,
+            });
+        }
+        let comments = self.read_comments()?;
+        Ok(Import {
+            comments,
+            functions,
+            path,
+        })
+    }
+
+    fn read_code(&mut self) -> Result<AbstractCode, BrilError> {
+        Ok(match self.read_u8()? {
+            CODE_LABEL => {
+                let label = self.read_str()?;
+                let position = self.read_pos()?;
+                let comments = self.read_comments()?;
+                AbstractCode::Label {
+                    comments,
+                    label,
+                    pos: position,
+                }
+            }
+            CODE_CONSTANT => {
+                let dest = self.read_str()?;
+                let const_type = self.read_opt_type()?;
+                let value = self.read_literal()?;
+                let position = self.read_pos()?;
+                let comments = self.read_comments()?;
+                AbstractCode::Instruction(AbstractInstruction::Constant {
+                    comments,
+                    dest,
+                    op: ConstOps::Const,
+                    pos: position,
+                    const_type,
+                    value,
+                })
+            }
+            CODE_VALUE => {
+                let op = self.read_str()?;
+                let dest = self.read_str()?;
+                let op_type = self.read_opt_type()?;
+                let args = self.read_strs()?;
+                let funcs = self.read_strs()?;
+                let labels = self.read_strs()?;
+                let position = self.read_pos()?;
+                let comments = self.read_comments()?;
+                AbstractCode::Instruction(AbstractInstruction::Value {
+                    args,
+                    comments,
+                    dest,
+                    funcs,
+                    labels,
+                    op,
+                    pos: position,
+                    op_type,
+                })
+            }
+            CODE_EFFECT => {
+                let op = self.read_str()?;
+                let args = self.read_strs()?;
+                let funcs = self.read_strs()?;
+                let labels = self.read_strs()?;
+                let position = self.read_pos()?;
+                let comments = self.read_comments()?;
+                AbstractCode::Instruction(AbstractInstruction::Effect {
+                    args,
+                    comments,
+                    funcs,
+                    labels,
+                    op,
+                    pos: position,
+                })
+            }
+            tag => return Err(BinaryError::InvalidTag { kind: "code", tag }.into()),
+        })
+    }
+
+    fn read_type(&mut self) -> Result<AbstractType, BrilError> {
+        self.read_opt_type()?.ok_or_else(|| {
+            BinaryError::InvalidTag {
+                kind: "type",
+                tag: TYPE_NONE,
+            }
+            .into()
+        })
+    }
+
+    /// Reads the names of parameterized types until the primitive type inside of them, without recursing on input which could nest them arbitrarily deep
+    fn read_opt_type(&mut self) -> Result<Option<AbstractType>, BrilError> {
+        let mut params = Vec::new();
+        let primitive = loop {
+            match self.read_u8()? {
+                TYPE_NONE if params.is_empty() => return Ok(None),
+                TYPE_PRIMITIVE => break AbstractType::Primitive(self.read_str()?),
+                TYPE_PARAMETERIZED if params.len() < MAX_DEPTH => params.push(self.read_str()?),
+                TYPE_PARAMETERIZED => return Err(BinaryError::TooDeep.into()),
+                tag => return Err(BinaryError::InvalidTag { kind: "type", tag }.into()),
+            }
+        };
+        Ok(Some(params.into_iter().rev().fold(primitive, |t, name| {
+            AbstractType::Parameterized(name, Box::new(t))
+        })))
+    }
+
+    fn read_literal(&mut self) -> Result<Literal, BrilError> {
+        Ok(match self.read_u8()? {
+            LITERAL_INT => {
+                let zigzag = self.read_varint()?;
+                Literal::Int((zigzag >> 1).cast_signed() ^ -(zigzag & 1).cast_signed())
+            }
+            LITERAL_FALSE => Literal::Bool(false),
+            LITERAL_TRUE => Literal::Bool(true),
+            LITERAL_FLOAT => {
+                let mut bytes = [0; 8];
+                self.input.read_exact(&mut bytes)?;
+                Literal::Float(f64::from_bits(u64::from_le_bytes(bytes)))
+            }
+            LITERAL_CHAR => {
+                let c = self.read_varint()?;
+                Literal::Char(
+                    u32::try_from(c)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or(BinaryError::InvalidChar(c))?,
+                )
+            }
+            tag => {
+                return Err(BinaryError::InvalidTag {
+                    kind: "literal",
+                    tag,
+                }
+                .into());
+            }
+        })
+    }
+
+    /// Reads a position and the chain of its origins, without recursing on input which could make the chain arbitrarily long
+    fn read_pos(&mut self) -> Result<Option<Position>, BrilError> {
+        if !self.positions {
+            return Ok(None);
+        }
+        let mut chain = Vec::new();
+        while self.read_bool()? {
+            if chain.len() == MAX_DEPTH {
+                return Err(BinaryError::TooDeep.into());
+            }
+            chain.push(self.read_one_pos()?);
+        }
+        Ok(chain.into_iter().rev().reduce(|origin, mut pos| {
+            pos.origin = Some(Box::new(origin));
+            pos
+        }))
+    }
+
+    /// Reads a position up to its origin, which is left for [`Self::read_pos`]
+    fn read_one_pos(&mut self) -> Result<Position, BrilError> {
+        let start = self.read_col_row()?;
+        let pos_end = if self.read_bool()? {
+            Some(self.read_col_row()?)
+        } else {
+            None
+        };
+        let src = self.read_opt_str()?;
+        let byte_span = if self.read_bool()? {
+            Some(ByteSpan {
+                start: self.read_varint()?,
+                end: self.read_varint()?,
+            })
+        } else {
+            None
+        };
+        Ok(Position {
+            pos: start,
+            pos_end,
+            src,
+            byte_span,
+            origin: None,
+        })
+    }
+
+    fn read_comments(&mut self) -> Result<Option<Box<Comments>>, BrilError> {
+        if !self.read_bool()? {
+            return Ok(None);
+        }
+        Ok(Some(Box::new(Comments {
+            leading: self.read_strs()?,
+            trailing: self.read_opt_str()?,
+            closing: self.read_strs()?,
+            after: self.read_strs()?,
+        })))
+    }
+
+    fn read_col_row(&mut self) -> Result<ColRow, BrilError> {
+        let row = self.read_varint()?;
+        let col = self.read_varint()?;
+        Ok(ColRow { col, row })
+    }
+
+    fn read_strs(&mut self) -> Result<Vec<String>, BrilError> {
+        let len = self.read_len()?;
+        let mut strs = Vec::new();
+        for _ in 0..len {
+            strs.push(self.read_str()?);
+        }
+        Ok(strs)
+    }
+
+    fn read_opt_str(&mut self) -> Result<Option<String>, BrilError> {
+        Ok(if self.read_bool()? {
+            Some(self.read_str()?)
+        } else {
+            None
+        })
+    }
+
+    fn read_str(&mut self) -> Result<String, BrilError> {
+        let reference = self.read_varint()?;
+        if reference != 0 {
+            return usize::try_from(reference - 1)
+                .ok()
+                .and_then(|idx| self.strings.get(idx))
+                .cloned()
+                .ok_or_else(|| BinaryError::InvalidStringRef(reference).into());
+        }
+        let len = self.read_varint()?;
+        let mut bytes = Vec::new();
+        // `take` avoids trusting the length with a single large allocation
+        if self.input.by_ref().take(len).read_to_end(&mut bytes)? as u64 != len {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        let s = String::from_utf8(bytes).map_err(BinaryError::from)?;
+        self.strings.push(s.clone());
+        Ok(s)
+    }
+
+    fn read_bool(&mut self) -> Result<bool, BrilError> {
+        match self.read_u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            tag => Err(BinaryError::InvalidTag {
+                kind: "option",
+                tag,
+            }
+            .into()),
+        }
+    }
+
+    fn read_len(&mut self) -> Result<usize, BrilError> {
+        usize::try_from(self.read_varint()?).map_err(|_| BinaryError::VarintOverflow.into())
+    }
+
+    fn read_varint(&mut self) -> Result<u64, BrilError> {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_u8()?;
+            // The tenth byte may only contribute the top bit
+            if shift == 63 && byte > 1 {
+                return Err(BinaryError::VarintOverflow.into());
+            }
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+            shift += 7;
+        }
+    }
+
+    fn read_u8(&mut self) -> Result<u8, BrilError> {
+        let mut byte = [0];
+        self.input.read_exact(&mut byte)?;
+        Ok(byte[0])
+    }
+}
+
+impl<R: Read> Iterator for ProgramReader<R> {
+    type Item = Result<Record, BrilError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record().transpose()
+    }
+}
+
+/// Writes an [`AbstractProgram`] to ```output``` in the binary format, including source positions if ```positions``` is set
+/// # Errors
+/// Will return an error if writing to ```output``` fails
+pub fn write_abstract_program<W: Write>(
+    p: &AbstractProgram,
+    output: W,
+    positions: bool,
+) -> Result<(), BrilError> {
+    let mut writer = ProgramWriter::new(BufWriter::new(output), positions)?;
+    for import in &p.imports {
+        writer.write_import(import)?;
+    }
+    for func in &p.functions {
+        writer.write_function(func)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Writes a [Program] to ```output``` in the binary format, including source positions if ```positions``` is set
+/// # Errors
+/// Will return an error if writing to ```output``` fails
+pub fn write_program<W: Write>(p: &Program, output: W, positions: bool) -> Result<(), BrilError> {
+    let mut writer = ProgramWriter::new(BufWriter::new(output), positions)?;
+    for import in &p.imports {
+        writer.write_import(import)?;
+    }
+    for func in &p.functions {
+        writer.write_function(&func.clone().into())?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// Reads an [`AbstractProgram`] in the binary format from ```input```
+/// # Errors
+/// Will return an error if ```input``` can not be read or is not a well-formed binary Bril program
+pub fn read_abstract_program<R: Read>(input: R) -> Result<AbstractProgram, BrilError> {
+    let mut program = AbstractProgram {
+        functions: Vec::new(),
+        imports: Vec::new(),
+    };
+    for record in ProgramReader::new(BufReader::new(input))? {
+        match record? {
+            Record::Function(func) => program.functions.push(func),
+            Record::Import(import) => program.imports.push(import),
+        }
+    }
+    Ok(program)
+}
+
+/// Reads a [Program] in the binary format from ```input```
+/// # Errors
+/// Will return an error if ```input``` can not be read, is not a well-formed binary Bril program, or can not be converted to [Program]
+pub fn read_program<R: Read>(input: R) -> Result<Program, BrilError> {
+    Ok(read_abstract_program(input)?.try_into()?)
+}
@@ -0,0 +1,527 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::Import;
+use crate::Position;
+use crate::cfg::BasicBlock;
+use crate::{
+    Argument, Code, ConstOps, EffectOps, Function, Instruction, Literal, Program, Type, ValueOps,
+};
+
+/// Generates variable and label names which have not been used yet
+///
+/// Names are a prefix followed by a counter shared between variables and labels, like `tmp0` and `label1`. Names passed to [`FreshNames::reserve`] are never generated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FreshNames {
+    var_prefix: String,
+    label_prefix: String,
+    counter: u64,
+    reserved: HashSet<String>,
+}
+
+impl Default for FreshNames {
+    fn default() -> Self {
+        Self::new("tmp", "label")
+    }
+}
+
+impl FreshNames {
+    /// Creates a generator of variables starting with ```var_prefix``` and labels starting with ```label_prefix```
+    #[must_use]
+    pub fn new(var_prefix: &str, label_prefix: &str) -> Self {
+        Self {
+            var_prefix: var_prefix.to_string(),
+            label_prefix: label_prefix.to_string(),
+            counter: 0,
+            reserved: HashSet::new(),
+        }
+    }
+
+    /// Marks ```name``` as used so that it is never generated
+    pub fn reserve(&mut self, name: &str) {
+        self.reserved.insert(name.to_string());
+    }
+
+    /// Returns a new variable name
+    pub fn var(&mut self) -> String {
+        let prefix = self.var_prefix.clone();
+        self.next(&prefix)
+    }
+
+    /// Returns a new label name
+    pub fn label(&mut self) -> String {
+        let prefix = self.label_prefix.clone();
+        self.next(&prefix)
+    }
+
+    fn next(&mut self, prefix: &str) -> String {
+        loop {
+            let name = format!("{prefix}{}", self.counter);
+            self.counter += 1;
+            if self.reserved.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+}
+
+/// Builds a [Function] by emitting one instruction or label at a time
+///
+/// Every helper which produces a value assigns it to a fresh variable and returns its name. The builder remembers the type of each variable it has seen so that operations like [`FunctionBuilder::id`] or `load` only need their arguments.
+///
+/// ```
+/// use bril_rs::builder::FunctionBuilder;
+/// use bril_rs::{Literal, Type};
+///
+/// let mut b = FunctionBuilder::new("main", None);
+/// let n = b.add_arg("n", Type::Int);
+/// let one = b.constant(Literal::Int(1));
+/// let next = b.add(&n, &one);
+/// b.print(&[&next]);
+/// let main = b.build();
+/// assert_eq!(main.to_string(), "@main(n: int) {\n  tmp0: int = const 1;\n  tmp1: int = add n tmp0;\n  print tmp1;\n}");
+/// ```
+#[derive(Debug, Clone)]
+pub struct FunctionBuilder {
+    name: String,
+    args: Vec<Argument>,
+    return_type: Option<Type>,
+    instrs: Vec<Code>,
+    names: FreshNames,
+    types: HashMap<String, Type>,
+    pos: Option<Position>,
+    current_pos: Option<Position>,
+}
+
+impl FunctionBuilder {
+    /// Starts a function called ```name``` which returns ```return_type```
+    #[must_use]
+    pub fn new(name: &str, return_type: Option<Type>) -> Self {
+        Self::with_names(name, return_type, FreshNames::default())
+    }
+
+    /// Starts a function like [`FunctionBuilder::new`] which takes its fresh names from ```names```, for example to keep them unique across functions
+    #[must_use]
+    pub fn with_names(name: &str, return_type: Option<Type>, names: FreshNames) -> Self {
+        Self {
+            name: name.to_string(),
+            args: Vec::new(),
+            return_type,
+            instrs: Vec::new(),
+            names,
+            types: HashMap::new(),
+            pos: None,
+            current_pos: None,
+        }
+    }
+
+    /// Adds an argument to the function and returns its name
+    pub fn add_arg(&mut self, name: &str, arg_type: Type) -> String {
+        self.declare(name, arg_type.clone());
+        self.args.push(Argument {
+            name: name.to_string(),
+            arg_type,
+        });
+        name.to_string()
+    }
+
+    /// Sets the source position of the function itself
+    pub fn set_function_position(&mut self, pos: Option<Position>) {
+        self.pos = pos;
+    }
+
+    /// Sets the source position given to every instruction and label emitted from now on
+    pub fn set_position(&mut self, pos: Option<Position>) {
+        self.current_pos = pos;
+    }
+
+    /// Returns a variable name which is not used anywhere in the function yet
+    pub fn fresh_var(&mut self) -> String {
+        self.names.var()
+    }
+
+    /// Returns a label which is not used anywhere in the function yet
+    pub fn fresh_label(&mut self) -> String {
+        self.names.label()
+    }
+
+    /// The type of ```var``` if it is an argument or has been assigned by an emitted instruction
+    #[must_use]
+    pub fn var_type(&self, var: &str) -> Option<&Type> {
+        self.types.get(var)
+    }
+
+    /// Records ```var``` as a variable of type ```var_type``` that may be assigned outside of the builder
+    pub fn declare(&mut self, var: &str, var_type: Type) {
+        self.names.reserve(var);
+        self.types.insert(var.to_string(), var_type);
+    }
+
+    /// Emits ```label```, which starts a new block
+    pub fn label(&mut self, label: &str) {
+        self.names.reserve(label);
+        self.instrs.push(Code::Label {
+            label: label.to_string(),
+            pos: self.current_pos.clone(),
+            comments: None,
+        });
+    }
+
+    /// Starts a new block with a fresh label and returns the label
+    pub fn new_block(&mut self) -> String {
+        let label = self.fresh_label();
+        self.label(&label);
+        label
+    }
+
+    /// Emits every label and instruction of ```block```
+    pub fn push_block(&mut self, block: BasicBlock) {
+        if let Some(label) = block.label {
+            self.names.reserve(&label);
+            self.instrs.push(Code::Label {
+                label,
+                pos: block.label_pos,
+                comments: block.label_comments,
+            });
+        }
+        for instr in block.instrs {
+            self.push(instr);
+        }
+    }
+
+    /// Whether the current block ends in a `jmp`, `br`, or `ret`, so that anything emitted next is unreachable until a label starts a new block
+    #[must_use]
+    pub fn is_terminated(&self) -> bool {
+        matches!(self.instrs.last(), Some(Code::Instruction(i)) if crate::cfg::is_terminator(i))
+    }
+
+    /// Emits ```instr``` as is
+    pub fn push(&mut self, instr: Instruction) {
+        match &instr {
+            Instruction::Constant {
+                dest, const_type, ..
+            } => self.declare(dest, const_type.clone()),
+            Instruction::Value { dest, op_type, .. } => self.declare(dest, op_type.clone()),
+            Instruction::Effect { .. } => {}
+        }
+        self.instrs.push(Code::Instruction(instr));
+    }
+
+    /// Emits a `const` of ```value``` into a fresh variable of the type of ```value```
+    pub fn constant(&mut self, value: Literal) -> String {
+        let dest = self.fresh_var();
+        self.constant_into(&dest, value.get_type(), value);
+        dest
+    }
+
+    /// Emits `dest: const_type = const value`
+    pub fn constant_into(&mut self, dest: &str, const_type: Type, value: Literal) {
+        self.push(Instruction::Constant {
+            dest: dest.to_string(),
+            op: ConstOps::Const,
+            pos: self.current_pos.clone(),
+            comments: None,
+            const_type,
+            value,
+        });
+    }
+
+    /// Emits ```op``` over ```args``` into a fresh variable of type ```op_type```
+    pub fn value(&mut self, op: ValueOps, op_type: Type, args: &[&str]) -> String {
+        let dest = self.fresh_var();
+        self.value_into(&dest, op, op_type, args, &[]);
+        dest
+    }
+
+    /// Emits `dest: op_type = op args funcs`
+    pub fn value_into(
+        &mut self,
+        dest: &str,
+        op: ValueOps,
+        op_type: Type,
+        args: &[&str],
+        funcs: &[&str],
+    ) {
+        self.push(Instruction::Value {
+            args: to_strings(args),
+            dest: dest.to_string(),
+            funcs: to_strings(funcs),
+            labels: Vec::new(),
+            op,
+            pos: self.current_pos.clone(),
+            comments: None,
+            op_type,
+        });
+    }
+
+    /// Emits the effect operation ```op```
+    pub fn effect(&mut self, op: EffectOps, args: &[&str], funcs: &[&str], labels: &[&str]) {
+        self.push(Instruction::Effect {
+            args: to_strings(args),
+            funcs: to_strings(funcs),
+            labels: to_strings(labels),
+            op,
+            pos: self.current_pos.clone(),
+            comments: None,
+        });
+    }
+
+    /// Emits a copy of ```src```
+    /// # Panics
+    /// Will panic if the type of ```src``` is not known to the builder, see [`FunctionBuilder::declare`]
+    pub fn id(&mut self, src: &str) -> String {
+        let op_type = self.expect_type(src).clone();
+        self.value(ValueOps::Id, op_type, &[src])
+    }
+
+    /// Emits `add a b`
+    pub fn add(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Add, Type::Int, &[a, b])
+    }
+
+    /// Emits `sub a b`
+    pub fn sub(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Sub, Type::Int, &[a, b])
+    }
+
+    /// Emits `mul a b`
+    pub fn mul(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Mul, Type::Int, &[a, b])
+    }
+
+    /// Emits `div a b`
+    pub fn div(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Div, Type::Int, &[a, b])
+    }
+
+    /// Emits `eq a b`
+    pub fn eq(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Eq, Type::Bool, &[a, b])
+    }
+
+    /// Emits `lt a b`
+    pub fn lt(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Lt, Type::Bool, &[a, b])
+    }
+
+    /// Emits `gt a b`
+    pub fn gt(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Gt, Type::Bool, &[a, b])
+    }
+
+    /// Emits `le a b`
+    pub fn le(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Le, Type::Bool, &[a, b])
+    }
+
+    /// Emits `ge a b`
+    pub fn ge(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Ge, Type::Bool, &[a, b])
+    }
+
+    /// Emits `not a`
+    pub fn not(&mut self, a: &str) -> String {
+        self.value(ValueOps::Not, Type::Bool, &[a])
+    }
+
+    /// Emits `and a b`
+    pub fn and(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::And, Type::Bool, &[a, b])
+    }
+
+    /// Emits `or a b`
+    pub fn or(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Or, Type::Bool, &[a, b])
+    }
+
+    /// Emits `fadd a b`
+    pub fn fadd(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Fadd, Type::Float, &[a, b])
+    }
+
+    /// Emits `fsub a b`
+    pub fn fsub(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Fsub, Type::Float, &[a, b])
+    }
+
+    /// Emits `fmul a b`
+    pub fn fmul(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Fmul, Type::Float, &[a, b])
+    }
+
+    /// Emits `fdiv a b`
+    pub fn fdiv(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Fdiv, Type::Float, &[a, b])
+    }
+
+    /// Emits `feq a b`
+    pub fn feq(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Feq, Type::Bool, &[a, b])
+    }
+
+    /// Emits `flt a b`
+    pub fn flt(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Flt, Type::Bool, &[a, b])
+    }
+
+    /// Emits `fgt a b`
+    pub fn fgt(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Fgt, Type::Bool, &[a, b])
+    }
+
+    /// Emits `fle a b`
+    pub fn fle(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Fle, Type::Bool, &[a, b])
+    }
+
+    /// Emits `fge a b`
+    pub fn fge(&mut self, a: &str, b: &str) -> String {
+        self.value(ValueOps::Fge, Type::Bool, &[a, b])
+    }
+
+    /// Emits a call of ```func``` which returns a value of type ```return_type```
+    pub fn call(&mut self, func: &str, args: &[&str], return_type: Type) -> String {
+        let dest = self.fresh_var();
+        self.value_into(&dest, ValueOps::Call, return_type, args, &[func]);
+        dest
+    }
+
+    /// Emits a call of ```func``` whose result, if any, is ignored
+    pub fn call_void(&mut self, func: &str, args: &[&str]) {
+        self.effect(EffectOps::Call, args, &[func], &[]);
+    }
+
+    /// Emits `alloc size` of a pointer to ```elem_type```
+    pub fn alloc(&mut self, size: &str, elem_type: Type) -> String {
+        self.value(ValueOps::Alloc, Type::Pointer(Box::new(elem_type)), &[size])
+    }
+
+    /// Emits `load ptr`
+    /// # Panics
+    /// Will panic if ```ptr``` is not known to the builder as a pointer, see [`FunctionBuilder::declare`]
+    pub fn load(&mut self, ptr: &str) -> String {
+        let elem_type = match self.expect_type(ptr) {
+            Type::Pointer(t) => (**t).clone(),
+            t => panic!("`{ptr}` has type {t} instead of a pointer type"),
+        };
+        self.value(ValueOps::Load, elem_type, &[ptr])
+    }
+
+    /// Emits `ptradd ptr offset`
+    /// # Panics
+    /// Will panic if the type of ```ptr``` is not known to the builder, see [`FunctionBuilder::declare`]
+    pub fn ptradd(&mut self, ptr: &str, offset: &str) -> String {
+        let op_type = self.expect_type(ptr).clone();
+        self.value(ValueOps::PtrAdd, op_type, &[ptr, offset])
+    }
+
+    /// Emits `store ptr value`
+    pub fn store(&mut self, ptr: &str, value: &str) {
+        self.effect(EffectOps::Store, &[ptr, value], &[], &[]);
+    }
+
+    /// Emits `free ptr`
+    pub fn free(&mut self, ptr: &str) {
+        self.effect(EffectOps::Free, &[ptr], &[], &[]);
+    }
+
+    /// Emits `jmp .target`
+    pub fn jmp(&mut self, target: &str) {
+        self.effect(EffectOps::Jump, &[], &[], &[target]);
+    }
+
+    /// Emits `br cond .on_true .on_false`
+    pub fn br(&mut self, cond: &str, on_true: &str, on_false: &str) {
+        self.effect(EffectOps::Branch, &[cond], &[], &[on_true, on_false]);
+    }
+
+    /// Emits `ret`, with ```value``` if it is provided
+    pub fn ret(&mut self, value: Option<&str>) {
+        self.effect(EffectOps::Return, value.as_slice(), &[], &[]);
+    }
+
+    /// Emits `print args`
+    pub fn print(&mut self, args: &[&str]) {
+        self.effect(EffectOps::Print, args, &[], &[]);
+    }
+
+    /// Emits `nop`
+    pub fn nop(&mut self) {
+        self.effect(EffectOps::Nop, &[], &[], &[]);
+    }
+
+    /// Finishes the function
+    #[must_use]
+    pub fn build(self) -> Function {
+        Function {
+            args: self.args,
+            instrs: self.instrs,
+            name: self.name,
+            pos: self.pos,
+            comments: None,
+            return_type: self.return_type,
+        }
+    }
+
+    /// Finishes the function and hands back the [`FreshNames`] it used, so that another function can continue from them
+    #[must_use]
+    pub fn build_with_names(self) -> (Function, FreshNames) {
+        let names = self.names.clone();
+        (self.build(), names)
+    }
+
+    fn expect_type(&self, var: &str) -> &Type {
+        self.types
+            .get(var)
+            .unwrap_or_else(|| panic!("The type of `{var}` is not known"))
+    }
+}
+
+/// Builds a [Program] out of [Function]s
+#[derive(Debug, Clone, Default)]
+pub struct ProgramBuilder {
+    functions: Vec<Function>,
+    imports: Vec<Import>,
+}
+
+impl ProgramBuilder {
+    /// Starts an empty program
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds ```func``` to the program
+    pub fn add_function(&mut self, func: Function) {
+        self.functions.push(func);
+    }
+
+    /// Builds a function called ```name``` with ```f``` and adds it to the program
+    pub fn function(
+        &mut self,
+        name: &str,
+        return_type: Option<Type>,
+        f: impl FnOnce(&mut FunctionBuilder),
+    ) {
+        let mut builder = FunctionBuilder::new(name, return_type);
+        f(&mut builder);
+        self.add_function(builder.build());
+    }
+
+    /// Adds ```import``` to the program
+    pub fn add_import(&mut self, import: Import) {
+        self.imports.push(import);
+    }
+
+    /// Finishes the program
+    #[must_use]
+    pub fn build(self) -> Program {
+        Program {
+            functions: self.functions,
+            imports: self.imports,
+        }
+    }
+}
+
+fn to_strings(names: &[&str]) -> Vec<String> {
+    names.iter().map(ToString::to_string).collect()
+}
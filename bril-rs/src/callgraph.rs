@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use crate::{Code, Program};
+
+/// The call graph of a [Program], with one node for each of its functions
+///
+/// Nodes are the indices of the functions in [`Program::functions`]. There is an edge from a function to each function it calls with `call`, no matter how many times it does so.
+/// Calls to functions which are not defined in the program, like imported ones, are left out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallGraph {
+    /// The name of each function
+    pub names: Vec<String>,
+    /// The functions called by each function, in the order of their first call
+    pub callees: Vec<Vec<usize>>,
+    /// The functions which call each function, in the order of the functions
+    pub callers: Vec<Vec<usize>>,
+    indices: HashMap<String, usize>,
+}
+
+impl CallGraph {
+    /// Builds the call graph of ```prog``` from the `funcs` of its instructions
+    #[must_use]
+    pub fn new(prog: &Program) -> Self {
+        let names: Vec<String> = prog.functions.iter().map(|f| f.name.clone()).collect();
+        let indices: HashMap<String, usize> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.clone(), i))
+            .collect();
+
+        let callees: Vec<Vec<usize>> = prog
+            .functions
+            .iter()
+            .map(|func| {
+                let mut called = Vec::new();
+                for code in &func.instrs {
+                    let Code::Instruction(instr) = code else {
+                        continue;
+                    };
+                    for callee in instr.funcs().filter_map(|f| indices.get(f).copied()) {
+                        if !called.contains(&callee) {
+                            called.push(callee);
+                        }
+                    }
+                }
+                called
+            })
+            .collect();
+
+        let mut graph = Self {
+            callers: vec![Vec::new(); names.len()],
+            names,
+            callees,
+            indices,
+        };
+        for (caller, called) in graph.callees.iter().enumerate() {
+            for callee in called {
+                graph.callers[*callee].push(caller);
+            }
+        }
+        graph
+    }
+
+    /// The number of functions in the graph
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Whether the program has no functions
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// The node of the function called ```name```
+    #[must_use]
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.indices.get(name).copied()
+    }
+
+    /// The strongly connected components of the graph, in which every function can reach every other one through calls
+    ///
+    /// Components are listed callees first: no component calls a function of a later one. Functions within a component are in program order.
+    #[must_use]
+    pub fn sccs(&self) -> Vec<Vec<usize>> {
+        Tarjan::new(self).run()
+    }
+
+    /// Whether ```f``` can call itself, either directly or through other functions
+    #[must_use]
+    pub fn is_recursive(&self, f: usize) -> bool {
+        self.callees[f].contains(&f)
+            || self
+                .sccs()
+                .iter()
+                .any(|scc| scc.len() > 1 && scc.contains(&f))
+    }
+
+    /// Every function in an order where callers come before the functions they call, except for calls within the same strongly connected component
+    ///
+    /// Reversing it gives the bottom-up order in which an inliner can visit the functions.
+    #[must_use]
+    pub fn topological_order(&self) -> Vec<usize> {
+        self.sccs().into_iter().rev().flatten().collect()
+    }
+
+    /// The functions which can be called, directly or indirectly, starting from ```roots```, including the roots themselves, in program order
+    #[must_use]
+    pub fn reachable_from(&self, roots: &[usize]) -> Vec<usize> {
+        let mut reached = vec![false; self.len()];
+        let mut stack = roots.to_vec();
+        while let Some(f) = stack.pop() {
+            if !std::mem::replace(&mut reached[f], true) {
+                stack.extend(&self.callees[f]);
+            }
+        }
+        (0..self.len()).filter(|f| reached[*f]).collect()
+    }
+
+    /// The functions which can be called while running `main`, see [`CallGraph::reachable_from`].
+    /// Returns [None] if the program has no `main` function, like a library which is only imported by other programs.
+    #[must_use]
+    pub fn reachable_from_main(&self) -> Option<Vec<usize>> {
+        self.index("main").map(|main| self.reachable_from(&[main]))
+    }
+}
+
+/// Tarjan's strongly connected components algorithm, with an explicit stack to avoid recursion on long chains of calls
+struct Tarjan<'a> {
+    graph: &'a CallGraph,
+    index: Vec<Option<usize>>,
+    low_link: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    next_index: usize,
+    sccs: Vec<Vec<usize>>,
+}
+
+impl<'a> Tarjan<'a> {
+    fn new(graph: &'a CallGraph) -> Self {
+        Self {
+            graph,
+            index: vec![None; graph.len()],
+            low_link: vec![0; graph.len()],
+            on_stack: vec![false; graph.len()],
+            stack: Vec::new(),
+            next_index: 0,
+            sccs: Vec::new(),
+        }
+    }
+
+    fn run(mut self) -> Vec<Vec<usize>> {
+        for f in 0..self.graph.len() {
+            if self.index[f].is_none() {
+                self.visit(f);
+            }
+        }
+        self.sccs
+    }
+
+    fn visit(&mut self, root: usize) {
+        // Each frame is a function and the index of the next callee to visit
+        let mut frames = vec![(root, 0)];
+        self.enter(root);
+        while let Some(&mut (f, ref mut next)) = frames.last_mut() {
+            if let Some(&callee) = self.graph.callees[f].get(*next) {
+                *next += 1;
+                match self.index[callee] {
+                    None => {
+                        self.enter(callee);
+                        frames.push((callee, 0));
+                    }
+                    Some(i) if self.on_stack[callee] => {
+                        self.low_link[f] = self.low_link[f].min(i);
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+
+            frames.pop();
+            if let Some(&(caller, _)) = frames.last() {
+                self.low_link[caller] = self.low_link[caller].min(self.low_link[f]);
+            }
+            if Some(self.low_link[f]) == self.index[f] {
+                let mut scc = Vec::new();
+                while let Some(g) = self.stack.pop() {
+                    self.on_stack[g] = false;
+                    scc.push(g);
+                    if g == f {
+                        break;
+                    }
+                }
+                scc.sort_unstable();
+                self.sccs.push(scc);
+            }
+        }
+    }
+
+    fn enter(&mut self, f: usize) {
+        self.index[f] = Some(self.next_index);
+        self.low_link[f] = self.next_index;
+        self.next_index += 1;
+        self.stack.push(f);
+        self.on_stack[f] = true;
+    }
+}
+
+/// Removes every function of ```prog``` which can not be called while running `main` and returns their names in program order.
+///
+/// Nothing is removed from a program without a `main` function.
+pub fn remove_dead_functions(prog: &mut Program) -> Vec<String> {
+    let graph = CallGraph::new(prog);
+    let Some(live) = graph.reachable_from_main() else {
+        return Vec::new();
+    };
+    let mut live = live.into_iter().peekable();
+    let mut removed = Vec::new();
+    let functions = std::mem::take(&mut prog.functions);
+    for (i, func) in functions.into_iter().enumerate() {
+        if live.next_if_eq(&i).is_some() {
+            prog.functions.push(func);
+        } else {
+            removed.push(func.name);
+        }
+    }
+    removed
+}
@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+use crate::{Argument, Code, EffectOps, Function, Instruction, Type};
+use crate::{Comments, Position};
+
+/// The errors that can occur while building a [Cfg] from a [Function]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "I allow the `Error` suffix for enums"
+)]
+pub enum CfgError {
+    /// A label was declared more than once in the same function
+    #[error("duplicate label `{0}` found")]
+    DuplicateLabel(String),
+    /// A jump or branch refers to a label that is not declared in the function
+    #[error("Could not find label: {0}")]
+    MissingLabel(String),
+}
+
+/// A maximal sequence of instructions with a single entry point and a single exit point
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct BasicBlock {
+    /// The label which starts this block. Only the entry block and unreachable code directly after a terminator may be unlabeled.
+    pub label: Option<String>,
+    /// The source position of the label if it was provided
+    pub label_pos: Option<Position>,
+    /// The `#` comments written around the label if it has any
+    pub label_comments: Option<Box<Comments>>,
+    /// The instructions of this block, including its terminator if it has one
+    pub instrs: Vec<Instruction>,
+}
+
+impl BasicBlock {
+    /// Returns the last instruction of the block if it is a `jmp`, `br`, or `ret`.
+    /// A block without a terminator falls through to the next block in the function.
+    #[must_use]
+    pub fn terminator(&self) -> Option<&Instruction> {
+        self.instrs.last().filter(|i| is_terminator(i))
+    }
+}
+
+/// Whether ```instr``` ends a basic block
+#[must_use]
+pub const fn is_terminator(instr: &Instruction) -> bool {
+    matches!(
+        instr,
+        Instruction::Effect {
+            op: EffectOps::Jump | EffectOps::Branch | EffectOps::Return,
+            ..
+        }
+    )
+}
+
+/// The control-flow graph of a [Function]
+///
+/// Blocks are kept in the same order as in the original function so that converting back with [`Cfg::to_code`] preserves fall-through behaviour.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cfg {
+    /// The name of the function
+    pub name: String,
+    /// Any arguments the function accepts
+    pub args: Vec<Argument>,
+    /// The possible return type of the function
+    pub return_type: Option<Type>,
+    /// The position of the function in the original source code
+    pub pos: Option<Position>,
+    /// The `#` comments written around the function in the original source code
+    pub comments: Option<Box<Comments>>,
+    /// The basic blocks of the function. There is always at least one block, the entry.
+    pub blocks: Vec<BasicBlock>,
+    /// Maps each label to the index of the block it starts
+    pub label_map: HashMap<String, usize>,
+    /// The indices of the blocks that control can flow to from each block
+    pub successors: Vec<Vec<usize>>,
+    /// The indices of the blocks that control can flow from into each block
+    pub predecessors: Vec<Vec<usize>>,
+}
+
+impl TryFrom<Function> for Cfg {
+    type Error = CfgError;
+
+    fn try_from(func: Function) -> Result<Self, Self::Error> {
+        Self::new(func)
+    }
+}
+
+impl From<Cfg> for Function {
+    fn from(cfg: Cfg) -> Self {
+        Self {
+            instrs: cfg.to_code(),
+            name: cfg.name,
+            args: cfg.args,
+            return_type: cfg.return_type,
+            pos: cfg.pos,
+            comments: cfg.comments,
+        }
+    }
+}
+
+impl Cfg {
+    /// Splits the instructions of ```func``` into basic blocks and computes the edges between them
+    /// # Errors
+    /// Will return an error if a label is declared twice or a jump or branch targets a missing label
+    pub fn new(func: Function) -> Result<Self, CfgError> {
+        let blocks = find_basic_blocks(func.instrs);
+        let mut cfg = Self {
+            name: func.name,
+            args: func.args,
+            return_type: func.return_type,
+            pos: func.pos,
+            comments: func.comments,
+            blocks,
+            label_map: HashMap::new(),
+            successors: Vec::new(),
+            predecessors: Vec::new(),
+        };
+        cfg.recompute_edges()?;
+        Ok(cfg)
+    }
+
+    /// The index of the entry block, where execution of the function begins
+    #[must_use]
+    pub const fn entry(&self) -> usize {
+        0
+    }
+
+    /// Rebuilds [`Cfg::label_map`], [`Cfg::successors`], and [`Cfg::predecessors`] from [`Cfg::blocks`].
+    /// This should be called after any transformation which adds, removes, or reorders blocks or changes their terminators.
+    /// # Errors
+    /// Will return an error if a label is declared twice or a jump or branch targets a missing label
+    pub fn recompute_edges(&mut self) -> Result<(), CfgError> {
+        if self.blocks.is_empty() {
+            self.blocks.push(BasicBlock::default());
+        }
+
+        self.label_map.clear();
+        for (idx, block) in self.blocks.iter().enumerate() {
+            if let Some(label) = &block.label
+                && self.label_map.insert(label.clone(), idx).is_some()
+            {
+                return Err(CfgError::DuplicateLabel(label.clone()));
+            }
+        }
+
+        let find = |label: &String| {
+            self.label_map
+                .get(label)
+                .copied()
+                .ok_or_else(|| CfgError::MissingLabel(label.clone()))
+        };
+
+        let last_idx = self.blocks.len() - 1;
+        let mut successors = Vec::with_capacity(self.blocks.len());
+        for (idx, block) in self.blocks.iter().enumerate() {
+            let mut succs = match block.terminator() {
+                Some(Instruction::Effect {
+                    op: EffectOps::Jump | EffectOps::Branch,
+                    labels,
+                    ..
+                }) => labels.iter().map(find).collect::<Result<Vec<_>, _>>()?,
+                None if idx < last_idx => vec![idx + 1],
+                // Either a `ret` or falling off the end of the function
+                Some(_) | None => Vec::new(),
+            };
+            // `br cond .l .l;` only has one successor
+            succs.dedup();
+            // A failing `guard` aborts the speculation and jumps to its label from the middle of the block
+            for instr in &block.instrs {
+                if let Instruction::Effect {
+                    op: EffectOps::Guard,
+                    labels,
+                    ..
+                } = instr
+                {
+                    for label in labels {
+                        let s = find(label)?;
+                        if !succs.contains(&s) {
+                            succs.push(s);
+                        }
+                    }
+                }
+            }
+            successors.push(succs);
+        }
+
+        let mut predecessors = vec![Vec::new(); self.blocks.len()];
+        for (idx, succs) in successors.iter().enumerate() {
+            for s in succs {
+                predecessors[*s].push(idx);
+            }
+        }
+
+        self.successors = successors;
+        self.predecessors = predecessors;
+        Ok(())
+    }
+
+    /// Flattens the blocks back into the instructions of a function, keeping all labels, source positions, and comments
+    #[must_use]
+    pub fn to_code(&self) -> Vec<Code> {
+        let mut code = Vec::new();
+        for block in &self.blocks {
+            if let Some(label) = &block.label {
+                code.push(Code::Label {
+                    label: label.clone(),
+                    pos: block.label_pos.clone(),
+                    comments: block.label_comments.clone(),
+                });
+            }
+            code.extend(block.instrs.iter().cloned().map(Code::Instruction));
+        }
+        code
+    }
+
+    /// Removes every block which can not be reached from the entry block. Returns whether any block was removed.
+    /// # Errors
+    /// Will return an error if the edges of the remaining blocks can not be recomputed
+    pub fn remove_unreachable_blocks(&mut self) -> Result<bool, CfgError> {
+        let mut reachable = vec![false; self.blocks.len()];
+        for b in self.reverse_post_order() {
+            reachable[b] = true;
+        }
+        if reachable.iter().all(|r| *r) {
+            return Ok(false);
+        }
+        // A reachable block never falls through into an unreachable one, so removing blocks does not change any fall-through edge
+        let mut idx = 0;
+        self.blocks.retain(|_| {
+            idx += 1;
+            reachable[idx - 1]
+        });
+        self.recompute_edges()?;
+        Ok(true)
+    }
+
+    /// Returns a label starting with ```base``` which is not yet used by any block of this function
+    #[must_use]
+    pub fn fresh_label(&self, base: &str) -> String {
+        if !self.label_map.contains_key(base) {
+            return base.to_string();
+        }
+        let mut i = 0;
+        loop {
+            let label = format!("{base}.{i}");
+            if !self.label_map.contains_key(&label) {
+                return label;
+            }
+            i += 1;
+        }
+    }
+
+    /// Returns the indices of all blocks reachable from the entry block in reverse post-order
+    #[must_use]
+    pub fn reverse_post_order(&self) -> Vec<usize> {
+        reverse_post_order(self.entry(), &self.successors)
+    }
+}
+
+/// Returns the nodes reachable from ```root``` in reverse post-order, where ```succs``` holds the successors of each node
+pub(crate) fn reverse_post_order(root: usize, succs: &[Vec<usize>]) -> Vec<usize> {
+    let mut visited = vec![false; succs.len()];
+    let mut order = Vec::with_capacity(succs.len());
+    // An explicit stack of (node, index of the next successor to visit) to avoid recursion on large functions
+    let mut stack = vec![(root, 0)];
+    visited[root] = true;
+    while let Some((node, next)) = stack.last_mut() {
+        if let Some(&succ) = succs[*node].get(*next) {
+            *next += 1;
+            if !visited[succ] {
+                visited[succ] = true;
+                stack.push((succ, 0));
+            }
+        } else {
+            order.push(*node);
+            stack.pop();
+        }
+    }
+    order.reverse();
+    order
+}
+
+fn find_basic_blocks(instrs: Vec<Code>) -> Vec<BasicBlock> {
+    let mut blocks = Vec::new();
+    let mut curr_block = BasicBlock::default();
+    let mut started = false;
+
+    for code in instrs {
+        match code {
+            Code::Label {
+                label,
+                pos,
+                comments,
+            } => {
+                if started {
+                    blocks.push(std::mem::take(&mut curr_block));
+                }
+                started = true;
+                curr_block.label = Some(label);
+                curr_block.label_pos = pos;
+                curr_block.label_comments = comments;
+            }
+            Code::Instruction(instr) => {
+                started = true;
+                let ends_block = is_terminator(&instr);
+                curr_block.instrs.push(instr);
+                if ends_block {
+                    blocks.push(std::mem::take(&mut curr_block));
+                    started = false;
+                }
+            }
+        }
+    }
+
+    if started || blocks.is_empty() {
+        blocks.push(curr_block);
+    }
+    blocks
+}
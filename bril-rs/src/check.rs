@@ -0,0 +1,765 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{self, Display, Formatter};
+
+use thiserror::Error;
+
+use crate::extension::{Extension, ExtensionError, ExtensionSet};
+use crate::{Code, EffectOps, Function, Instruction, Literal, Position, Program, Type, ValueOps};
+
+/// The ways in which a [Program] can fail to be well formed. The messages follow those of `brilck`
+// Having the #[error(...)] for all variants derives the Display trait as well
+#[derive(Error, Debug, Clone, PartialEq)]
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "I allow the `Error` suffix for enums"
+)]
+pub enum CheckError {
+    /// More than one function has this name
+    #[error("multiply defined function @{0}")]
+    DuplicateFunction(String),
+    /// More than one label in the function has this name
+    #[error("multiply defined label .{0}")]
+    DuplicateLabel(String),
+    /// A variable is assigned a different type than it was assigned before
+    #[error("new type {new} for {var} conflicts with old type {old}")]
+    ConflictingType {
+        /// The variable being assigned
+        var: String,
+        /// The type of this assignment
+        new: Type,
+        /// The type of the first assignment
+        old: Type,
+    },
+    /// The `@main` function must not return a value
+    #[error("@main must have no return type; found {0}")]
+    MainReturnType(Type),
+    /// A constant of a type like `ptr<int>` which has no literals
+    #[error("const of non-primitive type {0}")]
+    NonPrimitiveConst(Type),
+    /// The literal of a constant does not have its declared type
+    #[error("const value {value} does not match type {const_type}")]
+    BadConstValue {
+        /// The literal
+        value: Literal,
+        /// The declared type of the constant
+        const_type: Type,
+    },
+    /// The result type of an operation is not the type the operation produces
+    #[error("result type of {name} should be {expected}, but found {found}")]
+    BadResultType {
+        /// The operation, or the callee of a `call`
+        name: String,
+        /// The type the operation produces, where `T` may be any type
+        expected: String,
+        /// The declared result type
+        found: Type,
+    },
+    /// An operation which does not produce a value has a destination
+    #[error("{0} should have no result type")]
+    UnexpectedResult(String),
+    /// An operation which produces a value, like a `call` of a function with a return type, has no destination
+    #[error("missing result type {expected} for {name}")]
+    MissingResult {
+        /// The operation, or the callee of a `call`
+        name: String,
+        /// The type the operation produces, where `T` may be any type
+        expected: String,
+    },
+    /// An operation has the wrong number of arguments
+    #[error("{name} expects {expected} args, not {found}")]
+    BadNumArgs {
+        /// The operation, or the callee of a `call`
+        name: String,
+        /// The number of arguments the operation takes
+        expected: usize,
+        /// The number of arguments provided
+        found: usize,
+    },
+    /// An argument is not defined anywhere in the function
+    #[error("{var} (arg {index}) undefined")]
+    UndefinedVariable {
+        /// The argument
+        var: String,
+        /// The position of the argument among the arguments of the instruction
+        index: usize,
+    },
+    /// An argument does not have the type the operation expects
+    #[error("{var} has type {found}, but arg {index} for {name} should have type {expected}")]
+    BadArgType {
+        /// The argument
+        var: String,
+        /// The type of the argument
+        found: Type,
+        /// The position of the argument among the arguments of the instruction
+        index: usize,
+        /// The operation, or the callee of a `call`
+        name: String,
+        /// The type the operation expects, where `T` may be any type
+        expected: String,
+    },
+    /// An operation has the wrong number of labels
+    #[error("{op} needs {expected} labels; found {found}")]
+    BadNumLabels {
+        /// The operation
+        op: String,
+        /// The number of labels the operation takes
+        expected: usize,
+        /// The number of labels provided
+        found: usize,
+    },
+    /// An operation other than `call` names functions
+    #[error("{op} needs {expected} functions; found {found}")]
+    WrongFuncCount {
+        /// The operation
+        op: String,
+        /// The number of functions the operation takes
+        expected: usize,
+        /// The number of functions provided
+        found: usize,
+    },
+    /// A label is not declared in the function
+    #[error("label .{0} undefined")]
+    UndefinedLabel(String),
+    /// A `call` does not name exactly one function
+    #[error("call should have one function, not {0}")]
+    BadNumFuncs(usize),
+    /// A `call` names a function which is neither defined nor imported
+    #[error("function @{0} undefined")]
+    UndefinedFunction(String),
+    /// A `ret` without a value in a function with a return type
+    #[error("missing return value in function with return type")]
+    MissingReturnValue,
+    /// A `ret` with more than one argument
+    #[error("cannot return multiple values")]
+    MultipleReturnValues,
+    /// A `ret` with a value in a function without a return type
+    #[error("returning value in function without a return type")]
+    UnexpectedReturnValue,
+    /// The first argument of a `set` is a variable which no `get` defines
+    #[error("set of {0}, which is not defined by a get")]
+    SetWithoutGet(String),
+    /// A `get` of a variable which no `set` in the function writes
+    #[error("get of {0}, which is never set")]
+    GetWithoutSet(String),
+    /// A construct needs an extension which is not enabled
+    #[error("{construct} requires the `{extension}` extension")]
+    MissingExtension {
+        /// The extension which is needed
+        extension: Extension,
+        /// A description of the offending instruction, argument, return type, or import
+        construct: String,
+    },
+}
+
+/// A [`CheckError`] along with where it was found
+#[derive(Error, Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    /// The problem that was found
+    pub error: CheckError,
+    /// The name of the function the problem was found in, or [None] for a problem with an import
+    pub func: Option<String>,
+    /// The source position of the offending instruction, label, or function if it was provided
+    pub pos: Option<Position>,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.pos {
+            Some(Position { pos, src, .. }) => {
+                if let Some(src) = src {
+                    write!(f, "{src}:")?;
+                }
+                write!(f, "{}:{}: {}", pos.row, pos.col, self.error)
+            }
+            None => match &self.func {
+                Some(func) => write!(f, "@{func}: {}", self.error),
+                None => write!(f, "{}", self.error),
+            },
+        }
+    }
+}
+
+/// Checks that ```prog``` is well formed as described in <https://capra.cs.cornell.edu/bril/lang/wellformed.html>
+///
+/// Every construct must be from one of the ```extensions```, every variable must have a single type, every argument must be defined and have the type its operation expects, every label and function that is referred to must exist, and `set` and `get` must come in pairs.
+/// Unlike stopping at the first error, every problem that is found is returned: first the constructs which need other extensions, then the rest in the order of the functions and instructions of ```prog```. An empty result means that ```prog``` is well formed.
+///
+/// Calls to imported functions are not checked since their signatures are only known after linking.
+#[must_use]
+pub fn check_program(prog: &Program, extensions: ExtensionSet) -> Vec<Diagnostic> {
+    let mut funcs = HashMap::new();
+    for func in &prog.functions {
+        funcs.entry(func.name.as_str()).or_insert(func);
+    }
+
+    let imported = prog
+        .imports
+        .iter()
+        .flat_map(|i| &i.functions)
+        .map(|f| f.alias.as_ref().unwrap_or(&f.name).as_str())
+        .collect();
+
+    let mut diagnostics: Vec<Diagnostic> = extensions
+        .check_program(prog)
+        .into_iter()
+        .map(|e| match e {
+            ExtensionError::Missing {
+                extension,
+                construct,
+                func,
+                pos,
+            } => Diagnostic {
+                error: CheckError::MissingExtension {
+                    extension,
+                    construct,
+                },
+                func,
+                pos: pos.map(|p| *p),
+            },
+            ExtensionError::Unknown(_) => {
+                unreachable!("only parsing names finds unknown extensions")
+            }
+        })
+        .collect();
+    let mut seen = HashSet::new();
+    for func in &prog.functions {
+        let mut checker = FunctionChecker::new(func, &funcs, &imported);
+        if !seen.insert(func.name.as_str()) {
+            checker.report(CheckError::DuplicateFunction(func.name.clone()));
+        }
+        checker.check();
+        diagnostics.append(&mut checker.diagnostics);
+    }
+    diagnostics
+}
+
+/// The type an argument or result of an operation must have
+#[derive(Debug, Clone)]
+enum SigType {
+    /// Exactly this type
+    Exact(Type),
+    /// Any type, as long as every other occurrence of `T` in the signature has the same type
+    Var,
+    /// A pointer to `T`
+    PtrToVar,
+}
+
+impl SigType {
+    /// Whether a value of type ```t``` can be used where ```self``` is expected, binding `T` to ```t``` if it is not bound yet
+    fn accepts(&self, t: &Type, var: &mut Option<Type>) -> bool {
+        match self {
+            Self::Exact(expected) => compatible(expected, t),
+            Self::Var => {
+                if let Some(bound) = var {
+                    compatible(bound, t)
+                } else {
+                    *var = Some(t.clone());
+                    true
+                }
+            }
+            Self::PtrToVar => match t {
+                Type::Pointer(inner) => Self::Var.accepts(inner, var),
+                Type::Any => true,
+                _ => false,
+            },
+        }
+    }
+
+    /// Formats the type for a message, with `T` replaced by its binding if it has one
+    fn describe(&self, var: Option<&Type>) -> String {
+        match (self, var) {
+            (Self::Exact(t), _) | (Self::Var, Some(t)) => t.to_string(),
+            (Self::Var, None) => "T".to_string(),
+            (Self::PtrToVar, Some(t)) => format!("ptr<{t}>"),
+            (Self::PtrToVar, None) => "ptr<T>".to_string(),
+        }
+    }
+}
+
+/// Whether a value of type ```b``` can be used where ```a``` is expected. With dynamic types, `any` is compatible with every type
+fn compatible(a: &Type, b: &Type) -> bool {
+    if matches!(a, Type::Any) || matches!(b, Type::Any) {
+        return true;
+    }
+    a == b
+}
+
+/// The types of the arguments and result of an operation and how many labels it takes
+struct Signature {
+    args: Vec<SigType>,
+    dest: Option<SigType>,
+    labels: usize,
+}
+
+fn value_signature(op: ValueOps) -> Signature {
+    use SigType::{Exact, Var};
+    let (args, dest) = match op {
+        ValueOps::Add | ValueOps::Sub | ValueOps::Mul | ValueOps::Div => {
+            (vec![Exact(Type::Int), Exact(Type::Int)], Exact(Type::Int))
+        }
+        ValueOps::Eq | ValueOps::Lt | ValueOps::Gt | ValueOps::Le | ValueOps::Ge => {
+            (vec![Exact(Type::Int), Exact(Type::Int)], Exact(Type::Bool))
+        }
+        ValueOps::Not => (vec![Exact(Type::Bool)], Exact(Type::Bool)),
+        ValueOps::And | ValueOps::Or => (
+            vec![Exact(Type::Bool), Exact(Type::Bool)],
+            Exact(Type::Bool),
+        ),
+        ValueOps::Id => (vec![Var], Var),
+        ValueOps::Call => unreachable!("calls are checked against the signature of their callee"),
+        ValueOps::Get | ValueOps::Undef => (Vec::new(), Var),
+        ValueOps::Fadd | ValueOps::Fsub | ValueOps::Fmul | ValueOps::Fdiv => (
+            vec![Exact(Type::Float), Exact(Type::Float)],
+            Exact(Type::Float),
+        ),
+        ValueOps::Feq | ValueOps::Flt | ValueOps::Fgt | ValueOps::Fle | ValueOps::Fge => (
+            vec![Exact(Type::Float), Exact(Type::Float)],
+            Exact(Type::Bool),
+        ),
+        ValueOps::Ceq | ValueOps::Clt | ValueOps::Cgt | ValueOps::Cle | ValueOps::Cge => (
+            vec![Exact(Type::Char), Exact(Type::Char)],
+            Exact(Type::Bool),
+        ),
+        ValueOps::Char2int => (vec![Exact(Type::Char)], Exact(Type::Int)),
+        ValueOps::Int2char => (vec![Exact(Type::Int)], Exact(Type::Char)),
+        ValueOps::Alloc => (vec![Exact(Type::Int)], SigType::PtrToVar),
+        ValueOps::Load => (vec![SigType::PtrToVar], Var),
+        ValueOps::PtrAdd => (vec![SigType::PtrToVar, Exact(Type::Int)], SigType::PtrToVar),
+        ValueOps::Float2Bits => (vec![Exact(Type::Float)], Exact(Type::Int)),
+        ValueOps::Bits2Float => (vec![Exact(Type::Int)], Exact(Type::Float)),
+    };
+    Signature {
+        args,
+        dest: Some(dest),
+        labels: 0,
+    }
+}
+
+fn effect_signature(op: EffectOps) -> Signature {
+    use SigType::Var;
+    let (args, labels) = match op {
+        EffectOps::Jump => (Vec::new(), 1),
+        EffectOps::Branch => (vec![SigType::Exact(Type::Bool)], 2),
+        EffectOps::Nop | EffectOps::Speculate | EffectOps::Commit => (Vec::new(), 0),
+        EffectOps::Call | EffectOps::Return | EffectOps::Print => {
+            unreachable!("`{op}` does not have a fixed signature")
+        }
+        EffectOps::Store => (vec![SigType::PtrToVar, Var], 0),
+        EffectOps::Free => (vec![SigType::PtrToVar], 0),
+        EffectOps::Guard => (vec![SigType::Exact(Type::Bool)], 1),
+        EffectOps::Set => (vec![Var, Var], 0),
+    };
+    Signature {
+        args,
+        dest: None,
+        labels,
+    }
+}
+
+/// Whether ```value``` is a literal of ```const_type```. Integer literals are also accepted as floats
+const fn literal_matches(value: &Literal, const_type: &Type) -> bool {
+    matches!(
+        (value, const_type),
+        (Literal::Int(_), Type::Int)
+            | (Literal::Bool(_), Type::Bool)
+            | (Literal::Int(_) | Literal::Float(_), Type::Float)
+            | (Literal::Char(_), Type::Char)
+    )
+}
+
+fn code_pos(code: &Code) -> Option<Position> {
+    match code {
+        Code::Label { pos, .. } => pos.clone(),
+        Code::Instruction(instr) => instr.get_pos(),
+    }
+}
+
+/// Checks the instructions of a single function
+struct FunctionChecker<'a> {
+    func: &'a Function,
+    funcs: &'a HashMap<&'a str, &'a Function>,
+    imported: &'a HashSet<&'a str>,
+    /// The type of every variable defined in the function, including its arguments
+    vars: HashMap<&'a str, &'a Type>,
+    labels: HashSet<&'a str>,
+    /// The variables defined by a `get`
+    gets: HashSet<&'a str>,
+    /// The variables written to by a `set`
+    sets: HashSet<&'a str>,
+    /// The position of the instruction currently being checked
+    pos: Option<Position>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> FunctionChecker<'a> {
+    fn new(
+        func: &'a Function,
+        funcs: &'a HashMap<&'a str, &'a Function>,
+        imported: &'a HashSet<&'a str>,
+    ) -> Self {
+        Self {
+            func,
+            funcs,
+            imported,
+            vars: HashMap::new(),
+            labels: HashSet::new(),
+            gets: HashSet::new(),
+            sets: HashSet::new(),
+            pos: func.pos.clone(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn report(&mut self, error: CheckError) {
+        self.diagnostics.push(Diagnostic {
+            error,
+            func: Some(self.func.name.clone()),
+            pos: self.pos.clone(),
+        });
+    }
+
+    fn check(&mut self) {
+        for arg in &self.func.args {
+            self.add_type(&arg.name, &arg.arg_type);
+        }
+
+        // Gather the types of all variables and the names of all labels before checking any instruction since neither has to be declared before it is used
+        for code in &self.func.instrs {
+            self.pos = code_pos(code);
+            match code {
+                Code::Label { label, .. } => {
+                    if !self.labels.insert(label) {
+                        self.report(CheckError::DuplicateLabel(label.clone()));
+                    }
+                }
+                Code::Instruction(
+                    Instruction::Constant {
+                        dest,
+                        const_type: t,
+                        ..
+                    }
+                    | Instruction::Value {
+                        dest, op_type: t, ..
+                    },
+                ) => self.add_type(dest, t),
+                Code::Instruction(Instruction::Effect { .. }) => {}
+            }
+            if let Code::Instruction(instr) = code {
+                self.gather_shadow(instr);
+            }
+        }
+
+        for code in &self.func.instrs {
+            if let Code::Instruction(instr) = code {
+                self.pos = code_pos(code);
+                self.check_instruction(instr);
+                self.check_shadow(instr);
+            }
+        }
+
+        if self.func.name == "main"
+            && let Some(t) = &self.func.return_type
+        {
+            self.pos.clone_from(&self.func.pos);
+            self.report(CheckError::MainReturnType(t.clone()));
+        }
+    }
+
+    /// Records that ```var``` has type ```t```, reporting a conflict if it already has a different type
+    fn add_type(&mut self, var: &'a str, t: &'a Type) {
+        match self.vars.get(var) {
+            Some(old) if *old != t => {
+                let old = (*old).clone();
+                self.report(CheckError::ConflictingType {
+                    var: var.to_string(),
+                    new: t.clone(),
+                    old,
+                });
+            }
+            Some(_) => {}
+            None => {
+                self.vars.insert(var, t);
+            }
+        }
+    }
+
+    fn check_instruction(&mut self, instr: &Instruction) {
+        match instr {
+            Instruction::Constant {
+                const_type, value, ..
+            } => self.check_constant(const_type, value),
+            Instruction::Value {
+                op: ValueOps::Call,
+                args,
+                funcs,
+                labels,
+                op_type,
+                ..
+            } => self.check_call(funcs, args, labels, Some(op_type)),
+            Instruction::Value {
+                op,
+                args,
+                labels,
+                op_type,
+                ..
+            } => {
+                let name = op.to_string();
+                self.check_signature(
+                    &value_signature(*op),
+                    &name,
+                    &name,
+                    Some(op_type),
+                    args,
+                    labels,
+                );
+            }
+            Instruction::Effect {
+                op: EffectOps::Call,
+                args,
+                funcs,
+                labels,
+                ..
+            } => self.check_call(funcs, args, labels, None),
+            Instruction::Effect {
+                op: EffectOps::Return,
+                args,
+                labels,
+                ..
+            } => self.check_return(args, labels),
+            Instruction::Effect {
+                op: EffectOps::Print,
+                args,
+                labels,
+                ..
+            } => {
+                for (index, var) in args.iter().enumerate() {
+                    if !self.vars.contains_key(var.as_str()) {
+                        self.report(CheckError::UndefinedVariable {
+                            var: var.clone(),
+                            index,
+                        });
+                    }
+                }
+                self.check_labels("print", 0, labels);
+            }
+            Instruction::Effect {
+                op, args, labels, ..
+            } => {
+                let name = op.to_string();
+                self.check_signature(&effect_signature(*op), &name, &name, None, args, labels);
+            }
+        }
+
+        // Only a `call` names a function, which `check_call` checks
+        match instr {
+            Instruction::Value { op, funcs, .. } if *op != ValueOps::Call && !funcs.is_empty() => {
+                self.report(CheckError::WrongFuncCount {
+                    op: op.to_string(),
+                    expected: 0,
+                    found: funcs.len(),
+                });
+            }
+            Instruction::Effect { op, funcs, .. }
+                if *op != EffectOps::Call && !funcs.is_empty() =>
+            {
+                self.report(CheckError::WrongFuncCount {
+                    op: op.to_string(),
+                    expected: 0,
+                    found: funcs.len(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    /// Records the variables defined by `get` and written to by `set`
+    fn gather_shadow(&mut self, instr: &'a Instruction) {
+        match instr {
+            Instruction::Value {
+                op: ValueOps::Get,
+                dest,
+                ..
+            } => {
+                self.gets.insert(dest);
+            }
+            Instruction::Effect {
+                op: EffectOps::Set,
+                args,
+                ..
+            } => {
+                if let Some(target) = args.first() {
+                    self.sets.insert(target);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Checks that every `set` writes to a variable defined by a `get` and that every `get` reads a variable which is written to by some `set`
+    fn check_shadow(&mut self, instr: &Instruction) {
+        match instr {
+            Instruction::Value {
+                op: ValueOps::Get,
+                dest,
+                ..
+            } if !self.sets.contains(dest.as_str()) => {
+                self.report(CheckError::GetWithoutSet(dest.clone()));
+            }
+            Instruction::Effect {
+                op: EffectOps::Set,
+                args,
+                ..
+            } => {
+                // An undefined target is already reported as an undefined argument
+                if let [target, _] = args.as_slice()
+                    && self.vars.contains_key(target.as_str())
+                    && !self.gets.contains(target.as_str())
+                {
+                    self.report(CheckError::SetWithoutGet(target.clone()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn check_constant(&mut self, const_type: &Type, value: &Literal) {
+        match const_type {
+            Type::Pointer(_) => {
+                self.report(CheckError::NonPrimitiveConst(const_type.clone()));
+            }
+            // Any literal can be used with dynamic types
+            Type::Any => {}
+            _ => {
+                if !literal_matches(value, const_type) {
+                    self.report(CheckError::BadConstValue {
+                        value: value.clone(),
+                        const_type: const_type.clone(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn check_call(
+        &mut self,
+        funcs: &[String],
+        args: &[String],
+        labels: &[String],
+        dest: Option<&Type>,
+    ) {
+        let [callee] = funcs else {
+            self.report(CheckError::BadNumFuncs(funcs.len()));
+            return;
+        };
+        let Some(func) = self.funcs.get(callee.as_str()) else {
+            if !self.imported.contains(callee.as_str()) {
+                self.report(CheckError::UndefinedFunction(callee.clone()));
+            }
+            return;
+        };
+        let sig = Signature {
+            args: func
+                .args
+                .iter()
+                .map(|a| SigType::Exact(a.arg_type.clone()))
+                .collect(),
+            dest: func.return_type.clone().map(SigType::Exact),
+            labels: 0,
+        };
+        self.check_signature(&sig, &format!("@{callee}"), "call", dest, args, labels);
+    }
+
+    fn check_return(&mut self, args: &[String], labels: &[String]) {
+        match (&self.func.return_type, args.len()) {
+            (Some(_), 0) => self.report(CheckError::MissingReturnValue),
+            (Some(t), 1) => {
+                let sig = Signature {
+                    args: vec![SigType::Exact(t.clone())],
+                    dest: None,
+                    labels: 0,
+                };
+                self.check_signature(&sig, "ret", "ret", None, args, labels);
+            }
+            (Some(_), _) => self.report(CheckError::MultipleReturnValues),
+            (None, 0) => self.check_labels("ret", 0, labels),
+            (None, _) => self.report(CheckError::UnexpectedReturnValue),
+        }
+    }
+
+    /// Checks the result, arguments, and labels of an instruction against ```sig```.
+    /// ```name``` is used for the operation in messages, except for those about labels which use ```op```
+    fn check_signature(
+        &mut self,
+        sig: &Signature,
+        name: &str,
+        op: &str,
+        dest: Option<&Type>,
+        args: &[String],
+        labels: &[String],
+    ) {
+        let mut var = None;
+        match (dest, &sig.dest) {
+            (Some(found), Some(expected)) => {
+                if !expected.accepts(found, &mut var) {
+                    self.report(CheckError::BadResultType {
+                        name: name.to_string(),
+                        expected: expected.describe(var.as_ref()),
+                        found: found.clone(),
+                    });
+                }
+            }
+            (Some(_), None) => self.report(CheckError::UnexpectedResult(name.to_string())),
+            (None, Some(expected)) => self.report(CheckError::MissingResult {
+                name: name.to_string(),
+                expected: expected.describe(var.as_ref()),
+            }),
+            (None, None) => {}
+        }
+
+        if args.len() == sig.args.len() {
+            for (index, (arg, expected)) in args.iter().zip(&sig.args).enumerate() {
+                let Some(found) = self.vars.get(arg.as_str()) else {
+                    self.report(CheckError::UndefinedVariable {
+                        var: arg.clone(),
+                        index,
+                    });
+                    continue;
+                };
+                if !expected.accepts(found, &mut var) {
+                    let found = (*found).clone();
+                    self.report(CheckError::BadArgType {
+                        var: arg.clone(),
+                        found,
+                        index,
+                        name: name.to_string(),
+                        expected: expected.describe(var.as_ref()),
+                    });
+                }
+            }
+        } else {
+            self.report(CheckError::BadNumArgs {
+                name: name.to_string(),
+                expected: sig.args.len(),
+                found: args.len(),
+            });
+        }
+
+        self.check_labels(op, sig.labels, labels);
+    }
+
+    fn check_labels(&mut self, op: &str, expected: usize, labels: &[String]) {
+        if labels.len() != expected {
+            self.report(CheckError::BadNumLabels {
+                op: op.to_string(),
+                expected,
+                found: labels.len(),
+            });
+            return;
+        }
+        for label in labels {
+            if !self.labels.contains(label.as_str()) {
+                self.report(CheckError::UndefinedLabel(label.clone()));
+            }
+        }
+    }
+}
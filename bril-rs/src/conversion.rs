@@ -1,4 +1,4 @@
-use std::fmt::Display;
+use std::fmt::{self, Display, Formatter, Write};
 
 use crate::{
     AbstractArgument, AbstractCode, AbstractFunction, AbstractInstruction, AbstractProgram,
@@ -7,43 +7,63 @@ use crate::{
 
 use thiserror::Error;
 
-#[cfg(not(feature = "position"))]
-#[expect(
-    non_upper_case_globals,
-    reason = "This is a nifty trick to supply a global value for pos when it is not defined"
-)]
-const pos: Option<Position> = None;
-
-/// This is the [`std::error::Error`] implementation for `bril_rs`. This crate currently only supports errors from converting between [`AbstractProgram`] and [Program]
-// todo Should this also wrap Serde errors? In this case, maybe change the name from ConversionError
+/// The errors from converting between [`AbstractProgram`] and [Program]. Errors from reading and writing JSON are wrapped together with these in [`crate::BrilError`]
+///
+/// When the offending name is close to a valid one, like `addd` or `flaot`, the error carries that name as a suggestion.
 // Having the #[error(...)] for all variants derives the Display trait as well
-#[derive(Error, Debug)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
 #[expect(
     clippy::module_name_repetitions,
     reason = "I allow the `Error` suffix for enums"
 )]
 pub enum ConversionError {
-    /// Expected a primitive type like int or bool, found {0}"
-    #[error("Expected a primitive type like int or bool, found {0}")]
-    InvalidPrimitive(String),
+    /// Expected a primitive type like int or bool
+    #[error("Expected a primitive type like int or bool, found `{found}`{}", did_you_mean(.suggestion.as_deref()))]
+    InvalidPrimitive {
+        /// The name that was found
+        found: String,
+        /// The closest primitive type, if any is close enough
+        suggestion: Option<String>,
+    },
 
-    /// Expected a parameterized type like ptr, found {0}<{1}>
-    #[error("Expected a parameterized type like ptr, found {0}<{1}>")]
-    InvalidParameterized(String, String),
+    /// Expected a parameterized type like ptr
+    #[error("Expected a parameterized type like ptr, found `{found}<{inner}>`{}", did_you_mean(.suggestion.as_deref()))]
+    InvalidParameterized {
+        /// The name of the parameterized type that was found
+        found: String,
+        /// The type parameter that was found
+        inner: String,
+        /// The closest parameterized type, if any is close enough
+        suggestion: Option<String>,
+    },
 
-    /// Expected an value operation, found {0}
-    #[error("Expected an value operation, found {0}")]
-    InvalidValueOps(String),
+    /// Expected a value operation
+    #[error("Expected a value operation, found `{found}`{}", did_you_mean(.suggestion.as_deref()))]
+    InvalidValueOps {
+        /// The op that was found
+        found: String,
+        /// The closest value operation, if any is close enough
+        suggestion: Option<String>,
+    },
 
-    /// Expected an effect operation, found {0}
-    #[error("Expected an effect operation, found {0}")]
-    InvalidEffectOps(String),
+    /// Expected an effect operation
+    #[error("Expected an effect operation, found `{found}`{}", did_you_mean(.suggestion.as_deref()))]
+    InvalidEffectOps {
+        /// The op that was found
+        found: String,
+        /// The closest effect operation, if any is close enough
+        suggestion: Option<String>,
+    },
 
     /// Missing type signature
     #[error("Missing type signature")]
     MissingType,
 }
 
+fn did_you_mean(suggestion: Option<&str>) -> String {
+    suggestion.map_or_else(String::new, |s| format!(", did you mean `{s}`?"))
+}
+
 impl ConversionError {
     #[doc(hidden)]
     #[must_use]
@@ -51,95 +71,310 @@ impl ConversionError {
         PositionalConversionError {
             e: self,
             pos: pos_var,
+            func: None,
+            index: None,
+            field: None,
+        }
+    }
+
+    /// The valid name closest to the offending one, if the error is about a misspelled op or type
+    #[must_use]
+    pub fn suggestion(&self) -> Option<&str> {
+        match self {
+            Self::InvalidPrimitive { suggestion, .. }
+            | Self::InvalidParameterized { suggestion, .. }
+            | Self::InvalidValueOps { suggestion, .. }
+            | Self::InvalidEffectOps { suggestion, .. } => suggestion.as_deref(),
+            Self::MissingType => None,
         }
     }
 }
 
-/// Wraps [`ConversionError`] to optionally provide source code positions if they are available.
-#[derive(Error, Debug)]
+/// Returns the candidate closest to ```found```, as long as it is only a few edits away
+pub(crate) fn suggest<I, S>(found: &str, candidates: I) -> Option<String>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let max_distance = (found.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .map(|c| (edit_distance(found, c.as_ref()), c))
+        .filter(|(d, _)| *d <= max_distance)
+        // `min_by_key` keeps the first of equally close candidates
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c.as_ref().to_string())
+}
+
+/// The optimal string alignment distance between ```a``` and ```b```, which counts swapping two adjacent characters as a single edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // Three rows of the dynamic programming table are enough to look back for transpositions
+    let mut before: Vec<usize> = Vec::new();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut curr = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            curr[j] = (prev[j] + 1).min(curr[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                curr[j] = curr[j].min(before[j - 2] + 1);
+            }
+        }
+        before = std::mem::replace(&mut prev, curr);
+    }
+    prev[b.len()]
+}
+
+/// The part of a function or instruction which could not be converted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Field {
+    /// The `op` of an instruction
+    Op,
+    /// The `type` of an instruction
+    Type,
+    /// The type of the function argument with this name
+    ArgumentType(String),
+    /// The return type of a function
+    ReturnType,
+}
+
+impl Display for Field {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Op => write!(f, "op"),
+            Self::Type => write!(f, "type"),
+            Self::ArgumentType(name) => write!(f, "type of argument `{name}`"),
+            Self::ReturnType => write!(f, "return type"),
+        }
+    }
+}
+
+/// Wraps [`ConversionError`] with where it was found: the source position if it is available, the function, the index of the instruction, and the field which was invalid.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub struct PositionalConversionError {
-    #[doc(hidden)]
+    /// The problem that was found
     pub e: ConversionError,
-    #[doc(hidden)]
+    /// The source position of the offending instruction or function if it was provided
     pub pos: Option<Position>,
+    /// The name of the function the problem was found in
+    pub func: Option<String>,
+    /// The index into [`Function::instrs`] of the offending instruction, counting labels. This is [None] for problems in the signature of a function.
+    pub index: Option<usize>,
+    /// The part of the instruction or function signature which could not be converted
+    pub field: Option<Field>,
 }
 
 impl PositionalConversionError {
     #[doc(hidden)]
     #[must_use]
     pub const fn new(e: ConversionError) -> Self {
-        Self { e, pos: None }
+        e.add_pos(None)
+    }
+
+    /// Describes which function, instruction, and field the error was found in, like "the op of instruction 3 of @main"
+    #[must_use]
+    pub fn context(&self) -> Option<String> {
+        let mut context = self
+            .field
+            .as_ref()
+            .map(|field| format!("the {field}"))
+            .unwrap_or_default();
+        if let Some(index) = self.index {
+            if !context.is_empty() {
+                context.push_str(" of ");
+            }
+            write!(context, "instruction {index}").unwrap();
+        }
+        if let Some(func) = &self.func {
+            if !context.is_empty() {
+                context.push_str(" of ");
+            }
+            write!(context, "@{func}").unwrap();
+        }
+        (!context.is_empty()).then_some(context)
+    }
+
+    /// Renders the error in the style of a compiler diagnostic, quoting the line of ```source``` it was found on and underlining the offending code.
+    /// ```source``` should be the Bril text the program was parsed from; the snippet is left out when the error has no position or it is not inside ```source```.
+    #[must_use]
+    pub fn render(&self, source: &str) -> String {
+        let mut out = format!("error: {}\n", self.e);
+        let mut gutter = String::new();
+        if let Some(Position {
+            pos, pos_end, src, ..
+        }) = &self.pos
+        {
+            let row = pos.row.to_string();
+            gutter = " ".repeat(row.len());
+            let src = src.as_ref().map(|s| format!("{s}:")).unwrap_or_default();
+            writeln!(out, "{gutter}--> {src}{row}:{}", pos.col).unwrap();
+
+            let line = usize::try_from(pos.row)
+                .ok()
+                .and_then(|r| r.checked_sub(1))
+                .and_then(|r| source.lines().nth(r));
+            if let Some(line) = line {
+                let start = usize::try_from(pos.col).map_or(0, |c| c.saturating_sub(1));
+                // Only underline up to the end position when the code does not span several lines
+                let end = pos_end
+                    .filter(|end| end.row == pos.row)
+                    .and_then(|end| usize::try_from(end.col).ok())
+                    .map_or(start + 1, |c| c.saturating_sub(1))
+                    .min(line.len())
+                    .max(start + 1);
+                writeln!(out, "{gutter} |").unwrap();
+                writeln!(out, "{row} | {line}").unwrap();
+                writeln!(
+                    out,
+                    "{gutter} | {}{}",
+                    " ".repeat(start),
+                    "^".repeat(end - start)
+                )
+                .unwrap();
+            }
+        }
+        if let Some(context) = self.context() {
+            writeln!(out, "{gutter} = note: in {context}").unwrap();
+        }
+        out
     }
 }
 
 impl Display for PositionalConversionError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            #[cfg(feature = "position")]
-            Self { e, pos: Some(pos) } => {
-                write!(f, "Line {}, Column {}: {e}", pos.pos.row, pos.pos.col)
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(Position { pos, src, .. }) = &self.pos {
+            if let Some(src) = src {
+                write!(f, "{src}:")?;
             }
-            #[cfg(not(feature = "position"))]
-            Self { e: _, pos: Some(_) } => {
-                unreachable!()
+            write!(f, "{}:{}: ", pos.row, pos.col)?;
+        }
+        write!(f, "{}", self.e)?;
+        if let Some(context) = self.context() {
+            write!(f, " (in {context})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Every [`PositionalConversionError`] found while converting an [`AbstractFunction`] or [`AbstractProgram`], in the order of the functions and instructions.
+/// This is never empty.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub struct ConversionErrors(pub Vec<PositionalConversionError>);
+
+impl ConversionErrors {
+    /// Renders each error with [`PositionalConversionError::render`], separated by blank lines
+    #[must_use]
+    pub fn render(&self, source: &str) -> String {
+        self.0
+            .iter()
+            .map(|e| e.render(source))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl Display for ConversionErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, e) in self.0.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
             }
-            Self { e, pos: None } => write!(f, "{e}"),
+            write!(f, "{e}")?;
         }
+        Ok(())
+    }
+}
+
+impl From<PositionalConversionError> for ConversionErrors {
+    fn from(e: PositionalConversionError) -> Self {
+        Self(vec![e])
     }
 }
 
 impl TryFrom<AbstractProgram> for Program {
-    type Error = PositionalConversionError;
+    type Error = ConversionErrors;
     fn try_from(
-        AbstractProgram {
-            #[cfg(feature = "import")]
-            imports,
-            functions,
-        }: AbstractProgram,
+        AbstractProgram { imports, functions }: AbstractProgram,
     ) -> Result<Self, Self::Error> {
-        Ok(Self {
-            #[cfg(feature = "import")]
-            imports,
-            functions: functions
-                .into_iter()
-                .map(std::convert::TryInto::try_into)
-                .collect::<Result<Vec<Function>, _>>()?,
-        })
+        let mut errors = Vec::new();
+        let functions = functions
+            .into_iter()
+            .filter_map(|f| {
+                Function::try_from(f)
+                    .map_err(|ConversionErrors(e)| errors.extend(e))
+                    .ok()
+            })
+            .collect();
+        if errors.is_empty() {
+            Ok(Self { functions, imports })
+        } else {
+            Err(ConversionErrors(errors))
+        }
     }
 }
 
 impl TryFrom<AbstractFunction> for Function {
-    type Error = PositionalConversionError;
+    type Error = ConversionErrors;
     fn try_from(
         AbstractFunction {
             args,
             instrs,
             name,
             return_type,
-            #[cfg(feature = "position")]
             pos,
+            comments,
         }: AbstractFunction,
     ) -> Result<Self, Self::Error> {
+        let mut errors = Vec::new();
+
+        let mut converted_args = Vec::with_capacity(args.len());
+        for AbstractArgument { name, arg_type } in args {
+            match arg_type.try_into() {
+                Ok(arg_type) => converted_args.push(Argument { name, arg_type }),
+                Err(e) => errors.push(PositionalConversionError {
+                    field: Some(Field::ArgumentType(name)),
+                    ..e.add_pos(pos.clone())
+                }),
+            }
+        }
+
+        let return_type = return_type.and_then(|t| {
+            t.try_into()
+                .map_err(|e: ConversionError| {
+                    errors.push(PositionalConversionError {
+                        field: Some(Field::ReturnType),
+                        ..e.add_pos(pos.clone())
+                    });
+                })
+                .ok()
+        });
+
+        let mut converted_instrs = Vec::with_capacity(instrs.len());
+        for (index, code) in instrs.into_iter().enumerate() {
+            let found = errors.len();
+            if let Some(code) = convert_code(code, &mut errors) {
+                converted_instrs.push(code);
+            }
+            for e in &mut errors[found..] {
+                e.index = Some(index);
+            }
+        }
+
+        if !errors.is_empty() {
+            for e in &mut errors {
+                e.func = Some(name.clone());
+            }
+            return Err(ConversionErrors(errors));
+        }
         Ok(Self {
-            args: args
-                .into_iter()
-                .map(std::convert::TryInto::try_into)
-                .collect::<Result<Vec<Argument>, _>>()
-                .map_err(|e| e.add_pos(pos.clone()))?,
-            instrs: instrs
-                .into_iter()
-                .map(std::convert::TryInto::try_into)
-                .collect::<Result<Vec<Code>, _>>()?,
+            args: converted_args,
+            instrs: converted_instrs,
             name,
-            return_type: match return_type {
-                None => None,
-                Some(t) => Some(
-                    t.try_into()
-                        .map_err(|e: ConversionError| e.add_pos(pos.clone()))?,
-                ),
-            },
-            #[cfg(feature = "position")]
+            return_type,
             pos,
+            comments,
         })
     }
 }
@@ -159,102 +394,257 @@ impl TryFrom<AbstractArgument> for Argument {
 impl TryFrom<AbstractCode> for Code {
     type Error = PositionalConversionError;
     fn try_from(c: AbstractCode) -> Result<Self, Self::Error> {
-        Ok(match c {
-            AbstractCode::Label {
+        let mut errors = Vec::new();
+        convert_code(c, &mut errors).ok_or_else(|| errors.remove(0))
+    }
+}
+
+impl TryFrom<AbstractInstruction> for Instruction {
+    type Error = PositionalConversionError;
+    fn try_from(i: AbstractInstruction) -> Result<Self, Self::Error> {
+        let mut errors = Vec::new();
+        convert_instruction(i, &mut errors).ok_or_else(|| errors.remove(0))
+    }
+}
+
+fn convert_code(c: AbstractCode, errors: &mut Vec<PositionalConversionError>) -> Option<Code> {
+    match c {
+        AbstractCode::Label {
+            label,
+            pos,
+            comments,
+        } => Some(Code::Label {
+            label,
+            pos,
+            comments,
+        }),
+        AbstractCode::Instruction(i) => convert_instruction(i, errors).map(Code::Instruction),
+    }
+}
+
+/// Converts ```i```, pushing a problem with its type and a problem with its op onto ```errors``` when both are invalid
+fn convert_instruction(
+    i: AbstractInstruction,
+    errors: &mut Vec<PositionalConversionError>,
+) -> Option<Instruction> {
+    let mut report = |e: ConversionError, field: Field, pos: &Option<Position>| {
+        errors.push(PositionalConversionError {
+            field: Some(field),
+            ..e.add_pos(pos.clone())
+        });
+    };
+    match i {
+        AbstractInstruction::Constant {
+            dest,
+            op,
+            const_type,
+            value,
+            pos,
+            comments,
+        } => {
+            let const_type = const_type
+                .try_into()
+                .map_err(|e| report(e, Field::Type, &pos))
+                .ok()?;
+            Some(Instruction::Constant {
+                dest,
+                op,
+                const_type,
+                value,
+                pos,
+                comments,
+            })
+        }
+        AbstractInstruction::Value {
+            args,
+            dest,
+            funcs,
+            labels,
+            op,
+            op_type,
+            pos,
+            comments,
+        } => {
+            let op_type = op_type
+                .try_into()
+                .map_err(|e| report(e, Field::Type, &pos))
+                .ok();
+            let op = op.parse().map_err(|e| report(e, Field::Op, &pos)).ok();
+            Some(Instruction::Value {
+                args,
+                dest,
+                funcs,
+                labels,
+                op: op?,
+                op_type: op_type?,
+                pos,
+                comments,
+            })
+        }
+        AbstractInstruction::Effect {
+            args,
+            funcs,
+            labels,
+            op,
+            pos,
+            comments,
+        } => {
+            let op = op.parse().map_err(|e| report(e, Field::Op, &pos)).ok()?;
+            Some(Instruction::Effect {
+                args,
+                funcs,
+                labels,
+                op,
+                pos,
+                comments,
+            })
+        }
+    }
+}
+
+impl TryFrom<Option<AbstractType>> for Type {
+    type Error = ConversionError;
+
+    fn try_from(value: Option<AbstractType>) -> Result<Self, Self::Error> {
+        value.map_or(Err(ConversionError::MissingType), TryInto::try_into)
+    }
+}
+
+impl TryFrom<AbstractType> for Type {
+    type Error = ConversionError;
+    fn try_from(value: AbstractType) -> Result<Self, Self::Error> {
+        match value {
+            AbstractType::Primitive(t) => t.parse(),
+            AbstractType::Parameterized(t, ty) if t == "ptr" => {
+                Ok(Self::Pointer(Box::new((*ty).try_into()?)))
+            }
+            AbstractType::Parameterized(t, ty) => Err(ConversionError::InvalidParameterized {
+                suggestion: suggest(&t, ["ptr"]),
+                found: t,
+                inner: ty.to_string(),
+            }),
+        }
+    }
+}
+
+impl From<Program> for AbstractProgram {
+    fn from(Program { imports, functions }: Program) -> Self {
+        Self {
+            imports,
+            functions: functions.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<Function> for AbstractFunction {
+    fn from(
+        Function {
+            args,
+            instrs,
+            name,
+            return_type,
+            pos,
+            comments,
+        }: Function,
+    ) -> Self {
+        Self {
+            args: args.into_iter().map(Into::into).collect(),
+            instrs: instrs.into_iter().map(Into::into).collect(),
+            name,
+            return_type: return_type.map(Into::into),
+            pos,
+            comments,
+        }
+    }
+}
+
+impl From<Argument> for AbstractArgument {
+    fn from(Argument { name, arg_type }: Argument) -> Self {
+        Self {
+            name,
+            arg_type: arg_type.into(),
+        }
+    }
+}
+
+impl From<Code> for AbstractCode {
+    fn from(c: Code) -> Self {
+        match c {
+            Code::Label {
                 label,
-                #[cfg(feature = "position")]
                 pos,
+                comments,
             } => Self::Label {
                 label,
-                #[cfg(feature = "position")]
                 pos,
+                comments,
             },
-            AbstractCode::Instruction(i) => Self::Instruction(i.try_into()?),
-        })
+            Code::Instruction(i) => Self::Instruction(i.into()),
+        }
     }
 }
 
-impl TryFrom<AbstractInstruction> for Instruction {
-    type Error = PositionalConversionError;
-    fn try_from(i: AbstractInstruction) -> Result<Self, Self::Error> {
-        Ok(match i {
-            AbstractInstruction::Constant {
+impl From<Instruction> for AbstractInstruction {
+    fn from(i: Instruction) -> Self {
+        match i {
+            Instruction::Constant {
                 dest,
                 op,
                 const_type,
                 value,
-                #[cfg(feature = "position")]
                 pos,
+                comments,
             } => Self::Constant {
                 dest,
                 op,
-                const_type: const_type
-                    .try_into()
-                    .map_err(|e: ConversionError| e.add_pos(pos.clone()))?,
+                const_type: Some(const_type.into()),
                 value,
-                #[cfg(feature = "position")]
                 pos,
+                comments,
             },
-            AbstractInstruction::Value {
+            Instruction::Value {
                 args,
                 dest,
                 funcs,
                 labels,
                 op,
                 op_type,
-                #[cfg(feature = "position")]
                 pos,
+                comments,
             } => Self::Value {
                 args,
                 dest,
                 funcs,
                 labels,
-                op_type: op_type
-                    .try_into()
-                    .map_err(|e: ConversionError| e.add_pos(pos.clone()))?,
-                #[cfg(feature = "position")]
-                pos: pos.clone(),
-                op: op.parse().map_err(|e: ConversionError| e.add_pos(pos))?,
+                op: op.to_string(),
+                op_type: Some(op_type.into()),
+                pos,
+                comments,
             },
-            AbstractInstruction::Effect {
+            Instruction::Effect {
                 args,
                 funcs,
                 labels,
                 op,
-                #[cfg(feature = "position")]
                 pos,
+                comments,
             } => Self::Effect {
                 args,
                 funcs,
                 labels,
-                #[cfg(feature = "position")]
-                pos: pos.clone(),
-                op: op.parse().map_err(|e: ConversionError| e.add_pos(pos))?,
+                op: op.to_string(),
+                pos,
+                comments,
             },
-        })
-    }
-}
-
-impl TryFrom<Option<AbstractType>> for Type {
-    type Error = ConversionError;
-
-    fn try_from(value: Option<AbstractType>) -> Result<Self, Self::Error> {
-        value.map_or(Err(ConversionError::MissingType), TryInto::try_into)
+        }
     }
 }
 
-impl TryFrom<AbstractType> for Type {
-    type Error = ConversionError;
-    fn try_from(value: AbstractType) -> Result<Self, Self::Error> {
+impl From<Type> for AbstractType {
+    fn from(value: Type) -> Self {
         match value {
-            AbstractType::Primitive(t) => t.parse(),
-            #[cfg(feature = "memory")]
-            AbstractType::Parameterized(t, ty) if t == "ptr" => {
-                Ok(Self::Pointer(Box::new((*ty).try_into()?)))
-            }
-            AbstractType::Parameterized(t, ty) => {
-                Err(ConversionError::InvalidParameterized(t, ty.to_string()))
-            }
+            Type::Pointer(ty) => Self::Parameterized("ptr".to_string(), Box::new((*ty).into())),
+            t => Self::Primitive(t.to_string()),
         }
     }
 }
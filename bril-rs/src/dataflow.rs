@@ -0,0 +1,464 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet, VecDeque};
+
+use crate::cfg::Cfg;
+use crate::{Instruction, Literal, Type, ValueOps};
+
+/// The direction in which facts flow through the [Cfg]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Facts flow from the entry block along successor edges
+    Forward,
+    /// Facts flow from the exit blocks along predecessor edges
+    Backward,
+}
+
+/// A dataflow analysis is a lattice of facts together with a transfer function over basic blocks
+///
+/// [solve] iterates [`Analysis::transfer`] and [`Analysis::join`] until the facts of every block stop changing, so the lattice must have finite height and the transfer function must be monotone.
+pub trait Analysis {
+    /// An element of the lattice
+    type Fact: Clone + PartialEq;
+
+    /// Whether facts flow forward or backward through the [Cfg]
+    const DIRECTION: Direction;
+
+    /// The fact every block starts with before the analysis has reached it
+    fn init(&self, cfg: &Cfg) -> Self::Fact;
+
+    /// The fact at the boundary of the function. This is the fact flowing into the entry block for a forward analysis and into every block without successors for a backward analysis.
+    fn boundary(&self, cfg: &Cfg) -> Self::Fact;
+
+    /// Combines the facts flowing in from two different edges
+    fn join(&self, a: &Self::Fact, b: &Self::Fact) -> Self::Fact;
+
+    /// Computes the fact flowing out of the block at index ```block``` from the fact flowing into it
+    fn transfer(&self, cfg: &Cfg, block: usize, input: &Self::Fact) -> Self::Fact;
+}
+
+/// The facts computed by [solve] for each block of a [Cfg], indexed by block
+///
+/// [`DataflowResult::ins`] always holds the fact at the start of a block and [`DataflowResult::outs`] the fact at the end of a block, regardless of the [Direction] of the analysis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataflowResult<F> {
+    /// The fact at the start of each block
+    pub ins: Vec<F>,
+    /// The fact at the end of each block
+    pub outs: Vec<F>,
+}
+
+/// Runs ```analysis``` over ```cfg``` with a worklist until it reaches a fixed point
+pub fn solve<A: Analysis>(analysis: &A, cfg: &Cfg) -> DataflowResult<A::Fact> {
+    let num_blocks = cfg.blocks.len();
+    let init = analysis.init(cfg);
+    let boundary = analysis.boundary(cfg);
+    let mut ins = vec![init.clone(); num_blocks];
+    let mut outs = vec![init; num_blocks];
+
+    // Visiting blocks in reverse post-order (or post-order when going backward) reduces the number of iterations needed
+    let mut order = cfg.reverse_post_order();
+    // Unreachable blocks are still analyzed, after all of the reachable ones
+    let mut queued = vec![false; num_blocks];
+    for b in &order {
+        queued[*b] = true;
+    }
+    order.extend((0..num_blocks).filter(|b| !queued[*b]));
+    queued.fill(true);
+
+    let (sources, sinks, inputs, outputs) = match A::DIRECTION {
+        Direction::Forward => (&cfg.predecessors, &cfg.successors, &mut ins, &mut outs),
+        Direction::Backward => {
+            order.reverse();
+            (&cfg.successors, &cfg.predecessors, &mut outs, &mut ins)
+        }
+    };
+
+    let mut worklist: VecDeque<usize> = order.into();
+    while let Some(block) = worklist.pop_front() {
+        queued[block] = false;
+
+        let is_boundary = match A::DIRECTION {
+            Direction::Forward => block == cfg.entry(),
+            Direction::Backward => cfg.successors[block].is_empty(),
+        };
+        let joined = sources[block]
+            .iter()
+            .map(|s| &outputs[*s])
+            .chain(is_boundary.then_some(&boundary))
+            .fold(None, |acc: Option<A::Fact>, fact| {
+                Some(acc.map_or_else(|| fact.clone(), |acc| analysis.join(&acc, fact)))
+            });
+        if let Some(joined) = joined {
+            inputs[block] = joined;
+        }
+
+        let output = analysis.transfer(cfg, block, &inputs[block]);
+        if output != outputs[block] {
+            outputs[block] = output;
+            for s in &sinks[block] {
+                if !queued[*s] {
+                    queued[*s] = true;
+                    worklist.push_back(*s);
+                }
+            }
+        }
+    }
+
+    DataflowResult { ins, outs }
+}
+
+/// Returns ```base```, or ```base``` with a numeric suffix, such that the name is not in ```names```, and records it there
+pub(crate) fn fresh_name(base: &str, names: &mut HashSet<String>) -> String {
+    let mut name = base.to_string();
+    let mut i = 0;
+    while names.contains(&name) {
+        name = format!("{base}.{i}");
+        i += 1;
+    }
+    names.insert(name.clone());
+    name
+}
+
+/// The instruction at index ```index``` of the block at index ```block``` of a [Cfg]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Location {
+    /// The index of the block
+    pub block: usize,
+    /// The index of the instruction in the block
+    pub index: usize,
+}
+
+/// A definition of a variable, either by an instruction or as an argument of the function
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Definition {
+    /// The variable being defined
+    pub var: String,
+    /// Where the definition occurs, [None] for function arguments
+    pub location: Option<Location>,
+}
+
+/// A forward analysis of which [Definition]s may reach each program point
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReachingDefinitions;
+
+impl Analysis for ReachingDefinitions {
+    type Fact = BTreeSet<Definition>;
+
+    const DIRECTION: Direction = Direction::Forward;
+
+    fn init(&self, _cfg: &Cfg) -> Self::Fact {
+        BTreeSet::new()
+    }
+
+    fn boundary(&self, cfg: &Cfg) -> Self::Fact {
+        cfg.args
+            .iter()
+            .map(|a| Definition {
+                var: a.name.clone(),
+                location: None,
+            })
+            .collect()
+    }
+
+    fn join(&self, a: &Self::Fact, b: &Self::Fact) -> Self::Fact {
+        a.union(b).cloned().collect()
+    }
+
+    fn transfer(&self, cfg: &Cfg, block: usize, input: &Self::Fact) -> Self::Fact {
+        let mut fact = input.clone();
+        for (index, instr) in cfg.blocks[block].instrs.iter().enumerate() {
+            if let Some(var) = instr.dest() {
+                fact.retain(|d| &d.var != var);
+                fact.insert(Definition {
+                    var: var.clone(),
+                    location: Some(Location { block, index }),
+                });
+            }
+        }
+        fact
+    }
+}
+
+/// A backward analysis of which variables may be read before they are next written
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LiveVariables;
+
+impl Analysis for LiveVariables {
+    type Fact = BTreeSet<String>;
+
+    const DIRECTION: Direction = Direction::Backward;
+
+    fn init(&self, _cfg: &Cfg) -> Self::Fact {
+        BTreeSet::new()
+    }
+
+    fn boundary(&self, _cfg: &Cfg) -> Self::Fact {
+        BTreeSet::new()
+    }
+
+    fn join(&self, a: &Self::Fact, b: &Self::Fact) -> Self::Fact {
+        a.union(b).cloned().collect()
+    }
+
+    fn transfer(&self, cfg: &Cfg, block: usize, input: &Self::Fact) -> Self::Fact {
+        let mut fact = input.clone();
+        for instr in cfg.blocks[block].instrs.iter().rev() {
+            if let Some(var) = instr.dest() {
+                fact.remove(var);
+            }
+            fact.extend(instr.uses().cloned());
+        }
+        fact
+    }
+}
+
+/// A pure computation whose result only depends on the values of its arguments
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Expression {
+    /// The operation being computed
+    pub op: ValueOps,
+    /// The variables the operation is applied to
+    pub args: Vec<String>,
+}
+
+impl Expression {
+    /// Returns the [Expression] computed by ```instr``` if it is a pure value operation.
+    /// Calls, memory operations, and the `ssa` operations are never considered expressions.
+    #[must_use]
+    pub fn from_instruction(instr: &Instruction) -> Option<Self> {
+        match instr {
+            Instruction::Value {
+                op, args, funcs, ..
+            } if funcs.is_empty() && is_pure(*op) => Some(Self {
+                op: *op,
+                args: args.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Whether the result of ```op``` only depends on the values of its arguments
+#[must_use]
+pub const fn is_pure(op: ValueOps) -> bool {
+    !matches!(
+        op,
+        ValueOps::Call | ValueOps::Get | ValueOps::Undef | ValueOps::Alloc | ValueOps::Load
+    )
+}
+
+/// A forward analysis of which [Expression]s have definitely been computed, with none of their arguments redefined since, at each program point
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AvailableExpressions;
+
+impl Analysis for AvailableExpressions {
+    type Fact = BTreeSet<Expression>;
+
+    const DIRECTION: Direction = Direction::Forward;
+
+    // Start from every expression in the function so that the intersection in `join` can only remove expressions
+    fn init(&self, cfg: &Cfg) -> Self::Fact {
+        cfg.blocks
+            .iter()
+            .flat_map(|b| b.instrs.iter())
+            .filter_map(Expression::from_instruction)
+            .collect()
+    }
+
+    fn boundary(&self, _cfg: &Cfg) -> Self::Fact {
+        BTreeSet::new()
+    }
+
+    fn join(&self, a: &Self::Fact, b: &Self::Fact) -> Self::Fact {
+        a.intersection(b).cloned().collect()
+    }
+
+    fn transfer(&self, cfg: &Cfg, block: usize, input: &Self::Fact) -> Self::Fact {
+        let mut fact = input.clone();
+        for instr in &cfg.blocks[block].instrs {
+            let expr = Expression::from_instruction(instr);
+            if let Some(var) = instr.dest() {
+                fact.retain(|e| !e.args.contains(var));
+                if let Some(expr) = expr.filter(|e| !e.args.contains(var)) {
+                    fact.insert(expr);
+                }
+            }
+        }
+        fact
+    }
+}
+
+/// The abstract value of a variable in [`ConstantPropagation`]. Variables which have not been assigned on any path so far are absent from the fact.
+#[derive(Debug, Clone)]
+pub enum ConstValue {
+    /// The variable always holds this value
+    Constant(Literal),
+    /// The variable may hold different values
+    Varying,
+}
+
+impl PartialEq for ConstValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            // Compare floats by their bits so that `NaN` constants still reach a fixed point
+            (Self::Constant(Literal::Float(a)), Self::Constant(Literal::Float(b))) => {
+                a.to_bits() == b.to_bits()
+            }
+            (Self::Constant(a), Self::Constant(b)) => a == b,
+            (Self::Varying, Self::Varying) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ConstValue {}
+
+/// A forward analysis of which variables hold a known constant value at each program point
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConstantPropagation;
+
+impl Analysis for ConstantPropagation {
+    type Fact = BTreeMap<String, ConstValue>;
+
+    const DIRECTION: Direction = Direction::Forward;
+
+    fn init(&self, _cfg: &Cfg) -> Self::Fact {
+        BTreeMap::new()
+    }
+
+    fn boundary(&self, cfg: &Cfg) -> Self::Fact {
+        cfg.args
+            .iter()
+            .map(|a| (a.name.clone(), ConstValue::Varying))
+            .collect()
+    }
+
+    fn join(&self, a: &Self::Fact, b: &Self::Fact) -> Self::Fact {
+        let mut fact = a.clone();
+        for (var, value) in b {
+            fact.entry(var.clone())
+                .and_modify(|v| {
+                    if v != value {
+                        *v = ConstValue::Varying;
+                    }
+                })
+                .or_insert_with(|| value.clone());
+        }
+        fact
+    }
+
+    fn transfer(&self, cfg: &Cfg, block: usize, input: &Self::Fact) -> Self::Fact {
+        let mut fact = input.clone();
+        for instr in &cfg.blocks[block].instrs {
+            Self::transfer_instruction(&mut fact, instr);
+        }
+        fact
+    }
+}
+
+impl ConstantPropagation {
+    /// Updates ```fact``` to hold after ```instr``` executes, for passes which need the facts between the instructions of a block
+    pub fn transfer_instruction(fact: &mut BTreeMap<String, ConstValue>, instr: &Instruction) {
+        match instr {
+            Instruction::Constant {
+                dest,
+                const_type,
+                value,
+                ..
+            } => {
+                fact.insert(
+                    dest.clone(),
+                    ConstValue::Constant(normalize_literal(value, const_type)),
+                );
+            }
+            Instruction::Value { dest, args, op, .. } => {
+                let value = if args
+                    .iter()
+                    .any(|a| matches!(fact.get(a), Some(ConstValue::Varying)))
+                {
+                    Some(ConstValue::Varying)
+                } else {
+                    // If an argument has not been assigned on any path reaching here yet, neither has `dest`
+                    args.iter()
+                        .map(|a| match fact.get(a) {
+                            Some(ConstValue::Constant(l)) => Some(l.clone()),
+                            _ => None,
+                        })
+                        .collect::<Option<Vec<_>>>()
+                        .map(|literals| {
+                            fold_value_op(*op, &literals)
+                                .map_or(ConstValue::Varying, ConstValue::Constant)
+                        })
+                };
+                match value {
+                    Some(value) => fact.insert(dest.clone(), value),
+                    None => fact.remove(dest),
+                };
+            }
+            Instruction::Effect { .. } => {}
+        }
+    }
+}
+
+/// Gives a [Literal] the representation that matches its declared type.
+/// Bril allows float constants to be written as integers, like `x: float = const 1;`
+#[must_use]
+pub fn normalize_literal(value: &Literal, const_type: &Type) -> Literal {
+    match (value, const_type) {
+        #[expect(
+            clippy::cast_precision_loss,
+            reason = "This matches how interpreters read integer float constants"
+        )]
+        (Literal::Int(i), Type::Float) => Literal::Float(*i as f64),
+        _ => value.clone(),
+    }
+}
+
+/// Evaluates ```op``` on constant arguments with the semantics of the reference interpreter.
+///
+/// Returns [None] if ```op``` does not have a statically known result, like `call` or `load`, if the arguments do not have the expected types, or if evaluating it would be an error like dividing by zero.
+#[must_use]
+pub fn fold_value_op(op: ValueOps, args: &[Literal]) -> Option<Literal> {
+    Some(match (op, args) {
+        (ValueOps::Id, [l]) => l.clone(),
+        (ValueOps::Add, [Literal::Int(a), Literal::Int(b)]) => Literal::Int(a.wrapping_add(*b)),
+        (ValueOps::Sub, [Literal::Int(a), Literal::Int(b)]) => Literal::Int(a.wrapping_sub(*b)),
+        (ValueOps::Mul, [Literal::Int(a), Literal::Int(b)]) => Literal::Int(a.wrapping_mul(*b)),
+        (ValueOps::Div, [Literal::Int(a), Literal::Int(b)]) if *b != 0 => {
+            Literal::Int(a.wrapping_div(*b))
+        }
+        (ValueOps::Eq, [Literal::Int(a), Literal::Int(b)]) => Literal::Bool(a == b),
+        (ValueOps::Lt, [Literal::Int(a), Literal::Int(b)]) => Literal::Bool(a < b),
+        (ValueOps::Gt, [Literal::Int(a), Literal::Int(b)]) => Literal::Bool(a > b),
+        (ValueOps::Le, [Literal::Int(a), Literal::Int(b)]) => Literal::Bool(a <= b),
+        (ValueOps::Ge, [Literal::Int(a), Literal::Int(b)]) => Literal::Bool(a >= b),
+        (ValueOps::Not, [Literal::Bool(a)]) => Literal::Bool(!a),
+        (ValueOps::And, [Literal::Bool(a), Literal::Bool(b)]) => Literal::Bool(*a && *b),
+        (ValueOps::Or, [Literal::Bool(a), Literal::Bool(b)]) => Literal::Bool(*a || *b),
+        (ValueOps::Fadd, [Literal::Float(a), Literal::Float(b)]) => Literal::Float(a + b),
+        (ValueOps::Fsub, [Literal::Float(a), Literal::Float(b)]) => Literal::Float(a - b),
+        (ValueOps::Fmul, [Literal::Float(a), Literal::Float(b)]) => Literal::Float(a * b),
+        (ValueOps::Fdiv, [Literal::Float(a), Literal::Float(b)]) => Literal::Float(a / b),
+        #[expect(clippy::float_cmp, reason = "Bril's feq is exact comparison")]
+        (ValueOps::Feq, [Literal::Float(a), Literal::Float(b)]) => Literal::Bool(a == b),
+        (ValueOps::Flt, [Literal::Float(a), Literal::Float(b)]) => Literal::Bool(a < b),
+        (ValueOps::Fgt, [Literal::Float(a), Literal::Float(b)]) => Literal::Bool(a > b),
+        (ValueOps::Fle, [Literal::Float(a), Literal::Float(b)]) => Literal::Bool(a <= b),
+        (ValueOps::Fge, [Literal::Float(a), Literal::Float(b)]) => Literal::Bool(a >= b),
+        (ValueOps::Ceq, [Literal::Char(a), Literal::Char(b)]) => Literal::Bool(a == b),
+        (ValueOps::Clt, [Literal::Char(a), Literal::Char(b)]) => Literal::Bool(a < b),
+        (ValueOps::Cgt, [Literal::Char(a), Literal::Char(b)]) => Literal::Bool(a > b),
+        (ValueOps::Cle, [Literal::Char(a), Literal::Char(b)]) => Literal::Bool(a <= b),
+        (ValueOps::Cge, [Literal::Char(a), Literal::Char(b)]) => Literal::Bool(a >= b),
+        (ValueOps::Char2int, [Literal::Char(c)]) => Literal::Int(u32::from(*c).into()),
+        (ValueOps::Int2char, [Literal::Int(i)]) => {
+            Literal::Char(u32::try_from(*i).ok().and_then(char::from_u32)?)
+        }
+        (ValueOps::Float2Bits, [Literal::Float(f)]) => {
+            Literal::Int(i64::from_ne_bytes(f.to_ne_bytes()))
+        }
+        (ValueOps::Bits2Float, [Literal::Int(i)]) => {
+            Literal::Float(f64::from_ne_bytes(i.to_ne_bytes()))
+        }
+        _ => return None,
+    })
+}
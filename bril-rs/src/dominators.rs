@@ -0,0 +1,363 @@
+use std::collections::BTreeSet;
+
+use crate::cfg::{BasicBlock, Cfg, CfgError, reverse_post_order};
+use crate::{EffectOps, Instruction};
+
+/// A dominator tree computed with the algorithm of Cooper, Harvey, and Kennedy from "A Simple, Fast Dominance Algorithm"
+///
+/// Nodes are block indices of the [Cfg] the tree was computed from. Blocks which can not be reached from the root are not part of the tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DominatorTree {
+    /// The root of the tree
+    pub root: usize,
+    /// The immediate dominator of each block, [None] for the root and for unreachable blocks
+    pub idom: Vec<Option<usize>>,
+    /// The blocks immediately dominated by each block
+    pub children: Vec<Vec<usize>>,
+    /// The dominance frontier of each block: the blocks where its dominance ends
+    pub frontier: Vec<BTreeSet<usize>>,
+    // The pre-order number of each node and the largest pre-order number in its subtree, which make dominance queries constant time
+    pre: Vec<usize>,
+    last: Vec<usize>,
+}
+
+impl DominatorTree {
+    /// Computes the dominators of every block of ```cfg``` reachable from its entry
+    #[must_use]
+    pub fn new(cfg: &Cfg) -> Self {
+        Self::from_graph(cfg.entry(), &cfg.successors, &cfg.predecessors)
+    }
+
+    fn from_graph(root: usize, succs: &[Vec<usize>], preds: &[Vec<usize>]) -> Self {
+        let num_nodes = succs.len();
+        let rpo = reverse_post_order(root, succs);
+        let mut rpo_number = vec![usize::MAX; num_nodes];
+        for (i, b) in rpo.iter().enumerate() {
+            rpo_number[*b] = i;
+        }
+
+        // The root is its own immediate dominator while iterating to simplify `intersect`
+        let mut idom = vec![None; num_nodes];
+        idom[root] = Some(root);
+        let intersect = |idom: &[Option<usize>], mut a: usize, mut b: usize| {
+            while a != b {
+                while rpo_number[a] > rpo_number[b] {
+                    a = idom[a].unwrap();
+                }
+                while rpo_number[b] > rpo_number[a] {
+                    b = idom[b].unwrap();
+                }
+            }
+            a
+        };
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for b in rpo.iter().skip(1) {
+                let new_idom = preds[*b]
+                    .iter()
+                    .filter(|p| idom[**p].is_some())
+                    .fold(None, |acc, p| {
+                        Some(acc.map_or(*p, |acc| intersect(&idom, acc, *p)))
+                    });
+                if idom[*b] != new_idom {
+                    idom[*b] = new_idom;
+                    changed = true;
+                }
+            }
+        }
+        idom[root] = None;
+
+        let mut children = vec![Vec::new(); num_nodes];
+        for b in &rpo {
+            if let Some(d) = idom[*b] {
+                children[d].push(*b);
+            }
+        }
+
+        let mut frontier = vec![BTreeSet::new(); num_nodes];
+        for b in &rpo {
+            for p in preds[*b].iter().filter(|p| rpo_number[**p] != usize::MAX) {
+                let mut runner = Some(*p);
+                while runner != idom[*b] {
+                    let r = runner.unwrap();
+                    frontier[r].insert(*b);
+                    runner = idom[r];
+                }
+            }
+        }
+
+        let mut tree = Self {
+            root,
+            idom,
+            children,
+            frontier,
+            pre: vec![usize::MAX; num_nodes],
+            last: vec![usize::MAX; num_nodes],
+        };
+        tree.number();
+        tree
+    }
+
+    fn number(&mut self) {
+        let mut counter = 0;
+        let mut stack = vec![(self.root, 0)];
+        self.pre[self.root] = 0;
+        while let Some((node, next)) = stack.last_mut() {
+            if let Some(&child) = self.children[*node].get(*next) {
+                *next += 1;
+                counter += 1;
+                self.pre[child] = counter;
+                stack.push((child, 0));
+            } else {
+                self.last[*node] = counter;
+                stack.pop();
+            }
+        }
+    }
+
+    /// Whether ```block``` is part of the tree, meaning it is reachable from the root
+    #[must_use]
+    pub fn contains(&self, block: usize) -> bool {
+        self.pre.get(block).is_some_and(|p| *p != usize::MAX)
+    }
+
+    /// Whether every path from the root to ```b``` goes through ```a```. Every block dominates itself.
+    #[must_use]
+    pub fn dominates(&self, a: usize, b: usize) -> bool {
+        self.contains(a) && self.contains(b) && (self.pre[a]..=self.last[a]).contains(&self.pre[b])
+    }
+
+    /// Whether ```a``` dominates ```b``` and they are different blocks
+    #[must_use]
+    pub fn strictly_dominates(&self, a: usize, b: usize) -> bool {
+        a != b && self.dominates(a, b)
+    }
+
+    /// Returns every block which dominates ```block```, starting from ```block``` itself and ending with the root
+    #[must_use]
+    pub fn dominators(&self, block: usize) -> Vec<usize> {
+        if !self.contains(block) {
+            return Vec::new();
+        }
+        std::iter::successors(Some(block), |b| self.idom[*b]).collect()
+    }
+
+    /// Returns the blocks of the tree in pre-order, so that every block comes after its immediate dominator
+    #[must_use]
+    pub fn pre_order(&self) -> Vec<usize> {
+        let mut order = Vec::new();
+        let mut stack = vec![self.root];
+        while let Some(b) = stack.pop() {
+            order.push(b);
+            stack.extend(self.children[b].iter().rev());
+        }
+        order
+    }
+}
+
+/// The post-dominators of a [Cfg], a [`DominatorTree`] of the reversed graph
+///
+/// All blocks without successors are joined under a virtual exit node whose index is the number of blocks in the [Cfg]. Blocks which can not reach an exit, like those in an infinite loop, are not part of the tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PostDominatorTree {
+    /// The tree over the reversed [Cfg], rooted at [`PostDominatorTree::exit`]
+    pub tree: DominatorTree,
+}
+
+impl PostDominatorTree {
+    /// Computes the post-dominators of every block of ```cfg``` which can reach an exit
+    #[must_use]
+    pub fn new(cfg: &Cfg) -> Self {
+        let exit = cfg.blocks.len();
+        let mut succs = cfg.predecessors.clone();
+        let mut preds = cfg.successors.clone();
+        let exits: Vec<usize> = (0..exit)
+            .filter(|b| cfg.successors[*b].is_empty())
+            .collect();
+        for b in &exits {
+            preds[*b].push(exit);
+        }
+        succs.push(exits);
+        preds.push(Vec::new());
+        Self {
+            tree: DominatorTree::from_graph(exit, &succs, &preds),
+        }
+    }
+
+    /// The index of the virtual exit node
+    #[must_use]
+    pub const fn exit(&self) -> usize {
+        self.tree.root
+    }
+
+    /// The immediate post-dominator of ```block```. [None] if it is the virtual exit, can not reach an exit, or is only post-dominated by the virtual exit.
+    #[must_use]
+    pub fn ipdom(&self, block: usize) -> Option<usize> {
+        self.tree.idom[block].filter(|d| *d != self.exit())
+    }
+
+    /// Whether every path from ```b``` to an exit goes through ```a```
+    #[must_use]
+    pub fn post_dominates(&self, a: usize, b: usize) -> bool {
+        self.tree.dominates(a, b)
+    }
+}
+
+/// A natural loop: a set of blocks with a single entry, the header, and at least one back edge to it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NaturalLoop {
+    /// The only block of the loop which can be entered from outside of it
+    pub header: usize,
+    /// The sources of the back edges to the header
+    pub latches: Vec<usize>,
+    /// Every block of the loop, including the header and the latches
+    pub body: BTreeSet<usize>,
+    /// The edges leaving the loop as (source inside the loop, target outside the loop)
+    pub exits: Vec<(usize, usize)>,
+    /// The predecessor of the header outside of the loop, if it is unique and only flows into the header
+    pub preheader: Option<usize>,
+}
+
+impl NaturalLoop {
+    /// Whether ```block``` is part of this loop
+    #[must_use]
+    pub fn contains(&self, block: usize) -> bool {
+        self.body.contains(&block)
+    }
+}
+
+/// Returns the back edges of ```cfg``` as (latch, header) pairs, which are the edges whose target dominates their source
+#[must_use]
+pub fn back_edges(cfg: &Cfg, dom: &DominatorTree) -> Vec<(usize, usize)> {
+    cfg.successors
+        .iter()
+        .enumerate()
+        .flat_map(|(b, succs)| succs.iter().map(move |s| (b, *s)))
+        .filter(|(b, s)| dom.dominates(*s, *b))
+        .collect()
+}
+
+/// Finds the natural loops of ```cfg```. Back edges sharing a header are merged into a single loop.
+///
+/// Loops are ordered by header in reverse post-order, so an outer loop comes before the loops nested in it.
+#[must_use]
+pub fn natural_loops(cfg: &Cfg, dom: &DominatorTree) -> Vec<NaturalLoop> {
+    let mut loops: Vec<NaturalLoop> = Vec::new();
+    for (latch, header) in back_edges(cfg, dom) {
+        if let Some(l) = loops.iter_mut().find(|l| l.header == header) {
+            l.latches.push(latch);
+        } else {
+            loops.push(NaturalLoop {
+                header,
+                latches: vec![latch],
+                body: BTreeSet::new(),
+                exits: Vec::new(),
+                preheader: None,
+            });
+        }
+    }
+
+    for l in &mut loops {
+        l.body.insert(l.header);
+        let mut stack = l.latches.clone();
+        while let Some(b) = stack.pop() {
+            if l.body.insert(b) {
+                stack.extend(cfg.predecessors[b].iter().filter(|p| dom.contains(**p)));
+            }
+        }
+
+        l.exits = l
+            .body
+            .iter()
+            .flat_map(|b| cfg.successors[*b].iter().map(move |s| (*b, *s)))
+            .filter(|(_, s)| !l.body.contains(s))
+            .collect();
+
+        let mut outside = cfg.predecessors[l.header]
+            .iter()
+            .filter(|p| !l.body.contains(p));
+        l.preheader = match (outside.next(), outside.next()) {
+            (Some(p), None) if cfg.successors[*p].len() == 1 => Some(*p),
+            _ => None,
+        };
+    }
+
+    // Every header is reachable, so it has a place in the reverse post-order
+    let mut rpo_index = vec![usize::MAX; cfg.blocks.len()];
+    for (i, b) in cfg.reverse_post_order().into_iter().enumerate() {
+        rpo_index[b] = i;
+    }
+    loops.sort_by_key(|l| rpo_index[l.header]);
+    loops
+}
+
+/// Inserts an empty preheader block in front of the header of ```l``` and redirects every edge entering the loop to it.
+/// Returns the index of the new block.
+///
+/// Indices of blocks after the header shift by one, so any [`DominatorTree`] or [`NaturalLoop`] computed before this call is no longer valid.
+/// # Errors
+/// Will return an error if the edges of ```cfg``` can not be recomputed after the insertion
+pub fn insert_preheader(cfg: &mut Cfg, l: &NaturalLoop) -> Result<usize, CfgError> {
+    let header = l.header;
+    let header_label = cfg.blocks[header].label.clone();
+    let label = cfg.fresh_label(&format!(
+        "{}.preheader",
+        header_label.as_deref().unwrap_or("entry")
+    ));
+
+    // A latch placed just before the header would now fall through into the preheader
+    if header > 0
+        && l.contains(header - 1)
+        && cfg.blocks[header - 1].terminator().is_none()
+        && let Some(header_label) = &header_label
+    {
+        let latch = &mut cfg.blocks[header - 1];
+        let pos = latch
+            .instrs
+            .last()
+            .and_then(Instruction::get_pos)
+            .or_else(|| latch.label_pos.clone());
+        latch.instrs.push(Instruction::Effect {
+            args: Vec::new(),
+            funcs: Vec::new(),
+            labels: vec![header_label.clone()],
+            op: EffectOps::Jump,
+            pos,
+            comments: None,
+        });
+    }
+
+    for p in cfg.predecessors[header].clone() {
+        if l.contains(p) {
+            continue;
+        }
+        // A `guard` can jump to the header from anywhere in the block, not just at its end
+        for instr in &mut cfg.blocks[p].instrs {
+            if let Instruction::Effect {
+                op: EffectOps::Jump | EffectOps::Branch | EffectOps::Guard,
+                labels,
+                ..
+            } = instr
+            {
+                labels
+                    .iter_mut()
+                    .filter(|target| Some(target.as_str()) == header_label.as_deref())
+                    .for_each(|target| target.clone_from(&label));
+            }
+        }
+    }
+
+    cfg.blocks.insert(
+        header,
+        BasicBlock {
+            label: Some(label),
+            // The preheader stands in for the entry of the loop
+            label_pos: cfg.blocks[header].label_pos.clone(),
+            ..BasicBlock::default()
+        },
+    );
+    cfg.recompute_edges()?;
+    Ok(header)
+}
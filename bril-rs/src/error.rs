@@ -0,0 +1,79 @@
+use thiserror::Error;
+
+use crate::binary::BinaryError;
+use crate::cfg::CfgError;
+use crate::conversion::{ConversionError, ConversionErrors, PositionalConversionError};
+use crate::extension::ExtensionError;
+
+/// The crate-level error of `bril_rs`, returned by the fallible loading and outputting helpers like [`crate::try_load_program_from_read`]
+// Having the #[error(...)] for all variants derives the Display trait as well
+#[derive(Error, Debug)]
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "I allow the `Error` suffix for enums"
+)]
+pub enum BrilError {
+    /// Reading the input or writing the output failed
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// The input was not valid JSON or did not have the shape of a Bril program
+    // serde_json already reports the line and column as part of its message
+    #[error("{source}")]
+    Json {
+        /// The underlying error from `serde_json`
+        source: serde_json::Error,
+        /// The line of the JSON input where the error was found, starting from 1
+        line: usize,
+        /// The column of the JSON input where the error was found, starting from 1
+        column: usize,
+    },
+
+    /// The input was valid JSON but could not be converted from [`crate::AbstractProgram`] into [`crate::Program`]
+    #[error(transparent)]
+    Conversion(#[from] ConversionErrors),
+
+    /// The input started with [`crate::binary::MAGIC`] but was not a well-formed binary Bril program
+    #[error(transparent)]
+    Binary(#[from] BinaryError),
+
+    /// The program uses an extension which the tool does not support
+    // Boxed since the error carries a whole source position, which would make every `Result` of this crate larger
+    #[error(transparent)]
+    Extension(Box<ExtensionError>),
+
+    /// A [`crate::Function`] could not be split into a [`crate::cfg::Cfg`]
+    #[error(transparent)]
+    Cfg(#[from] CfgError),
+}
+
+impl From<serde_json::Error> for BrilError {
+    fn from(e: serde_json::Error) -> Self {
+        if e.is_io() {
+            return Self::Io(e.into());
+        }
+        Self::Json {
+            line: e.line(),
+            column: e.column(),
+            source: e,
+        }
+    }
+}
+
+impl From<ExtensionError> for BrilError {
+    fn from(e: ExtensionError) -> Self {
+        Self::Extension(Box::new(e))
+    }
+}
+
+impl From<ConversionError> for BrilError {
+    fn from(e: ConversionError) -> Self {
+        Self::Conversion(PositionalConversionError::new(e).into())
+    }
+}
+
+impl From<PositionalConversionError> for BrilError {
+    fn from(e: PositionalConversionError) -> Self {
+        Self::Conversion(e.into())
+    }
+}
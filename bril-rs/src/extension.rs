@@ -0,0 +1,406 @@
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{Code, EffectOps, Function, Instruction, Literal, Position, Program, Type, ValueOps};
+
+/// An extension of the Bril core language
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Extension {
+    /// <https://capra.cs.cornell.edu/bril/lang/float.html>
+    Float,
+    /// <https://capra.cs.cornell.edu/bril/lang/memory.html>
+    Memory,
+    /// <https://capra.cs.cornell.edu/bril/lang/ssa.html>
+    Ssa,
+    /// <https://capra.cs.cornell.edu/bril/lang/spec.html>
+    Speculate,
+    /// <https://capra.cs.cornell.edu/bril/lang/char.html>
+    Char,
+    /// <https://capra.cs.cornell.edu/bril/lang/bitcast.html>
+    Bitcast,
+    /// <https://capra.cs.cornell.edu/bril/lang/dynamic.html>
+    Dynamic,
+    /// <https://capra.cs.cornell.edu/bril/lang/import.html>
+    Import,
+}
+
+impl Extension {
+    /// Every extension, in the order they are listed by [`ExtensionSet`]
+    pub const ALL: [Self; 8] = [
+        Self::Float,
+        Self::Memory,
+        Self::Ssa,
+        Self::Speculate,
+        Self::Char,
+        Self::Bitcast,
+        Self::Dynamic,
+        Self::Import,
+    ];
+
+    /// The name of the extension, as it is written on the command line
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Float => "float",
+            Self::Memory => "memory",
+            Self::Ssa => "ssa",
+            Self::Speculate => "speculate",
+            Self::Char => "char",
+            Self::Bitcast => "bitcast",
+            Self::Dynamic => "dynamic",
+            Self::Import => "import",
+        }
+    }
+
+    const fn bit(self) -> u16 {
+        1 << self as u16
+    }
+}
+
+impl Display for Extension {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Extension {
+    type Err = ExtensionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|e| e.name() == s)
+            .ok_or_else(|| ExtensionError::Unknown(s.to_string()))
+    }
+}
+
+/// The errors from parsing an [`ExtensionSet`] or checking a [Program] against one
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[expect(
+    clippy::module_name_repetitions,
+    reason = "I allow the `Error` suffix for enums"
+)]
+pub enum ExtensionError {
+    /// The name is not that of any [`Extension`]
+    #[error("unknown extension `{0}`, expected one of {all}", all = ExtensionSet::ALL)]
+    Unknown(String),
+    /// The program uses a construct from an extension which is not in the set
+    #[error("{}{construct} requires the `{extension}` extension", location(.func.as_deref(), .pos.as_deref()))]
+    Missing {
+        /// The extension which is needed
+        extension: Extension,
+        /// A description of the offending instruction, argument, return type, or import
+        construct: String,
+        /// The function the construct was found in, if any
+        func: Option<String>,
+        /// The source position of the construct if it was provided
+        pos: Option<Box<Position>>,
+    },
+}
+
+fn location(func: Option<&str>, pos: Option<&Position>) -> String {
+    match (func, pos) {
+        (_, Some(Position { pos, src, .. })) => src.as_ref().map_or_else(
+            || format!("{}:{}: ", pos.row, pos.col),
+            |src| format!("{src}:{}:{}: ", pos.row, pos.col),
+        ),
+        (Some(func), None) => format!("@{func}: "),
+        (None, None) => String::new(),
+    }
+}
+
+/// A set of [`Extension`]s which a tool accepts, or which a program uses.
+///
+/// The representation of programs always includes every extension, so tools which only implement some of them can reject the rest up front with [`ExtensionSet::accepts`] instead of failing part way through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ExtensionSet(u16);
+
+impl ExtensionSet {
+    /// Only the core language
+    pub const CORE: Self = Self(0);
+    /// Every extension
+    pub const ALL: Self = {
+        let mut set = Self::CORE;
+        let mut i = 0;
+        while i < Extension::ALL.len() {
+            set = set.with(Extension::ALL[i]);
+            i += 1;
+        }
+        set
+    };
+
+    /// This set with ```ext``` added
+    #[must_use]
+    pub const fn with(self, ext: Extension) -> Self {
+        Self(self.0 | ext.bit())
+    }
+
+    /// This set with ```ext``` removed
+    #[must_use]
+    pub const fn without(self, ext: Extension) -> Self {
+        Self(self.0 & !ext.bit())
+    }
+
+    /// Whether ```ext``` is in the set
+    #[must_use]
+    pub const fn contains(self, ext: Extension) -> bool {
+        self.0 & ext.bit() != 0
+    }
+
+    /// The extensions which are in either set
+    #[must_use]
+    pub const fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// The extensions which are in both sets
+    #[must_use]
+    pub const fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    /// Whether every extension of this set is also in ```other```
+    #[must_use]
+    pub const fn is_subset(self, other: Self) -> bool {
+        self.0 & !other.0 == 0
+    }
+
+    /// Adds ```ext``` to the set, returning whether it was not already present
+    pub const fn insert(&mut self, ext: Extension) -> bool {
+        let added = !self.contains(ext);
+        *self = self.with(ext);
+        added
+    }
+
+    /// The extensions in the set
+    pub fn iter(self) -> impl Iterator<Item = Extension> {
+        Extension::ALL
+            .into_iter()
+            .filter(move |e| self.contains(*e))
+    }
+
+    /// The extensions which ```prog``` uses
+    #[must_use]
+    pub fn used_by(prog: &Program) -> Self {
+        ExtensionChecker::new(Self::ALL, prog).used
+    }
+
+    /// Finds every construct of ```prog``` which needs an extension that is not in this set, in the order of the functions and instructions of ```prog```
+    #[must_use]
+    pub fn check_program(self, prog: &Program) -> Vec<ExtensionError> {
+        ExtensionChecker::new(self, prog).errors
+    }
+
+    /// Checks that ```prog``` only uses extensions in this set
+    /// # Errors
+    /// Will return the first construct of ```prog``` which needs an extension that is not in this set
+    pub fn accepts(self, prog: &Program) -> Result<(), ExtensionError> {
+        self.check_program(prog)
+            .into_iter()
+            .next()
+            .map_or(Ok(()), Err)
+    }
+}
+
+impl FromIterator<Extension> for ExtensionSet {
+    fn from_iter<I: IntoIterator<Item = Extension>>(iter: I) -> Self {
+        let mut set = Self::CORE;
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<Extension> for ExtensionSet {
+    fn extend<I: IntoIterator<Item = Extension>>(&mut self, iter: I) {
+        for ext in iter {
+            self.insert(ext);
+        }
+    }
+}
+
+/// Lists the extensions separated by commas, or `core` for the empty set
+impl Display for ExtensionSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if *self == Self::CORE {
+            return write!(f, "core");
+        }
+        for (i, ext) in self.iter().enumerate() {
+            if i != 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{ext}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a comma separated list of extensions like `float,memory`. `core` and the empty string are the empty set and `all` is every extension.
+impl FromStr for ExtensionSet {
+    type Err = ExtensionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .try_fold(Self::CORE, |set, name| match name {
+                "core" => Ok(set),
+                "all" => Ok(Self::ALL),
+                _ => Ok(set.with(name.parse()?)),
+            })
+    }
+}
+
+impl ValueOps {
+    /// The extension which defines this operation, or [None] if it is part of the core language
+    #[must_use]
+    pub const fn extension(self) -> Option<Extension> {
+        match self {
+            Self::Add
+            | Self::Sub
+            | Self::Mul
+            | Self::Div
+            | Self::Eq
+            | Self::Lt
+            | Self::Gt
+            | Self::Le
+            | Self::Ge
+            | Self::Not
+            | Self::And
+            | Self::Or
+            | Self::Call
+            | Self::Id => None,
+            Self::Fadd
+            | Self::Fsub
+            | Self::Fmul
+            | Self::Fdiv
+            | Self::Feq
+            | Self::Flt
+            | Self::Fgt
+            | Self::Fle
+            | Self::Fge => Some(Extension::Float),
+            Self::Alloc | Self::Load | Self::PtrAdd => Some(Extension::Memory),
+            Self::Get | Self::Undef => Some(Extension::Ssa),
+            Self::Ceq
+            | Self::Clt
+            | Self::Cgt
+            | Self::Cle
+            | Self::Cge
+            | Self::Char2int
+            | Self::Int2char => Some(Extension::Char),
+            Self::Float2Bits | Self::Bits2Float => Some(Extension::Bitcast),
+        }
+    }
+}
+
+impl EffectOps {
+    /// The extension which defines this operation, or [None] if it is part of the core language
+    #[must_use]
+    pub const fn extension(self) -> Option<Extension> {
+        match self {
+            Self::Jump | Self::Branch | Self::Call | Self::Return | Self::Print | Self::Nop => None,
+            Self::Store | Self::Free => Some(Extension::Memory),
+            Self::Speculate | Self::Commit | Self::Guard => Some(Extension::Speculate),
+            Self::Set => Some(Extension::Ssa),
+        }
+    }
+}
+
+impl Type {
+    /// The extensions needed to write this type. A pointer needs [`Extension::Memory`] along with whatever its element type needs.
+    #[must_use]
+    pub fn extensions(&self) -> ExtensionSet {
+        match self {
+            Self::Int | Self::Bool => ExtensionSet::CORE,
+            Self::Float => ExtensionSet::CORE.with(Extension::Float),
+            Self::Char => ExtensionSet::CORE.with(Extension::Char),
+            Self::Any => ExtensionSet::CORE.with(Extension::Dynamic),
+            Self::Pointer(t) => t.extensions().with(Extension::Memory),
+        }
+    }
+}
+
+impl Literal {
+    /// The extension needed to write this literal, or [None] if it is part of the core language
+    #[must_use]
+    pub const fn extension(&self) -> Option<Extension> {
+        match self {
+            Self::Int(_) | Self::Bool(_) => None,
+            Self::Float(_) => Some(Extension::Float),
+            Self::Char(_) => Some(Extension::Char),
+        }
+    }
+}
+
+/// Walks a program, recording the extensions it uses and every construct whose extensions are not in ```allowed```
+struct ExtensionChecker {
+    allowed: ExtensionSet,
+    used: ExtensionSet,
+    errors: Vec<ExtensionError>,
+    func: Option<String>,
+    pos: Option<Position>,
+}
+
+impl ExtensionChecker {
+    fn new(allowed: ExtensionSet, prog: &Program) -> Self {
+        let mut checker = Self {
+            allowed,
+            used: ExtensionSet::CORE,
+            errors: Vec::new(),
+            func: None,
+            pos: None,
+        };
+        for import in &prog.imports {
+            checker.require(ExtensionSet::CORE.with(Extension::Import), || {
+                format!("import of `{}`", import.path.display())
+            });
+        }
+        for func in &prog.functions {
+            checker.function(func);
+        }
+        checker
+    }
+
+    fn function(&mut self, func: &Function) {
+        self.func = Some(func.name.clone());
+        self.pos.clone_from(&func.pos);
+        for arg in &func.args {
+            self.require(arg.arg_type.extensions(), || format!("argument `{arg}`"));
+        }
+        if let Some(t) = &func.return_type {
+            self.require(t.extensions(), || format!("return type `{t}`"));
+        }
+        for code in &func.instrs {
+            let Code::Instruction(instr) = code else {
+                continue;
+            };
+            let needed = match instr {
+                Instruction::Constant {
+                    const_type, value, ..
+                } => const_type
+                    .extensions()
+                    .union(value.extension().into_iter().collect()),
+                Instruction::Value { op, op_type, .. } => op_type
+                    .extensions()
+                    .union(op.extension().into_iter().collect()),
+                Instruction::Effect { op, .. } => op.extension().into_iter().collect(),
+            };
+            self.pos = instr.get_pos();
+            self.require(needed, || format!("`{instr}`"));
+        }
+    }
+
+    /// Reports the first extension of ```needed``` which is not allowed, if any
+    fn require(&mut self, needed: ExtensionSet, construct: impl FnOnce() -> String) {
+        self.used = self.used.union(needed);
+        if let Some(extension) = needed.iter().find(|e| !self.allowed.contains(*e)) {
+            self.errors.push(ExtensionError::Missing {
+                extension,
+                construct: construct(),
+                func: self.func.clone(),
+                pos: self.pos.clone().map(Box::new),
+            });
+        }
+    }
+}
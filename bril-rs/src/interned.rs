@@ -0,0 +1,523 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::ops::Index;
+
+use crate::Import;
+use crate::{
+    Argument, Code, ConstOps, EffectOps, Function, Instruction, Literal, Program, Type, ValueOps,
+};
+use crate::{Comments, Position};
+
+/// An index into a [`SymbolTable`]
+pub trait Id: Copy + Eq + Hash {
+    /// The id of the ```index```th symbol of a table
+    /// # Panics
+    /// Will panic if ```index``` does not fit in the id
+    fn from_index(index: usize) -> Self;
+    /// The position of the symbol in its table
+    fn index(self) -> usize;
+}
+
+macro_rules! id {
+    ($(#[$doc:meta])* $name:ident) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(u32);
+
+        impl Id for $name {
+            fn from_index(index: usize) -> Self {
+                Self(u32::try_from(index).expect("A symbol table can hold at most u32::MAX symbols"))
+            }
+
+            fn index(self) -> usize {
+                self.0 as usize
+            }
+        }
+    };
+}
+
+id!(
+    /// A variable of an [`InternedFunction`], resolved with [`InternedFunction::vars`]
+    VarId
+);
+id!(
+    /// A label of an [`InternedFunction`], resolved with [`InternedFunction::labels`]
+    LabelId
+);
+id!(
+    /// The name of a function, resolved with [`InternedProgram::funcs`]
+    FuncId
+);
+
+/// Maps names to dense ids and back. Ids are handed out in the order names are first interned, starting from 0
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolTable<I> {
+    names: Vec<String>,
+    ids: HashMap<String, I>,
+}
+
+impl<I> Default for SymbolTable<I> {
+    fn default() -> Self {
+        Self {
+            names: Vec::new(),
+            ids: HashMap::new(),
+        }
+    }
+}
+
+impl<I: Id> SymbolTable<I> {
+    /// An empty table
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the id of ```name```, adding it to the table if it is not there yet
+    /// # Panics
+    /// Will panic if the table already holds `u32::MAX` names
+    pub fn intern(&mut self, name: &str) -> I {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        let id = I::from_index(self.names.len());
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        id
+    }
+
+    /// The id of ```name``` if it has been interned
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<I> {
+        self.ids.get(name).copied()
+    }
+
+    /// The name of ```id```
+    /// # Panics
+    /// Will panic if ```id``` does not come from this table
+    #[must_use]
+    pub fn name(&self, id: I) -> &str {
+        &self.names[id.index()]
+    }
+
+    /// The number of names in the table
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Whether the table holds no names
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Iterates over every id and its name in the order they were interned
+    pub fn iter(&self) -> impl Iterator<Item = (I, &str)> {
+        self.names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (I::from_index(i), name.as_str()))
+    }
+}
+
+impl<I: Id> Index<I> for SymbolTable<I> {
+    type Output = str;
+
+    fn index(&self, id: I) -> &str {
+        self.name(id)
+    }
+}
+
+/// A [Program] where every function name is interned into a [`FuncId`]
+///
+/// Converting from a [Program] and back gives the same [Program]. Defined functions are interned first and in order, so the ```i```th function of [`InternedProgram::functions`] has the ```i```th id unless names are duplicated.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct InternedProgram {
+    /// The names of every function which is defined or called in the program
+    pub funcs: SymbolTable<FuncId>,
+    /// The functions of the program
+    pub functions: Vec<InternedFunction>,
+    /// The imports of the program, which are kept as they are
+    pub imports: Vec<Import>,
+}
+
+impl InternedProgram {
+    /// The definition of the function named ```id```, if it is defined in this program
+    #[must_use]
+    pub fn function(&self, id: FuncId) -> Option<&InternedFunction> {
+        self.functions
+            .get(id.index())
+            .filter(|f| f.name == id)
+            .or_else(|| self.functions.iter().find(|f| f.name == id))
+    }
+}
+
+impl From<Program> for InternedProgram {
+    fn from(Program { functions, imports }: Program) -> Self {
+        let mut funcs = SymbolTable::new();
+        for f in &functions {
+            funcs.intern(&f.name);
+        }
+        let functions = functions
+            .into_iter()
+            .map(|f| InternedFunction::new(f, &mut funcs))
+            .collect();
+        Self {
+            funcs,
+            functions,
+            imports,
+        }
+    }
+}
+
+impl From<InternedProgram> for Program {
+    fn from(prog: InternedProgram) -> Self {
+        Self {
+            functions: prog
+                .functions
+                .into_iter()
+                .map(|f| f.into_function(&prog.funcs))
+                .collect(),
+            imports: prog.imports,
+        }
+    }
+}
+
+/// A [Function] whose variables and labels are interned into tables of their own
+#[derive(Debug, Clone, PartialEq)]
+pub struct InternedFunction {
+    /// The name of the function
+    pub name: FuncId,
+    /// Any arguments the function accepts
+    pub args: Vec<InternedArgument>,
+    /// The possible return type of the function
+    pub return_type: Option<Type>,
+    /// The instructions of the function
+    pub instrs: Vec<InternedCode>,
+    /// The names of the variables of the function, starting with its arguments
+    pub vars: SymbolTable<VarId>,
+    /// The names of the labels which are declared or referred to in the function
+    pub labels: SymbolTable<LabelId>,
+    /// The position of the function in the original source code
+    pub pos: Option<Position>,
+    /// The `#` comments written around the function in the original source code
+    pub comments: Option<Box<Comments>>,
+}
+
+/// An argument of an [`InternedFunction`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InternedArgument {
+    /// The variable of the argument
+    pub name: VarId,
+    /// The type of the argument
+    pub arg_type: Type,
+}
+
+/// A label or an instruction of an [`InternedFunction`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum InternedCode {
+    /// A label
+    Label {
+        /// The name of the label
+        label: LabelId,
+        /// Where the label is located in source code
+        pos: Option<Position>,
+        /// The `#` comments written around the label in source code
+        comments: Option<Box<Comments>>,
+    },
+    /// An instruction
+    Instruction(InternedInstruction),
+}
+
+/// An [Instruction] with interned operands
+#[derive(Debug, Clone, PartialEq)]
+pub enum InternedInstruction {
+    /// <https://capra.cs.cornell.edu/bril/lang/syntax.html#constant>
+    Constant {
+        /// destination variable
+        dest: VarId,
+        /// "const"
+        op: ConstOps,
+        /// Type of variable
+        const_type: Type,
+        /// The literal being stored in the variable
+        value: Literal,
+        /// The source position of the instruction if provided
+        pos: Option<Position>,
+        /// The `#` comments written around the instruction if provided
+        comments: Option<Box<Comments>>,
+    },
+    /// <https://capra.cs.cornell.edu/bril/lang/syntax.html#value-operation>
+    Value {
+        /// The operation being executed
+        op: ValueOps,
+        /// The variable that the result is stored in
+        dest: VarId,
+        /// The type of the variable
+        op_type: Type,
+        /// List of variables as arguments
+        args: Vec<VarId>,
+        /// List of function names
+        funcs: Vec<FuncId>,
+        /// List of labels
+        labels: Vec<LabelId>,
+        /// The source position of the instruction if provided
+        pos: Option<Position>,
+        /// The `#` comments written around the instruction if provided
+        comments: Option<Box<Comments>>,
+    },
+    /// <https://capra.cs.cornell.edu/bril/lang/syntax.html#effect-operation>
+    Effect {
+        /// The operation being executed
+        op: EffectOps,
+        /// List of variables as arguments
+        args: Vec<VarId>,
+        /// List of function names
+        funcs: Vec<FuncId>,
+        /// List of labels
+        labels: Vec<LabelId>,
+        /// The source position of the instruction if provided
+        pos: Option<Position>,
+        /// The `#` comments written around the instruction if provided
+        comments: Option<Box<Comments>>,
+    },
+}
+
+impl InternedInstruction {
+    /// The variable this instruction writes to, if any
+    #[must_use]
+    pub const fn dest(&self) -> Option<VarId> {
+        match self {
+            Self::Constant { dest, .. } | Self::Value { dest, .. } => Some(*dest),
+            Self::Effect { .. } => None,
+        }
+    }
+
+    /// The variables this instruction reads. For `set`, this includes the shadow variable it writes to
+    #[must_use]
+    pub fn args(&self) -> &[VarId] {
+        match self {
+            Self::Constant { .. } => &[],
+            Self::Value { args, .. } | Self::Effect { args, .. } => args,
+        }
+    }
+
+    /// The functions this instruction calls
+    #[must_use]
+    pub fn funcs(&self) -> &[FuncId] {
+        match self {
+            Self::Constant { .. } => &[],
+            Self::Value { funcs, .. } | Self::Effect { funcs, .. } => funcs,
+        }
+    }
+
+    /// The labels this instruction may jump to
+    #[must_use]
+    pub fn labels(&self) -> &[LabelId] {
+        match self {
+            Self::Constant { .. } => &[],
+            Self::Value { labels, .. } | Self::Effect { labels, .. } => labels,
+        }
+    }
+}
+
+impl InternedFunction {
+    /// Interns the variables and labels of ```func```, and its name and the functions it calls into ```funcs```
+    #[must_use]
+    pub fn new(func: Function, funcs: &mut SymbolTable<FuncId>) -> Self {
+        let mut vars = SymbolTable::new();
+        let mut labels = SymbolTable::new();
+        let args = func
+            .args
+            .into_iter()
+            .map(|a| InternedArgument {
+                name: vars.intern(&a.name),
+                arg_type: a.arg_type,
+            })
+            .collect();
+        let instrs = func
+            .instrs
+            .into_iter()
+            .map(|code| match code {
+                Code::Label {
+                    label,
+                    pos,
+                    comments,
+                } => InternedCode::Label {
+                    label: labels.intern(&label),
+                    pos,
+                    comments,
+                },
+                Code::Instruction(instr) => InternedCode::Instruction(intern_instruction(
+                    instr,
+                    &mut vars,
+                    &mut labels,
+                    funcs,
+                )),
+            })
+            .collect();
+        Self {
+            name: funcs.intern(&func.name),
+            args,
+            return_type: func.return_type,
+            instrs,
+            vars,
+            labels,
+            pos: func.pos,
+            comments: func.comments,
+        }
+    }
+
+    /// Resolves every id of this function back into its name, using ```funcs``` for the names of functions
+    /// # Panics
+    /// Will panic if a [`FuncId`] of this function does not come from ```funcs```
+    #[must_use]
+    pub fn into_function(self, funcs: &SymbolTable<FuncId>) -> Function {
+        let vars = &self.vars;
+        let labels = &self.labels;
+        let var_names = |ids: Vec<VarId>| ids.into_iter().map(|v| vars[v].to_string()).collect();
+        let label_names =
+            |ids: Vec<LabelId>| ids.into_iter().map(|l| labels[l].to_string()).collect();
+        let func_names = |ids: Vec<FuncId>| ids.into_iter().map(|f| funcs[f].to_string()).collect();
+
+        let instrs = self
+            .instrs
+            .into_iter()
+            .map(|code| match code {
+                InternedCode::Label {
+                    label,
+                    pos,
+                    comments,
+                } => Code::Label {
+                    label: labels[label].to_string(),
+                    pos,
+                    comments,
+                },
+                InternedCode::Instruction(InternedInstruction::Constant {
+                    dest,
+                    op,
+                    const_type,
+                    value,
+                    pos,
+                    comments,
+                }) => Code::Instruction(Instruction::Constant {
+                    dest: vars[dest].to_string(),
+                    op,
+                    pos,
+                    comments,
+                    const_type,
+                    value,
+                }),
+                InternedCode::Instruction(InternedInstruction::Value {
+                    op,
+                    dest,
+                    op_type,
+                    args,
+                    funcs: callees,
+                    labels: targets,
+                    pos,
+                    comments,
+                }) => Code::Instruction(Instruction::Value {
+                    args: var_names(args),
+                    dest: vars[dest].to_string(),
+                    funcs: func_names(callees),
+                    labels: label_names(targets),
+                    op,
+                    pos,
+                    comments,
+                    op_type,
+                }),
+                InternedCode::Instruction(InternedInstruction::Effect {
+                    op,
+                    args,
+                    funcs: callees,
+                    labels: targets,
+                    pos,
+                    comments,
+                }) => Code::Instruction(Instruction::Effect {
+                    args: var_names(args),
+                    funcs: func_names(callees),
+                    labels: label_names(targets),
+                    op,
+                    pos,
+                    comments,
+                }),
+            })
+            .collect();
+
+        Function {
+            args: self
+                .args
+                .into_iter()
+                .map(|a| Argument {
+                    name: vars[a.name].to_string(),
+                    arg_type: a.arg_type,
+                })
+                .collect(),
+            instrs,
+            name: funcs[self.name].to_string(),
+            pos: self.pos,
+            comments: self.comments,
+            return_type: self.return_type,
+        }
+    }
+}
+
+fn intern_instruction(
+    instr: Instruction,
+    vars: &mut SymbolTable<VarId>,
+    labels: &mut SymbolTable<LabelId>,
+    funcs: &mut SymbolTable<FuncId>,
+) -> InternedInstruction {
+    match instr {
+        Instruction::Constant {
+            dest,
+            op,
+            pos,
+            comments,
+            const_type,
+            value,
+        } => InternedInstruction::Constant {
+            dest: vars.intern(&dest),
+            op,
+            const_type,
+            value,
+            pos,
+            comments,
+        },
+        Instruction::Value {
+            args,
+            dest,
+            funcs: callees,
+            labels: targets,
+            op,
+            pos,
+            comments,
+            op_type,
+        } => InternedInstruction::Value {
+            op,
+            dest: vars.intern(&dest),
+            op_type,
+            args: args.iter().map(|a| vars.intern(a)).collect(),
+            funcs: callees.iter().map(|f| funcs.intern(f)).collect(),
+            labels: targets.iter().map(|l| labels.intern(l)).collect(),
+            pos,
+            comments,
+        },
+        Instruction::Effect {
+            args,
+            funcs: callees,
+            labels: targets,
+            op,
+            pos,
+            comments,
+        } => InternedInstruction::Effect {
+            op,
+            args: args.iter().map(|a| vars.intern(a)).collect(),
+            funcs: callees.iter().map(|f| funcs.intern(f)).collect(),
+            labels: targets.iter().map(|l| labels.intern(l)).collect(),
+            pos,
+            comments,
+        },
+    }
+}
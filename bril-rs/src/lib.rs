@@ -5,26 +5,113 @@
 
 /// Provides the unstructured representation of Bril programs
 pub mod abstract_program;
+/// Provides a compact binary encoding of programs which is faster to load than JSON
+pub mod binary;
+/// Provides [`builder::FunctionBuilder`] and [`builder::ProgramBuilder`] for constructing programs without writing out every field of each instruction
+pub mod builder;
+/// Provides [`callgraph::CallGraph`], which tracks which functions of a [Program] call each other, and the removal of functions which are never called
+pub mod callgraph;
+/// Provides the control-flow graph of basic blocks for a [Function]
+pub mod cfg;
+/// Provides [`check::check_program`], which reports every way in which a [Program] is not well formed
+pub mod check;
 /// Provides the Error handling and conversion between [`AbstractProgram`] and [Program]
 pub mod conversion;
+/// Provides a worklist dataflow framework over [`cfg::Cfg`] along with common analyses
+pub mod dataflow;
+/// Provides dominator trees, dominance frontiers, post-dominators, and natural loops of a [`cfg::Cfg`]
+pub mod dominators;
+/// Provides [`BrilError`], the error type of the fallible loading and outputting helpers
+pub mod error;
+/// Provides [`extension::ExtensionSet`], which tools use to accept or reject the extensions a [Program] uses
+pub mod extension;
+/// Provides [`interned::InternedProgram`], a representation of [Program] where variables, labels, and functions are interned into `Copy` ids
+pub mod interned;
+/// Provides optimization passes over [Function]s and a [`opt::PassManager`] to run them to a fixed point
+pub mod opt;
 /// Provides the structured representation of Bril programs
 pub mod program;
+/// Provides helpers for passes to carry the source positions of the code they replace over to the code they derive from it, and [`provenance::lost_positions`] to find the code which lost its position
+pub mod provenance;
+/// Provides conversion of a [Function] into and out of SSA form with `set` and `get`
+pub mod ssa;
+/// Provides [`visit::Visitor`] and [`visit::VisitorMut`] for traversing programs, along with iterators over the variables, functions, and labels each [Instruction] refers to
+pub mod visit;
 pub use abstract_program::*;
+pub use error::BrilError;
 pub use program::*;
 
 use std::io::{self, Write};
 
-// todo Have versions of the output_* functions that take a [std::io::Write]
 // todo possible deprecate/remove the wrapper functions to make the code base cleaner
-// todo Wrap the outputs of serde in an error instead of panic-ing
+
+/// Parses a Bril program from ```input``` in JSON format to [Program]
+///
+/// Input starting with [`binary::MAGIC`] is read in the binary format instead.
+/// # Errors
+/// Will return an error if ```input``` can not be read or is not well-formed bril JSON
+pub fn try_load_program_from_read<R: std::io::Read>(mut input: R) -> Result<Program, BrilError> {
+    let mut buffer = Vec::new();
+    input.read_to_end(&mut buffer)?;
+    if binary::is_binary(&buffer) {
+        return binary::read_program(buffer.as_slice());
+    }
+    Ok(serde_json::from_slice(&buffer)?)
+}
+
+/// Parses a Bril program from ```input``` in JSON format to [`AbstractProgram`] and then converts it to [Program]
+///
+/// Unlike [`try_load_program_from_read`], errors in op codes or types are reported as [`conversion::ConversionErrors`], which lists every invalid op and type along with the function, instruction, and source position it was found at.
+/// # Errors
+/// Will return an error if ```input``` can not be read, is not well-formed bril JSON, or can not be converted to [Program]
+pub fn try_load_program_from_abstract_read<R: std::io::Read>(
+    input: R,
+) -> Result<Program, BrilError> {
+    Ok(try_load_abstract_program_from_read(input)?.try_into()?)
+}
+
+/// Parses a Bril program from ```input``` in JSON format to [`AbstractProgram`]
+///
+/// Input starting with [`binary::MAGIC`] is read in the binary format instead.
+/// # Errors
+/// Will return an error if ```input``` can not be read or is not well-formed bril JSON
+pub fn try_load_abstract_program_from_read<R: std::io::Read>(
+    mut input: R,
+) -> Result<AbstractProgram, BrilError> {
+    let mut buffer = Vec::new();
+    input.read_to_end(&mut buffer)?;
+    if binary::is_binary(&buffer) {
+        return binary::read_abstract_program(buffer.as_slice());
+    }
+    Ok(serde_json::from_slice(&buffer)?)
+}
+
+/// Writes a [Program] to ```output``` in JSON format, followed by a newline
+/// # Errors
+/// Will return an error if writing to ```output``` fails
+pub fn try_output_program<W: Write>(p: &Program, mut output: W) -> Result<(), BrilError> {
+    serde_json::to_writer_pretty(&mut output, p)?;
+    output.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Writes an [`AbstractProgram`] to ```output``` in JSON format, followed by a newline
+/// # Errors
+/// Will return an error if writing to ```output``` fails
+pub fn try_output_abstract_program<W: Write>(
+    p: &AbstractProgram,
+    mut output: W,
+) -> Result<(), BrilError> {
+    serde_json::to_writer_pretty(&mut output, p)?;
+    output.write_all(b"\n")?;
+    Ok(())
+}
 
 /// A helper function for parsing a Bril program from ```input``` in JSON format to [Program]
 /// # Panics
-/// Will panic if the input JSON is not well-formed bril JSON
-pub fn load_program_from_read<R: std::io::Read>(mut input: R) -> Program {
-    let mut buffer = String::new();
-    input.read_to_string(&mut buffer).unwrap();
-    serde_json::from_str(&buffer).unwrap()
+/// Will panic if the input JSON is not well-formed bril JSON. See [`try_load_program_from_read`] for a non-panicking version.
+pub fn load_program_from_read<R: std::io::Read>(input: R) -> Program {
+    try_load_program_from_read(input).unwrap_or_else(|e| panic!("{e}"))
 }
 
 /// A wrapper of [`load_program_from_read`] which assumes [`std::io::Stdin`]
@@ -35,19 +122,16 @@ pub fn load_program() -> Program {
 
 /// Outputs a [Program] to [`std::io::Stdout`]
 /// # Panics
-/// This can panic, though I'm not sure when since serialization should always succeed
+/// Will panic if writing to [`std::io::Stdout`] fails. See [`try_output_program`] for a non-panicking version.
 pub fn output_program(p: &Program) {
-    serde_json::to_writer_pretty(io::stdout(), p).unwrap();
-    io::stdout().write_all(b"\n").unwrap();
+    try_output_program(p, io::stdout().lock()).unwrap_or_else(|e| panic!("{e}"));
 }
 
 /// A helper function for parsing a Bril program from ```input``` in JSON format to [`AbstractProgram`]
 /// # Panics
-/// Will panic if the input JSON is not well-formed bril JSON
-pub fn load_abstract_program_from_read<R: std::io::Read>(mut input: R) -> AbstractProgram {
-    let mut buffer = String::new();
-    input.read_to_string(&mut buffer).unwrap();
-    serde_json::from_str(&buffer).unwrap()
+/// Will panic if the input JSON is not well-formed bril JSON. See [`try_load_abstract_program_from_read`] for a non-panicking version.
+pub fn load_abstract_program_from_read<R: std::io::Read>(input: R) -> AbstractProgram {
+    try_load_abstract_program_from_read(input).unwrap_or_else(|e| panic!("{e}"))
 }
 
 /// A wrapper of [`load_abstract_program_from_read`] which assumes [`std::io::Stdin`]
@@ -58,8 +142,87 @@ pub fn load_abstract_program() -> AbstractProgram {
 
 /// Outputs an [`AbstractProgram`] to [`std::io::Stdout`]
 /// # Panics
-/// This can panic, though I'm not sure when since serialization should always succeed
+/// Will panic if writing to [`std::io::Stdout`] fails. See [`try_output_abstract_program`] for a non-panicking version.
 pub fn output_abstract_program(p: &AbstractProgram) {
-    serde_json::to_writer_pretty(io::stdout(), p).unwrap();
-    io::stdout().write_all(b"\n").unwrap();
+    try_output_abstract_program(p, io::stdout().lock()).unwrap_or_else(|e| panic!("{e}"));
+}
+
+/// Writes an [`AbstractProgram`] to an output one function at a time, so that the whole program never has to be in memory
+///
+/// The output is exactly what [`try_output_abstract_program`] writes for the same program.
+#[derive(Debug)]
+pub struct AbstractProgramWriter<W: Write> {
+    output: W,
+    functions: usize,
+    /// Imports come after the functions in the JSON, so they are kept until the end
+    imports: Vec<Import>,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> AbstractProgramWriter<W> {
+    /// Starts a program in ```output```
+    pub const fn new(output: W) -> Self {
+        Self {
+            output,
+            functions: 0,
+            imports: Vec::new(),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Writes ```function``` after the ones written before it
+    /// # Errors
+    /// Will return an error if writing to the output fails
+    pub fn write_function(&mut self, function: &AbstractFunction) -> Result<(), BrilError> {
+        let separator: &[u8] = if self.functions == 0 {
+            b"{\n  \"functions\": [\n    "
+        } else {
+            b",\n    "
+        };
+        self.output.write_all(separator)?;
+        self.functions += 1;
+        self.write_indented(function, b"\n    ")
+    }
+
+    /// Adds ```import``` to the imports of the program
+    pub fn add_import(&mut self, import: Import) {
+        self.imports.push(import);
+    }
+
+    /// Ends the program and returns the output it was written to
+    /// # Errors
+    /// Will return an error if writing to the output fails
+    pub fn finish(mut self) -> Result<W, BrilError> {
+        let end: &[u8] = if self.functions == 0 {
+            b"{\n  \"functions\": []"
+        } else {
+            b"\n  ]"
+        };
+        self.output.write_all(end)?;
+        if !self.imports.is_empty() {
+            self.output.write_all(b",\n  \"imports\": ")?;
+            let imports = std::mem::take(&mut self.imports);
+            self.write_indented(&imports, b"\n  ")?;
+        }
+        self.output.write_all(b"\n}\n")?;
+        Ok(self.output)
+    }
+
+    /// Writes ```value``` as pretty JSON which is nested inside of the program, with ```newline``` at the start of each line after the first
+    fn write_indented<T: serde::Serialize>(
+        &mut self,
+        value: &T,
+        newline: &[u8],
+    ) -> Result<(), BrilError> {
+        self.buffer.clear();
+        serde_json::to_writer_pretty(&mut self.buffer, value)?;
+        // Strings in JSON never contain a raw newline, so every one of them is between two lines
+        for (i, line) in self.buffer.split(|b| *b == b'\n').enumerate() {
+            if i > 0 {
+                self.output.write_all(newline)?;
+            }
+            self.output.write_all(line)?;
+        }
+        Ok(())
+    }
 }
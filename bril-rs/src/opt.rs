@@ -0,0 +1,681 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::cfg::{Cfg, CfgError};
+use crate::dataflow::{
+    Analysis, ConstValue, ConstantPropagation, Direction, fold_value_op, fresh_name, is_pure,
+    normalize_literal, solve,
+};
+use crate::{Code, ConstOps, EffectOps, Function, Instruction, Literal, Program, Type, ValueOps};
+
+/// A transformation of a single [Function] which preserves the behaviour of the program
+///
+/// The built-in passes do not model the rollback of the `speculate` extension and leave functions which use it unchanged.
+pub trait Pass {
+    /// The name used to select the pass, like with `bril-opt --passes`
+    fn name(&self) -> &'static str;
+
+    /// Transforms ```func``` in place and returns whether anything changed
+    /// # Errors
+    /// Will return an error if the pass needs the [Cfg] of ```func``` and it can not be built
+    fn run(&self, func: &mut Function) -> Result<bool, CfgError>;
+}
+
+/// The names of the built-in passes in the order [`PassManager::standard`] runs them
+pub const PASS_NAMES: [&str; 5] = ["const-prop", "unreachable", "copy-prop", "lvn", "dce"];
+
+/// Returns the built-in pass called ```name```, see [`PASS_NAMES`]
+#[must_use]
+pub fn pass_by_name(name: &str) -> Option<Box<dyn Pass>> {
+    Some(match name {
+        "const-prop" => Box::new(GlobalConstantPropagation),
+        "unreachable" => Box::new(UnreachableBlockElimination),
+        "copy-prop" => Box::new(CopyPropagation),
+        "lvn" => Box::new(LocalValueNumbering),
+        "dce" => Box::new(DeadCodeElimination),
+        _ => return None,
+    })
+}
+
+/// Runs a pipeline of [Pass]es over each function of a program until none of them changes anything
+pub struct PassManager {
+    passes: Vec<Box<dyn Pass>>,
+    max_iterations: usize,
+}
+
+impl Default for PassManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PassManager {
+    /// Creates a [`PassManager`] without any passes
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            passes: Vec::new(),
+            // The built-in passes converge in a handful of iterations, this only guards against passes which keep undoing each other
+            max_iterations: 100,
+        }
+    }
+
+    /// Creates a [`PassManager`] with every built-in pass in the order of [`PASS_NAMES`]
+    #[must_use]
+    pub fn standard() -> Self {
+        Self {
+            passes: PASS_NAMES.into_iter().filter_map(pass_by_name).collect(),
+            ..Self::new()
+        }
+    }
+
+    /// Appends ```pass``` to the end of the pipeline
+    pub fn add_pass(&mut self, pass: Box<dyn Pass>) {
+        self.passes.push(pass);
+    }
+
+    /// Limits how many times the whole pipeline is run on a function. With a limit of 1 every pass runs exactly once.
+    pub const fn set_max_iterations(&mut self, max_iterations: usize) {
+        self.max_iterations = max_iterations;
+    }
+
+    /// Runs the pipeline over ```func``` until it stops changing. Returns whether anything changed.
+    /// # Errors
+    /// Will return an error if one of the passes fails
+    pub fn run_function(&self, func: &mut Function) -> Result<bool, CfgError> {
+        let mut changed = false;
+        for _ in 0..self.max_iterations {
+            let mut iteration_changed = false;
+            for pass in &self.passes {
+                iteration_changed |= pass.run(func)?;
+            }
+            if !iteration_changed {
+                break;
+            }
+            changed = true;
+        }
+        Ok(changed)
+    }
+
+    /// Runs the pipeline over every function of ```program```. Returns whether anything changed.
+    /// # Errors
+    /// Will return an error if one of the passes fails
+    pub fn run(&self, program: &mut Program) -> Result<bool, CfgError> {
+        let mut changed = false;
+        for func in &mut program.functions {
+            changed |= self.run_function(func)?;
+        }
+        Ok(changed)
+    }
+}
+
+/// Trivial dead code elimination
+///
+/// Removes instructions whose result is never used anywhere in the function and assignments which are overwritten in the same block before being read, until there are none left.
+/// Only instructions without side effects are removed, so calls, memory operations, and operations which may fail at runtime like `div` are always kept.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeadCodeElimination;
+
+impl Pass for DeadCodeElimination {
+    fn name(&self) -> &'static str {
+        "dce"
+    }
+
+    fn run(&self, func: &mut Function) -> Result<bool, CfgError> {
+        transform(func, |cfg| {
+            let mut changed = false;
+            loop {
+                let used: HashSet<String> = cfg
+                    .blocks
+                    .iter()
+                    .flat_map(|b| b.instrs.iter())
+                    .flat_map(Instruction::uses)
+                    .cloned()
+                    .collect();
+                let mut removed = false;
+                for block in &mut cfg.blocks {
+                    let len = block.instrs.len();
+                    block.instrs.retain(|instr| {
+                        !is_removable(instr) || instr.dest().is_some_and(|d| used.contains(d))
+                    });
+                    removed |= block.instrs.len() != len;
+                    removed |= remove_dead_stores(&mut block.instrs);
+                }
+                if !removed {
+                    return Ok(changed);
+                }
+                changed = true;
+            }
+        })
+    }
+}
+
+/// Local value numbering with constant folding
+///
+/// Within each block, an operation which recomputes a value already held by a variable becomes a copy of that variable, an operation whose arguments are all constants becomes a constant, and each argument is replaced by the variable which first held its value.
+/// Commutative operations like `add a b` and `add b a` are given the same number.
+/// An assignment to a variable which is assigned again later in the block is renamed so that its value stays available to the rest of the block.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalValueNumbering;
+
+impl Pass for LocalValueNumbering {
+    fn name(&self) -> &'static str {
+        "lvn"
+    }
+
+    fn run(&self, func: &mut Function) -> Result<bool, CfgError> {
+        let mut names = variable_names(func);
+        transform(func, |cfg| {
+            let mut changed = false;
+            for block in &mut cfg.blocks {
+                changed |= number_block(&mut block.instrs, &mut names);
+            }
+            Ok(changed)
+        })
+    }
+}
+
+/// Global constant propagation and folding using [`ConstantPropagation`]
+///
+/// Every operation whose result is the same constant on all paths is replaced by that constant, and every `br` on a constant condition is replaced by a `jmp`.
+/// Blocks left unreachable by the latter are removed by [`UnreachableBlockElimination`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GlobalConstantPropagation;
+
+impl Pass for GlobalConstantPropagation {
+    fn name(&self) -> &'static str {
+        "const-prop"
+    }
+
+    fn run(&self, func: &mut Function) -> Result<bool, CfgError> {
+        transform(func, |cfg| {
+            let result = solve(&ConstantPropagation, cfg);
+            let mut changed = false;
+            for (block, fact) in cfg.blocks.iter_mut().zip(result.ins) {
+                let mut fact = fact;
+                for instr in &mut block.instrs {
+                    ConstantPropagation::transfer_instruction(&mut fact, instr);
+                    let replacement = match instr {
+                        Instruction::Value { dest, op_type, .. } => match fact.get(dest) {
+                            Some(ConstValue::Constant(value)) if is_representable(value) => Some(
+                                constant(dest.clone(), op_type.clone(), value.clone(), instr),
+                            ),
+                            _ => None,
+                        },
+                        Instruction::Effect {
+                            op: EffectOps::Branch,
+                            args,
+                            labels,
+                            ..
+                        } => match (args.as_slice(), labels.as_slice()) {
+                            ([cond], [on_true, on_false]) => match fact.get(cond) {
+                                Some(ConstValue::Constant(Literal::Bool(b))) => {
+                                    Some(jump(if *b { on_true } else { on_false }.clone(), instr))
+                                }
+                                _ => None,
+                            },
+                            _ => None,
+                        },
+                        Instruction::Constant { .. } | Instruction::Effect { .. } => None,
+                    };
+                    if let Some(replacement) = replacement {
+                        *instr = replacement;
+                        changed = true;
+                    }
+                }
+            }
+            if changed {
+                cfg.recompute_edges()?;
+            }
+            Ok(changed)
+        })
+    }
+}
+
+/// Global copy propagation
+///
+/// Replaces each use of a variable assigned by `id` with the source of the copy wherever the source is known to still hold the same value on every path.
+/// The copies themselves are left for [`DeadCodeElimination`] to remove.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CopyPropagation;
+
+impl Pass for CopyPropagation {
+    fn name(&self) -> &'static str {
+        "copy-prop"
+    }
+
+    fn run(&self, func: &mut Function) -> Result<bool, CfgError> {
+        transform(func, |cfg| {
+            let result = solve(&AvailableCopies, cfg);
+            let mut changed = false;
+            for (block, fact) in cfg.blocks.iter_mut().zip(result.ins) {
+                // Blocks which are never reached are left alone
+                let Some(mut copies) = fact else {
+                    continue;
+                };
+                for instr in &mut block.instrs {
+                    for arg in instr.uses_mut() {
+                        if let Some(source) = copies.get(arg) {
+                            arg.clone_from(source);
+                            changed = true;
+                        }
+                    }
+                    transfer_copies(&mut copies, instr);
+                }
+            }
+            Ok(changed)
+        })
+    }
+}
+
+/// Removes the blocks which can not be reached from the entry of the function, see [`Cfg::remove_unreachable_blocks`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnreachableBlockElimination;
+
+impl Pass for UnreachableBlockElimination {
+    fn name(&self) -> &'static str {
+        "unreachable"
+    }
+
+    fn run(&self, func: &mut Function) -> Result<bool, CfgError> {
+        transform(func, Cfg::remove_unreachable_blocks)
+    }
+}
+
+/// Runs ```f``` over the [Cfg] of ```func``` and writes the result back if it reports a change
+fn transform(
+    func: &mut Function,
+    f: impl FnOnce(&mut Cfg) -> Result<bool, CfgError>,
+) -> Result<bool, CfgError> {
+    if uses_speculation(func) {
+        return Ok(false);
+    }
+    let mut cfg = Cfg::new(func.clone())?;
+    let changed = f(&mut cfg)?;
+    if changed {
+        *func = cfg.into();
+    }
+    Ok(changed)
+}
+
+fn uses_speculation(func: &Function) -> bool {
+    func.instrs.iter().any(|code| {
+        matches!(
+            code,
+            Code::Instruction(Instruction::Effect {
+                op: EffectOps::Speculate,
+                ..
+            })
+        )
+    })
+}
+
+/// Every variable name which appears in ```func```, so that new names do not clash with them
+fn variable_names(func: &Function) -> HashSet<String> {
+    let mut names: HashSet<String> = func.args.iter().map(|a| a.name.clone()).collect();
+    for code in &func.instrs {
+        if let Code::Instruction(instr) = code {
+            names.extend(instr.dest().cloned());
+            names.extend(instr.uses().cloned());
+        }
+    }
+    names
+}
+
+/// Whether removing ```instr``` can not change the behaviour of the program when its result is never used
+const fn is_removable(instr: &Instruction) -> bool {
+    match instr {
+        Instruction::Constant { .. } => true,
+        Instruction::Value { op, .. } => is_pure(*op) && !may_fail(*op),
+        Instruction::Effect { .. } => false,
+    }
+}
+
+/// Whether ```op``` is an error for some values of its arguments
+const fn may_fail(op: ValueOps) -> bool {
+    matches!(op, ValueOps::Div | ValueOps::Int2char)
+}
+
+/// Removes the assignments in a block which are overwritten before being read. Returns whether any was removed.
+fn remove_dead_stores(instrs: &mut Vec<Instruction>) -> bool {
+    let mut dead = vec![false; instrs.len()];
+    // The assignments which have not been read yet
+    let mut unread: HashMap<&String, usize> = HashMap::new();
+    for (idx, instr) in instrs.iter().enumerate() {
+        for arg in instr.uses() {
+            unread.remove(arg);
+        }
+        if let Some(var) = instr.dest()
+            && let Some(prev) = unread.insert(var, idx)
+            && is_removable(&instrs[prev])
+        {
+            dead[prev] = true;
+        }
+    }
+    if !dead.contains(&true) {
+        return false;
+    }
+    let mut idx = 0;
+    instrs.retain(|_| {
+        idx += 1;
+        !dead[idx - 1]
+    });
+    true
+}
+
+/// The key of a value in [`LocalValueNumbering`]: either a constant or an operation over value numbers
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Value {
+    Int(i64),
+    Bool(bool),
+    // Floats are keyed by their bits so that `0.0` and `-0.0` stay different values
+    Float(u64),
+    Char(char),
+    Operation(ValueOps, Vec<usize>),
+}
+
+impl Value {
+    const fn constant(literal: &Literal) -> Self {
+        match literal {
+            Literal::Int(i) => Self::Int(*i),
+            Literal::Bool(b) => Self::Bool(*b),
+            Literal::Float(f) => Self::Float(f.to_bits()),
+            Literal::Char(c) => Self::Char(*c),
+        }
+    }
+}
+
+#[derive(Default)]
+struct ValueTable {
+    numbers: HashMap<Value, usize>,
+    // For each value number, the variable that holds it, if any still does
+    holders: Vec<Option<String>>,
+    // For each value number, its constant value if it is known
+    constants: Vec<Option<Literal>>,
+    // A `BTreeMap` so that picking a new holder is deterministic
+    variables: BTreeMap<String, usize>,
+}
+
+impl ValueTable {
+    fn new_number(&mut self, constant: Option<Literal>) -> usize {
+        self.holders.push(None);
+        self.constants.push(constant);
+        self.holders.len() - 1
+    }
+
+    /// The number of ```value```, which is new if no variable holds it anymore
+    fn number_of(&mut self, value: Value, constant: Option<Literal>) -> usize {
+        match self.numbers.get(&value) {
+            Some(n) if self.holders[*n].is_some() => *n,
+            _ => {
+                let n = self.new_number(constant);
+                self.numbers.insert(value, n);
+                n
+            }
+        }
+    }
+
+    /// The number of the value held by ```var```, which is new if ```var``` has not been assigned yet in this block
+    fn lookup(&mut self, var: &str) -> usize {
+        if let Some(n) = self.variables.get(var) {
+            return *n;
+        }
+        let n = self.new_number(None);
+        self.assign(var, n);
+        n
+    }
+
+    /// The variable holding the value ```var``` holds
+    fn holder(&mut self, var: &str) -> String {
+        let n = self.lookup(var);
+        self.holders[n].clone().unwrap()
+    }
+
+    fn assign(&mut self, var: &str, number: usize) {
+        if let Some(old) = self.variables.insert(var.to_string(), number)
+            && old != number
+            && self.holders[old].as_deref() == Some(var)
+        {
+            self.holders[old] = self
+                .variables
+                .iter()
+                .find(|(_, n)| **n == old)
+                .map(|(v, _)| v.clone());
+        }
+        if self.holders[number].is_none() {
+            self.holders[number] = Some(var.to_string());
+        }
+    }
+}
+
+/// Runs [`LocalValueNumbering`] over the instructions of a single block. Returns whether anything changed.
+fn number_block(instrs: &mut [Instruction], names: &mut HashSet<String>) -> bool {
+    let mut table = ValueTable::default();
+    let mut changed = false;
+    let mut last_assignment = HashMap::new();
+    for (idx, instr) in instrs.iter().enumerate() {
+        if let Some(var) = instr.dest() {
+            last_assignment.insert(var.clone(), idx);
+        }
+    }
+
+    for (idx, instr) in instrs.iter_mut().enumerate() {
+        for arg in instr.uses_mut() {
+            let holder = table.holder(arg);
+            if *arg != holder {
+                *arg = holder;
+                changed = true;
+            }
+        }
+
+        let number = match instr {
+            Instruction::Constant {
+                const_type, value, ..
+            } => {
+                let literal = normalize_literal(value, const_type);
+                table.number_of(Value::constant(&literal), Some(literal))
+            }
+            Instruction::Value {
+                op, args, funcs, ..
+            } if funcs.is_empty() && is_pure(*op) => {
+                let numbers: Vec<usize> = args.iter().map(|a| table.lookup(a)).collect();
+                let folded = numbers
+                    .iter()
+                    .map(|n| table.constants[*n].clone())
+                    .collect::<Option<Vec<_>>>()
+                    .and_then(|literals| fold_value_op(*op, &literals));
+                let number = match (folded, numbers.as_slice()) {
+                    (Some(literal), _) => table.number_of(Value::constant(&literal), Some(literal)),
+                    (None, [n]) if *op == ValueOps::Id => *n,
+                    (None, _) => {
+                        let mut numbers = numbers;
+                        if is_commutative(*op) {
+                            numbers.sort_unstable();
+                        }
+                        table.number_of(Value::Operation(*op, numbers), None)
+                    }
+                };
+
+                let replacement = match (&table.constants[number], &table.holders[number]) {
+                    (Some(literal), _) if is_representable(literal) => Some(constant(
+                        instr.dest().unwrap().clone(),
+                        op_type(instr).clone(),
+                        literal.clone(),
+                        instr,
+                    )),
+                    (_, Some(holder))
+                        if !matches!(
+                            instr,
+                            Instruction::Value { op: ValueOps::Id, args, .. } if args == std::slice::from_ref(holder)
+                        ) =>
+                    {
+                        Some(copy(
+                            instr.dest().unwrap().clone(),
+                            op_type(instr).clone(),
+                            holder.clone(),
+                            instr,
+                        ))
+                    }
+                    _ => None,
+                };
+                if let Some(replacement) = replacement {
+                    *instr = replacement;
+                    changed = true;
+                }
+                number
+            }
+            Instruction::Value { .. } => table.new_number(None),
+            Instruction::Effect { .. } => continue,
+        };
+
+        let (Instruction::Constant { dest, .. } | Instruction::Value { dest, .. }) = instr else {
+            unreachable!()
+        };
+        if last_assignment[dest.as_str()] == idx {
+            table.assign(dest, number);
+        } else {
+            // The original name still refers to this value until it is assigned again
+            let renamed = fresh_name(&format!("{dest}.lvn"), names);
+            table.assign(&renamed, number);
+            table.assign(dest, number);
+            *dest = renamed;
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// Whether the arguments of ```op``` can be swapped without changing its result
+const fn is_commutative(op: ValueOps) -> bool {
+    matches!(
+        op,
+        ValueOps::Add
+            | ValueOps::Mul
+            | ValueOps::Eq
+            | ValueOps::And
+            | ValueOps::Or
+            | ValueOps::Fadd
+            | ValueOps::Fmul
+            | ValueOps::Feq
+            | ValueOps::Ceq
+    )
+}
+
+/// A forward analysis of which variables are known to hold the same value as the variable they were copied from with `id`
+///
+/// The fact maps each such variable to its source, and is [None] for blocks the analysis has not reached yet.
+struct AvailableCopies;
+
+impl Analysis for AvailableCopies {
+    type Fact = Option<BTreeMap<String, String>>;
+
+    const DIRECTION: Direction = Direction::Forward;
+
+    fn init(&self, _cfg: &Cfg) -> Self::Fact {
+        None
+    }
+
+    fn boundary(&self, _cfg: &Cfg) -> Self::Fact {
+        Some(BTreeMap::new())
+    }
+
+    fn join(&self, a: &Self::Fact, b: &Self::Fact) -> Self::Fact {
+        match (a, b) {
+            (Some(a), Some(b)) => Some(
+                a.iter()
+                    .filter(|(var, source)| b.get(*var) == Some(source))
+                    .map(|(var, source)| (var.clone(), source.clone()))
+                    .collect(),
+            ),
+            (None, fact) | (fact, None) => fact.clone(),
+        }
+    }
+
+    fn transfer(&self, cfg: &Cfg, block: usize, input: &Self::Fact) -> Self::Fact {
+        input.clone().map(|mut copies| {
+            for instr in &cfg.blocks[block].instrs {
+                transfer_copies(&mut copies, instr);
+            }
+            copies
+        })
+    }
+}
+
+fn transfer_copies(copies: &mut BTreeMap<String, String>, instr: &Instruction) {
+    let Some(var) = instr.dest() else {
+        return;
+    };
+    // Sources are resolved through earlier copies so that no source is itself a copy
+    let source = match instr {
+        Instruction::Value {
+            op: ValueOps::Id,
+            args,
+            funcs,
+            ..
+        } if funcs.is_empty() && args.len() == 1 => {
+            Some(copies.get(&args[0]).unwrap_or(&args[0]).clone())
+        }
+        _ => None,
+    };
+    copies.remove(var);
+    copies.retain(|_, s| s != var);
+    if let Some(source) = source.filter(|s| s != var) {
+        copies.insert(var.clone(), source);
+    }
+}
+
+/// Whether ```literal``` can be written as the value of a `const`. JSON has no way to write infinite or `NaN` floats.
+const fn is_representable(literal: &Literal) -> bool {
+    match literal {
+        Literal::Float(f) => f.is_finite(),
+        _ => true,
+    }
+}
+
+const fn op_type(instr: &Instruction) -> &Type {
+    match instr {
+        Instruction::Constant { const_type, .. } => const_type,
+        Instruction::Value { op_type, .. } => op_type,
+        Instruction::Effect { .. } => unreachable!(),
+    }
+}
+
+fn constant(dest: String, const_type: Type, value: Literal, original: &Instruction) -> Instruction {
+    Instruction::Constant {
+        dest,
+        op: ConstOps::Const,
+        pos: original.get_pos(),
+        comments: original.get_comments(),
+        const_type,
+        value,
+    }
+}
+
+/// Creates `dest: op_type = id src`, keeping the source position and comments of the ```original``` instruction it replaces
+pub(crate) fn copy(
+    dest: String,
+    op_type: Type,
+    src: String,
+    original: &Instruction,
+) -> Instruction {
+    Instruction::Value {
+        args: vec![src],
+        dest,
+        funcs: Vec::new(),
+        labels: Vec::new(),
+        op: ValueOps::Id,
+        pos: original.get_pos(),
+        comments: original.get_comments(),
+        op_type,
+    }
+}
+
+fn jump(target: String, original: &Instruction) -> Instruction {
+    Instruction::Effect {
+        args: Vec::new(),
+        funcs: Vec::new(),
+        labels: vec![target],
+        op: EffectOps::Jump,
+        pos: original.get_pos(),
+        comments: original.get_comments(),
+    }
+}
@@ -6,15 +6,13 @@ use std::{
 
 use serde::{Deserialize, Serialize};
 
-use crate::conversion::ConversionError;
+use crate::conversion::{ConversionError, suggest};
 
 /// Equivalent to a file of bril code
-#[cfg_attr(not(feature = "float"), derive(Eq))]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Program {
     /// A list of functions declared in the program
     pub functions: Vec<Function>,
-    #[cfg(feature = "import")]
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     /// A list of imports for this program
     pub imports: Vec<Import>,
@@ -22,7 +20,6 @@ pub struct Program {
 
 impl Display for Program {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        #[cfg(feature = "import")]
         for i in &self.imports {
             writeln!(f, "{i}")?;
         }
@@ -34,9 +31,11 @@ impl Display for Program {
 }
 
 /// <https://capra.cs.cornell.edu/bril/lang/import.html#syntax>
-#[cfg(feature = "import")]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Import {
+    /// The `#` comments written around this import in the original source code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comments: Option<Box<Comments>>,
     /// A list of functions to be imported
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub functions: Vec<ImportedFunction>,
@@ -44,9 +43,10 @@ pub struct Import {
     pub path: std::path::PathBuf,
 }
 
-#[cfg(feature = "import")]
 impl Display for Import {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let comments = self.comments.as_deref();
+        write_leading_comments(f, comments, "")?;
         write!(f, "from {}", self.path.display())?;
         if !self.functions.is_empty() {
             write!(f, " import ")?;
@@ -58,12 +58,11 @@ impl Display for Import {
             }
         }
         write!(f, ";")?;
-        Ok(())
+        write_trailing_comment(f, comments)
     }
 }
 
 /// <https://capra.cs.cornell.edu/bril/lang/import.html#syntax>
-#[cfg(feature = "import")]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct ImportedFunction {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -73,7 +72,6 @@ pub struct ImportedFunction {
     pub name: String,
 }
 
-#[cfg(feature = "import")]
 impl Display for ImportedFunction {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.name)?;
@@ -85,19 +83,20 @@ impl Display for ImportedFunction {
 }
 
 /// <https://capra.cs.cornell.edu/bril/lang/syntax.html#function>
-#[cfg_attr(not(feature = "float"), derive(Eq))]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Function {
     /// Any arguments the function accepts
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub args: Vec<Argument>,
+    /// The `#` comments written around this function in the original source code
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comments: Option<Box<Comments>>,
     /// The instructions of this function
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub instrs: Vec<Code>,
     /// The name of the function
     pub name: String,
     /// The position of this function in the original source code
-    #[cfg(feature = "position")]
     #[serde(flatten, skip_serializing_if = "Option::is_none")]
     pub pos: Option<Position>,
     /// The possible return type of this function
@@ -108,6 +107,8 @@ pub struct Function {
 
 impl Display for Function {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let comments = self.comments.as_deref();
+        write_leading_comments(f, comments, "")?;
         write!(f, "@{}", self.name)?;
         if !self.args.is_empty() {
             write!(f, "(")?;
@@ -122,15 +123,54 @@ impl Display for Function {
         if let Some(tpe) = self.return_type.as_ref() {
             write!(f, ": {tpe}")?;
         }
-        writeln!(f, " {{")?;
+        write!(f, " {{")?;
+        write_trailing_comment(f, comments)?;
+        writeln!(f)?;
         for instr in &self.instrs {
             writeln!(f, "{instr}")?;
         }
+        write_closing_comments(f, comments)?;
         write!(f, "}}")?;
+        for comment in comments.iter().flat_map(|c| &c.after) {
+            write!(f, "\n#{comment}")?;
+        }
         Ok(())
     }
 }
 
+/// Writes each leading comment on its own line, indented like the code they are attached to
+pub(crate) fn write_leading_comments(
+    f: &mut Formatter<'_>,
+    comments: Option<&Comments>,
+    indent: &str,
+) -> fmt::Result {
+    for comment in comments.iter().flat_map(|c| &c.leading) {
+        writeln!(f, "{indent}#{comment}")?;
+    }
+    Ok(())
+}
+
+/// Writes the trailing comment, if any, at the end of the current line
+pub(crate) fn write_trailing_comment(
+    f: &mut Formatter<'_>,
+    comments: Option<&Comments>,
+) -> fmt::Result {
+    comments
+        .and_then(|c| c.trailing.as_ref())
+        .map_or(Ok(()), |comment| write!(f, "  #{comment}"))
+}
+
+/// Writes the comments which come before the closing brace of a function
+pub(crate) fn write_closing_comments(
+    f: &mut Formatter<'_>,
+    comments: Option<&Comments>,
+) -> fmt::Result {
+    for comment in comments.iter().flat_map(|c| &c.closing) {
+        writeln!(f, "  #{comment}")?;
+    }
+    Ok(())
+}
+
 /// An argument of a function
 /// <https://capra.cs.cornell.edu/bril/lang/syntax.html#function>
 /// Example: a : int
@@ -151,16 +191,17 @@ impl Display for Argument {
 
 /// <https://capra.cs.cornell.edu/bril/lang/syntax.html#function>
 /// Code is a Label or an Instruction
-#[cfg_attr(not(feature = "float"), derive(Eq))]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum Code {
     /// <https://capra.cs.cornell.edu/bril/lang/syntax.html#label>
     Label {
+        /// The `#` comments written around the label in source code
+        #[serde(skip_serializing_if = "Option::is_none")]
+        comments: Option<Box<Comments>>,
         /// The name of the label
         label: String,
         /// Where the label is located in source code
-        #[cfg(feature = "position")]
         #[serde(flatten, skip_serializing_if = "Option::is_none")]
         pos: Option<Position>,
     },
@@ -172,27 +213,36 @@ impl Display for Code {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             Self::Label {
-                label,
-                #[cfg(feature = "position")]
-                    pos: _,
-            } => write!(f, ".{label}:"),
-            Self::Instruction(instr) => write!(f, "  {instr}"),
+                label, comments, ..
+            } => {
+                let comments = comments.as_deref();
+                write_leading_comments(f, comments, "")?;
+                write!(f, ".{label}:")?;
+                write_trailing_comment(f, comments)
+            }
+            Self::Instruction(instr) => {
+                let comments = instr.comments();
+                write_leading_comments(f, comments, "  ")?;
+                write!(f, "  {instr}")?;
+                write_trailing_comment(f, comments)
+            }
         }
     }
 }
 
 /// <https://capra.cs.cornell.edu/bril/lang/syntax.html#instruction>
-#[cfg_attr(not(feature = "float"), derive(Eq))]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum Instruction {
     /// <https://capra.cs.cornell.edu/bril/lang/syntax.html#constant>
     Constant {
+        /// The `#` comments written around the instruction if provided
+        #[serde(skip_serializing_if = "Option::is_none")]
+        comments: Option<Box<Comments>>,
         /// destination variable
         dest: String,
         /// "const"
         op: ConstOps,
-        #[cfg(feature = "position")]
         /// The source position of the instruction if provided
         #[serde(flatten, skip_serializing_if = "Option::is_none")]
         pos: Option<Position>,
@@ -207,6 +257,9 @@ pub enum Instruction {
         /// List of variables as arguments
         #[serde(default, skip_serializing_if = "Vec::is_empty")]
         args: Vec<String>,
+        /// The `#` comments written around the instruction if provided
+        #[serde(skip_serializing_if = "Option::is_none")]
+        comments: Option<Box<Comments>>,
         /// destination variable
         dest: String,
         /// List of strings as function names
@@ -218,7 +271,6 @@ pub enum Instruction {
         /// Operation being executed
         op: ValueOps,
         /// The source position of the instruction if provided
-        #[cfg(feature = "position")]
         #[serde(flatten, skip_serializing_if = "Option::is_none")]
         pos: Option<Position>,
         /// Type of variable
@@ -230,6 +282,9 @@ pub enum Instruction {
         /// List of variables as arguments
         #[serde(default, skip_serializing_if = "Vec::is_empty")]
         args: Vec<String>,
+        /// The `#` comments written around the instruction if provided
+        #[serde(skip_serializing_if = "Option::is_none")]
+        comments: Option<Box<Comments>>,
         /// List of strings as function names
         #[serde(default, skip_serializing_if = "Vec::is_empty")]
         funcs: Vec<String>,
@@ -239,13 +294,11 @@ pub enum Instruction {
         /// Operation being executed
         op: EffectOps,
         /// The source position of the instruction if provided
-        #[cfg(feature = "position")]
         #[serde(flatten, skip_serializing_if = "Option::is_none")]
         pos: Option<Position>,
     },
 }
 
-#[cfg(feature = "position")]
 impl Instruction {
     /// A helper function to extract the position value if it exists from an instruction
     #[must_use]
@@ -256,6 +309,34 @@ impl Instruction {
             }
         }
     }
+
+    /// The comments attached to the instruction
+    fn comments(&self) -> Option<&Comments> {
+        match self {
+            Self::Constant { comments, .. }
+            | Self::Value { comments, .. }
+            | Self::Effect { comments, .. } => comments.as_deref(),
+        }
+    }
+
+    /// A helper function to extract the comments of an instruction if it has any
+    #[must_use]
+    pub fn get_comments(&self) -> Option<Box<Comments>> {
+        match self {
+            Self::Constant { comments, .. }
+            | Self::Value { comments, .. }
+            | Self::Effect { comments, .. } => comments.clone(),
+        }
+    }
+
+    /// Replaces the position of the instruction with ```new_pos```
+    pub fn set_pos(&mut self, new_pos: Option<Position>) {
+        match self {
+            Self::Constant { pos, .. } | Self::Value { pos, .. } | Self::Effect { pos, .. } => {
+                *pos = new_pos;
+            }
+        }
+    }
 }
 
 impl Display for Instruction {
@@ -266,8 +347,8 @@ impl Display for Instruction {
                 dest,
                 const_type,
                 value,
-                #[cfg(feature = "position")]
-                    pos: _,
+                pos: _,
+                comments: _,
             } => {
                 write!(f, "{dest}: {const_type} = {op} {value};")
             }
@@ -278,8 +359,8 @@ impl Display for Instruction {
                 args,
                 funcs,
                 labels,
-                #[cfg(feature = "position")]
-                    pos: _,
+                pos: _,
+                comments: _,
             } => {
                 write!(f, "{dest}: {op_type} = {op}")?;
                 for func in funcs {
@@ -298,8 +379,8 @@ impl Display for Instruction {
                 args,
                 funcs,
                 labels,
-                #[cfg(feature = "position")]
-                    pos: _,
+                pos: _,
+                comments: _,
             } => {
                 write!(f, "{op}")?;
                 for func in funcs {
@@ -318,7 +399,7 @@ impl Display for Instruction {
 }
 
 /// <https://capra.cs.cornell.edu/bril/lang/syntax.html#constant>
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum ConstOps {
     /// "const"
     #[serde(rename = "const")]
@@ -334,7 +415,7 @@ impl Display for ConstOps {
 }
 
 /// <https://capra.cs.cornell.edu/bril/lang/syntax.html#effect-operation>
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
 pub enum EffectOps {
     /// <https://capra.cs.cornell.edu/bril/lang/core.html#control>
@@ -353,25 +434,37 @@ pub enum EffectOps {
     /// <https://capra.cs.cornell.edu/bril/lang/core.html#miscellaneous>
     Nop,
     /// <https://capra.cs.cornell.edu/bril/lang/memory.html#operations>
-    #[cfg(feature = "memory")]
     Store,
     /// <https://capra.cs.cornell.edu/bril/lang/memory.html#operations>
-    #[cfg(feature = "memory")]
     Free,
     /// <https://capra.cs.cornell.edu/bril/lang/spec.html#operations>
-    #[cfg(feature = "speculate")]
     Speculate,
     /// <https://capra.cs.cornell.edu/bril/lang/spec.html#operations>
-    #[cfg(feature = "speculate")]
     Commit,
     /// <https://capra.cs.cornell.edu/bril/lang/spec.html#operations>
-    #[cfg(feature = "speculate")]
     Guard,
     /// <https://capra.cs.cornell.edu/bril/lang/ssa2.html#operations>
-    #[cfg(feature = "ssa")]
     Set,
 }
 
+impl EffectOps {
+    /// Every operation, in the order they are declared
+    pub const ALL: [Self; 12] = [
+        Self::Jump,
+        Self::Branch,
+        Self::Call,
+        Self::Return,
+        Self::Print,
+        Self::Nop,
+        Self::Store,
+        Self::Free,
+        Self::Speculate,
+        Self::Commit,
+        Self::Guard,
+        Self::Set,
+    ];
+}
+
 impl Display for EffectOps {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
@@ -381,17 +474,11 @@ impl Display for EffectOps {
             Self::Return => write!(f, "ret"),
             Self::Print => write!(f, "print"),
             Self::Nop => write!(f, "nop"),
-            #[cfg(feature = "memory")]
             Self::Store => write!(f, "store"),
-            #[cfg(feature = "memory")]
             Self::Free => write!(f, "free"),
-            #[cfg(feature = "speculate")]
             Self::Speculate => write!(f, "speculate"),
-            #[cfg(feature = "speculate")]
             Self::Commit => write!(f, "commit"),
-            #[cfg(feature = "speculate")]
             Self::Guard => write!(f, "guard"),
-            #[cfg(feature = "ssa")]
             Self::Set => write!(f, "set"),
         }
     }
@@ -408,25 +495,22 @@ impl FromStr for EffectOps {
             "ret" => Self::Return,
             "print" => Self::Print,
             "nop" => Self::Nop,
-            #[cfg(feature = "memory")]
             "store" => Self::Store,
-            #[cfg(feature = "memory")]
             "free" => Self::Free,
-            #[cfg(feature = "speculate")]
             "speculate" => Self::Speculate,
-            #[cfg(feature = "speculate")]
             "commit" => Self::Commit,
-            #[cfg(feature = "speculate")]
             "guard" => Self::Guard,
-            #[cfg(feature = "ssa")]
             "set" => Self::Set,
-            e => Err(ConversionError::InvalidEffectOps(e.to_string()))?,
+            e => Err(ConversionError::InvalidEffectOps {
+                found: e.to_string(),
+                suggestion: suggest(e, Self::ALL.map(|op| op.to_string())),
+            })?,
         })
     }
 }
 
 /// <https://capra.cs.cornell.edu/bril/lang/syntax.html#value-operation>
-#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
 pub enum ValueOps {
     /// <https://capra.cs.cornell.edu/bril/lang/core.html#arithmetic>
@@ -458,76 +542,96 @@ pub enum ValueOps {
     /// <https://capra.cs.cornell.edu/bril/lang/core.html#miscellaneous>
     Id,
     /// <https://capra.cs.cornell.edu/bril/lang/ssa2.html#operations>
-    #[cfg(feature = "ssa")]
     Get,
     /// <https://capra.cs.cornell.edu/bril/lang/ssa2.html#operations>
-    #[cfg(feature = "ssa")]
     Undef,
     /// <https://capra.cs.cornell.edu/bril/lang/float.html#operations>
-    #[cfg(feature = "float")]
     Fadd,
     /// <https://capra.cs.cornell.edu/bril/lang/float.html#operations>
-    #[cfg(feature = "float")]
     Fsub,
     /// <https://capra.cs.cornell.edu/bril/lang/float.html#operations>
-    #[cfg(feature = "float")]
     Fmul,
     /// <https://capra.cs.cornell.edu/bril/lang/float.html#operations>
-    #[cfg(feature = "float")]
     Fdiv,
     /// <https://capra.cs.cornell.edu/bril/lang/float.html#operations>
-    #[cfg(feature = "float")]
     Feq,
     /// <https://capra.cs.cornell.edu/bril/lang/float.html#operations>
-    #[cfg(feature = "float")]
     Flt,
     /// <https://capra.cs.cornell.edu/bril/lang/float.html#operations>
-    #[cfg(feature = "float")]
     Fgt,
     /// <https://capra.cs.cornell.edu/bril/lang/float.html#operations>
-    #[cfg(feature = "float")]
     Fle,
     /// <https://capra.cs.cornell.edu/bril/lang/float.html#operations>
-    #[cfg(feature = "float")]
     Fge,
     /// <https://capra.cs.cornell.edu/bril/lang/char.html#operations>
-    #[cfg(feature = "char")]
     Ceq,
     /// <https://capra.cs.cornell.edu/bril/lang/char.html#operations>
-    #[cfg(feature = "char")]
     Clt,
     /// <https://capra.cs.cornell.edu/bril/lang/char.html#operations>
-    #[cfg(feature = "char")]
     Cgt,
     /// <https://capra.cs.cornell.edu/bril/lang/char.html#operations>
-    #[cfg(feature = "char")]
     Cle,
     /// <https://capra.cs.cornell.edu/bril/lang/char.html#operations>
-    #[cfg(feature = "char")]
     Cge,
     /// <https://capra.cs.cornell.edu/bril/lang/char.html#operations>
-    #[cfg(feature = "char")]
     Char2int,
     /// <https://capra.cs.cornell.edu/bril/lang/char.html#operations>
-    #[cfg(feature = "char")]
     Int2char,
     /// <https://capra.cs.cornell.edu/bril/lang/memory.html#operations>
-    #[cfg(feature = "memory")]
     Alloc,
     /// <https://capra.cs.cornell.edu/bril/lang/memory.html#operations>
-    #[cfg(feature = "memory")]
     Load,
     /// <https://capra.cs.cornell.edu/bril/lang/memory.html#operations>
-    #[cfg(feature = "memory")]
     PtrAdd,
     /// <https://capra.cs.cornell.edu/bril/lang/bitcast.html#operations>
-    #[cfg(feature = "bitcast")]
     Float2Bits,
     /// <https://capra.cs.cornell.edu/bril/lang/bitcast.html#operations>
-    #[cfg(feature = "bitcast")]
     Bits2Float,
 }
 
+impl ValueOps {
+    /// Every operation, in the order they are declared
+    pub const ALL: [Self; 37] = [
+        Self::Add,
+        Self::Sub,
+        Self::Mul,
+        Self::Div,
+        Self::Eq,
+        Self::Lt,
+        Self::Gt,
+        Self::Le,
+        Self::Ge,
+        Self::Not,
+        Self::And,
+        Self::Or,
+        Self::Call,
+        Self::Id,
+        Self::Get,
+        Self::Undef,
+        Self::Fadd,
+        Self::Fsub,
+        Self::Fmul,
+        Self::Fdiv,
+        Self::Feq,
+        Self::Flt,
+        Self::Fgt,
+        Self::Fle,
+        Self::Fge,
+        Self::Ceq,
+        Self::Clt,
+        Self::Cgt,
+        Self::Cle,
+        Self::Cge,
+        Self::Char2int,
+        Self::Int2char,
+        Self::Alloc,
+        Self::Load,
+        Self::PtrAdd,
+        Self::Float2Bits,
+        Self::Bits2Float,
+    ];
+}
+
 impl Display for ValueOps {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
@@ -545,51 +649,28 @@ impl Display for ValueOps {
             Self::Or => write!(f, "or"),
             Self::Call => write!(f, "call"),
             Self::Id => write!(f, "id"),
-            #[cfg(feature = "ssa")]
             Self::Get => write!(f, "get"),
-            #[cfg(feature = "ssa")]
             Self::Undef => write!(f, "undef"),
-            #[cfg(feature = "float")]
             Self::Fadd => write!(f, "fadd"),
-            #[cfg(feature = "float")]
             Self::Fsub => write!(f, "fsub"),
-            #[cfg(feature = "float")]
             Self::Fmul => write!(f, "fmul"),
-            #[cfg(feature = "float")]
             Self::Fdiv => write!(f, "fdiv"),
-            #[cfg(feature = "float")]
             Self::Feq => write!(f, "feq"),
-            #[cfg(feature = "float")]
             Self::Flt => write!(f, "flt"),
-            #[cfg(feature = "float")]
             Self::Fgt => write!(f, "fgt"),
-            #[cfg(feature = "float")]
             Self::Fle => write!(f, "fle"),
-            #[cfg(feature = "float")]
             Self::Fge => write!(f, "fge"),
-            #[cfg(feature = "char")]
             Self::Ceq => write!(f, "ceq"),
-            #[cfg(feature = "char")]
             Self::Clt => write!(f, "clt"),
-            #[cfg(feature = "char")]
             Self::Cgt => write!(f, "cgt"),
-            #[cfg(feature = "char")]
             Self::Cle => write!(f, "cle"),
-            #[cfg(feature = "char")]
             Self::Cge => write!(f, "cge"),
-            #[cfg(feature = "char")]
             Self::Char2int => write!(f, "char2int"),
-            #[cfg(feature = "char")]
             Self::Int2char => write!(f, "int2char"),
-            #[cfg(feature = "memory")]
             Self::Alloc => write!(f, "alloc"),
-            #[cfg(feature = "memory")]
             Self::Load => write!(f, "load"),
-            #[cfg(feature = "memory")]
             Self::PtrAdd => write!(f, "ptradd"),
-            #[cfg(feature = "bitcast")]
             Self::Float2Bits => write!(f, "float2bits"),
-            #[cfg(feature = "bitcast")]
             Self::Bits2Float => write!(f, "bits2float"),
         }
     }
@@ -614,53 +695,33 @@ impl FromStr for ValueOps {
             "call" => Self::Call,
             "id" => Self::Id,
             "sub" => Self::Sub,
-            #[cfg(feature = "ssa")]
             "get" => Self::Get,
-            #[cfg(feature = "ssa")]
             "undef" => Self::Undef,
-            #[cfg(feature = "float")]
             "fadd" => Self::Fadd,
-            #[cfg(feature = "float")]
             "fsub" => Self::Fsub,
-            #[cfg(feature = "float")]
             "fmul" => Self::Fmul,
-            #[cfg(feature = "float")]
             "fdiv" => Self::Fdiv,
-            #[cfg(feature = "float")]
             "feq" => Self::Feq,
-            #[cfg(feature = "float")]
             "flt" => Self::Flt,
-            #[cfg(feature = "float")]
             "fgt" => Self::Fgt,
-            #[cfg(feature = "float")]
             "fle" => Self::Fle,
-            #[cfg(feature = "float")]
             "fge" => Self::Fge,
-            #[cfg(feature = "char")]
             "ceq" => Self::Ceq,
-            #[cfg(feature = "char")]
             "clt" => Self::Clt,
-            #[cfg(feature = "char")]
             "cgt" => Self::Cgt,
-            #[cfg(feature = "char")]
             "cle" => Self::Cle,
-            #[cfg(feature = "char")]
             "cge" => Self::Cge,
-            #[cfg(feature = "char")]
             "char2int" => Self::Char2int,
-            #[cfg(feature = "char")]
             "int2char" => Self::Int2char,
-            #[cfg(feature = "memory")]
             "alloc" => Self::Alloc,
-            #[cfg(feature = "memory")]
             "load" => Self::Load,
-            #[cfg(feature = "memory")]
             "ptradd" => Self::PtrAdd,
-            #[cfg(feature = "bitcast")]
             "bits2float" => Self::Bits2Float,
-            #[cfg(feature = "bitcast")]
             "float2bits" => Self::Float2Bits,
-            v => Err(ConversionError::InvalidValueOps(v.to_string()))?,
+            v => Err(ConversionError::InvalidValueOps {
+                found: v.to_string(),
+                suggestion: suggest(v, Self::ALL.map(|op| op.to_string())),
+            })?,
         })
     }
 }
@@ -674,17 +735,13 @@ pub enum Type {
     /// <https://capra.cs.cornell.edu/bril/lang/core.html#types>
     Bool,
     /// <https://capra.cs.cornell.edu/bril/lang/float.html#types>
-    #[cfg(feature = "float")]
     Float,
     /// <https://capra.cs.cornell.edu/bril/lang/char.html#types>
-    #[cfg(feature = "char")]
     Char,
     /// <https://capra.cs.cornell.edu/bril/lang/memory.html#types>
-    #[cfg(feature = "memory")]
     #[serde(rename = "ptr")]
     Pointer(Box<Self>),
     /// <https://capra.cs.cornell.edu/bril/lang/dynamic.html#types>]
-    #[cfg(feature = "dynamic")]
     #[serde(rename = "any")]
     Any,
 }
@@ -694,13 +751,9 @@ impl Display for Type {
         match self {
             Self::Int => write!(f, "int"),
             Self::Bool => write!(f, "bool"),
-            #[cfg(feature = "float")]
             Self::Float => write!(f, "float"),
-            #[cfg(feature = "char")]
             Self::Char => write!(f, "char"),
-            #[cfg(feature = "memory")]
             Self::Pointer(tpe) => write!(f, "ptr<{tpe}>"),
-            #[cfg(feature = "dynamic")]
             Self::Any => write!(f, "any"),
         }
     }
@@ -713,19 +766,18 @@ impl FromStr for Type {
         match s {
             "int" => Ok(Self::Int),
             "bool" => Ok(Self::Bool),
-            #[cfg(feature = "float")]
             "float" => Ok(Self::Float),
-            #[cfg(feature = "char")]
             "char" => Ok(Self::Char),
-            #[cfg(feature = "dynamic")]
             "any" => Ok(Self::Any),
-            _ => Err(ConversionError::InvalidPrimitive(s.to_string())),
+            _ => Err(ConversionError::InvalidPrimitive {
+                found: s.to_string(),
+                suggestion: suggest(s, ["int", "bool", "float", "char", "any"]),
+            }),
         }
     }
 }
 
 /// A JSON number/value
-#[cfg_attr(not(feature = "float"), derive(Eq, Hash))]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(untagged)]
 pub enum Literal {
@@ -734,10 +786,8 @@ pub enum Literal {
     /// Booleans
     Bool(bool),
     /// Floating Points
-    #[cfg(feature = "float")]
     Float(f64),
     /// UTF-16 Characters
-    #[cfg(feature = "char")]
     Char(char),
 }
 
@@ -746,15 +796,12 @@ impl Display for Literal {
         match self {
             Self::Int(i) => write!(f, "{i}"),
             Self::Bool(b) => write!(f, "{b}"),
-            #[cfg(feature = "float")]
             Self::Float(x) => write!(f, "{x}"),
-            #[cfg(feature = "char")]
             Self::Char(c) => write!(f, "\'{}\'", escape_char(*c)),
         }
     }
 }
 
-#[cfg(feature = "char")]
 fn escape_char(c: char) -> String {
     match c {
         '\u{0000}' => "\\0".to_string(),
@@ -776,9 +823,7 @@ impl Literal {
         match self {
             Self::Int(_) => Type::Int,
             Self::Bool(_) => Type::Bool,
-            #[cfg(feature = "float")]
             Self::Float(_) => Type::Float,
-            #[cfg(feature = "char")]
             Self::Char(_) => Type::Char,
         }
     }
@@ -795,6 +840,53 @@ pub struct Position {
     /// Optional absolute path to source file
     #[serde(skip_serializing_if = "Option::is_none")]
     pub src: Option<String>,
+    /// Optional byte offsets of the code in the source file, which unlike rows and columns can be used to slice the source text directly
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub byte_span: Option<ByteSpan>,
+    /// Optional position of the code this was derived from, like the instruction of a callee which was inlined at this position.
+    /// Following it gives the chain of origins, see [`Position::origins`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origin: Option<Box<Self>>,
+}
+
+/// The byte offsets of a syntax object in its source file, see [`Position::byte_span`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteSpan {
+    /// The offset of the first byte
+    pub start: u64,
+    /// The offset just past the last byte
+    pub end: u64,
+}
+
+/// The `#` comments attached to an import, function, label, or instruction in the text format
+///
+/// Each comment is the text after its `#`, up to the end of the line.
+/// They are kept apart from the [`Position`] of the code, so that they are kept by passes which drop or replace positions, and do not need positions to be recorded at all.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct Comments {
+    /// The comments on their own lines just before the code
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub leading: Vec<String>,
+    /// The comment at the end of the line the code ends on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trailing: Option<String>,
+    /// For a function, the comments on their own lines after its last instruction, before the closing brace
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub closing: Vec<String>,
+    /// For the last function of a file, the comments after its closing brace
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub after: Vec<String>,
+}
+
+impl Comments {
+    /// Whether there are no comments at all
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.leading.is_empty()
+            && self.trailing.is_none()
+            && self.closing.is_empty()
+            && self.after.is_empty()
+    }
 }
 
 /// <https://capra.cs.cornell.edu/bril/lang/syntax.html#source-positions>
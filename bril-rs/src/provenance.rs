@@ -0,0 +1,225 @@
+use std::borrow::Borrow;
+use std::fmt::Display;
+
+use crate::{ByteSpan, Code, ColRow, Function, Position, Program};
+
+impl Position {
+    /// The smallest position which covers both ```self``` and ```other```, keeping the origin of ```self```.
+    ///
+    /// A single span can not cover code from two source files, so ```self``` is returned unchanged when ```other``` has a different `src`.
+    #[must_use]
+    pub fn span(&self, other: &Self) -> Self {
+        if self.src != other.src {
+            return self.clone();
+        }
+        let key = |c: &ColRow| (c.row, c.col);
+        let start = std::cmp::min_by_key(self.pos, other.pos, key);
+        let end = std::cmp::max_by_key(self.end(), other.end(), key);
+        Self {
+            pos: start,
+            pos_end: (end != start || self.pos_end.is_some() || other.pos_end.is_some())
+                .then_some(end),
+            src: self.src.clone(),
+            byte_span: match (self.byte_span, other.byte_span) {
+                (Some(a), Some(b)) => Some(ByteSpan {
+                    start: a.start.min(b.start),
+                    end: a.end.max(b.end),
+                }),
+                _ => None,
+            },
+            origin: self.origin.clone(),
+        }
+    }
+
+    /// Returns a copy of ```self``` whose chain of origins ends with ```origin```
+    #[must_use]
+    pub fn derived_from(&self, origin: Self) -> Self {
+        let mut derived = self.clone();
+        derived.push_origin(origin);
+        derived
+    }
+
+    fn push_origin(&mut self, origin: Self) {
+        if let Some(next) = self.origin.as_deref_mut() {
+            next.push_origin(origin);
+        } else {
+            self.origin = Some(Box::new(origin));
+        }
+    }
+
+    /// The chain of origins of ```self```, starting with the code it was directly derived from
+    pub fn origins(&self) -> impl Iterator<Item = &Self> {
+        std::iter::successors(self.origin.as_deref(), |p| p.origin.as_deref())
+    }
+
+    const fn end(&self) -> ColRow {
+        match self.pos_end {
+            Some(end) => end,
+            None => self.pos,
+        }
+    }
+}
+
+/// The [`Position::span`] of every one of ```positions```, or [None] if there are none
+///
+/// This is the position of code derived from several sources, like an instruction which replaces all of them.
+pub fn merge_positions<P: Borrow<Position>>(
+    positions: impl IntoIterator<Item = P>,
+) -> Option<Position> {
+    positions.into_iter().fold(None, |merged, p| {
+        Some(merged.map_or_else(|| p.borrow().clone(), |m: Position| m.span(p.borrow())))
+    })
+}
+
+/// The position of code placed at ```at``` which was copied from ```origin```, like an inlined instruction at its call site.
+/// If either is missing the other is used on its own.
+#[must_use]
+pub fn derive_position(at: Option<&Position>, origin: Option<&Position>) -> Option<Position> {
+    match (at, origin) {
+        (Some(at), Some(origin)) => Some(at.derived_from(origin.clone())),
+        (Some(p), None) | (None, Some(p)) => Some(p.clone()),
+        (None, None) => None,
+    }
+}
+
+/// Gives every instruction and label of ```func``` without a position the position of the closest code before it which has one.
+/// Returns how many positions were filled in.
+///
+/// Code at the start of the function takes the position of the first code which has one, or else the position of the function itself.
+/// This is a fallback for passes which can not tell where the code they add came from.
+pub fn fill_positions(func: &mut Function) -> usize {
+    let first = func
+        .instrs
+        .iter()
+        .find_map(code_pos)
+        .or_else(|| func.pos.clone());
+    let mut previous = first;
+    let mut filled = 0;
+    for code in &mut func.instrs {
+        match code_pos(code) {
+            Some(pos) => previous = Some(pos),
+            None if previous.is_some() => {
+                set_code_pos(code, previous.clone());
+                filled += 1;
+            }
+            None => {}
+        }
+    }
+    filled
+}
+
+fn code_pos(code: &Code) -> Option<Position> {
+    match code {
+        Code::Label { pos, .. } => pos.clone(),
+        Code::Instruction(instr) => instr.get_pos(),
+    }
+}
+
+fn set_code_pos(code: &mut Code, new_pos: Option<Position>) {
+    match code {
+        Code::Label { pos, .. } => *pos = new_pos,
+        Code::Instruction(instr) => instr.set_pos(new_pos),
+    }
+}
+
+/// How much of a function has source positions, as reported by [`position_coverage`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PositionCoverage {
+    /// The name of the function
+    pub function: String,
+    /// The number of instructions in the function
+    pub instructions: usize,
+    /// The number of labels in the function
+    pub labels: usize,
+    /// The instructions and labels which do not have a position, in the order they appear in
+    pub missing: Vec<Code>,
+}
+
+impl PositionCoverage {
+    /// Counts the instructions and labels of ```func``` which have a source position
+    #[must_use]
+    pub fn of(func: &Function) -> Self {
+        Self {
+            function: func.name.clone(),
+            instructions: func
+                .instrs
+                .iter()
+                .filter(|c| matches!(c, Code::Instruction(_)))
+                .count(),
+            labels: func
+                .instrs
+                .iter()
+                .filter(|c| matches!(c, Code::Label { .. }))
+                .count(),
+            missing: func
+                .instrs
+                .iter()
+                .filter(|c| code_pos(c).is_none())
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// Whether every instruction and label has a position
+    #[must_use]
+    pub const fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+
+    fn missing_labels(&self) -> usize {
+        self.missing
+            .iter()
+            .filter(|c| matches!(c, Code::Label { .. }))
+            .count()
+    }
+}
+
+impl Display for PositionCoverage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let missing_labels = self.missing_labels();
+        let missing_instructions = self.missing.len() - missing_labels;
+        writeln!(
+            f,
+            "@{}: {}/{} instructions and {}/{} labels have source positions",
+            self.function,
+            self.instructions - missing_instructions,
+            self.instructions,
+            self.labels - missing_labels,
+            self.labels
+        )?;
+        for code in &self.missing {
+            match code {
+                Code::Label { .. } => writeln!(f, "  {code}")?,
+                Code::Instruction(_) => writeln!(f, "    {code}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The [`PositionCoverage`] of each function of ```prog```
+#[must_use]
+pub fn position_coverage(prog: &Program) -> Vec<PositionCoverage> {
+    prog.functions.iter().map(PositionCoverage::of).collect()
+}
+
+/// Lints the result of running passes over ```before``` to get ```after``` for code which lost its source position
+///
+/// Returns the coverage of each function of ```after``` which has more instructions or labels without a position than the function of the same name in ```before```.
+/// Functions which are new in ```after``` are reported if they are missing any position.
+#[must_use]
+pub fn lost_positions(before: &Program, after: &Program) -> Vec<PositionCoverage> {
+    after
+        .functions
+        .iter()
+        .map(PositionCoverage::of)
+        .filter(|coverage| {
+            let previously_missing = before
+                .functions
+                .iter()
+                .find(|f| f.name == coverage.function)
+                .map_or(0, |f| PositionCoverage::of(f).missing.len());
+            coverage.missing.len() > previously_missing
+        })
+        .collect()
+}
@@ -0,0 +1,374 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::cfg::{BasicBlock, Cfg, CfgError};
+use crate::dataflow::{LiveVariables, fresh_name, solve};
+use crate::dominators::DominatorTree;
+use crate::opt::copy;
+use crate::{EffectOps, Function, Instruction, Literal, Type, ValueOps};
+
+/// Converts ```func``` into SSA form using the `set`/`get` encoding of <https://capra.cs.cornell.edu/bril/lang/ssa2.html>
+///
+/// `get`s are placed at the iterated dominance frontier of each variable's definitions, pruned to the blocks where the variable is live, and each `get` is fed by a `set` at the end of every predecessor.
+/// Every definition is renamed to `{var}.{n}` while function arguments keep their names. A variable which may be read before it is written is given an `undef` definition at the start of the function.
+///
+/// Blocks which are unreachable from the entry are removed, and an unlabeled entry block is added if the first block of the function is the target of a jump.
+/// ```func``` itself should not already contain `set` or `get` instructions, and it should not use speculation since aborting one restores variables to values which renaming can not track.
+/// # Errors
+/// Will return an error if a label is declared twice or a jump or branch targets a missing label
+pub fn to_ssa(func: Function) -> Result<Function, CfgError> {
+    let mut cfg = Cfg::new(func)?;
+    cfg.remove_unreachable_blocks()?;
+    if !cfg.predecessors[cfg.entry()].is_empty() {
+        cfg.blocks.insert(0, BasicBlock::default());
+        cfg.recompute_edges()?;
+    }
+
+    let types = variable_types(&cfg);
+    let gets = place_gets(&cfg, &types);
+
+    let mut renamer = Renamer::new(&cfg, types);
+    let (get_dests, sets) = renamer.rename(&mut cfg, &gets);
+
+    for (b, block) in cfg.blocks.iter_mut().enumerate() {
+        // `set`s take the position of the end of their block and `get`s the position of its start
+        let end_pos = block.instrs.last().and_then(Instruction::get_pos);
+        let start_pos = block
+            .label_pos
+            .clone()
+            .or_else(|| block.instrs.first().and_then(Instruction::get_pos));
+        let mut set_instrs: Vec<_> = sets[b]
+            .iter()
+            .map(|(succ, var, value)| {
+                let mut set = effect(
+                    EffectOps::Set,
+                    vec![get_dests[*succ][var].clone(), value.clone()],
+                );
+                set.set_pos(end_pos.clone());
+                set
+            })
+            .collect();
+        set_instrs.sort_by_key(ToString::to_string);
+        let end = block.instrs.len() - usize::from(block.terminator().is_some());
+        block.instrs.splice(end..end, set_instrs);
+
+        let get_instrs = get_dests[b].iter().map(|(var, dest)| Instruction::Value {
+            args: Vec::new(),
+            dest: dest.clone(),
+            funcs: Vec::new(),
+            labels: Vec::new(),
+            op: ValueOps::Get,
+            pos: start_pos.clone(),
+            comments: None,
+            op_type: renamer.types[var].clone(),
+        });
+        block.instrs.splice(0..0, get_instrs);
+    }
+
+    let undefs = renamer.inits.iter().map(|(var, dest)| Instruction::Value {
+        args: Vec::new(),
+        dest: dest.clone(),
+        funcs: Vec::new(),
+        labels: Vec::new(),
+        op: ValueOps::Undef,
+        pos: cfg.pos.clone(),
+        comments: None,
+        op_type: renamer.types[var].clone(),
+    });
+    cfg.blocks[0].instrs.splice(0..0, undefs);
+
+    Ok(cfg.into())
+}
+
+/// Lowers a function in the `set`/`get` SSA form back to ordinary copies
+///
+/// Each shadow variable `x` becomes an ordinary variable, so `set x y` becomes `x.shadow = id y` and `x = get` becomes `x = id x.shadow`.
+/// `undef` becomes a constant of the same type so the result no longer needs the `ssa` extension, except for types without a literal, like pointers, which keep their `undef`.
+#[must_use]
+pub fn from_ssa(mut func: Function) -> Function {
+    let mut types: HashMap<String, Type> = func
+        .args
+        .iter()
+        .map(|a| (a.name.clone(), a.arg_type.clone()))
+        .collect();
+    let mut names: HashSet<String> = types.keys().cloned().collect();
+    for instr in func.instrs.iter().filter_map(as_instruction) {
+        match instr {
+            Instruction::Constant {
+                dest, const_type, ..
+            } => {
+                types
+                    .entry(dest.clone())
+                    .or_insert_with(|| const_type.clone());
+                names.insert(dest.clone());
+            }
+            Instruction::Value {
+                dest, op_type, op, ..
+            } => {
+                // The type of a `get` is the type of its shadow variable, so it takes precedence
+                if *op == ValueOps::Get {
+                    types.insert(dest.clone(), op_type.clone());
+                } else {
+                    types.entry(dest.clone()).or_insert_with(|| op_type.clone());
+                }
+                names.insert(dest.clone());
+            }
+            Instruction::Effect { args, .. } => names.extend(args.iter().cloned()),
+        }
+    }
+
+    let mut shadows: HashMap<String, String> = HashMap::new();
+    let mut shadow = |var: &String| {
+        shadows
+            .entry(var.clone())
+            .or_insert_with(|| fresh_name(&format!("{var}.shadow"), &mut names))
+            .clone()
+    };
+
+    for code in &mut func.instrs {
+        let crate::Code::Instruction(instr) = code else {
+            continue;
+        };
+        let lowered = match &*instr {
+            Instruction::Effect {
+                op: EffectOps::Set,
+                args,
+                ..
+            } if args.len() == 2 => types
+                .get(&args[0])
+                .or_else(|| types.get(&args[1]))
+                .map(|op_type| copy(shadow(&args[0]), op_type.clone(), args[1].clone(), instr)),
+            Instruction::Value {
+                op: ValueOps::Get,
+                dest,
+                op_type,
+                ..
+            } => Some(copy(dest.clone(), op_type.clone(), shadow(dest), instr)),
+            Instruction::Value {
+                op: ValueOps::Undef,
+                dest,
+                op_type,
+                ..
+            } => default_literal(op_type).map(|value| Instruction::Constant {
+                dest: dest.clone(),
+                op: crate::ConstOps::Const,
+                pos: instr.get_pos(),
+                comments: instr.get_comments(),
+                const_type: op_type.clone(),
+                value,
+            }),
+            _ => None,
+        };
+        if let Some(lowered) = lowered {
+            *instr = lowered;
+        }
+    }
+    func
+}
+
+/// Whether every variable of ```func``` is assigned by at most one instruction and is not also a function argument.
+///
+/// Since a `get` assigns the variable of the same name, this also means every shadow variable is read by at most one `get`.
+#[must_use]
+pub fn is_ssa(func: &Function) -> bool {
+    let mut assigned: HashSet<&String> = func.args.iter().map(|a| &a.name).collect();
+    func.instrs
+        .iter()
+        .filter_map(as_instruction)
+        .all(|instr| instr.defs().all(|dest| assigned.insert(dest)))
+}
+
+const fn as_instruction(code: &crate::Code) -> Option<&Instruction> {
+    match code {
+        crate::Code::Instruction(i) => Some(i),
+        crate::Code::Label { .. } => None,
+    }
+}
+
+const fn effect(op: EffectOps, args: Vec<String>) -> Instruction {
+    Instruction::Effect {
+        args,
+        funcs: Vec::new(),
+        labels: Vec::new(),
+        op,
+        pos: None,
+        comments: None,
+    }
+}
+
+const fn default_literal(t: &Type) -> Option<Literal> {
+    match t {
+        Type::Int => Some(Literal::Int(0)),
+        Type::Bool => Some(Literal::Bool(false)),
+        Type::Float => Some(Literal::Float(0.0)),
+        Type::Char => Some(Literal::Char('\0')),
+        _ => None,
+    }
+}
+
+fn variable_types(cfg: &Cfg) -> HashMap<String, Type> {
+    let mut types: HashMap<String, Type> = cfg
+        .args
+        .iter()
+        .map(|a| (a.name.clone(), a.arg_type.clone()))
+        .collect();
+    for instr in cfg.blocks.iter().flat_map(|b| b.instrs.iter()) {
+        if let Instruction::Constant {
+            dest,
+            const_type: t,
+            ..
+        }
+        | Instruction::Value {
+            dest, op_type: t, ..
+        } = instr
+        {
+            types.entry(dest.clone()).or_insert_with(|| t.clone());
+        }
+    }
+    types
+}
+
+// Returns, for each block, the variables which need a `get` at the start of the block
+fn place_gets(cfg: &Cfg, types: &HashMap<String, Type>) -> Vec<BTreeSet<String>> {
+    let dom = DominatorTree::new(cfg);
+    let live = solve(&LiveVariables, cfg);
+
+    let mut defs: HashMap<&String, BTreeSet<usize>> = HashMap::new();
+    for a in &cfg.args {
+        defs.entry(&a.name).or_default().insert(cfg.entry());
+    }
+    for (b, block) in cfg.blocks.iter().enumerate() {
+        for instr in &block.instrs {
+            for dest in instr.defs() {
+                defs.entry(dest).or_default().insert(b);
+            }
+        }
+    }
+
+    let mut gets = vec![BTreeSet::new(); cfg.blocks.len()];
+    for (var, def_blocks) in defs {
+        if !types.contains_key(var) {
+            continue;
+        }
+        let mut worklist: Vec<usize> = def_blocks.iter().copied().collect();
+        while let Some(b) = worklist.pop() {
+            for d in &dom.frontier[b] {
+                if live.ins[*d].contains(var) && gets[*d].insert(var.clone()) {
+                    worklist.push(*d);
+                }
+            }
+        }
+    }
+    gets
+}
+
+struct Renamer {
+    types: HashMap<String, Type>,
+    names: HashSet<String>,
+    counters: HashMap<String, usize>,
+    stacks: HashMap<String, Vec<String>>,
+    // Variables which may be read before being written, mapped to the name of their `undef` definition
+    inits: BTreeMap<String, String>,
+}
+
+type GetDests = Vec<BTreeMap<String, String>>;
+// (successor, variable, value to send)
+type Sets = Vec<Vec<(usize, String, String)>>;
+
+impl Renamer {
+    fn new(cfg: &Cfg, types: HashMap<String, Type>) -> Self {
+        let mut names: HashSet<String> = cfg.args.iter().map(|a| a.name.clone()).collect();
+        for instr in cfg.blocks.iter().flat_map(|b| b.instrs.iter()) {
+            names.extend(instr.defs().chain(instr.uses()).cloned());
+        }
+        Self {
+            types,
+            names,
+            counters: HashMap::new(),
+            stacks: cfg
+                .args
+                .iter()
+                .map(|a| (a.name.clone(), vec![a.name.clone()]))
+                .collect(),
+            inits: BTreeMap::new(),
+        }
+    }
+
+    fn push_fresh(&mut self, var: &str, pushed: &mut Vec<String>) -> String {
+        let counter = self.counters.entry(var.to_string()).or_default();
+        let name = loop {
+            let name = format!("{var}.{counter}");
+            *counter += 1;
+            if self.names.insert(name.clone()) {
+                break name;
+            }
+        };
+        self.stacks
+            .entry(var.to_string())
+            .or_default()
+            .push(name.clone());
+        pushed.push(var.to_string());
+        name
+    }
+
+    fn peek(&mut self, var: &str) -> String {
+        if let Some(name) = self.stacks.get(var).and_then(|s| s.last()) {
+            return name.clone();
+        }
+        if !self.types.contains_key(var) {
+            // Never defined anywhere, so leave it for the interpreter to report
+            return var.to_string();
+        }
+        if let Some(name) = self.inits.get(var) {
+            return name.clone();
+        }
+        let name = fresh_name(&format!("{var}.undef"), &mut self.names);
+        self.inits.insert(var.to_string(), name.clone());
+        name
+    }
+
+    // Walks the dominator tree, renaming every definition and use, and returns the new names of the `get`s and the `set`s that feed them
+    fn rename(&mut self, cfg: &mut Cfg, gets: &[BTreeSet<String>]) -> (GetDests, Sets) {
+        let dom = DominatorTree::new(cfg);
+        let mut get_dests = vec![BTreeMap::new(); cfg.blocks.len()];
+        let mut sets = vec![Vec::new(); cfg.blocks.len()];
+        let mut pushed: Vec<Vec<String>> = vec![Vec::new(); cfg.blocks.len()];
+
+        // An explicit stack of (block, whether its subtree is done) to avoid recursion on deep dominator trees
+        let mut work = vec![(dom.root, false)];
+        while let Some((b, done)) = work.pop() {
+            if done {
+                for var in std::mem::take(&mut pushed[b]) {
+                    self.stacks.get_mut(&var).unwrap().pop();
+                }
+                continue;
+            }
+            work.push((b, true));
+
+            let mut block_pushed = Vec::new();
+            for var in &gets[b] {
+                let name = self.push_fresh(var, &mut block_pushed);
+                get_dests[b].insert(var.clone(), name);
+            }
+
+            for instr in &mut cfg.blocks[b].instrs {
+                for a in instr.uses_mut() {
+                    *a = self.peek(a);
+                }
+                for dest in instr.defs_mut() {
+                    *dest = self.push_fresh(dest, &mut block_pushed);
+                }
+            }
+
+            for s in &cfg.successors[b] {
+                for var in &gets[*s] {
+                    let value = self.peek(var);
+                    sets[b].push((*s, var.clone(), value));
+                }
+            }
+
+            pushed[b] = block_pushed;
+            work.extend(dom.children[b].iter().rev().map(|c| (*c, false)));
+        }
+        (get_dests, sets)
+    }
+}
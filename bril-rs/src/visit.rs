@@ -0,0 +1,366 @@
+use crate::EffectOps;
+use crate::{Argument, Code, Function, Instruction, Literal, Program, Type};
+
+impl Instruction {
+    /// The variable assigned by this instruction, if any
+    #[must_use]
+    pub const fn dest(&self) -> Option<&String> {
+        match self {
+            Self::Constant { dest, .. } | Self::Value { dest, .. } => Some(dest),
+            Self::Effect { .. } => None,
+        }
+    }
+
+    /// The variables assigned by this instruction, which is at most one
+    pub fn defs(&self) -> impl Iterator<Item = &String> {
+        self.dest().into_iter()
+    }
+
+    /// The variables assigned by this instruction, for passes which rename them
+    pub fn defs_mut(&mut self) -> impl Iterator<Item = &mut String> {
+        match self {
+            Self::Constant { dest, .. } | Self::Value { dest, .. } => Some(dest),
+            Self::Effect { .. } => None,
+        }
+        .into_iter()
+    }
+
+    /// The variables whose values are read by this instruction, in order
+    ///
+    /// The first argument of `set` names the `get` whose shadow variable it writes rather than a value being read, so it is not included.
+    pub fn uses(&self) -> impl Iterator<Item = &String> {
+        let args: &[String] = match self {
+            Self::Constant { .. } => &[],
+            Self::Effect {
+                op: EffectOps::Set,
+                args,
+                ..
+            } => args.get(1..).unwrap_or_default(),
+            Self::Value { args, .. } | Self::Effect { args, .. } => args,
+        };
+        args.iter()
+    }
+
+    /// The same variables as [`Instruction::uses`], for passes which rename them
+    pub fn uses_mut(&mut self) -> impl Iterator<Item = &mut String> {
+        let args: &mut [String] = match self {
+            Self::Constant { .. } => &mut [],
+            Self::Effect {
+                op: EffectOps::Set,
+                args,
+                ..
+            } => args.get_mut(1..).unwrap_or_default(),
+            Self::Value { args, .. } | Self::Effect { args, .. } => args,
+        };
+        args.iter_mut()
+    }
+
+    /// The functions called by this instruction
+    pub fn funcs(&self) -> impl Iterator<Item = &String> {
+        let funcs: &[String] = match self {
+            Self::Constant { .. } => &[],
+            Self::Value { funcs, .. } | Self::Effect { funcs, .. } => funcs,
+        };
+        funcs.iter()
+    }
+
+    /// The functions called by this instruction, for passes which rename them
+    pub fn funcs_mut(&mut self) -> impl Iterator<Item = &mut String> {
+        let funcs: &mut [String] = match self {
+            Self::Constant { .. } => &mut [],
+            Self::Value { funcs, .. } | Self::Effect { funcs, .. } => funcs,
+        };
+        funcs.iter_mut()
+    }
+
+    /// The labels this instruction may jump to
+    pub fn labels(&self) -> impl Iterator<Item = &String> {
+        let labels: &[String] = match self {
+            Self::Constant { .. } => &[],
+            Self::Value { labels, .. } | Self::Effect { labels, .. } => labels,
+        };
+        labels.iter()
+    }
+
+    /// The labels this instruction may jump to, for passes which rename them
+    pub fn labels_mut(&mut self) -> impl Iterator<Item = &mut String> {
+        let labels: &mut [String] = match self {
+            Self::Constant { .. } => &mut [],
+            Self::Value { labels, .. } | Self::Effect { labels, .. } => labels,
+        };
+        labels.iter_mut()
+    }
+}
+
+/// A read-only traversal of a [Program]
+///
+/// Each `visit_*` method defaults to the matching `walk_*` function, which visits the children of the node. Overriding a method and calling the `walk_*` function from it keeps the traversal going.
+/// The leaf methods, like [`Visitor::visit_use`], do nothing by default.
+pub trait Visitor {
+    /// Visits a whole program
+    fn visit_program(&mut self, program: &Program) {
+        walk_program(self, program);
+    }
+
+    /// Visits a function, its arguments, return type, and code
+    fn visit_function(&mut self, function: &Function) {
+        walk_function(self, function);
+    }
+
+    /// Visits an argument of a function
+    fn visit_argument(&mut self, argument: &Argument) {
+        walk_argument(self, argument);
+    }
+
+    /// Visits a label or an instruction
+    fn visit_code(&mut self, code: &Code) {
+        walk_code(self, code);
+    }
+
+    /// Visits an instruction
+    fn visit_instruction(&mut self, instr: &Instruction) {
+        walk_instruction(self, instr);
+    }
+
+    /// Visits a type, including the types nested in it
+    fn visit_type(&mut self, t: &Type) {
+        walk_type(self, t);
+    }
+
+    /// Visits the value of a `const`
+    fn visit_literal(&mut self, _literal: &Literal) {}
+
+    /// Visits a label declared in the code of a function
+    fn visit_label(&mut self, _label: &str) {}
+
+    /// Visits a variable read by an instruction, see [`Instruction::uses`]
+    fn visit_use(&mut self, _var: &str) {}
+
+    /// Visits a variable assigned by an instruction or declared as a function argument
+    fn visit_def(&mut self, _var: &str) {}
+
+    /// Visits the first argument of a `set`, which names the `get` it sends a value to
+    fn visit_set_target(&mut self, _var: &str) {}
+
+    /// Visits the name of a function called by an instruction
+    fn visit_func_ref(&mut self, _func: &str) {}
+
+    /// Visits a label referred to by an instruction
+    fn visit_label_ref(&mut self, _label: &str) {}
+}
+
+/// Visits every function of ```program```
+pub fn walk_program<V: Visitor + ?Sized>(visitor: &mut V, program: &Program) {
+    for function in &program.functions {
+        visitor.visit_function(function);
+    }
+}
+
+/// Visits the arguments, return type, and code of ```function```
+pub fn walk_function<V: Visitor + ?Sized>(visitor: &mut V, function: &Function) {
+    for argument in &function.args {
+        visitor.visit_argument(argument);
+    }
+    if let Some(t) = &function.return_type {
+        visitor.visit_type(t);
+    }
+    for code in &function.instrs {
+        visitor.visit_code(code);
+    }
+}
+
+/// Visits the name and type of ```argument```
+pub fn walk_argument<V: Visitor + ?Sized>(visitor: &mut V, argument: &Argument) {
+    visitor.visit_def(&argument.name);
+    visitor.visit_type(&argument.arg_type);
+}
+
+/// Visits the label or instruction in ```code```
+pub fn walk_code<V: Visitor + ?Sized>(visitor: &mut V, code: &Code) {
+    match code {
+        Code::Label { label, .. } => visitor.visit_label(label),
+        Code::Instruction(instr) => visitor.visit_instruction(instr),
+    }
+}
+
+/// Visits the parts of ```instr``` in the order they are evaluated: the variables it reads, the functions it calls, the labels it refers to, and then the variable it assigns along with its type or literal
+pub fn walk_instruction<V: Visitor + ?Sized>(visitor: &mut V, instr: &Instruction) {
+    if let Instruction::Effect {
+        op: EffectOps::Set,
+        args,
+        ..
+    } = instr
+        && let Some(target) = args.first()
+    {
+        visitor.visit_set_target(target);
+    }
+    for var in instr.uses() {
+        visitor.visit_use(var);
+    }
+    for func in instr.funcs() {
+        visitor.visit_func_ref(func);
+    }
+    for label in instr.labels() {
+        visitor.visit_label_ref(label);
+    }
+    match instr {
+        Instruction::Constant {
+            dest,
+            const_type,
+            value,
+            ..
+        } => {
+            visitor.visit_def(dest);
+            visitor.visit_type(const_type);
+            visitor.visit_literal(value);
+        }
+        Instruction::Value { dest, op_type, .. } => {
+            visitor.visit_def(dest);
+            visitor.visit_type(op_type);
+        }
+        Instruction::Effect { .. } => {}
+    }
+}
+
+/// Visits the type nested in ```t```, if any
+pub fn walk_type<V: Visitor + ?Sized>(visitor: &mut V, t: &Type) {
+    if let Type::Pointer(inner) = t {
+        visitor.visit_type(inner);
+    }
+}
+
+/// A traversal of a [Program] which may modify it in place
+///
+/// This mirrors [Visitor], with `walk_*_mut` functions providing the default traversal. Overriding [`VisitorMut::visit_use`] and [`VisitorMut::visit_def`] is enough to rename variables throughout a program.
+pub trait VisitorMut {
+    /// Visits a whole program
+    fn visit_program(&mut self, program: &mut Program) {
+        walk_program_mut(self, program);
+    }
+
+    /// Visits a function, its arguments, return type, and code
+    fn visit_function(&mut self, function: &mut Function) {
+        walk_function_mut(self, function);
+    }
+
+    /// Visits an argument of a function
+    fn visit_argument(&mut self, argument: &mut Argument) {
+        walk_argument_mut(self, argument);
+    }
+
+    /// Visits a label or an instruction
+    fn visit_code(&mut self, code: &mut Code) {
+        walk_code_mut(self, code);
+    }
+
+    /// Visits an instruction
+    fn visit_instruction(&mut self, instr: &mut Instruction) {
+        walk_instruction_mut(self, instr);
+    }
+
+    /// Visits a type, including the types nested in it
+    fn visit_type(&mut self, t: &mut Type) {
+        walk_type_mut(self, t);
+    }
+
+    /// Visits the value of a `const`
+    fn visit_literal(&mut self, _literal: &mut Literal) {}
+
+    /// Visits a label declared in the code of a function
+    fn visit_label(&mut self, _label: &mut String) {}
+
+    /// Visits a variable read by an instruction, see [`Instruction::uses`]
+    fn visit_use(&mut self, _var: &mut String) {}
+
+    /// Visits a variable assigned by an instruction or declared as a function argument
+    fn visit_def(&mut self, _var: &mut String) {}
+
+    /// Visits the first argument of a `set`, which names the `get` it sends a value to
+    fn visit_set_target(&mut self, _var: &mut String) {}
+
+    /// Visits the name of a function called by an instruction
+    fn visit_func_ref(&mut self, _func: &mut String) {}
+
+    /// Visits a label referred to by an instruction
+    fn visit_label_ref(&mut self, _label: &mut String) {}
+}
+
+/// Visits every function of ```program```
+pub fn walk_program_mut<V: VisitorMut + ?Sized>(visitor: &mut V, program: &mut Program) {
+    for function in &mut program.functions {
+        visitor.visit_function(function);
+    }
+}
+
+/// Visits the arguments, return type, and code of ```function```
+pub fn walk_function_mut<V: VisitorMut + ?Sized>(visitor: &mut V, function: &mut Function) {
+    for argument in &mut function.args {
+        visitor.visit_argument(argument);
+    }
+    if let Some(t) = &mut function.return_type {
+        visitor.visit_type(t);
+    }
+    for code in &mut function.instrs {
+        visitor.visit_code(code);
+    }
+}
+
+/// Visits the name and type of ```argument```
+pub fn walk_argument_mut<V: VisitorMut + ?Sized>(visitor: &mut V, argument: &mut Argument) {
+    visitor.visit_def(&mut argument.name);
+    visitor.visit_type(&mut argument.arg_type);
+}
+
+/// Visits the label or instruction in ```code```
+pub fn walk_code_mut<V: VisitorMut + ?Sized>(visitor: &mut V, code: &mut Code) {
+    match code {
+        Code::Label { label, .. } => visitor.visit_label(label),
+        Code::Instruction(instr) => visitor.visit_instruction(instr),
+    }
+}
+
+/// Visits the parts of ```instr``` in the same order as [`walk_instruction`]
+pub fn walk_instruction_mut<V: VisitorMut + ?Sized>(visitor: &mut V, instr: &mut Instruction) {
+    if let Instruction::Effect {
+        op: EffectOps::Set,
+        args,
+        ..
+    } = instr
+        && let Some(target) = args.first_mut()
+    {
+        visitor.visit_set_target(target);
+    }
+    for var in instr.uses_mut() {
+        visitor.visit_use(var);
+    }
+    for func in instr.funcs_mut() {
+        visitor.visit_func_ref(func);
+    }
+    for label in instr.labels_mut() {
+        visitor.visit_label_ref(label);
+    }
+    match instr {
+        Instruction::Constant {
+            dest,
+            const_type,
+            value,
+            ..
+        } => {
+            visitor.visit_def(dest);
+            visitor.visit_type(const_type);
+            visitor.visit_literal(value);
+        }
+        Instruction::Value { dest, op_type, .. } => {
+            visitor.visit_def(dest);
+            visitor.visit_type(op_type);
+        }
+        Instruction::Effect { .. } => {}
+    }
+}
+
+/// Visits the type nested in ```t```, if any
+pub fn walk_type_mut<V: VisitorMut + ?Sized>(visitor: &mut V, t: &mut Type) {
+    if let Type::Pointer(inner) = t {
+        visitor.visit_type(inner);
+    }
+}
@@ -0,0 +1,174 @@
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use bril_rs::binary::{self, BinaryError};
+use bril_rs::{AbstractCode, AbstractInstruction, AbstractProgram, BrilError, Program};
+
+fn collect_programs(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            collect_programs(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "bril")
+            // `plot.vl.json` is a Vega-Lite plot of the benchmark results, not a program
+            || (path.extension().is_some_and(|ext| ext == "json")
+                && !path.to_string_lossy().ends_with(".vl.json"))
+        {
+            files.push(path);
+        }
+    }
+}
+
+fn all_programs() -> Vec<(PathBuf, AbstractProgram)> {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+    let mut files = Vec::new();
+    collect_programs(&root.join("test"), &mut files);
+    collect_programs(&root.join("benchmarks"), &mut files);
+    files.sort();
+    files
+        .into_iter()
+        .map(|path| {
+            let file = File::open(&path).unwrap();
+            let program = if path.extension().is_some_and(|ext| ext == "bril") {
+                bril2json::parse_abstract_program_from_read(
+                    file,
+                    true,
+                    true,
+                    Some(path.to_string_lossy().into_owned()),
+                )
+            } else {
+                bril_rs::load_abstract_program_from_read(file)
+            };
+            (path, program)
+        })
+        .collect()
+}
+
+fn to_json(p: &AbstractProgram) -> serde_json::Value {
+    serde_json::to_value(p).unwrap()
+}
+
+fn strip_positions(mut p: AbstractProgram) -> AbstractProgram {
+    for func in &mut p.functions {
+        func.pos = None;
+        for code in &mut func.instrs {
+            match code {
+                AbstractCode::Label { pos, .. }
+                | AbstractCode::Instruction(
+                    AbstractInstruction::Constant { pos, .. }
+                    | AbstractInstruction::Value { pos, .. }
+                    | AbstractInstruction::Effect { pos, .. },
+                ) => *pos = None,
+            }
+        }
+    }
+    p
+}
+
+#[test]
+fn abstract_programs_round_trip() {
+    let programs = all_programs();
+    assert!(
+        programs.len() > 200,
+        "only found {} programs",
+        programs.len()
+    );
+    for (path, program) in programs {
+        let mut bytes = Vec::new();
+        binary::write_abstract_program(&program, &mut bytes, true).unwrap();
+        assert!(binary::is_binary(&bytes));
+        let decoded = binary::read_abstract_program(bytes.as_slice()).unwrap();
+        assert_eq!(to_json(&program), to_json(&decoded), "{}", path.display());
+
+        let mut bytes = Vec::new();
+        binary::write_abstract_program(&program, &mut bytes, false).unwrap();
+        let decoded = binary::read_abstract_program(bytes.as_slice()).unwrap();
+        assert_eq!(
+            to_json(&strip_positions(program)),
+            to_json(&decoded),
+            "{}",
+            path.display()
+        );
+    }
+}
+
+#[test]
+fn programs_round_trip() {
+    for (path, program) in all_programs() {
+        // Some tests are deliberately ill-typed or use ops which only exist in `AbstractProgram`
+        let Ok(program) = Program::try_from(program) else {
+            continue;
+        };
+        let mut bytes = Vec::new();
+        binary::write_program(&program, &mut bytes, true).unwrap();
+        let decoded = binary::read_program(bytes.as_slice()).unwrap();
+        assert_eq!(program, decoded, "{}", path.display());
+    }
+}
+
+#[test]
+fn loaders_detect_binary_input() {
+    for (path, program) in all_programs() {
+        let mut bytes = Vec::new();
+        binary::write_abstract_program(&program, &mut bytes, true).unwrap();
+        let loaded = bril_rs::try_load_abstract_program_from_read(bytes.as_slice()).unwrap();
+        assert_eq!(to_json(&program), to_json(&loaded), "{}", path.display());
+    }
+}
+
+#[test]
+fn rejects_bad_headers() {
+    assert!(matches!(
+        binary::read_abstract_program(&b"{\"functions\": []}"[..]),
+        Err(BrilError::Binary(BinaryError::BadMagic))
+    ));
+
+    for version in [binary::VERSION - 1, binary::VERSION + 1] {
+        let mut other = binary::MAGIC.to_vec();
+        other.extend([version as u8, 0, 0]);
+        assert!(matches!(
+            binary::read_abstract_program(other.as_slice()),
+            Err(BrilError::Binary(BinaryError::UnsupportedVersion(v))) if v == version
+        ));
+    }
+
+    let mut truncated = Vec::new();
+    binary::write_abstract_program(&all_programs()[0].1, &mut truncated, true).unwrap();
+    truncated.pop();
+    assert!(matches!(
+        binary::read_abstract_program(truncated.as_slice()),
+        Err(BrilError::Io(_))
+    ));
+}
+
+#[test]
+fn rejects_deep_nesting() {
+    // The header, then a function named `f` without arguments
+    let function = |positions: u8| -> Vec<u8> {
+        let mut bytes = binary::MAGIC.to_vec();
+        bytes.extend([binary::VERSION as u8, positions, 1, 0, 1, b'f', 0]);
+        bytes
+    };
+
+    // A return type of `ptr<ptr<ptr<...`, where each `ptr` after the first refers back to the string table
+    let mut types = function(0);
+    types.extend([2, 0, 3, b'p', b't', b'r']);
+    for _ in 0..100_000 {
+        types.extend([2, 1]);
+    }
+    assert!(matches!(
+        binary::read_abstract_program(types.as_slice()),
+        Err(BrilError::Binary(BinaryError::TooDeep))
+    ));
+
+    // No return type, then a position at 1:1 whose origin is at 1:1, and so on
+    let mut origins = function(1);
+    origins.push(0);
+    for _ in 0..100_000 {
+        origins.extend([1, 1, 1, 0, 0, 0]);
+    }
+    assert!(matches!(
+        binary::read_abstract_program(origins.as_slice()),
+        Err(BrilError::Binary(BinaryError::TooDeep))
+    ));
+}
@@ -0,0 +1,90 @@
+use bril_rs::builder::{FreshNames, FunctionBuilder, ProgramBuilder};
+use bril_rs::{Literal, Program, Type, ValueOps};
+
+const EXPECTED: &str = r#"{
+  "functions": [
+    {
+      "name": "main",
+      "instrs": [
+        { "op": "const", "dest": "tmp0", "type": "int", "value": 4 },
+        { "op": "alloc", "dest": "tmp1", "type": { "ptr": "int" }, "args": ["tmp0"] },
+        { "op": "const", "dest": "i", "type": "int", "value": 0 },
+        { "label": "label2" },
+        { "op": "lt", "dest": "tmp3", "type": "bool", "args": ["i", "tmp0"] },
+        { "op": "br", "args": ["tmp3"], "labels": ["label4", "label5"] },
+        { "label": "label4" },
+        { "op": "ptradd", "dest": "tmp6", "type": { "ptr": "int" }, "args": ["tmp1", "i"] },
+        { "op": "store", "args": ["tmp6", "i"] },
+        { "op": "call", "dest": "tmp7", "type": "int", "args": ["i"], "funcs": ["next"] },
+        { "op": "id", "dest": "i", "type": "int", "args": ["tmp7"] },
+        { "op": "jmp", "labels": ["label2"] },
+        { "label": "label5" },
+        { "op": "load", "dest": "tmp8", "type": "int", "args": ["tmp1"] },
+        { "op": "print", "args": ["tmp8"] },
+        { "op": "free", "args": ["tmp1"] }
+      ]
+    },
+    {
+      "name": "next",
+      "args": [{ "name": "n", "type": "int" }],
+      "type": "int",
+      "instrs": [
+        { "op": "const", "dest": "tmp0", "type": "int", "value": 1 },
+        { "op": "add", "dest": "tmp1", "type": "int", "args": ["n", "tmp0"] },
+        { "op": "ret", "args": ["tmp1"] }
+      ]
+    }
+  ]
+}"#;
+
+#[test]
+fn built_program_matches_its_json() {
+    let mut program = ProgramBuilder::new();
+    program.function("main", None, |b| {
+        let size = b.constant(Literal::Int(4));
+        let array = b.alloc(&size, Type::Int);
+        b.constant_into("i", Type::Int, Literal::Int(0));
+        let head = b.new_block();
+        let more = b.lt("i", &size);
+        let (body, exit) = (b.fresh_label(), b.fresh_label());
+        b.br(&more, &body, &exit);
+        b.label(&body);
+        let slot = b.ptradd(&array, "i");
+        b.store(&slot, "i");
+        let next = b.call("next", &["i"], Type::Int);
+        b.value_into("i", ValueOps::Id, Type::Int, &[&next], &[]);
+        b.jmp(&head);
+        assert!(b.is_terminated());
+        b.label(&exit);
+        assert!(!b.is_terminated());
+        let first = b.load(&array);
+        b.print(&[&first]);
+        b.free(&array);
+    });
+    program.function("next", Some(Type::Int), |b| {
+        let n = b.add_arg("n", Type::Int);
+        let one = b.constant(Literal::Int(1));
+        let sum = b.add(&n, &one);
+        b.ret(Some(&sum));
+    });
+
+    let expected: Program = serde_json::from_str(EXPECTED).unwrap();
+    assert_eq!(program.build(), expected);
+}
+
+#[test]
+fn fresh_names_skip_reserved_ones() {
+    let mut names = FreshNames::new("v", "l");
+    names.reserve("v0");
+    names.reserve("l2");
+    assert_eq!(names.var(), "v1");
+    assert_eq!(names.label(), "l3");
+
+    let mut b = FunctionBuilder::with_names("f", None, names);
+    b.declare("v4", Type::Bool);
+    assert_eq!(b.var_type("v4"), Some(&Type::Bool));
+    assert_eq!(b.fresh_var(), "v5");
+    let (_, names) = b.build_with_names();
+    let mut b = FunctionBuilder::with_names("g", None, names);
+    assert_eq!(b.fresh_label(), "l6");
+}
@@ -0,0 +1,102 @@
+use bril_rs::Program;
+use bril_rs::callgraph::{CallGraph, remove_dead_functions};
+
+const PROGRAM: &str = "@main {
+  x: int = const 3;
+  even: bool = call @is_even x;
+  print even;
+  call @log x;
+}
+@is_even(n: int): bool {
+  zero: int = const 0;
+  done: bool = eq n zero;
+  br done .yes .no;
+.yes:
+  t: bool = const true;
+  ret t;
+.no:
+  one: int = const 1;
+  m: int = sub n one;
+  r: bool = call @is_odd m;
+  ret r;
+}
+@is_odd(n: int): bool {
+  zero: int = const 0;
+  done: bool = eq n zero;
+  br done .yes .no;
+.yes:
+  f: bool = const false;
+  ret f;
+.no:
+  one: int = const 1;
+  m: int = sub n one;
+  r: bool = call @is_even m;
+  ret r;
+}
+@log(n: int) {
+  print n;
+}
+@unused {
+  call @unused;
+  call @log;
+}
+";
+
+fn parse(source: &str) -> Program {
+    bril2json::parse_abstract_program_from_read(source.as_bytes(), false, false, None)
+        .try_into()
+        .unwrap()
+}
+
+#[test]
+fn calls_become_edges() {
+    let graph = CallGraph::new(&parse(PROGRAM));
+    let [main, even, odd, log, unused] =
+        ["main", "is_even", "is_odd", "log", "unused"].map(|name| graph.index(name).unwrap());
+    assert_eq!(graph.callees[main], vec![even, log]);
+    assert_eq!(graph.callers[even], vec![main, odd]);
+    assert_eq!(graph.callers[log], vec![main, unused]);
+    assert_eq!(graph.callees[unused], vec![unused, log]);
+    assert!(graph.index("missing").is_none());
+}
+
+#[test]
+fn recursion_is_found_in_strongly_connected_components() {
+    let graph = CallGraph::new(&parse(PROGRAM));
+    let [main, even, odd, log, unused] =
+        ["main", "is_even", "is_odd", "log", "unused"].map(|name| graph.index(name).unwrap());
+    let sccs = graph.sccs();
+    assert_eq!(sccs.len(), 4);
+    assert!(sccs.contains(&vec![even, odd]));
+    assert!(graph.is_recursive(even));
+    assert!(graph.is_recursive(unused));
+    assert!(!graph.is_recursive(main));
+    assert!(!graph.is_recursive(log));
+
+    let order = graph.topological_order();
+    let position = |f| order.iter().position(|g| *g == f).unwrap();
+    assert!(position(main) < position(even));
+    assert!(position(main) < position(odd));
+    assert!(position(main) < position(log));
+    assert!(position(unused) < position(log));
+}
+
+#[test]
+fn functions_not_reachable_from_main_are_removed() {
+    let mut prog = parse(PROGRAM);
+    let graph = CallGraph::new(&prog);
+    assert_eq!(graph.reachable_from_main(), Some(vec![0, 1, 2, 3]));
+    assert_eq!(remove_dead_functions(&mut prog), vec!["unused"]);
+    assert_eq!(
+        prog.functions
+            .iter()
+            .map(|f| f.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["main", "is_even", "is_odd", "log"]
+    );
+
+    let mut library = parse("@f {\n  call @g;\n}\n@g {\n}\n");
+    assert_eq!(CallGraph::new(&library).reachable_from_main(), None);
+    assert!(remove_dead_functions(&mut library).is_empty());
+    assert_eq!(library.functions.len(), 2);
+}
@@ -0,0 +1,100 @@
+use bril_rs::cfg::{Cfg, CfgError};
+use bril_rs::{Function, Program};
+
+fn parse(source: &str) -> Program {
+    bril2json::parse_abstract_program_from_read(source.as_bytes(), false, false, None)
+        .try_into()
+        .unwrap()
+}
+
+fn cfg(source: &str) -> Cfg {
+    Cfg::new(parse(source).functions.remove(0)).unwrap()
+}
+
+#[test]
+fn blocks_are_split_at_labels_and_terminators() {
+    let mut cfg = cfg("@main {
+  x: int = const 1;
+.a:
+  print x;
+  jmp .c;
+  print x;
+.c:
+  ret;
+}
+");
+    let labels: Vec<_> = cfg.blocks.iter().map(|b| b.label.as_deref()).collect();
+    assert_eq!(labels, vec![None, Some("a"), None, Some("c")]);
+    assert_eq!(cfg.label_map["c"], 3);
+    // The unlabeled block after the `jmp` falls through but can never be reached
+    assert_eq!(cfg.successors, vec![vec![1], vec![3], vec![3], vec![]]);
+    assert_eq!(cfg.predecessors, vec![vec![], vec![0], vec![], vec![1, 2]]);
+    assert_eq!(cfg.reverse_post_order(), vec![0, 1, 3]);
+
+    assert!(cfg.remove_unreachable_blocks().unwrap());
+    assert_eq!(cfg.successors, vec![vec![1], vec![2], vec![]]);
+    assert!(!cfg.remove_unreachable_blocks().unwrap());
+}
+
+#[test]
+fn branches_and_returns_end_blocks() {
+    let cfg = cfg("@main(b: bool) {
+  br b .same .same;
+.same:
+  br b .then .else;
+.then:
+  ret;
+.else:
+  print b;
+}
+");
+    assert_eq!(cfg.successors, vec![vec![1], vec![2, 3], vec![], vec![]]);
+}
+
+#[test]
+fn guards_jump_to_their_label_from_the_middle_of_a_block() {
+    let cfg = cfg("@main(b: bool) {
+  speculate;
+  guard b .abort;
+  commit;
+  print b;
+  ret;
+.abort:
+  print b;
+}
+");
+    assert_eq!(cfg.blocks.len(), 2);
+    assert_eq!(cfg.successors, vec![vec![1], vec![]]);
+    assert_eq!(cfg.predecessors[1], vec![0]);
+}
+
+#[test]
+fn blocks_flatten_back_into_the_function() {
+    let func = parse(
+        "@main {
+.loop:
+  jmp .loop;
+}
+",
+    )
+    .functions
+    .remove(0);
+    let cfg = Cfg::new(func.clone()).unwrap();
+    assert_eq!(cfg.fresh_label("loop"), "loop.0");
+    assert_eq!(cfg.fresh_label("exit"), "exit");
+    assert_eq!(Function::from(cfg), func);
+}
+
+#[test]
+fn bad_labels_are_errors() {
+    let twice = parse("@main {\n.a:\n.a:\n}\n").functions.remove(0);
+    assert_eq!(
+        Cfg::new(twice),
+        Err(CfgError::DuplicateLabel("a".to_string()))
+    );
+    let missing = parse("@main {\n  jmp .b;\n}\n").functions.remove(0);
+    assert_eq!(
+        Cfg::new(missing),
+        Err(CfgError::MissingLabel("b".to_string()))
+    );
+}
@@ -0,0 +1,96 @@
+use bril_rs::conversion::{ConversionError, ConversionErrors, Field};
+use bril_rs::{AbstractProgram, EffectOps, Program, Type, ValueOps};
+
+const TYPOS: &str = "@main(n: itn): flaot {
+  one: int = const 1;
+  x: int = addd n one;
+  y: ptr<int> = alloc one;
+  z: pointer<int> = load y;
+  prnt x;
+  ret x;
+}
+";
+
+fn parse(source: &str) -> AbstractProgram {
+    bril2json::parse_abstract_program_from_read(source.as_bytes(), true, true, None)
+}
+
+#[test]
+fn misspelled_names_are_suggested() {
+    let suggestion = |e: ConversionError| e.suggestion().map(str::to_string);
+    assert_eq!(
+        suggestion("addd".parse::<ValueOps>().unwrap_err()),
+        Some("add".to_string())
+    );
+    assert_eq!(
+        suggestion("pritn".parse::<EffectOps>().unwrap_err()),
+        Some("print".to_string())
+    );
+    assert_eq!(
+        suggestion("flaot".parse::<Type>().unwrap_err()),
+        Some("float".to_string())
+    );
+    assert_eq!(
+        suggestion("frobnicate".parse::<ValueOps>().unwrap_err()),
+        None
+    );
+}
+
+#[test]
+fn every_error_in_a_function_is_reported() {
+    let ConversionErrors(errors) = Program::try_from(parse(TYPOS)).unwrap_err();
+    let found: Vec<_> = errors
+        .iter()
+        .map(|e| {
+            (
+                e.func.as_deref(),
+                e.index,
+                e.field.clone(),
+                e.pos.as_ref().map(|p| p.pos.row),
+            )
+        })
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (
+                Some("main"),
+                None,
+                Some(Field::ArgumentType("n".to_string())),
+                Some(1)
+            ),
+            (Some("main"), None, Some(Field::ReturnType), Some(1)),
+            (Some("main"), Some(1), Some(Field::Op), Some(3)),
+            (Some("main"), Some(3), Some(Field::Type), Some(5)),
+            (Some("main"), Some(4), Some(Field::Op), Some(6)),
+        ]
+    );
+}
+
+#[test]
+fn both_type_and_op_of_an_instruction_are_reported() {
+    let ConversionErrors(errors) =
+        Program::try_from(parse("@main {\n  x: nit = ad;\n}\n")).unwrap_err();
+    let fields: Vec<_> = errors.iter().map(|e| e.field.clone()).collect();
+    assert_eq!(fields, vec![Some(Field::Type), Some(Field::Op)]);
+}
+
+#[test]
+fn render_shows_the_offending_line() {
+    let source = "@main {\n  x: int = const 1;\n  y: int = addd x x;\n}\n";
+    let errors = Program::try_from(parse(source)).unwrap_err();
+    assert_eq!(
+        errors.to_string(),
+        "3:3: Expected a value operation, found `addd`, did you mean `add`? (in the op of instruction 1 of @main)"
+    );
+    assert_eq!(
+        errors.render(source),
+        "error: Expected a value operation, found `addd`, did you mean `add`?
+ --> 3:3
+  |
+3 |   y: int = addd x x;
+  |   ^^^^^^^^^^^^^^^^^^
+  = note: in the op of instruction 1 of @main
+"
+    );
+}
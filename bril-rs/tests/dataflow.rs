@@ -0,0 +1,67 @@
+use std::collections::BTreeSet;
+
+use bril_rs::Program;
+use bril_rs::cfg::Cfg;
+use bril_rs::dataflow::{Definition, LiveVariables, Location, ReachingDefinitions, solve};
+
+/// Blocks: 0 is the entry, 1 the loop header, 2 the body, and 3 the exit
+const LOOP: &str = "@main(n: int) {
+  i: int = const 0;
+  one: int = const 1;
+.head:
+  done: bool = lt n i;
+  br done .exit .body;
+.body:
+  i: int = add i one;
+  jmp .head;
+.exit:
+  print i;
+}
+";
+
+fn cfg(source: &str) -> Cfg {
+    let program: Program =
+        bril2json::parse_abstract_program_from_read(source.as_bytes(), false, false, None)
+            .try_into()
+            .unwrap();
+    Cfg::new(program.functions.into_iter().next().unwrap()).unwrap()
+}
+
+fn names(vars: &[&str]) -> BTreeSet<String> {
+    vars.iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn live_variables_flow_around_the_loop() {
+    let result = solve(&LiveVariables, &cfg(LOOP));
+    assert_eq!(result.ins[0], names(&["n"]));
+    assert_eq!(result.ins[1], names(&["i", "n", "one"]));
+    assert_eq!(result.outs[1], names(&["i", "n", "one"]));
+    // `i` is live into the body since the loop reads it again in the header
+    assert_eq!(result.ins[2], names(&["i", "n", "one"]));
+    assert_eq!(result.ins[3], names(&["i"]));
+    assert_eq!(result.outs[3], names(&[]));
+}
+
+#[test]
+fn definitions_of_the_loop_reach_its_header() {
+    let at = |var: &str, location: Option<(usize, usize)>| Definition {
+        var: var.to_string(),
+        location: location.map(|(block, index)| Location { block, index }),
+    };
+    let result = solve(&ReachingDefinitions, &cfg(LOOP));
+    assert_eq!(result.ins[0], BTreeSet::from([at("n", None)]));
+    assert_eq!(
+        result.ins[1],
+        BTreeSet::from([
+            at("n", None),
+            at("i", Some((0, 0))),
+            at("one", Some((0, 1))),
+            at("i", Some((2, 0))),
+            at("done", Some((1, 0))),
+        ])
+    );
+    // Redefining `i` in the body kills the definition from the entry
+    assert!(!result.outs[2].contains(&at("i", Some((0, 0)))));
+    assert_eq!(result.ins[3], result.outs[1]);
+}
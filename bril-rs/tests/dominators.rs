@@ -0,0 +1,144 @@
+use std::collections::BTreeSet;
+
+use bril_rs::cfg::Cfg;
+use bril_rs::dominators::{
+    DominatorTree, PostDominatorTree, back_edges, insert_preheader, natural_loops,
+};
+use bril_rs::{EffectOps, Instruction, Program};
+
+/// Blocks: 0 is the entry, 1 and 2 the two sides, and 3 where they join
+const DIAMOND: &str = "@main(b: bool) {
+  br b .left .right;
+.left:
+  jmp .join;
+.right:
+  print b;
+.join:
+  print b;
+}
+";
+
+/// Blocks: 0 is the entry, 1 the header, 2 and 3 the body, and 4 the exit
+const LOOP: &str = "@main(n: int) {
+  i: int = const 0;
+.head:
+  done: bool = lt n i;
+  br done .exit .body;
+.body:
+  print i;
+.latch:
+  one: int = const 1;
+  i: int = add i one;
+  jmp .head;
+.exit:
+  print i;
+}
+";
+
+fn cfg(source: &str) -> Cfg {
+    let program: Program =
+        bril2json::parse_abstract_program_from_read(source.as_bytes(), false, false, None)
+            .try_into()
+            .unwrap();
+    Cfg::new(program.functions.into_iter().next().unwrap()).unwrap()
+}
+
+#[test]
+fn diamond_dominators() {
+    let cfg = cfg(DIAMOND);
+    let dom = DominatorTree::new(&cfg);
+    assert_eq!(dom.idom, vec![None, Some(0), Some(0), Some(0)]);
+    assert_eq!(
+        dom.children[0].iter().copied().collect::<BTreeSet<_>>(),
+        BTreeSet::from([1, 2, 3])
+    );
+    assert!(dom.dominates(0, 3));
+    assert!(!dom.dominates(1, 3));
+    assert!(!dom.strictly_dominates(3, 3));
+    assert_eq!(dom.dominators(3), vec![3, 0]);
+    assert_eq!(
+        dom.frontier,
+        vec![
+            BTreeSet::new(),
+            BTreeSet::from([3]),
+            BTreeSet::from([3]),
+            BTreeSet::new()
+        ]
+    );
+
+    let post = PostDominatorTree::new(&cfg);
+    assert_eq!(post.exit(), 4);
+    assert_eq!(post.ipdom(0), Some(3));
+    assert_eq!(post.ipdom(1), Some(3));
+    assert_eq!(post.ipdom(3), None);
+    assert!(post.post_dominates(3, 2));
+    assert!(!post.post_dominates(1, 0));
+    assert!(natural_loops(&cfg, &dom).is_empty());
+}
+
+#[test]
+fn loop_dominators() {
+    let cfg = cfg(LOOP);
+    let dom = DominatorTree::new(&cfg);
+    assert_eq!(dom.idom, vec![None, Some(0), Some(1), Some(2), Some(1)]);
+    assert_eq!(dom.frontier[2], BTreeSet::from([1]));
+    assert_eq!(dom.frontier[3], BTreeSet::from([1]));
+    assert_eq!(dom.frontier[1], BTreeSet::from([1]));
+    assert_eq!(back_edges(&cfg, &dom), vec![(3, 1)]);
+
+    let post = PostDominatorTree::new(&cfg);
+    assert_eq!(post.ipdom(0), Some(1));
+    assert_eq!(post.ipdom(3), Some(1));
+    assert_eq!(post.ipdom(1), Some(4));
+
+    let loops = natural_loops(&cfg, &dom);
+    assert_eq!(loops.len(), 1);
+    let l = &loops[0];
+    assert_eq!(l.header, 1);
+    assert_eq!(l.latches, vec![3]);
+    assert_eq!(l.body, BTreeSet::from([1, 2, 3]));
+    assert_eq!(l.exits, vec![(1, 4)]);
+    // The entry falls through into the header, so it already is a preheader
+    assert_eq!(l.preheader, Some(0));
+}
+
+#[test]
+fn preheaders_take_every_edge_into_the_loop() {
+    let mut cfg = cfg("@main(b: bool) {
+  speculate;
+  guard b .head;
+  commit;
+  br b .head .exit;
+.head:
+  print b;
+  jmp .head;
+.exit:
+  ret;
+}
+");
+    let dom = DominatorTree::new(&cfg);
+    let loops = natural_loops(&cfg, &dom);
+    assert_eq!(loops[0].preheader, None);
+
+    let preheader = insert_preheader(&mut cfg, &loops[0]).unwrap();
+    assert_eq!(preheader, 1);
+    assert_eq!(cfg.blocks[1].label.as_deref(), Some("head.preheader"));
+    assert_eq!(cfg.predecessors[1], vec![0]);
+    assert_eq!(cfg.predecessors[2], vec![1, 2]);
+    let targets: Vec<_> = cfg.blocks[0]
+        .instrs
+        .iter()
+        .filter_map(|i| match i {
+            Instruction::Effect {
+                op: EffectOps::Guard | EffectOps::Branch,
+                labels,
+                ..
+            } => Some(labels[0].as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(targets, vec!["head.preheader", "head.preheader"]);
+
+    let dom = DominatorTree::new(&cfg);
+    assert_eq!(natural_loops(&cfg, &dom)[0].preheader, Some(1));
+}
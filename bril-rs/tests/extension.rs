@@ -0,0 +1,94 @@
+use bril_rs::Program;
+use bril_rs::extension::{Extension, ExtensionError, ExtensionSet};
+
+const PROGRAM: &str = "@main(f: float) {
+  size: int = const 2;
+  p: ptr<float> = alloc size;
+  store p f;
+  c: char = const 'a';
+  print c;
+  free p;
+}
+";
+
+fn parse(source: &str) -> Program {
+    bril2json::parse_abstract_program_from_read(source.as_bytes(), true, false, None)
+        .try_into()
+        .unwrap()
+}
+
+#[test]
+fn sets_are_parsed_from_names() {
+    let set: ExtensionSet = "float, memory,".parse().unwrap();
+    assert_eq!(
+        set,
+        ExtensionSet::CORE
+            .with(Extension::Float)
+            .with(Extension::Memory)
+    );
+    assert_eq!(set.to_string(), "float,memory");
+    assert_eq!("core".parse(), Ok(ExtensionSet::CORE));
+    assert_eq!("".parse(), Ok(ExtensionSet::CORE));
+    assert_eq!("ssa,all".parse(), Ok(ExtensionSet::ALL));
+    assert_eq!(ExtensionSet::CORE.to_string(), "core");
+
+    let error = "float,flaot".parse::<ExtensionSet>().unwrap_err();
+    assert_eq!(error, ExtensionError::Unknown("flaot".to_string()));
+    assert_eq!(
+        error.to_string(),
+        "unknown extension `flaot`, expected one of float,memory,ssa,speculate,char,bitcast,dynamic,import"
+    );
+}
+
+#[test]
+fn programs_use_the_extensions_of_their_types_and_ops() {
+    let program = parse(PROGRAM);
+    assert_eq!(
+        ExtensionSet::used_by(&program),
+        ExtensionSet::CORE
+            .with(Extension::Float)
+            .with(Extension::Memory)
+            .with(Extension::Char)
+    );
+    assert_eq!(ExtensionSet::ALL.accepts(&program), Ok(()));
+    assert_eq!(
+        ExtensionSet::used_by(&parse("@main {\n  print;\n}\n")),
+        ExtensionSet::CORE
+    );
+}
+
+#[test]
+fn missing_extensions_are_reported_where_they_are_used() {
+    let program = parse(PROGRAM);
+    let allowed = ExtensionSet::CORE.with(Extension::Float);
+    let errors: Vec<_> = allowed
+        .check_program(&program)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            "3:3: `p: ptr<float> = alloc size;` requires the `memory` extension",
+            "4:3: `store p f;` requires the `memory` extension",
+            "5:3: `c: char = const 'a';` requires the `char` extension",
+            "7:3: `free p;` requires the `memory` extension",
+        ]
+    );
+    assert_eq!(
+        allowed.accepts(&program).unwrap_err().to_string(),
+        errors[0]
+    );
+
+    let without_positions: Program =
+        bril2json::parse_abstract_program_from_read(PROGRAM.as_bytes(), false, false, None)
+            .try_into()
+            .unwrap();
+    assert_eq!(
+        ExtensionSet::CORE
+            .accepts(&without_positions)
+            .unwrap_err()
+            .to_string(),
+        "@main: argument `f: float` requires the `float` extension"
+    );
+}
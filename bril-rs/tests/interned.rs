@@ -0,0 +1,86 @@
+use std::path::Path;
+
+use bril_rs::Program;
+use bril_rs::interned::{
+    FuncId, InternedCode, InternedInstruction, InternedProgram, LabelId, VarId,
+};
+
+fn parse(source: &str) -> Program {
+    bril2json::parse_abstract_program_from_read(source.as_bytes(), true, true, None)
+        .try_into()
+        .unwrap()
+}
+
+#[test]
+fn benchmarks_round_trip() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../benchmarks");
+    let mut count = 0;
+    for suite in std::fs::read_dir(dir).unwrap() {
+        let suite = suite.unwrap().path();
+        if !suite.is_dir() {
+            continue;
+        }
+        for entry in std::fs::read_dir(suite).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().is_none_or(|e| e != "bril") {
+                continue;
+            }
+            let program = parse(&std::fs::read_to_string(&path).unwrap());
+            let interned = InternedProgram::from(program.clone());
+            assert_eq!(Program::from(interned), program, "{}", path.display());
+            count += 1;
+        }
+    }
+    assert!(count > 0);
+}
+
+#[test]
+fn names_are_interned_in_order() {
+    let program = parse(
+        "@main(n: int) {
+.top:
+  m: int = call @double n;
+  call @log m;
+  jmp .top;
+}
+@double(x: int): int {
+  y: int = add x x;
+  ret y;
+}
+",
+    );
+    let interned = InternedProgram::from(program);
+    let names: Vec<_> = interned.funcs.iter().map(|(_, name)| name).collect();
+    // Defined functions come first, then the ones which are only called
+    assert_eq!(names, vec!["main", "double", "log"]);
+    let double = interned.funcs.get("double").unwrap();
+    assert_eq!(interned.function(double).unwrap().name, double);
+    assert!(
+        interned
+            .function(interned.funcs.get("log").unwrap())
+            .is_none()
+    );
+
+    let main = &interned.functions[0];
+    assert_eq!(main.name, interned.funcs.get("main").unwrap());
+    assert_eq!(main.args[0].name, main.vars.get("n").unwrap());
+    assert_eq!(main.vars.name(main.args[0].name), "n");
+    assert_eq!(main.vars.iter().count(), 2);
+    let InternedCode::Label { label, .. } = &main.instrs[0] else {
+        panic!("expected a label");
+    };
+    assert_eq!(main.labels[*label], *"top");
+    let InternedCode::Instruction(call @ InternedInstruction::Value { .. }) = &main.instrs[1]
+    else {
+        panic!("expected a call");
+    };
+    let expected_args: &[VarId] = &[main.vars.get("n").unwrap()];
+    assert_eq!(call.args(), expected_args);
+    let expected_funcs: &[FuncId] = &[double];
+    assert_eq!(call.funcs(), expected_funcs);
+    let InternedCode::Instruction(jmp) = &main.instrs[3] else {
+        panic!("expected a jump");
+    };
+    let expected_labels: &[LabelId] = &[*label];
+    assert_eq!(jmp.labels(), expected_labels);
+}
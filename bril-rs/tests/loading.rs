@@ -0,0 +1,34 @@
+use bril_rs::{
+    BrilError, try_load_abstract_program_from_read, try_load_program_from_abstract_read,
+    try_load_program_from_read,
+};
+
+#[test]
+fn malformed_json_reports_where_it_stopped() {
+    let input = "{\n  \"functions\": [\n    {\"name\": \"main\",, \"instrs\": []}\n  ]\n}\n";
+    let Err(BrilError::Json { line, column, .. }) = try_load_program_from_read(input.as_bytes())
+    else {
+        panic!("expected a JSON error");
+    };
+    assert_eq!((line, column), (3, 21));
+
+    let Err(BrilError::Json { line, .. }) = try_load_abstract_program_from_read(&b"{"[..]) else {
+        panic!("expected a JSON error");
+    };
+    assert_eq!(line, 1);
+}
+
+#[test]
+fn a_bad_op_is_a_conversion_error() {
+    let input = r#"{"functions": [{"name": "main", "instrs": [
+        {"op": "const", "dest": "x", "type": "int", "value": 1},
+        {"op": "frobnicate", "dest": "y", "type": "int", "args": ["x"]}
+    ]}]}"#;
+    let Err(BrilError::Conversion(errors)) = try_load_program_from_abstract_read(input.as_bytes())
+    else {
+        panic!("expected a conversion error");
+    };
+    assert_eq!(errors.0.len(), 1);
+    assert_eq!(errors.0[0].func.as_deref(), Some("main"));
+    assert_eq!(errors.0[0].index, Some(1));
+}
@@ -0,0 +1,125 @@
+use bril_rs::provenance::{
+    PositionCoverage, derive_position, fill_positions, lost_positions, merge_positions,
+};
+use bril_rs::ssa::{from_ssa, to_ssa};
+use bril_rs::{ByteSpan, Code, ColRow, Position, Program, binary};
+
+const LOOP: &str = "@main(n: int) {
+  i: int = const 0;
+  one: int = const 1;
+.loop:
+  cond: bool = lt i n;
+  br cond .body .done;
+.body:
+  i: int = add i one;
+  jmp .loop;
+.done:
+  print i;
+}
+";
+
+fn parse(source: &str) -> Program {
+    bril2json::parse_abstract_program_from_read(source.as_bytes(), true, true, None)
+        .try_into()
+        .unwrap()
+}
+
+fn at(row: u64, col: u64, end: Option<(u64, u64)>, src: &str) -> Position {
+    Position {
+        pos: ColRow { col, row },
+        pos_end: end.map(|(row, col)| ColRow { col, row }),
+        src: Some(src.to_string()),
+        byte_span: None,
+        origin: None,
+    }
+}
+
+#[test]
+fn merged_positions_span_their_sources() {
+    let merged = merge_positions([
+        at(3, 5, Some((3, 12)), "a.bril"),
+        at(2, 3, None, "a.bril"),
+        at(9, 1, None, "b.bril"),
+    ])
+    .unwrap();
+    assert_eq!(merged, at(2, 3, Some((3, 12)), "a.bril"));
+    assert_eq!(merge_positions(Vec::<Position>::new()), None);
+}
+
+#[test]
+fn derived_positions_keep_their_origins() {
+    let call = at(4, 3, None, "main.bril");
+    let callee = at(10, 3, None, "lib.bril");
+    let inlined = derive_position(Some(&call), Some(&callee)).unwrap();
+    let twice = derive_position(Some(&at(1, 1, None, "main.bril")), Some(&inlined)).unwrap();
+    assert_eq!(
+        twice.origins().map(|p| p.pos.row).collect::<Vec<_>>(),
+        vec![4, 10]
+    );
+    assert_eq!(derive_position(None, Some(&callee)), Some(callee));
+
+    // Origins and byte spans survive both the JSON and the binary encoding
+    let mut prog = parse(LOOP);
+    if let Code::Instruction(i) = &mut prog.functions[0].instrs[0] {
+        i.set_pos(Some(Position {
+            byte_span: Some(ByteSpan { start: 18, end: 35 }),
+            ..twice.clone()
+        }));
+    }
+    let json: Program = serde_json::from_str(&serde_json::to_string(&prog).unwrap()).unwrap();
+    assert_eq!(json, prog);
+    let mut bytes = Vec::new();
+    binary::write_program(&prog, &mut bytes, true).unwrap();
+    assert_eq!(binary::read_program(bytes.as_slice()).unwrap(), prog);
+}
+
+#[test]
+fn missing_positions_are_filled_from_the_code_before() {
+    let mut prog = parse(LOOP);
+    let func = &mut prog.functions[0];
+    let positions: Vec<_> = func.instrs.iter().map(position).collect();
+    for i in [0, 4, 6] {
+        if let Code::Instruction(instr) = &mut func.instrs[i] {
+            instr.set_pos(None);
+        }
+    }
+    let coverage = PositionCoverage::of(func);
+    assert_eq!(coverage.missing.len(), 3);
+    assert_eq!(
+        coverage.to_string().lines().next(),
+        Some("@main: 4/7 instructions and 3/3 labels have source positions")
+    );
+
+    assert_eq!(fill_positions(func), 3);
+    assert!(PositionCoverage::of(func).is_complete());
+    assert_eq!(position(&func.instrs[0]), positions[1]);
+    assert_eq!(position(&func.instrs[4]), positions[3]);
+    assert_eq!(position(&func.instrs[6]), positions[5]);
+}
+
+#[test]
+fn ssa_round_trips_keep_positions() {
+    let before = parse(LOOP);
+    let mut after = before.clone();
+    after.functions = after
+        .functions
+        .into_iter()
+        .map(|f| from_ssa(to_ssa(f).unwrap()))
+        .collect();
+    assert_eq!(lost_positions(&before, &after), Vec::new());
+
+    let mut stripped = after.clone();
+    if let Code::Instruction(instr) = &mut stripped.functions[0].instrs[1] {
+        instr.set_pos(None);
+    }
+    let lost = lost_positions(&before, &stripped);
+    assert_eq!(lost.len(), 1);
+    assert_eq!(lost[0].function, "main");
+}
+
+fn position(code: &Code) -> Option<Position> {
+    match code {
+        Code::Label { pos, .. } => pos.clone(),
+        Code::Instruction(instr) => instr.get_pos(),
+    }
+}
@@ -0,0 +1,79 @@
+use std::path::Path;
+
+use bril_rs::Program;
+use bril_rs::ssa::{from_ssa, is_ssa, to_ssa};
+use brilirs::basic_block::BBProgram;
+
+fn run(prog: &Program, args: &[String]) -> String {
+    brilirs::check::type_check(prog).unwrap();
+    let bbprog = BBProgram::try_from(prog.clone()).unwrap();
+    let args = args.to_vec();
+    // Benchmarks like `ackermann` recurse deeper than the default stack of a test thread allows
+    std::thread::Builder::new()
+        .stack_size(1 << 28)
+        .spawn(move || {
+            let mut out = Vec::new();
+            brilirs::interp::execute_main(&bbprog, &mut out, &args, false, std::io::sink())
+                .unwrap();
+            String::from_utf8(out).unwrap()
+        })
+        .unwrap()
+        .join()
+        .unwrap()
+}
+
+fn map_functions(prog: &Program, f: impl Fn(bril_rs::Function) -> bril_rs::Function) -> Program {
+    let mut prog = prog.clone();
+    prog.functions = prog.functions.into_iter().map(f).collect();
+    prog
+}
+
+#[test]
+fn round_trips_print_the_same_output() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../benchmarks/core");
+    let mut paths: Vec<_> = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension().is_some_and(|e| e == "bril"))
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty());
+    for path in paths {
+        let source = std::fs::read_to_string(&path).unwrap();
+        let args: Vec<String> = source
+            .lines()
+            .find_map(|l| l.strip_prefix('#')?.trim_start().strip_prefix("ARGS:"))
+            .map(|a| a.split_whitespace().map(str::to_string).collect())
+            .unwrap_or_default();
+        let prog: Program =
+            bril2json::parse_abstract_program_from_read(source.as_bytes(), false, false, None)
+                .try_into()
+                .unwrap();
+        let expected = run(&prog, &args);
+
+        let ssa = map_functions(&prog, |f| to_ssa(f).unwrap());
+        for f in &ssa.functions {
+            assert!(is_ssa(f), "{} @{}", path.display(), f.name);
+        }
+        assert_eq!(run(&ssa, &args), expected, "{}", path.display());
+
+        let back = map_functions(&ssa, from_ssa);
+        assert_eq!(run(&back, &args), expected, "{}", path.display());
+    }
+}
+
+#[test]
+fn reassigned_variables_are_not_ssa() {
+    let prog: Program = bril2json::parse_abstract_program_from_read(
+        "@main(a: int) {\n  x: int = const 1;\n  x: int = add x a;\n  print x;\n}\n".as_bytes(),
+        false,
+        false,
+        None,
+    )
+    .try_into()
+    .unwrap();
+    let func = prog.functions[0].clone();
+    assert!(!is_ssa(&func));
+    let ssa = to_ssa(func).unwrap();
+    assert!(is_ssa(&ssa));
+}
@@ -0,0 +1,131 @@
+use bril_rs::visit::{Visitor, VisitorMut, walk_type};
+use bril_rs::{Code, Program, Type};
+
+const PROGRAM: &str = "@main(n: int) {
+  p: ptr<int> = alloc n;
+  r: int = call @f n;
+  br b .a .b;
+.a:
+  set x.0 r;
+.b:
+  x.0: int = get;
+}
+@f(n: int): int {
+  ret n;
+}
+";
+
+fn parse(source: &str) -> Program {
+    bril2json::parse_abstract_program_from_read(source.as_bytes(), false, false, None)
+        .try_into()
+        .unwrap()
+}
+
+/// Records everything it visits in order
+#[derive(Default)]
+struct Recorder(Vec<String>);
+
+impl Visitor for Recorder {
+    fn visit_type(&mut self, t: &Type) {
+        self.0.push(format!("type {t}"));
+        walk_type(self, t);
+    }
+
+    fn visit_label(&mut self, label: &str) {
+        self.0.push(format!("label {label}"));
+    }
+
+    fn visit_use(&mut self, var: &str) {
+        self.0.push(format!("use {var}"));
+    }
+
+    fn visit_def(&mut self, var: &str) {
+        self.0.push(format!("def {var}"));
+    }
+
+    fn visit_set_target(&mut self, var: &str) {
+        self.0.push(format!("set {var}"));
+    }
+
+    fn visit_func_ref(&mut self, func: &str) {
+        self.0.push(format!("call {func}"));
+    }
+
+    fn visit_label_ref(&mut self, label: &str) {
+        self.0.push(format!("goto {label}"));
+    }
+}
+
+#[test]
+fn visitor_sees_every_part_in_order() {
+    let mut recorder = Recorder::default();
+    recorder.visit_program(&parse(PROGRAM));
+    assert_eq!(
+        recorder.0,
+        vec![
+            "def n",
+            "type int",
+            "use n",
+            "def p",
+            "type ptr<int>",
+            "type int",
+            "use n",
+            "call f",
+            "def r",
+            "type int",
+            "use b",
+            "goto a",
+            "goto b",
+            "label a",
+            "set x.0",
+            "use r",
+            "label b",
+            "def x.0",
+            "type int",
+            "def n",
+            "type int",
+            "type int",
+            "use n",
+        ]
+    );
+}
+
+/// Appends a suffix to every variable
+struct Rename;
+
+impl VisitorMut for Rename {
+    fn visit_use(&mut self, var: &mut String) {
+        var.push('!');
+    }
+
+    fn visit_def(&mut self, var: &mut String) {
+        var.push('!');
+    }
+
+    fn visit_set_target(&mut self, var: &mut String) {
+        var.push('!');
+    }
+}
+
+#[test]
+fn mutable_visitor_renames_every_variable() {
+    let mut program = parse(PROGRAM);
+    Rename.visit_program(&mut program);
+    let main = &program.functions[0];
+    assert_eq!(main.args[0].name, "n!");
+    let vars: Vec<_> = main
+        .instrs
+        .iter()
+        .filter_map(|c| match c {
+            Code::Instruction(i) => Some(i),
+            Code::Label { .. } => None,
+        })
+        .flat_map(|i| i.defs().chain(i.uses()).cloned().collect::<Vec<_>>())
+        .collect();
+    assert_eq!(vars, vec!["p!", "n!", "r!", "n!", "b!", "r!", "x.0!"]);
+    let set = main.instrs.iter().find_map(|c| match c {
+        Code::Instruction(i) if i.to_string().starts_with("set") => Some(i.to_string()),
+        _ => None,
+    });
+    assert_eq!(set.as_deref(), Some("set x.0! r!;"));
+}
@@ -290,12 +290,27 @@ def instr_to_string(instr):
             return rhs
 
 
+def trailing_comment(code):
+    """The trailing comment of a function, label, or instruction, to end
+    its line with.
+    """
+    comment = code.get("comments", {}).get("trailing")
+    return "  #{}".format(comment) if comment is not None else ""
+
+
+def print_comments(code, key, indent):
+    for comment in code.get("comments", {}).get(key, []):
+        print("{}#{}".format(indent, comment))
+
+
 def print_instr(instr):
-    print("  {};".format(instr_to_string(instr)))
+    print_comments(instr, "leading", "  ")
+    print("  {};{}".format(instr_to_string(instr), trailing_comment(instr)))
 
 
 def print_label(label):
-    print(".{}:".format(label["label"]))
+    print_comments(label, "leading", "")
+    print(".{}:{}".format(label["label"], trailing_comment(label)))
 
 
 def args_to_string(args):
@@ -311,11 +326,13 @@ def args_to_string(args):
 
 def print_func(func):
     typ = func.get("type", "void")
+    print_comments(func, "leading", "")
     print(
-        "@{}{}{} {{".format(
+        "@{}{}{} {{{}".format(
             func["name"],
             args_to_string(func.get("args", [])),
             ": {}".format(type_to_str(typ)) if typ != "void" else "",
+            trailing_comment(func),
         )
     )
     for instr_or_label in func["instrs"]:
@@ -323,6 +340,7 @@ def print_func(func):
             print_label(instr_or_label)
         else:
             print_instr(instr_or_label)
+    print_comments(func, "closing", "  ")
     print("}")
 
 
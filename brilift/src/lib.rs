@@ -1,11 +1,97 @@
 mod rt;
 pub mod translator;
 
-use crate::translator::{Translator, find_func};
+use crate::translator::{Translator, all_vars, find_func};
 use bril_rs as bril;
 use bril_rs::Program;
+use bril_rs::extension::{Extension, ExtensionError, ExtensionSet};
 use cranelift_jit::JITModule;
 use cranelift_object::ObjectModule;
+use std::fmt;
+
+/// The extensions that Brilift can translate. Programs using anything else are rejected by [`check`] before compilation.
+pub const EXTENSIONS: ExtensionSet = ExtensionSet::CORE
+    .with(Extension::Float)
+    .with(Extension::Memory);
+
+/// The reasons Brilift can refuse to compile or run a program.
+#[derive(Debug)]
+pub enum CompileError {
+    /// The program uses an extension which is not in [`EXTENSIONS`].
+    Extension(ExtensionError),
+    /// There is no `@main` function.
+    NoMain,
+    /// An argument of `@main` is a pointer, which can't be given on the command line.
+    PointerArgument(String),
+    /// A pointer is printed, which the runtime can't do.
+    PrintPointer { func: String, arg: String },
+    /// The arguments given to `@main` by [`jit_run`] don't match its parameters.
+    Arguments(String),
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Extension(e) => write!(f, "{e}"),
+            Self::NoMain => write!(f, "there is no @main function"),
+            Self::PointerArgument(arg) => {
+                write!(f, "@main can't take the pointer argument `{arg}`")
+            }
+            Self::PrintPointer { func, arg } => {
+                write!(f, "@{func}: the pointer `{arg}` can't be printed")
+            }
+            Self::Arguments(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+impl From<ExtensionError> for CompileError {
+    fn from(e: ExtensionError) -> Self {
+        Self::Extension(e)
+    }
+}
+
+/// Check that Brilift can translate a program: it only uses [`EXTENSIONS`], it has a `@main`
+/// which doesn't take pointers, and it never prints a pointer.
+pub fn check(program: &Program) -> Result<(), CompileError> {
+    EXTENSIONS.accepts(program)?;
+
+    let main = program
+        .functions
+        .iter()
+        .find(|f| f.name == "main")
+        .ok_or(CompileError::NoMain)?;
+    if let Some(arg) = main
+        .args
+        .iter()
+        .find(|arg| matches!(arg.arg_type, bril::Type::Pointer(_)))
+    {
+        return Err(CompileError::PointerArgument(arg.name.clone()));
+    }
+
+    for func in &program.functions {
+        let var_types = all_vars(func);
+        let printed = func.instrs.iter().flat_map(|code| match code {
+            bril::Code::Instruction(bril::Instruction::Effect {
+                op: bril::EffectOps::Print,
+                args,
+                ..
+            }) => args.as_slice(),
+            _ => &[],
+        });
+        for arg in printed {
+            if let Some(bril::Type::Pointer(_)) = var_types.get(arg) {
+                return Err(CompileError::PrintPointer {
+                    func: func.name.clone(),
+                    arg: arg.clone(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
 
 /// Compile a program ahead of time to an object file.
 ///
@@ -14,13 +100,17 @@ use cranelift_object::ObjectModule;
 /// * `output` - the filename where we should write the object file
 /// * `opt_level` - a Cranelift optimization level
 /// * `dump_ir` - optionally emit the Cranelift IR to stdout
+///
+/// Returns an error without compiling anything if the program fails [`check`].
 pub fn compile(
     program: &Program,
     target: Option<String>,
     output: &str,
     opt_level: &str,
     dump_ir: bool,
-) {
+) -> Result<(), CompileError> {
+    check(program)?;
+
     // Compile.
     let mut trans = Translator::<ObjectModule>::new(target, opt_level);
     trans.compile_prog(program, dump_ir);
@@ -31,6 +121,7 @@ pub fn compile(
 
     // Write object file.
     trans.emit(output);
+    Ok(())
 }
 
 /// Just-in-time compile and execute a Bril program.
@@ -38,7 +129,12 @@ pub fn compile(
 /// * `program` - the Bril program to compile
 /// * `args` - the arguments to pass to the `@main` function
 /// * `dump_ir` - optionally emit the Cranelift IR to stdout
-pub fn jit_run(program: &Program, args: Vec<String>, dump_ir: bool) {
+///
+/// Returns an error without running anything if the program fails [`check`] or `args` don't
+/// match the parameters of `@main`.
+pub fn jit_run(program: &Program, args: Vec<String>, dump_ir: bool) -> Result<(), CompileError> {
+    check(program)?;
+
     // Compile.
     let mut trans = Translator::<JITModule>::new();
     trans.compile_prog(program, dump_ir);
@@ -49,27 +145,42 @@ pub fn jit_run(program: &Program, args: Vec<String>, dump_ir: bool) {
 
     // Parse CLI arguments.
     if main.args.len() != args.len() {
-        panic!(
+        return Err(CompileError::Arguments(format!(
             "@main expects {} arguments; got {}",
             main.args.len(),
             args.len()
-        );
+        )));
     }
-    let main_args: Vec<bril::Literal> = main
+    let bad_arg = |arg: &bril::Argument, val_str: &str| {
+        CompileError::Arguments(format!(
+            "`{val_str}` is not a valid {} for `{}`",
+            arg.arg_type, arg.name
+        ))
+    };
+    let main_args = main
         .args
         .iter()
         .zip(args)
         .map(|(arg, val_str)| match arg.arg_type {
-            bril::Type::Int => bril::Literal::Int(val_str.parse().unwrap()),
-            bril::Type::Bool => bril::Literal::Bool(val_str == "true"),
-            bril::Type::Float => bril::Literal::Float(val_str.parse().unwrap()),
-            bril::Type::Char => bril::Literal::Char(val_str.parse().unwrap()),
-            bril::Type::Pointer(_) => unimplemented!("pointers not supported as main args"),
+            bril::Type::Int => val_str
+                .parse()
+                .map(bril::Literal::Int)
+                .map_err(|_| bad_arg(arg, &val_str)),
+            bril::Type::Bool => Ok(bril::Literal::Bool(val_str == "true")),
+            bril::Type::Float => val_str
+                .parse()
+                .map(bril::Literal::Float)
+                .map_err(|_| bad_arg(arg, &val_str)),
+            bril::Type::Pointer(_) => unreachable!("pointer arguments are rejected by `check`"),
+            bril::Type::Char | bril::Type::Any => {
+                unreachable!("`{}` is not in {EXTENSIONS}", arg.arg_type)
+            }
         })
-        .collect();
+        .collect::<Result<Vec<_>, _>>()?;
 
     // Invoke the main function.
     unsafe { trans.run(entry_id, &main_args) };
+    Ok(())
 }
 
 /// The C runtime library for Rust library users.
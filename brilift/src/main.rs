@@ -1,6 +1,6 @@
 use argh::FromArgs;
 use bril_rs as bril;
-use brilift::{compile, jit_run};
+use brilift::{EXTENSIONS, compile, jit_run};
 use std::str::FromStr;
 
 #[derive(FromArgs)]
@@ -26,6 +26,12 @@ struct BriliftArgs {
     #[argh(switch, short = 'v', description = "verbose logging")]
     verbose: bool,
 
+    #[argh(
+        switch,
+        description = "print the extensions which can be compiled and exit"
+    )]
+    extensions: bool,
+
     #[argh(
         option,
         short = 'O',
@@ -72,6 +78,11 @@ impl FromStr for OptLevel {
 fn main() {
     let args: BriliftArgs = argh::from_env();
 
+    if args.extensions {
+        println!("{EXTENSIONS}");
+        return;
+    }
+
     // Set up logging.
     simplelog::TermLogger::init(
         if args.verbose {
@@ -86,10 +97,16 @@ fn main() {
     .unwrap();
 
     // Load the Bril program from stdin.
-    let prog = bril::load_program();
+    let prog = match bril::try_load_program_from_read(std::io::stdin().lock()) {
+        Ok(prog) => prog,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
 
-    if args.jit {
-        jit_run(&prog, args.args, args.dump_ir);
+    let result = if args.jit {
+        jit_run(&prog, args.args, args.dump_ir)
     } else {
         compile(
             &prog,
@@ -97,6 +114,10 @@ fn main() {
             &args.output,
             args.opt_level.to_str(),
             args.dump_ir,
-        );
+        )
+    };
+    if let Err(e) = result {
+        eprintln!("{e}");
+        std::process::exit(1);
     }
 }
@@ -1,3 +1,4 @@
+use crate::EXTENSIONS;
 use crate::rt;
 use bril_rs as bril;
 use core::mem;
@@ -160,8 +161,8 @@ fn translate_type(typ: &bril::Type, pointer_type: ir::Type) -> ir::Type {
         bril::Type::Int => ir::types::I64,
         bril::Type::Bool => ir::types::I8,
         bril::Type::Float => ir::types::F64,
-        bril::Type::Char => ir::types::I32,
         bril::Type::Pointer(_) => pointer_type,
+        bril::Type::Char | bril::Type::Any => unreachable!("`{typ}` is not in {EXTENSIONS}"),
     }
 }
 
@@ -182,7 +183,7 @@ fn translate_sig(func: &bril::Function, pointer_type: ir::Type) -> ir::Signature
 }
 
 /// Get all the variables defined in a function (and their types), including the arguments.
-fn all_vars(func: &bril::Function) -> HashMap<&String, &bril::Type> {
+pub(crate) fn all_vars(func: &bril::Function) -> HashMap<&String, &bril::Type> {
     func.instrs
         .iter()
         .filter_map(|inst| match inst {
@@ -307,12 +308,14 @@ impl CompileEnv<'_> {
 
             // Print each value according to its type.
             let arg_val = builder.use_var(self.vars[arg]);
-            let print_func = match self.var_types[arg] {
+            let print_func = match &self.var_types[arg] {
                 bril::Type::Int => RTFunc::PrintInt,
                 bril::Type::Bool => RTFunc::PrintBool,
                 bril::Type::Float => RTFunc::PrintFloat,
-                bril::Type::Char => unimplemented!(),
-                bril::Type::Pointer(_) => unimplemented!(),
+                bril::Type::Pointer(_) => unreachable!("printing pointers is rejected by `check`"),
+                typ @ (bril::Type::Char | bril::Type::Any) => {
+                    unreachable!("`{typ}` is not in {EXTENSIONS}")
+                }
             };
             let print_ref = self.rt_refs[print_func];
             builder.ins().call(print_ref, &[arg_val]);
@@ -350,14 +353,8 @@ impl CompileEnv<'_> {
                 };
                 builder.ins().f64const(val)
             }
-            bril::Type::Char => {
-                let val = match lit {
-                    bril::Literal::Char(c) => *c,
-                    _ => panic!("incorrect literal type for char"),
-                };
-                builder.ins().iconst(ir::types::I32, val as i64)
-            }
             bril::Type::Pointer(_) => panic!("pointer literals not allowed"),
+            bril::Type::Char | bril::Type::Any => unreachable!("`{typ}` is not in {EXTENSIONS}"),
         }
     }
 
@@ -462,7 +459,7 @@ impl CompileEnv<'_> {
                 | bril::EffectOps::Commit
                 | bril::EffectOps::Guard
                 | bril::EffectOps::Set => {
-                    unimplemented!()
+                    unreachable!("`{inst}` is not in {EXTENSIONS}")
                 }
             },
             bril::Instruction::Value {
@@ -567,7 +564,9 @@ impl CompileEnv<'_> {
                 | bril::ValueOps::Cle
                 | bril::ValueOps::Cge
                 | bril::ValueOps::Char2int
-                | bril::ValueOps::Int2char => unimplemented!(),
+                | bril::ValueOps::Int2char
+                | bril::ValueOps::Float2Bits
+                | bril::ValueOps::Bits2Float => unreachable!("`{inst}` is not in {EXTENSIONS}"),
             },
         }
     }
@@ -823,12 +822,16 @@ impl<M: Module> Translator<M> {
             .iter()
             .enumerate()
             .map(|(i, arg)| {
-                let parse_ref = rt_setup_refs[match arg.arg_type {
+                let parse_ref = rt_setup_refs[match &arg.arg_type {
                     bril::Type::Int => RTSetupFunc::ParseInt,
                     bril::Type::Bool => RTSetupFunc::ParseBool,
                     bril::Type::Float => RTSetupFunc::ParseFloat,
-                    bril::Type::Char => unimplemented!(),
-                    bril::Type::Pointer(_) => unimplemented!("can't print pointers"),
+                    typ @ (bril::Type::Char | bril::Type::Any) => {
+                        unreachable!("`{typ}` is not in {EXTENSIONS}")
+                    }
+                    bril::Type::Pointer(_) => {
+                        unreachable!("pointer arguments of @main are rejected by `check`")
+                    }
                 }];
                 let idx_arg = builder.ins().iconst(ir::types::I64, (i + 1) as i64); // skip argv[0]
                 let inst = builder.ins().call(parse_ref, &[argv_arg, idx_arg]);
@@ -1053,7 +1056,7 @@ impl Translator<JITModule> {
                 bril::Literal::Int(i) => i as *const i64 as *const u8,
                 bril::Literal::Bool(b) => b as *const bool as *const u8,
                 bril::Literal::Float(f) => f as *const f64 as *const u8,
-                bril::Literal::Char(c) => c as *const char as *const u8,
+                bril::Literal::Char(_) => unreachable!("`char` is not in {EXTENSIONS}"),
             })
             .collect()
     }
@@ -122,7 +122,7 @@ impl BBFunction {
     });
 
     func.instrs.iter().try_for_each(|code| {
-      if let bril_rs::Code::Label { label, pos } = code {
+      if let bril_rs::Code::Label { label, pos, .. } = code {
         if label_map.contains_key(label) {
           return Err(InterpError::DuplicateLabel(label.clone()).add_pos(pos.clone()));
         }
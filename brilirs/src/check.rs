@@ -88,6 +88,7 @@ fn type_check_instruction<'a>(
       const_type,
       value,
       pos: _,
+      comments: _,
     } => {
       if const_type == &Type::Float && value.get_type() == Type::Int {
         update_env(env, dest, const_type)
@@ -104,6 +105,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_args(2, args)?;
       check_num_funcs(0, funcs)?;
@@ -121,6 +123,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_args(2, args)?;
       check_num_funcs(0, funcs)?;
@@ -138,6 +141,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_args(1, args)?;
       check_num_funcs(0, funcs)?;
@@ -154,6 +158,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_args(2, args)?;
       check_num_funcs(0, funcs)?;
@@ -171,6 +176,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_args(1, args)?;
       check_num_funcs(0, funcs)?;
@@ -186,6 +192,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_args(2, args)?;
       check_num_funcs(0, funcs)?;
@@ -203,6 +210,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_args(2, args)?;
       check_num_funcs(0, funcs)?;
@@ -219,6 +227,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
       op_type,
     } => {
       check_num_args(2, args)?;
@@ -236,6 +245,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
       op_type,
     } => {
       check_num_args(1, args)?;
@@ -252,6 +262,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
       op_type,
     } => {
       check_num_args(1, args)?;
@@ -268,6 +279,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
       op_type,
     } => {
       check_num_args(1, args)?;
@@ -284,6 +296,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
       op_type,
     } => {
       check_num_args(1, args)?;
@@ -301,10 +314,15 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_funcs(1, funcs)?;
       check_num_labels(0, labels)?;
-      let callee_func = prog.functions.iter().find(|f| f.name == funcs[0]).unwrap();
+      let callee_func = prog
+        .functions
+        .iter()
+        .find(|f| f.name == funcs[0])
+        .ok_or_else(|| InterpError::FuncNotFound(funcs[0].clone()))?;
 
       if args.len() != callee_func.args.len() {
         return Err(InterpError::BadNumArgs(callee_func.args.len(), args.len()));
@@ -335,6 +353,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_args(0, args)?;
       check_num_funcs(0, funcs)?;
@@ -350,6 +369,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_args(0, args)?;
       check_num_funcs(0, funcs)?;
@@ -365,6 +385,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_args(1, args)?;
       check_num_funcs(0, funcs)?;
@@ -381,6 +402,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_args(1, args)?;
       check_num_funcs(0, funcs)?;
@@ -397,6 +419,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_args(2, args)?;
       check_num_funcs(0, funcs)?;
@@ -413,6 +436,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_args(0, args)?;
       check_num_funcs(0, funcs)?;
@@ -425,6 +449,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_args(1, args)?;
       check_asmt_type(&Type::Bool, get_type(env, 0, args)?)?;
@@ -438,6 +463,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_funcs(0, funcs)?;
       check_num_labels(0, labels)?;
@@ -462,6 +488,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_funcs(0, funcs)?;
       check_num_labels(0, labels)?;
@@ -476,6 +503,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_args(0, args)?;
       check_num_funcs(0, funcs)?;
@@ -488,6 +516,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_funcs(1, funcs)?;
       check_num_labels(0, labels)?;
@@ -523,6 +552,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_args(2, args)?;
       check_num_funcs(0, funcs)?;
@@ -537,6 +567,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_args(1, args)?;
       check_num_funcs(0, funcs)?;
@@ -550,6 +581,7 @@ fn type_check_instruction<'a>(
       funcs,
       labels,
       pos: _,
+      comments: _,
     } => {
       check_num_args(2, args)?;
       check_num_funcs(0, funcs)?;
@@ -563,9 +595,8 @@ fn type_check_instruction<'a>(
       funcs: _,
       labels: _,
       pos: _,
-    } => {
-      unimplemented!("brilirs does not currently support the speculative execution")
-    }
+      comments: _,
+    } => Err(InterpError::SpeculationUnsupported),
   }
 }
 
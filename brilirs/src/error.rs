@@ -1,6 +1,10 @@
 use std::fmt::Display;
 
-use bril_rs::{Position, conversion::PositionalConversionError};
+use bril_rs::{
+  Position,
+  conversion::{ConversionErrors, PositionalConversionError},
+};
+use bril2json::ParseError;
 use std::error::Error;
 use thiserror::Error;
 
@@ -57,6 +61,8 @@ pub enum InterpError {
   IoError(#[from] std::io::Error),
   #[error("value ${0} cannot be converted to char")]
   ToCharError(i64),
+  #[error("brilirs does not currently support the speculative execution extension")]
+  SpeculationUnsupported,
   #[error(
     "You probably shouldn't see this error, this is here to handle conversions between InterpError and PositionalError"
   )]
@@ -92,6 +98,7 @@ impl Display for PositionalInterpError {
             pos,
             pos_end: Some(end),
             src: Some(s),
+            ..
           }),
       } => {
         write!(
@@ -107,6 +114,7 @@ impl Display for PositionalInterpError {
             pos,
             pos_end: None,
             src: Some(s),
+            ..
           }),
       } => {
         write!(f, "{s}:{}:{} \n\t {e}", pos.row, pos.col)
@@ -118,6 +126,7 @@ impl Display for PositionalInterpError {
             pos,
             pos_end: Some(end),
             src: None,
+            ..
           }),
       } => {
         write!(
@@ -128,11 +137,13 @@ impl Display for PositionalInterpError {
       }
       Self {
         e,
-        pos: Some(Position {
-          pos,
-          pos_end: None,
-          src: None,
-        }),
+        pos:
+          Some(Position {
+            pos,
+            pos_end: None,
+            src: None,
+            ..
+          }),
       } => {
         write!(f, "Line {}, Column {}: {e}", pos.row, pos.col)
       }
@@ -154,10 +165,42 @@ impl From<InterpError> for PositionalInterpError {
 }
 
 impl From<PositionalConversionError> for PositionalInterpError {
-  fn from(PositionalConversionError { e, pos }: PositionalConversionError) -> Self {
+  fn from(PositionalConversionError { e, pos, .. }: PositionalConversionError) -> Self {
     Self {
       e: Box::new(e),
       pos,
     }
   }
 }
+
+impl From<ParseError> for PositionalInterpError {
+  fn from(e: ParseError) -> Self {
+    match e {
+      // A single error keeps its position so that the offending line can be shown
+      ParseError::Syntax(mut errors) if errors.len() == 1 => {
+        let error = errors.remove(0);
+        Self {
+          e: error.message().into(),
+          pos: Some(error.pos),
+        }
+      }
+      e => Self {
+        e: Box::new(e),
+        pos: None,
+      },
+    }
+  }
+}
+
+impl From<ConversionErrors> for PositionalInterpError {
+  fn from(ConversionErrors(mut errors): ConversionErrors) -> Self {
+    // A single error keeps its position so that the offending line can be shown
+    if errors.len() == 1 {
+      return errors.remove(0).into();
+    }
+    Self {
+      e: Box::new(ConversionErrors(errors)),
+      pos: None,
+    }
+  }
+}
@@ -148,6 +148,7 @@ impl FlatIR {
         dest,
         op: ConstOps::Const,
         pos: _,
+        comments: _,
         const_type,
         value,
       } => Ok(Self::Const {
@@ -170,6 +171,7 @@ impl FlatIR {
         funcs: _,
         labels: _,
         pos: _,
+        comments: _,
         op_type: _,
       } => {
         let dest = get_num_from_map(dest, num_var_map);
@@ -192,6 +194,7 @@ impl FlatIR {
         funcs: _,
         labels: _,
         pos: _,
+        comments: _,
         op_type: _,
       } => {
         let dest = get_num_from_map(dest, num_var_map);
@@ -234,6 +237,7 @@ impl FlatIR {
         funcs: _,
         labels: _,
         pos: _,
+        comments: _,
         op_type: _,
       } => {
         let dest = get_num_from_map(dest, num_var_map);
@@ -256,6 +260,7 @@ impl FlatIR {
         funcs,
         labels: _,
         pos: _,
+        comments: _,
         op_type: _,
       } => {
         let dest = get_num_from_map(dest, num_var_map);
@@ -272,6 +277,7 @@ impl FlatIR {
         funcs: _,
         labels: _,
         pos: _,
+        comments: _,
       } => Ok(Self::Nop),
       Instruction::Effect {
         op: EffectOps::Jump,
@@ -279,6 +285,7 @@ impl FlatIR {
         funcs: _,
         labels,
         pos: _,
+        comments: _,
       } => {
         let dest = labels
           .into_iter()
@@ -298,6 +305,7 @@ impl FlatIR {
         funcs: _,
         labels,
         pos: _,
+        comments: _,
       } => {
         let arg = args
           .into_iter()
@@ -324,6 +332,7 @@ impl FlatIR {
         funcs: _,
         labels: _,
         pos: _,
+        comments: _,
       } => {
         if args.is_empty() {
           Ok(Self::ReturnVoid)
@@ -342,6 +351,7 @@ impl FlatIR {
         funcs,
         labels: _,
         pos: _,
+        comments: _,
       } => {
         let args = args
           .into_iter()
@@ -356,6 +366,7 @@ impl FlatIR {
         funcs: _,
         labels: _,
         pos: _,
+        comments: _,
       } => {
         if args.len() == 1 {
           let arg = args
@@ -378,6 +389,7 @@ impl FlatIR {
         funcs: _,
         labels: _,
         pos: _,
+        comments: _,
       } => {
         let mut iter = args.into_iter().map(|v| get_num_from_map(v, num_var_map));
         let arg0 = iter.next().unwrap();
@@ -390,6 +402,7 @@ impl FlatIR {
         funcs: _,
         labels: _,
         pos: _,
+        comments: _,
       } => {
         let mut iter = args.into_iter().map(|v| get_num_from_map(v, num_var_map));
         let arg0 = iter.next().unwrap();
@@ -402,6 +415,7 @@ impl FlatIR {
         funcs: _,
         labels: _,
         pos: _,
+        comments: _,
       } => {
         let arg = args
           .into_iter()
@@ -413,7 +427,7 @@ impl FlatIR {
       Instruction::Effect {
         op: EffectOps::Speculate | EffectOps::Guard | EffectOps::Commit,
         ..
-      } => unimplemented!("brilirs does not currently support the speculative execution extension"),
+      } => Err(InterpError::SpeculationUnsupported),
     }
   }
 }
@@ -37,7 +37,7 @@ pub fn run_input<T: std::io::Write, U: std::io::Write>(
   //      - bril_rs takes file.json as input
   //      - bril2json takes file.bril as input
   let prog: Program = if cli_args.text {
-    bril2json::parse_abstract_program_from_read(input, true, true, cli_args.file).try_into()?
+    bril2json::try_parse_abstract_program_from_read(input, true, true, cli_args.file)?.try_into()?
   } else {
     bril_rs::load_abstract_program_from_read(input).try_into()?
   };
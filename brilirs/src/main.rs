@@ -22,11 +22,14 @@ fn main() {
   ) {
     eprintln!("error: {e}");
     if let PositionalInterpError {
-      pos: Some(Position {
-        pos,
-        pos_end,
-        src: Some(src),
-      }),
+      pos:
+        Some(Position {
+          pos,
+          pos_end,
+          src: Some(src),
+          origin,
+          ..
+        }),
       ..
     } = e
     {
@@ -52,6 +55,19 @@ fn main() {
           eprintln!("{:>width$}", "^", width = end.col as usize);
         }
       }
+
+      // Code moved or copied by a pass also points back at where it came from
+      let mut origin = origin.as_deref();
+      while let Some(o) = origin {
+        match &o.src {
+          Some(s) => eprintln!("note: derived from {s}:{}:{}", o.pos.row, o.pos.col),
+          None => eprintln!(
+            "note: derived from line {}, column {}",
+            o.pos.row, o.pos.col
+          ),
+        }
+        origin = o.origin.as_deref();
+      }
     }
     std::process::exit(2)
   }